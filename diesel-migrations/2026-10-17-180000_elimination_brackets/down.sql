@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE player_bracket_entry DROP COLUMN seed;
+ALTER TABLE brackets DROP COLUMN seed_source_bracket_id;
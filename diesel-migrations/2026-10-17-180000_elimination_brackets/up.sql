@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE brackets ADD COLUMN seed_source_bracket_id INTEGER NULL;
+ALTER TABLE player_bracket_entry ADD COLUMN seed INTEGER NULL;
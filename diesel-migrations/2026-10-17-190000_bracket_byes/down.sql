@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE brackets DROP COLUMN bye_points;
+DROP TABLE bracket_byes;
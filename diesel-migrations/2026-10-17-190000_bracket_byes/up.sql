@@ -0,0 +1,16 @@
+CREATE TABLE bracket_byes (
+   id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_id INTEGER NOT NULL,
+   round_id   INTEGER NOT NULL,
+   player_id  INTEGER NOT NULL,
+
+   FOREIGN KEY(bracket_id) REFERENCES brackets(id),
+   FOREIGN KEY(round_id) REFERENCES bracket_rounds(id),
+   FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+-- nobody gets two byes in one bracket
+CREATE UNIQUE INDEX bracket_bye_player ON bracket_byes(bracket_id, player_id);
+
+-- measured in half points, like everything else: 2 is a win, 1 is a draw
+ALTER TABLE brackets ADD COLUMN bye_points INTEGER NOT NULL DEFAULT 2;
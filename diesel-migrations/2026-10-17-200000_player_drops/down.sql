@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE player_bracket_entry DROP COLUMN dropped_at;
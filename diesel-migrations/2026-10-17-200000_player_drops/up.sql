@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE player_bracket_entry ADD COLUMN dropped_at BIGINT NULL;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE brackets DROP COLUMN tiebreakers;
+ALTER TABLE brackets DROP COLUMN forfeit_time_penalty;
+ALTER TABLE brackets DROP COLUMN points_per_loss;
+ALTER TABLE brackets DROP COLUMN points_per_draw;
+ALTER TABLE brackets DROP COLUMN points_per_win;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- points are doubled, like bye_points
+ALTER TABLE brackets ADD COLUMN points_per_win INTEGER NOT NULL DEFAULT 2;
+ALTER TABLE brackets ADD COLUMN points_per_draw INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE brackets ADD COLUMN points_per_loss INTEGER NOT NULL DEFAULT 0;
+-- seconds
+ALTER TABLE brackets ADD COLUMN forfeit_time_penalty INTEGER NOT NULL DEFAULT 10800;
+ALTER TABLE brackets ADD COLUMN tiebreakers TEXT NOT NULL DEFAULT '["Buchholz","AverageTime"]';
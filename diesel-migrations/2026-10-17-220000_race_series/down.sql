@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE bracket_race_games;
+ALTER TABLE bracket_races DROP COLUMN best_of;
+ALTER TABLE brackets DROP COLUMN best_of;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- races are a single game unless they're a best-of-N series
+ALTER TABLE brackets ADD COLUMN best_of INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE bracket_races ADD COLUMN best_of INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE bracket_race_games (
+   id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id INTEGER NOT NULL,
+   -- 1-indexed
+   game_num        INTEGER NOT NULL,
+   player_1_result TEXT NOT NULL,
+   player_2_result TEXT NOT NULL,
+   outcome         TEXT NOT NULL,
+   racetime_gg_url TEXT NULL,
+
+   FOREIGN KEY(bracket_race_id) REFERENCES bracket_races(id)
+);
+
+CREATE UNIQUE INDEX bracket_race_game_num ON bracket_race_games(bracket_race_id, game_num);
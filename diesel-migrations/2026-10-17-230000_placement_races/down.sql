@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE brackets DROP COLUMN placement_points;
+ALTER TABLE brackets DROP COLUMN group_size;
+DROP TABLE bracket_race_entrants;
+ALTER TABLE bracket_races DROP COLUMN placement_race;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- placement races have any number of entrants (listed in bracket_race_entrants) and are scored by where
+-- everyone finished. player_1_id and player_2_id are just the first two entrants
+ALTER TABLE bracket_races ADD COLUMN placement_race BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE bracket_race_entrants (
+   id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id INTEGER NOT NULL,
+   player_id       INTEGER NOT NULL,
+   result          TEXT NULL,
+   -- 1-indexed; players with the same result share a placement
+   placement       INTEGER NULL,
+
+   FOREIGN KEY(bracket_race_id) REFERENCES bracket_races(id),
+   FOREIGN KEY(player_id) REFERENCES players(id)
+);
+
+CREATE UNIQUE INDEX bracket_race_entrant_player ON bracket_race_entrants(bracket_race_id, player_id);
+
+-- for group brackets
+ALTER TABLE brackets ADD COLUMN group_size INTEGER NOT NULL DEFAULT 4;
+-- points for 1st, 2nd, ... place (doubled, like all points). empty means the default table
+ALTER TABLE brackets ADD COLUMN placement_points TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE seasons DROP COLUMN qualifier_max_attempts;
+ALTER TABLE seasons DROP COLUMN qualifier_aggregation;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE seasons ADD COLUMN qualifier_aggregation TEXT NOT NULL DEFAULT '"Best"';
+-- NULL means unlimited
+ALTER TABLE seasons ADD COLUMN qualifier_max_attempts INTEGER NULL;
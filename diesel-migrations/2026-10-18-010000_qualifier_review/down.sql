@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE qualifier_submissions DROP COLUMN reviewed_at;
+ALTER TABLE qualifier_submissions DROP COLUMN review_reason;
+ALTER TABLE qualifier_submissions DROP COLUMN reviewer_discord_id;
+ALTER TABLE qualifier_submissions DROP COLUMN review_state;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+ALTER TABLE qualifier_submissions ADD COLUMN review_state TEXT NOT NULL DEFAULT '"Pending"';
+-- discord id of the admin who reviewed it
+ALTER TABLE qualifier_submissions ADD COLUMN reviewer_discord_id TEXT NULL;
+ALTER TABLE qualifier_submissions ADD COLUMN review_reason TEXT NULL;
+ALTER TABLE qualifier_submissions ADD COLUMN reviewed_at BIGINT NULL;
+-- everything submitted before reviews existed was accepted as-is
+UPDATE qualifier_submissions SET review_state = '"Verified"';
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- each player's Glicko-2 rating after every bracket round they raced in. These are all
+-- recomputed from scratch from the race results, so nothing else should write here.
+CREATE TABLE player_ratings (
+   id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   player_id  INTEGER NOT NULL,
+   round_id   INTEGER NOT NULL,
+   rating     DOUBLE NOT NULL,
+   deviation  DOUBLE NOT NULL,
+   volatility DOUBLE NOT NULL,
+   -- how many (pairwise) games this rating update was based on
+   games      INTEGER NOT NULL,
+
+   FOREIGN KEY(player_id) REFERENCES players(id),
+   FOREIGN KEY(round_id) REFERENCES bracket_rounds(id)
+);
+
+CREATE INDEX player_ratings_player ON player_ratings(player_id);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- tokens for the write API. Each belongs to a discord user, and only works while that user is an
+-- admin. We only keep a hash of the token itself: it's shown once, when it's created.
+CREATE TABLE api_tokens (
+   id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   discord_id  TEXT NOT NULL,
+   -- whatever the user called it, so they can tell their tokens apart
+   name        TEXT NOT NULL,
+   token_hash  TEXT NOT NULL UNIQUE,
+   created     BIGINT NOT NULL,
+   last_used   BIGINT NULL,
+   revoked     BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE webhook_deliveries;
+DROP TABLE webhook_subscriptions;
@@ -0,0 +1,31 @@
+-- Your SQL goes here
+-- URLs that league events (races scheduled, results reported, etc) get POSTed to
+CREATE TABLE webhook_subscriptions (
+   id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   url         TEXT NOT NULL,
+   -- used to sign deliveries, so receivers can check they came from us
+   secret      TEXT NOT NULL,
+   -- serialized list of the event kinds this subscription wants
+   events      TEXT NOT NULL,
+   created     BIGINT NOT NULL,
+   active      BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+-- one row per event per subscription. This is both the retry queue and the delivery log.
+CREATE TABLE webhook_deliveries (
+   id                INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   subscription_id   INTEGER NOT NULL,
+   event             TEXT NOT NULL,
+   payload           TEXT NOT NULL,
+   -- serialized DeliveryState
+   state             TEXT NOT NULL,
+   attempts          INTEGER NOT NULL DEFAULT 0,
+   next_attempt      BIGINT NULL,
+   last_status_code  INTEGER NULL,
+   last_error        TEXT NULL,
+   created           BIGINT NOT NULL,
+   delivered         BIGINT NULL,
+   FOREIGN KEY(subscription_id) REFERENCES webhook_subscriptions(id)
+);
+
+CREATE INDEX webhook_deliveries_next_attempt ON webhook_deliveries(next_attempt);
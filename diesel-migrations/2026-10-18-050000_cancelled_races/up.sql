@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- races that were scheduled and then cancelled. The races themselves are deleted, but calendar
+-- feeds need to keep saying that they're cancelled so subscribers' calendars drop them.
+CREATE TABLE cancelled_races (
+   id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   -- the deleted race's id, so this stays the same calendar event
+   bracket_race_id INTEGER NOT NULL UNIQUE,
+   bracket_id      INTEGER NOT NULL,
+   -- serialized list of every player who was in the race
+   player_ids      TEXT NOT NULL,
+   -- "<bracket>: <player> vs <player>", as of when it was cancelled
+   title           TEXT NOT NULL,
+   scheduled_for   BIGINT NOT NULL,
+   cancelled       BIGINT NOT NULL,
+   FOREIGN KEY(bracket_id) REFERENCES brackets(id)
+);
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- times a player is usually free to race, every week, in their own time zone
+CREATE TABLE availability_windows (
+   id           INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   player_id    INTEGER NOT NULL,
+   -- 0 is Monday
+   weekday      INTEGER NOT NULL,
+   -- minutes after local midnight. end_minute is after start_minute, and can be 1440 (midnight at
+   -- the end of the day)
+   start_minute INTEGER NOT NULL,
+   end_minute   INTEGER NOT NULL,
+   -- IANA name, e.g. "America/New_York"
+   timezone     TEXT NOT NULL,
+   FOREIGN KEY(player_id) REFERENCES players(id)
+);
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- requests to move an already scheduled race, which only happen once someone else in the race
+-- agrees, plus admins moving races directly. Nothing is deleted, so this is the race's history.
+CREATE TABLE reschedule_proposals (
+   id                   INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id      INTEGER NOT NULL,
+   -- the player who wants to move the race (or the admin who moved it)
+   proposer_discord_id  TEXT NOT NULL,
+   proposed_time        BIGINT NOT NULL,
+   -- what the race was scheduled for when this was proposed
+   previous_time        BIGINT,
+   -- serialized ProposalState
+   state                TEXT NOT NULL,
+   created              BIGINT NOT NULL,
+   -- whoever accepted, declined or countered it
+   responder_discord_id TEXT,
+   resolved             BIGINT,
+   FOREIGN KEY(bracket_race_id) REFERENCES bracket_races(id)
+);
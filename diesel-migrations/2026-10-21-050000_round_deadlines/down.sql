@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE bracket_rounds DROP COLUMN play_by_escalated;
+ALTER TABLE bracket_rounds DROP COLUMN schedule_by_escalated;
+ALTER TABLE bracket_rounds DROP COLUMN schedule_reminders_sent;
+ALTER TABLE bracket_rounds DROP COLUMN missed_deadline_policy;
+ALTER TABLE bracket_rounds DROP COLUMN play_by;
+ALTER TABLE bracket_rounds DROP COLUMN schedule_by;
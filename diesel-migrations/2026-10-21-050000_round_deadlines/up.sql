@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- epoch timestamps; NULL means the round doesn't have that deadline
+ALTER TABLE bracket_rounds ADD COLUMN schedule_by BIGINT NULL;
+ALTER TABLE bracket_rounds ADD COLUMN play_by BIGINT NULL;
+-- what happens to races that still haven't been played once play_by passes
+ALTER TABLE bracket_rounds ADD COLUMN missed_deadline_policy TEXT NOT NULL DEFAULT '"Nothing"';
+-- which of the deadline reminders/escalations the worker has already done
+ALTER TABLE bracket_rounds ADD COLUMN schedule_reminders_sent BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE bracket_rounds ADD COLUMN schedule_by_escalated BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE bracket_rounds ADD COLUMN play_by_escalated BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- who did what to which entity, for every mutating admin and player action. Nothing is deleted.
+CREATE TABLE audit_events (
+   id          INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   created     BIGINT NOT NULL,
+   -- serialized Actor, e.g. "discord:<user id>", "api_token:<token id>" or "system"
+   actor       TEXT NOT NULL,
+   -- what they did, usually the command or route's name
+   action      TEXT NOT NULL,
+   -- serialized AuditEntity
+   entity_type TEXT NOT NULL,
+   entity_id   TEXT NOT NULL,
+   -- the entity as JSON before and after; NULL when it didn't exist (or wasn't recorded)
+   before_json TEXT,
+   after_json  TEXT
+);
+CREATE INDEX audit_events_entity ON audit_events(entity_type, entity_id);
+CREATE INDEX audit_events_actor ON audit_events(actor);
+CREATE INDEX audit_events_created ON audit_events(created);
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- players contesting the reported result of a finished race. Disputes wait for an admin, who
+-- either keeps the result or corrects it. Nothing is deleted, so this is the race's history.
+CREATE TABLE race_disputes (
+   id                  INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id     INTEGER NOT NULL,
+   -- the player who disputed the result
+   opener_discord_id   TEXT NOT NULL,
+   reason              TEXT NOT NULL,
+   evidence_url        TEXT,
+   -- serialized DisputeState
+   state               TEXT NOT NULL,
+   created             BIGINT NOT NULL,
+   -- the admin who resolved it
+   resolver_discord_id TEXT,
+   resolution_note     TEXT,
+   resolved            BIGINT,
+   FOREIGN KEY(bracket_race_id) REFERENCES bracket_races(id)
+);
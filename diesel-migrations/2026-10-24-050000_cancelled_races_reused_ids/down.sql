@@ -0,0 +1,20 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE __new_cancelled_races (
+   id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id INTEGER NOT NULL UNIQUE,
+   bracket_id      INTEGER NOT NULL,
+   player_ids      TEXT NOT NULL,
+   title           TEXT NOT NULL,
+   scheduled_for   BIGINT NOT NULL,
+   cancelled       BIGINT NOT NULL,
+   FOREIGN KEY(bracket_id) REFERENCES brackets(id)
+);
+
+-- only the latest cancellation of each race survives
+INSERT INTO __new_cancelled_races(id, bracket_race_id, bracket_id, player_ids, title, scheduled_for, cancelled)
+SELECT                            id, bracket_race_id, bracket_id, player_ids, title, scheduled_for, cancelled
+FROM cancelled_races
+WHERE id IN (SELECT MAX(id) FROM cancelled_races GROUP BY bracket_race_id);
+
+DROP TABLE cancelled_races;
+ALTER TABLE __new_cancelled_races RENAME TO cancelled_races;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- bracket race ids get reused once the race with the highest id is deleted, so the same id can be
+-- cancelled more than once. Calendar events for cancelled races use this table's id instead.
+CREATE TABLE __new_cancelled_races (
+   id              INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id INTEGER NOT NULL,
+   bracket_id      INTEGER NOT NULL,
+   -- serialized list of every player who was in the race
+   player_ids      TEXT NOT NULL,
+   -- "<bracket>: <player> vs <player>", as of when it was cancelled
+   title           TEXT NOT NULL,
+   scheduled_for   BIGINT NOT NULL,
+   cancelled       BIGINT NOT NULL,
+   FOREIGN KEY(bracket_id) REFERENCES brackets(id)
+);
+
+INSERT INTO __new_cancelled_races(id, bracket_race_id, bracket_id, player_ids, title, scheduled_for, cancelled)
+SELECT                            id, bracket_race_id, bracket_id, player_ids, title, scheduled_for, cancelled
+FROM cancelled_races;
+
+DROP TABLE cancelled_races;
+ALTER TABLE __new_cancelled_races RENAME TO cancelled_races;
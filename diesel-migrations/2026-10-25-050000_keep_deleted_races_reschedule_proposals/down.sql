@@ -0,0 +1,22 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE __new_reschedule_proposals (
+   id                   INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id      INTEGER NOT NULL,
+   proposer_discord_id  TEXT NOT NULL,
+   proposed_time        BIGINT NOT NULL,
+   previous_time        BIGINT,
+   state                TEXT NOT NULL,
+   created              BIGINT NOT NULL,
+   responder_discord_id TEXT,
+   resolved             BIGINT,
+   FOREIGN KEY(bracket_race_id) REFERENCES bracket_races(id)
+);
+
+-- proposals for deleted races can't point at them any more
+INSERT INTO __new_reschedule_proposals(id, bracket_race_id, proposer_discord_id, proposed_time, previous_time, state, created, responder_discord_id, resolved)
+SELECT                                 id, bracket_race_id, proposer_discord_id, proposed_time, previous_time, state, created, responder_discord_id, resolved
+FROM reschedule_proposals
+WHERE race_deleted IS NULL;
+
+DROP TABLE reschedule_proposals;
+ALTER TABLE __new_reschedule_proposals RENAME TO reschedule_proposals;
@@ -0,0 +1,28 @@
+-- Your SQL goes here
+-- proposals outlive their race, so that deleting a race (e.g. rolling back a round) doesn't lose
+-- its history. Race ids get reused, so proposals for deleted races are marked as such rather than
+-- pointing at whatever race has that id now.
+CREATE TABLE __new_reschedule_proposals (
+   id                   INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   bracket_race_id      INTEGER NOT NULL,
+   -- the player who wants to move the race (or the admin who moved it)
+   proposer_discord_id  TEXT NOT NULL,
+   proposed_time        BIGINT NOT NULL,
+   -- what the race was scheduled for when this was proposed
+   previous_time        BIGINT,
+   -- serialized ProposalState
+   state                TEXT NOT NULL,
+   created              BIGINT NOT NULL,
+   -- whoever accepted, declined or countered it
+   responder_discord_id TEXT,
+   resolved             BIGINT,
+   -- when bracket_race_id was deleted
+   race_deleted         BIGINT
+);
+
+INSERT INTO __new_reschedule_proposals(id, bracket_race_id, proposer_discord_id, proposed_time, previous_time, state, created, responder_discord_id, resolved)
+SELECT                                 id, bracket_race_id, proposer_discord_id, proposed_time, previous_time, state, created, responder_discord_id, resolved
+FROM reschedule_proposals;
+
+DROP TABLE reschedule_proposals;
+ALTER TABLE __new_reschedule_proposals RENAME TO reschedule_proposals;
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE bracket_race_infos DROP COLUMN times_suggested;
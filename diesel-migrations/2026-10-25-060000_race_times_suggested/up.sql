@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- when the bot DMed everyone in the race times they're all available for, so it only does it once
+ALTER TABLE bracket_race_infos ADD COLUMN times_suggested BIGINT NULL;
@@ -24,6 +24,12 @@ const TOPNAV_PAGES: Page[] = [
     navItemSelector: '#previous-seasons-link',
   },
 
+  // Stats
+  {
+    pathFormat: new RegExp('^/stats$'),
+    navItemSelector: '#stats-link',
+  },
+
   // Asyncs
   {
     pathFormat: new RegExp('^/asyncs$'),
@@ -30,6 +30,7 @@ async function get_qualifiers(season_ordinal) {
             } else {
                 parsed.Ok.forEach(q => {
                     q.time = seconds_to_hmmss(q.time);
+                    q.score_display = q.score === null ? '' : seconds_to_hmmss(q.score);
                 });
                 return parsed.Ok;
             }
@@ -39,16 +40,26 @@ async function get_qualifiers(season_ordinal) {
 function build_row(template, qual_row, seen) {
     let row = template.content.cloneNode(true).querySelector('tr');
     let cols = row.querySelectorAll('td');
-    let [place, player, time, delete_] = cols;
+    let [place, player, score, time, delete_] = cols;
     let name = qual_row.player_name;
-    if (seen.players[name]) {
+    const placeContentContainer = place.querySelector('span');
+    if (qual_row.review_state === 'Rejected') {
         row.classList.add("hidden", "obsolete-qualifier-times");
-        const placeContentContainer = place.querySelector('span');
-        placeContentContainer.textContent = "(obsolete)";
+        placeContentContainer.textContent = "(rejected)";
+    } else if (seen.players[name]) {
+        // players who don't have a score yet have all of their attempts shown
+        if (!qual_row.counts && qual_row.score !== null) {
+            row.classList.add("hidden", "obsolete-qualifier-times");
+            placeContentContainer.textContent = "(obsolete)";
+        }
     } else {
-        const placeContentContainer = place.querySelector('span');
-        placeContentContainer.textContent = seen.place;
-        seen.place += 1;
+        if (qual_row.score === null) {
+            placeContentContainer.textContent = "-";
+        } else {
+            placeContentContainer.textContent = seen.place;
+            seen.place += 1;
+        }
+        score.querySelector('span').textContent = qual_row.score_display;
         seen.players[name] = true;
     }
     let player_anchor = player.querySelector('a');
@@ -58,6 +69,9 @@ function build_row(template, qual_row, seen) {
     let time_anchor = time.querySelector('a');
     time_anchor.href = qual_row.vod;
     time_anchor.textContent = qual_row.time;
+    if (qual_row.review_state === 'Pending') {
+        time_anchor.textContent += ' (unverified)';
+    }
 
     if (delete_) {
         delete_.dataset['target_id'] = qual_row.id;
@@ -65,10 +79,22 @@ function build_row(template, qual_row, seen) {
     return row;
 }
 
+/*
+ the server sorts by time; this groups each player's attempts together, ordered by their score
+ (players who don't have a score yet go last), with the attempts that make up the score first.
+ Array.sort is stable, so each player's attempts stay sorted by time otherwise.
+ */
+function by_score(a, b) {
+    let score = q => q.score === null ? Infinity : q.score;
+    return (score(a) - score(b))
+        || (a.player_id - b.player_id)
+        || (b.counts - a.counts);
+}
+
 function build_rows(qualifiers) {
     const template = document.querySelector('template#qualifier_submission');
     var seen = { place: 1, players: {} };
-    let rows = qualifiers.map(e => build_row(template, e, seen));
+    let rows = [...qualifiers].sort(by_score).map(e => build_row(template, e, seen));
     return rows;
 }
 
@@ -462,6 +462,8 @@ fn make_bracket(
                 player_1_result: Some(serde_json::to_string(&race_result.player_1_result).unwrap()),
                 player_2_result: Some(serde_json::to_string(&race_result.player_2_result).unwrap()),
                 outcome: Some(serde_json::to_string(&race_result.outcome).unwrap()),
+                best_of: 1,
+                placement_race: false,
             };
             if let Err(e) = nbr.save(db) {
                 return Err(anyhow!("Error saving race {p1_name} vs {p2_name}: {e}"));
@@ -16,6 +16,8 @@ struct Qualifier {
     player_name: String,
     time: u64,
     vod: String,
+    score: Option<u64>,
+    counts: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -27,15 +29,29 @@ fn main() -> Result<(), Box<dyn Error>> {
     let resp = reqwest::blocking::get(&url)?.json::<Result<Vec<Qualifier>, String>>()??;
 
     let mut wtr = csv::Writer::from_writer(std::io::stdout());
-    wtr.write_record(&["player_name", "time", "vod"])?;
+    wtr.write_record(&["player_name", "score", "best_time", "vod"])?;
     let mut seen: HashSet<String> = Default::default();
 
+    // sorted by time, so the first counted qualifier for each player is their best one
+    let mut rows = vec![];
     for qual in resp {
+        let score = match qual.score {
+            Some(s) if qual.counts => s,
+            _ => continue,
+        };
         if !seen.insert(qual.player_name.clone()) {
             continue;
         }
-        let hms = format_hms(qual.time);
-        wtr.write_record(&[qual.player_name, hms, qual.vod])?;
+        rows.push((score, qual));
+    }
+    rows.sort_by_key(|(score, _)| *score);
+    for (score, qual) in rows {
+        wtr.write_record(&[
+            qual.player_name,
+            format_hms(score),
+            format_hms(qual.time),
+            qual.vod,
+        ])?;
     }
     wtr.flush()?;
     Ok(())
@@ -0,0 +1,12 @@
+use nmg_league_bot::db::raw_diesel_cxn_from_env;
+use nmg_league_bot::models::ratings::recompute_ratings;
+
+/// recomputes every player's rating from scratch. Ratings are updated whenever a race finishes,
+/// but this is needed after bulk changes like `add_historical_data`.
+fn main() -> anyhow::Result<()> {
+    dotenv::dotenv()?;
+    let mut cxn = raw_diesel_cxn_from_env()?;
+    let n = recompute_ratings(&mut cxn)?;
+    println!("Saved {n} ratings");
+    Ok(())
+}
@@ -74,6 +74,7 @@ async fn run_bracket(
                 player_1_result: p1r,
                 player_2: other_guy,
                 player_2_result: p2r,
+                racetime_gg_url: None,
                 force_update: false,
                 channel_id: chans.match_results,
             };
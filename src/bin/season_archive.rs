@@ -0,0 +1,74 @@
+use clap::{Parser, Subcommand};
+use nmg_league_bot::db::raw_diesel_cxn_from_env;
+use nmg_league_bot::models::season_archive::{export_season, SeasonArchive};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+/// exports a season to a JSON archive (or a directory of CSVs, one per table), or imports one
+/// into the database in `DATABASE_URL`
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    Export {
+        /// Season number to export
+        #[arg(short, long)]
+        season: i32,
+        /// File to write the archive to (or directory to write CSVs to, with --csv)
+        #[arg(short, long)]
+        out: PathBuf,
+        /// Write a CSV per table instead of a JSON archive
+        #[arg(long)]
+        csv: bool,
+    },
+    Import {
+        /// Archive to import, or a directory of CSVs exported with --csv
+        archive: PathBuf,
+        /// Season number to import it as, if not the one it was exported from
+        #[arg(short, long)]
+        season: Option<i32>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    dotenv::dotenv()?;
+    let args = Args::parse();
+    let mut db = raw_diesel_cxn_from_env()?;
+    match args.command {
+        Command::Export { season, out, csv } => {
+            let archive = export_season(season, &mut db)?;
+            if csv {
+                archive.write_csvs(&out)?;
+            } else {
+                serde_json::to_writer_pretty(BufWriter::new(File::create(&out)?), &archive)?;
+            }
+            println!(
+                "Exported season {season}: {} brackets, {} races, {} qualifiers",
+                archive.brackets.len(),
+                archive.races.len(),
+                archive.qualifiers.len()
+            );
+        }
+        Command::Import { archive, season } => {
+            let archive: SeasonArchive = if archive.is_dir() {
+                SeasonArchive::read_csvs(&archive)?
+            } else {
+                serde_json::from_reader(BufReader::new(File::open(archive)?))?
+            };
+            let imported = archive.import(season, &mut db)?;
+            println!(
+                "Imported season {} ({} brackets, {} races). Run recompute_ratings to include it in ratings.",
+                imported.ordinal,
+                archive.brackets.len(),
+                archive.races.len()
+            );
+        }
+    }
+    Ok(())
+}
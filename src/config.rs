@@ -22,6 +22,7 @@ const SIRIUS_INBOX_CHANNEL_ID_VAR: &str = "SIRIUS_INBOX_CHANNEL_ID";
 const ZSR_CHANNEL_ID_VAR: &str = "ZSR_CHANNEL_ID";
 const COMMENTARY_DISCUSSION_CHANNEL_ID_VAR: &str = "COMMENTARY_DISCUSSION_CHANNEL_ID";
 const MATCH_RESULTS_CHANNEL_ID_VAR: &str = "MATCH_RESULTS_CHANNEL_ID";
+const ADMIN_CHANNEL_ID_VAR: &str = "ADMIN_CHANNEL_ID";
 
 const CLIENT_ID_VAR: &str = "CLIENT_ID";
 const CLIENT_SECRET_VAR: &str = "CLIENT_SECRET";
@@ -79,6 +80,8 @@ pub struct Config {
     pub zsr_channel_id: Id<ChannelMarker>,
     pub commentary_discussion_channel_id: Id<ChannelMarker>,
     pub match_results_channel_id: Id<ChannelMarker>,
+    /// where admins get told about things that need their attention, like missed deadlines
+    pub admin_channel_id: Id<ChannelMarker>,
 
     pub discord_authorize_url: String,
 
@@ -153,6 +156,7 @@ impl Config {
             zsr_channel_id: id_from_env(ZSR_CHANNEL_ID_VAR),
             commentary_discussion_channel_id: id_from_env(COMMENTARY_DISCUSSION_CHANNEL_ID_VAR),
             match_results_channel_id: id_from_env(MATCH_RESULTS_CHANNEL_ID_VAR),
+            admin_channel_id: id_from_env(ADMIN_CHANNEL_ID_VAR),
             discord_client_id: env_var(CLIENT_ID_VAR),
             discord_client_secret: env_var(CLIENT_SECRET_VAR),
             discord_authorize_url: env_var(AUTHORIZE_URL_VAR),
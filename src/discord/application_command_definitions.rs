@@ -1,10 +1,15 @@
 use crate::discord::command_option_default;
 use crate::discord::constants::{
-    ADD_PLAYER_TO_BRACKET_CMD, CANCEL_ASYNC_CMD, CHECK_USER_INFO_CMD, COMMENTATORS_CMD,
-    CREATE_ASYNC_CMD, CREATE_BRACKET_CMD, CREATE_PLAYER_CMD, CREATE_SEASON_CMD, FINISH_BRACKET_CMD,
-    GENERATE_PAIRINGS_CMD, REPORT_RACE_CMD, RESCHEDULE_RACE_CMD, SCHEDULE_RACE_CMD,
-    SEE_UNSCHEDULED_RACES_CMD, SET_RESTREAM_CMD, SET_SEASON_STATE_CMD, SUBMIT_QUALIFIER_CMD,
-    UPDATE_FINISHED_RACE_CMD, UPDATE_USER_INFO_CMD, USER_PROFILE_CMD,
+    ADD_PLAYER_TO_BRACKET_CMD, AVAILABILITY_CMD, CANCEL_ASYNC_CMD, CHECK_USER_INFO_CMD,
+    COMMENTATORS_CMD, CREATE_API_TOKEN_CMD, CREATE_ASYNC_CMD, CREATE_BRACKET_CMD,
+    CREATE_PLAYER_CMD, CREATE_SEASON_CMD, DISPUTES_CMD, DISPUTE_RACE_CMD,
+    DROP_PLAYER_FROM_BRACKET_CMD, FINISH_BRACKET_CMD, GENERATE_PAIRINGS_CMD, OVERDUE_RACES_CMD,
+    REPORT_PLACEMENT_RACE_CMD, REPORT_RACE_CMD, RESCHEDULE_RACE_CMD, RESOLVE_DISPUTE_CMD,
+    REVIEW_QUALIFIERS_CMD, REVOKE_API_TOKEN_CMD, ROLLBACK_ROUND_CMD, SCHEDULE_RACE_CMD,
+    SEE_UNSCHEDULED_RACES_CMD, SET_BRACKET_BEST_OF_CMD, SET_BRACKET_SCORING_CMD,
+    SET_QUALIFIER_RULES_CMD, SET_RESTREAM_CMD, SET_ROUND_DEADLINES_CMD, SET_SEASON_STATE_CMD,
+    SUBMIT_QUALIFIER_CMD, SUGGEST_TIMES_CMD, UPDATE_FINISHED_RACE_CMD, UPDATE_USER_INFO_CMD,
+    USER_PROFILE_CMD,
 };
 use nmg_league_bot::models::season::SeasonState;
 use twilight_model::application::command::{
@@ -13,8 +18,11 @@ use twilight_model::application::command::{
 };
 
 use nmg_league_bot::config::CONFIG;
+use nmg_league_bot::models::bracket_rounds::MissedDeadlinePolicy;
 use nmg_league_bot::models::brackets::BracketType;
+use nmg_league_bot::models::scoring::Tiebreaker;
 use nmg_league_bot::utils::enum_variants_serialized;
+use nmg_league_bot::worker_funcs::DroppedPlayerRaces;
 use twilight_model::guild::Permissions;
 use twilight_util::builder::command::CommandBuilder;
 
@@ -182,6 +190,44 @@ pub fn application_command_definitions() -> Vec<Command> {
         kind: CommandOptionType::String,
         ..command_option_default()
     })
+    .option(CommandOption {
+        description:
+            "Elimination brackets only: seed from this bracket's standings instead of qualifiers"
+                .to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "seed_from_bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description:
+            "Swiss brackets only: what a bye counts as (2 = a win, 1 = a draw, 0 = a loss). Default 2"
+                .to_string(),
+        description_localizations: None,
+        max_value: Some(CommandOptionValue::Integer(2)),
+        min_value: Some(CommandOptionValue::Integer(0)),
+        name: "bye_points".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Group brackets only: the most players in each group's races. Default 4"
+            .to_string(),
+        description_localizations: None,
+        max_value: Some(CommandOptionValue::Integer(8)),
+        min_value: Some(CommandOptionValue::Integer(3)),
+        name: "group_size".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
     .build();
 
     let add_player_to_bracket = CommandBuilder::new(
@@ -213,6 +259,328 @@ pub fn application_command_definitions() -> Vec<Command> {
     })
     .build();
 
+    let aggregations = ["Best", "AverageOfBest", "Last"]
+        .into_iter()
+        .map(|s| CommandOptionChoice {
+            name: s.to_string(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::String(s.to_string()),
+        })
+        .collect();
+
+    let set_qualifier_rules = CommandBuilder::new(
+        SET_QUALIFIER_RULES_CMD.to_string(),
+        "Set how a season's qualifier scores are computed, and how many attempts players get"
+            .to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "The Season's ordinal".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "season_ordinal".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        choices: Some(aggregations),
+        description: "Which submissions make up a player's score (default Best)".to_string(),
+        name: "aggregation".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "How many of the best times to average (for AverageOfBest)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "average_of".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "How many qualifiers each player may submit (0 for unlimited)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(0)),
+        name: "max_attempts".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .build();
+
+    let review_qualifiers = CommandBuilder::new(
+        REVIEW_QUALIFIERS_CMD.to_string(),
+        "Verify or reject the active season's pending qualifier submissions".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .build();
+
+    let tiebreaker_names = enum_iterator::all::<Tiebreaker>()
+        .map(|t| format!("{t:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let set_bracket_scoring = CommandBuilder::new(
+        SET_BRACKET_SCORING_CMD.to_string(),
+        "Set a bracket's scoring rules (points are doubled: a 1 point win is 2)".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Bracket ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Half-points for a win (default 2)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(0)),
+        name: "win_points".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Half-points for a draw (default 1)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(0)),
+        name: "draw_points".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Half-points for a loss (default 0)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(0)),
+        name: "loss_points".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Minutes a forfeit counts as for time tiebreakers (default 180)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "forfeit_penalty_minutes".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: format!("In order, comma separated: {tiebreaker_names}"),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "tiebreakers".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description:
+            "Half-points for 1st, 2nd, ... in placement races, comma separated (\"none\" to reset)"
+                .to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "placement_points".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
+    let set_bracket_best_of = CommandBuilder::new(
+        SET_BRACKET_BEST_OF_CMD.to_string(),
+        "Set how many games a bracket's races are (applies to races without results)".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Bracket ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Best of how many games (odd, 1 for single races)".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "best_of".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .build();
+
+    let missed_deadline_policies = enum_variants_serialized::<MissedDeadlinePolicy>()
+        .map(|s| CommandOptionChoice {
+            name: s.clone(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::String(s),
+        })
+        .collect();
+
+    let set_round_deadlines = CommandBuilder::new(
+        SET_ROUND_DEADLINES_CMD.to_string(),
+        "Set (or clear) a round's scheduling and play-by deadlines".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Bracket ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Round number".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "round".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Races must be scheduled by the end of this day (YYYY/MM/DD, US/Eastern)"
+            .to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "schedule_by".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Races must be played by the end of this day (YYYY/MM/DD, US/Eastern)"
+            .to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "play_by".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        choices: Some(missed_deadline_policies),
+        description: "What happens to races that aren't played by then (default: nothing)"
+            .to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "missed_deadline".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
+    let overdue_races = CommandBuilder::new(
+        OVERDUE_RACES_CMD.to_string(),
+        "See races that missed their play-by deadline and are waiting for an admin decision"
+            .to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .build();
+
+    let dropped_player_races = enum_variants_serialized::<DroppedPlayerRaces>()
+        .map(|s| CommandOptionChoice {
+            name: s.clone(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::String(s),
+        })
+        .collect();
+
+    let drop_player_from_bracket = CommandBuilder::new(
+        DROP_PLAYER_FROM_BRACKET_CMD.to_string(),
+        "Drop a player out of a bracket".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "The player".to_string(),
+        description_localizations: None,
+        name: "user".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::User,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Bracket ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        choices: Some(dropped_player_races),
+        description: "What to do with their unfinished races".to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "unfinished_races".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
     let finish_bracket = CommandBuilder::new(
         FINISH_BRACKET_CMD.to_string(),
         "Finish bracket".to_string(),
@@ -399,6 +767,56 @@ pub fn application_command_definitions() -> Vec<Command> {
     })
     .build();
 
+    let report_placement_race = CommandBuilder::new(
+        REPORT_PLACEMENT_RACE_CMD.to_string(),
+        "Report results for a race with more than two players".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Race id".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: None,
+        name: "race_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: r#"Comma separated "name result", e.g. "alice 1:23:45, bob forfeit""#
+            .to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "results".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Racetime url (if any)".to_string(),
+        description_localizations: None,
+        max_length: None,
+        min_length: None,
+        name: "racetime_url".to_string(),
+        name_localizations: None,
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Overwrite results of a race that's already finished".to_string(),
+        description_localizations: None,
+        name: "force".to_string(),
+        name_localizations: None,
+        required: Some(false),
+        kind: CommandOptionType::Boolean,
+        ..command_option_default()
+    })
+    .build();
+
     let update_finished_race = CommandBuilder::new(
         UPDATE_FINISHED_RACE_CMD.to_string(),
         "Report race".to_string(),
@@ -439,7 +857,8 @@ pub fn application_command_definitions() -> Vec<Command> {
         ..command_option_default()
     })
     .option(CommandOption {
-        description: "Racetime url (if any)".to_string(),
+        description: "Racetime url (required for series: identifies the game to correct)"
+            .to_string(),
         description_localizations: None,
         max_length: None,
         min_length: None,
@@ -450,6 +869,108 @@ pub fn application_command_definitions() -> Vec<Command> {
     })
     .build();
 
+    let dispute_race = CommandBuilder::new(
+        DISPUTE_RACE_CMD,
+        "Dispute the reported result of one of your races",
+        CommandType::ChatInput,
+    )
+    .option(CommandOption {
+        description: "Race id (at the bottom of its match results post)".to_string(),
+        name: "race_id".to_string(),
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "What's wrong with the result".to_string(),
+        max_length: Some(1000),
+        name: "reason".to_string(),
+        required: Some(true),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Link to evidence (e.g. a VOD)".to_string(),
+        name: "evidence_url".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
+    let disputes = CommandBuilder::new(
+        DISPUTES_CMD,
+        "See disputed race results that are waiting for an admin decision",
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .build();
+
+    let resolve_dispute = CommandBuilder::new(
+        RESOLVE_DISPUTE_CMD,
+        "Keep or correct a disputed race result",
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Dispute id".to_string(),
+        name: "dispute_id".to_string(),
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        choices: Some(vec![
+            CommandOptionChoice {
+                name: "Keep the reported result".to_string(),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String("keep".to_string()),
+            },
+            CommandOptionChoice {
+                name: "Correct the result".to_string(),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String("correct".to_string()),
+            },
+        ]),
+        description: "What to do about it".to_string(),
+        name: "decision".to_string(),
+        required: Some(true),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Explanation for the players".to_string(),
+        max_length: Some(1000),
+        name: "note".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: r#"Corrected player 1 result ("forfeit" if forfeit, h:mm:ss otherwise)"#
+            .to_string(),
+        name: "p1_result".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: r#"Corrected player 2 result ("forfeit" if forfeit, h:mm:ss otherwise)"#
+            .to_string(),
+        name: "p2_result".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "Racetime url (required to correct a series: identifies the game)".to_string(),
+        name: "racetime_url".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
     let generate_pairings = CommandBuilder::new(
         GENERATE_PAIRINGS_CMD.to_string(),
         "Generate next round pairings for a bracket".to_string(),
@@ -469,6 +990,64 @@ pub fn application_command_definitions() -> Vec<Command> {
     })
     .build();
 
+    let rollback_round = CommandBuilder::new(
+        ROLLBACK_ROUND_CMD.to_string(),
+        "Delete a bracket's latest round, if none of it has been played, so it can be regenerated"
+            .to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Bracket ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "bracket_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .build();
+
+    let create_api_token = CommandBuilder::new(
+        CREATE_API_TOKEN_CMD.to_string(),
+        "Create a token for the website API".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "What the token is for (e.g. scheduling bot)".to_string(),
+        description_localizations: None,
+        max_length: Some(100),
+        min_length: None,
+        name: "name".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::String,
+        ..command_option_default()
+    })
+    .build();
+
+    let revoke_api_token = CommandBuilder::new(
+        REVOKE_API_TOKEN_CMD.to_string(),
+        "Revoke one of your website API tokens".to_string(),
+        CommandType::ChatInput,
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+    .option(CommandOption {
+        description: "Token ID".to_string(),
+        description_localizations: None,
+        max_value: None,
+        min_value: Some(CommandOptionValue::Integer(1)),
+        name: "token_id".to_string(),
+        name_localizations: None,
+        required: Some(true),
+        kind: CommandOptionType::Integer,
+        ..command_option_default()
+    })
+    .build();
+
     let submit_qualifier = CommandBuilder::new(
         SUBMIT_QUALIFIER_CMD.to_string(),
         "Submit a time for qualification",
@@ -619,6 +1198,99 @@ pub fn application_command_definitions() -> Vec<Command> {
     )
     .build();
 
+    let weekdays: Vec<CommandOptionChoice> = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, day)| CommandOptionChoice {
+        name: day.to_string(),
+        name_localizations: None,
+        value: CommandOptionChoiceValue::Integer(i as i64),
+    })
+    .collect();
+
+    let availability = CommandBuilder::new(
+        AVAILABILITY_CMD,
+        "Set the times you're usually free to race",
+        CommandType::ChatInput,
+    )
+    .option(CommandOption {
+        description: "add a weekly window you're available in".to_string(),
+        kind: CommandOptionType::SubCommand,
+        name: "add".to_string(),
+        options: Some(vec![
+            CommandOption {
+                choices: Some(weekdays),
+                description: "day of the week".to_string(),
+                kind: CommandOptionType::Integer,
+                name: "day".to_string(),
+                required: Some(true),
+                ..command_option_default()
+            },
+            CommandOption {
+                description: "start time, e.g. 19:00 or 7pm".to_string(),
+                kind: CommandOptionType::String,
+                name: "start".to_string(),
+                required: Some(true),
+                ..command_option_default()
+            },
+            CommandOption {
+                description: "end time, e.g. 23:30 or 11:30pm (24:00 for midnight)".to_string(),
+                kind: CommandOptionType::String,
+                name: "end".to_string(),
+                required: Some(true),
+                ..command_option_default()
+            },
+            CommandOption {
+                autocomplete: Some(true),
+                description: "your time zone, e.g. America/New_York (wait for suggestions)"
+                    .to_string(),
+                kind: CommandOptionType::String,
+                name: "timezone".to_string(),
+                required: Some(true),
+                ..command_option_default()
+            },
+        ]),
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "see your availability".to_string(),
+        kind: CommandOptionType::SubCommand,
+        name: "list".to_string(),
+        options: Some(vec![]),
+        ..command_option_default()
+    })
+    .option(CommandOption {
+        description: "remove all of your availability".to_string(),
+        kind: CommandOptionType::SubCommand,
+        name: "clear".to_string(),
+        options: Some(vec![]),
+        ..command_option_default()
+    })
+    .build();
+
+    let suggest_times = CommandBuilder::new(
+        SUGGEST_TIMES_CMD,
+        "Find times that you and your opponent are both available to race",
+        CommandType::ChatInput,
+    )
+    .option(CommandOption {
+        description: "Opponent (only needed if you have more than one race to schedule)"
+            .to_string(),
+        name: "opponent".to_string(),
+        required: Some(false),
+        kind: CommandOptionType::User,
+        ..command_option_default()
+    })
+    .build();
+
     let commentator_bundle = CommandBuilder::new(
         COMMENTATORS_CMD,
         "Commentary subcommands",
@@ -701,20 +1373,36 @@ pub fn application_command_definitions() -> Vec<Command> {
         cancel_async_race,
         create_season,
         set_season_state,
+        set_qualifier_rules,
+        review_qualifiers,
         create_bracket,
         finish_bracket,
+        set_bracket_scoring,
+        set_bracket_best_of,
+        set_round_deadlines,
+        overdue_races,
         create_player,
         add_player_to_bracket,
+        drop_player_from_bracket,
         schedule_race,
         report_race,
+        report_placement_race,
         generate_pairings,
+        rollback_round,
         reschedule_race,
         update_finished_race,
+        dispute_race,
+        disputes,
+        resolve_dispute,
         submit_qualifier,
         update_user_info,
         check_user_info,
         see_unscheduled_races,
+        availability,
+        suggest_times,
         set_restream,
+        create_api_token,
+        revoke_api_token,
         // user command[s]
         user_profile,
         commentator_bundle,
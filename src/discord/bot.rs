@@ -1,7 +1,9 @@
 use core::default::Default;
 use std::sync::Arc;
 
+use chrono::{TimeZone, Utc};
 use diesel::SqliteConnection;
+use either::Either;
 use lazy_static::lazy_static;
 use log::{debug, info, warn};
 use nmg_league_bot::config::CONFIG;
@@ -30,24 +32,37 @@ use twilight_util::builder::InteractionResponseDataBuilder;
 use crate::discord::application_command_definitions::application_command_definitions;
 use crate::discord::components::action_row;
 use crate::discord::constants::{
-    CUSTOM_ID_FINISH_RUN, CUSTOM_ID_FORFEIT_MODAL, CUSTOM_ID_FORFEIT_MODAL_INPUT,
-    CUSTOM_ID_FORFEIT_RUN, CUSTOM_ID_START_RUN, CUSTOM_ID_USER_TIME, CUSTOM_ID_USER_TIME_MODAL,
+    CUSTOM_ID_ACCEPT_RESCHEDULE, CUSTOM_ID_CONFIRM_SLOT, CUSTOM_ID_COUNTER_RESCHEDULE,
+    CUSTOM_ID_COUNTER_RESCHEDULE_MODAL, CUSTOM_ID_COUNTER_RESCHEDULE_MODAL_INPUT,
+    CUSTOM_ID_DECLINE_RESCHEDULE, CUSTOM_ID_FINISH_RUN, CUSTOM_ID_FORFEIT_MODAL,
+    CUSTOM_ID_FORFEIT_MODAL_INPUT, CUSTOM_ID_FORFEIT_RUN, CUSTOM_ID_REJECT_QUALIFIER,
+    CUSTOM_ID_REJECT_QUALIFIER_MODAL, CUSTOM_ID_REJECT_QUALIFIER_MODAL_INPUT, CUSTOM_ID_START_RUN,
+    CUSTOM_ID_USER_TIME, CUSTOM_ID_USER_TIME_MODAL, CUSTOM_ID_VERIFY_QUALIFIER,
     CUSTOM_ID_VOD_MODAL, CUSTOM_ID_VOD_MODAL_INPUT, CUSTOM_ID_VOD_READY,
 };
 use crate::discord::discord_state::DiscordOperations;
 use crate::discord::discord_state::DiscordState;
-use crate::discord::interaction_handlers::application_commands::handle_application_interaction;
+use crate::discord::interaction_handlers::application_commands::{
+    handle_application_interaction, parse_scheduling_text,
+};
 use crate::discord::interactions_utils::{
-    button_component, plain_interaction_response, update_resp_to_plain_content,
+    button_component, plain_ephemeral_response, plain_interaction_response,
+    update_resp_to_plain_content,
 };
 use crate::discord::reaction_handlers::{handle_reaction_add, handle_reaction_remove};
-use crate::discord::ErrorResponse;
+use crate::discord::{self, ErrorResponse, ScheduleRaceError};
 use crate::{Shutdown, Webhooks};
 use nmg_league_bot::db::DieselConnectionManager;
 use nmg_league_bot::models::asyncs::race::AsyncRace;
 use nmg_league_bot::models::asyncs::race_run::AsyncRaceRun;
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
+use nmg_league_bot::models::bracket_races::BracketRace;
+use nmg_league_bot::models::player::Player;
+use nmg_league_bot::models::qualifer_submission::ReviewState;
+use nmg_league_bot::models::reschedule_proposals::{ProposalState, RescheduleProposal};
 use nmg_league_bot::twitch_client::TwitchClientBundle;
 use nmg_league_bot::utils::ResultErrToString;
+use nmg_league_bot::BracketRaceState;
 
 pub(crate) fn launch(
     client: Arc<Client>,
@@ -203,16 +218,26 @@ async fn handle_async_run_start(
     let race = rr
         .get_race(&mut conn)
         .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e.to_string()))?;
+    let audit = NewAuditEvent::new(
+        &discord::interaction_actor(&interaction, USER_FACING_ERROR)?,
+        "start_async_run",
+        AuditEntity::AsyncRace,
+        race.id,
+    )
+    .before(&rr);
     rr.start();
     match rr.save(&mut conn).await {
-        Ok(_) => Ok(Some(
-            run_started_interaction_response(&race, &rr, None).map_err(|e| {
-                ErrorResponse::new(
-                    USER_FACING_ERROR,
-                    format!("Error sending the /run started/ interaction response {}", e),
-                )
-            })?,
-        )),
+        Ok(_) => {
+            audit.after(&rr).record(&mut conn);
+            Ok(Some(
+                run_started_interaction_response(&race, &rr, None).map_err(|e| {
+                    ErrorResponse::new(
+                        USER_FACING_ERROR,
+                        format!("Error sending the /run started/ interaction response {}", e),
+                    )
+                })?,
+            ))
+        }
         Err(e) => Err(ErrorResponse::new(
             USER_FACING_ERROR,
             format!("Error updating race run: {}", e),
@@ -223,6 +248,8 @@ async fn handle_async_run_start(
 async fn update_race_run<F>(
     message_id: Id<MessageMarker>,
     f: F,
+    actor: &Actor,
+    action: &str,
     conn: &mut SqliteConnection,
 ) -> Result<(), String>
 where
@@ -236,11 +263,14 @@ where
     };
     match rro {
         Some(mut rr) => {
+            let audit =
+                NewAuditEvent::new(actor, action, AuditEntity::AsyncRace, rr.race_id()).before(&rr);
             f(&mut rr);
             {
                 if let Err(e) = rr.save(conn).await {
                     Err(format!("Error saving race {}: {}", rr.id, e))
                 } else {
+                    audit.after(&rr).record(conn);
                     Ok(())
                 }
             }
@@ -300,10 +330,17 @@ async fn handle_run_forfeit_modal(
         .diesel_cxn()
         .await
         .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    let actor = discord::interaction_actor(&interaction, USER_FACING_ERROR)?;
     let ir = if FORFEIT_REGEX.is_match(&ut) {
-        update_race_run(mid, |rr| rr.forfeit(), &mut conn)
-            .await
-            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+        update_race_run(
+            mid,
+            |rr| rr.forfeit(),
+            &actor,
+            "forfeit_async_run",
+            &mut conn,
+        )
+        .await
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
 
         update_resp_to_plain_content(
             "You have forfeited this match. Please let the admins know if there are any issues.",
@@ -353,11 +390,14 @@ async fn handle_async_run_finish(
         .diesel_cxn()
         .await
         .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    let actor = discord::interaction_actor(&interaction, USER_FACING_ERROR)?;
     if let Err(e) = update_race_run(
         mid,
         |rr| {
             rr.finish();
         },
+        &actor,
+        "finish_async_run",
         &mut conn,
     )
     .await
@@ -433,14 +473,21 @@ async fn handle_user_time_modal(
         .diesel_cxn()
         .await
         .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
-    update_race_run(mid, |rr| rr.report_user_time(ut), &mut conn)
-        .await
-        .map_err(|e| {
-            ErrorResponse::new(
-                "Something went wrong reporting your time. Please ping FoxLisk.",
-                e,
-            )
-        })?;
+    let actor = discord::interaction_actor(&interaction, USER_FACING_ERROR)?;
+    update_race_run(
+        mid,
+        |rr| rr.report_user_time(ut),
+        &actor,
+        "report_async_time",
+        &mut conn,
+    )
+    .await
+    .map_err(|e| {
+        ErrorResponse::new(
+            "Something went wrong reporting your time. Please ping FoxLisk.",
+            e,
+        )
+    })?;
 
     let ir = InteractionResponse {
         kind: InteractionResponseType::UpdateMessage,
@@ -499,20 +546,520 @@ async fn handle_vod_modal(
         .await
         .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
 
-    update_race_run(mid, |rr| rr.set_vod(user_input), &mut conn)
-        .await
-        .map_err(|e| {
-            ErrorResponse::new(
-                "Something went wrong reporting your VoD. Please ping FoxLisk.",
-                format!("Error saving vod reporting: {}", e),
-            )
-        })?;
+    let actor = discord::interaction_actor(&interaction, USER_FACING_ERROR)?;
+    update_race_run(
+        mid,
+        |rr| rr.set_vod(user_input),
+        &actor,
+        "report_async_vod",
+        &mut conn,
+    )
+    .await
+    .map_err(|e| {
+        ErrorResponse::new(
+            "Something went wrong reporting your VoD. Please ping FoxLisk.",
+            format!("Error saving vod reporting: {}", e),
+        )
+    })?;
     let ir = plain_interaction_response(
         "Thank you, your race is completed. Please message the admins if there are any issues.",
     );
     Ok(Some(ir))
 }
 
+/// shows the next qualifier submission to review in place of the one that was just reviewed
+async fn update_to_next_pending_qualifier(
+    preamble: &str,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    let data = discord::next_pending_qualifier(Some(preamble), state)
+        .await
+        .map_err(|e| ErrorResponse::new("Error loading the next qualifier to review.", e))?;
+    Ok(Some(InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(data),
+    }))
+}
+
+/// only admins can review qualifiers. This is checked again on every button press, since the
+/// review message's buttons could outlive someone's admin role.
+async fn ensure_admin_interaction(
+    interaction: &Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<(), ErrorResponse> {
+    match state.application_command_run_by_admin(interaction).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ErrorResponse::new(
+            "Only admins can review qualifiers.",
+            "Non-admin tried to review a qualifier",
+        )),
+        Err(e) => Err(ErrorResponse::new("Error checking admin status.", e)),
+    }
+}
+
+fn parse_submission_id(id: &str) -> Result<i32, ErrorResponse> {
+    id.parse()
+        .map_err(|_| ErrorResponse::new("Invalid qualifier submission.", format!("Bad id {id}")))
+}
+
+async fn handle_verify_qualifier(
+    id: &str,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    ensure_admin_interaction(&interaction, state).await?;
+    let submission_id = parse_submission_id(id)?;
+    discord::review_qualifier_submission(
+        submission_id,
+        ReviewState::Verified,
+        interaction.author_id(),
+        None,
+        state,
+    )
+    .await
+    .map_err(|e| ErrorResponse::new("Error verifying qualifier.", e))?;
+    update_to_next_pending_qualifier("Verified.", state).await
+}
+
+fn handle_reject_qualifier_button(id: &str) -> InteractionResponse {
+    create_modal(
+        &format!("{CUSTOM_ID_REJECT_QUALIFIER_MODAL}:{id}"),
+        "Why is this qualifier being rejected? The player will be sent this reason.",
+        "Reject qualifier",
+        vec![Component::TextInput(TextInput {
+            id: None,
+            custom_id: CUSTOM_ID_REJECT_QUALIFIER_MODAL_INPUT.to_string(),
+            label: Some("Reason".to_string()),
+            max_length: Some(1000),
+            min_length: Some(1),
+            placeholder: None,
+            required: Some(true),
+            style: TextInputStyle::Paragraph,
+            value: None,
+        })],
+    )
+}
+
+async fn handle_reject_qualifier_modal(
+    id: &str,
+    mut interaction_data: ModalInteractionData,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    const USER_FACING_ERROR: &str = "Error rejecting qualifier.";
+    ensure_admin_interaction(&interaction, state).await?;
+    let submission_id = parse_submission_id(id)?;
+    let reason = get_field_from_modal_components(
+        std::mem::take(&mut interaction_data.components),
+        CUSTOM_ID_REJECT_QUALIFIER_MODAL_INPUT,
+    )
+    .ok_or(ErrorResponse::new(
+        USER_FACING_ERROR,
+        "Error getting rejection reason from modal.",
+    ))?;
+    discord::review_qualifier_submission(
+        submission_id,
+        ReviewState::Rejected,
+        interaction.author_id(),
+        Some(reason),
+        state,
+    )
+    .await
+    .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    update_to_next_pending_qualifier("Rejected; the player has been sent a DM.", state).await
+}
+
+/// schedules a race for one of the times suggested by `/suggest_times`. Anyone in the race can
+/// pick one, but only while the race is still unscheduled.
+async fn handle_confirm_slot(
+    slot: &str,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    const USER_FACING_ERROR: &str = "Error scheduling race.";
+    let (race_id, when) = slot
+        .split_once(':')
+        .and_then(|(r, t)| {
+            let race_id = r.parse::<i32>().ok()?;
+            let when = Utc.timestamp_opt(t.parse().ok()?, 0).single()?;
+            Some((race_id, when))
+        })
+        .ok_or(ErrorResponse::new(
+            USER_FACING_ERROR,
+            format!("Bad slot {slot}"),
+        ))?;
+    let uid = interaction.author_id().ok_or(ErrorResponse::new(
+        USER_FACING_ERROR,
+        "No user found on slot confirmation",
+    ))?;
+    // schedule_race needs its own connection, so this one has to be gone by then
+    let (mut race, in_race) = {
+        let mut conn = state
+            .diesel_cxn()
+            .await
+            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+        let race = BracketRace::get_by_id(race_id, &mut conn)
+            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+        let in_race = match Player::get_by_discord_id(&uid.to_string(), &mut conn)
+            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+        {
+            Some(p) => race
+                .includes_player(&p, &mut conn)
+                .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?,
+            None => false,
+        };
+        (race, in_race)
+    };
+    if !in_race {
+        return Ok(Some(plain_ephemeral_response(
+            "Only the players in this race can pick a time for it.",
+        )));
+    }
+    if race
+        .state()
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+        != BracketRaceState::New
+    {
+        return Ok(Some(plain_ephemeral_response(
+            "This race has already been scheduled.",
+        )));
+    }
+    if when <= Utc::now() {
+        return Ok(Some(plain_ephemeral_response(
+            "That time has already passed. Try `/suggest_times` again.",
+        )));
+    }
+    // claim the race before scheduling it, so that it can't be scheduled twice
+    let claimed = {
+        let mut conn = state
+            .diesel_cxn()
+            .await
+            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+        race.claim_for_scheduling(&mut conn)
+            .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+    };
+    if !claimed {
+        return Ok(Some(plain_ephemeral_response(
+            "This race has already been scheduled.",
+        )));
+    }
+    let race_id = race.id;
+    let content =
+        match discord::schedule_race(race.clone(), when, &Actor::discord(uid), state).await {
+            Ok(s) => s,
+            Err(e) => {
+                // the race wasn't scheduled, so let someone else try
+                match state.diesel_cxn().await {
+                    Ok(mut conn) => {
+                        if let Err(re) = race.release_scheduling_claim(&mut conn) {
+                            warn!("Error releasing race {race_id} for scheduling: {re}");
+                        }
+                    }
+                    Err(re) => {
+                        warn!("Error releasing race {race_id} for scheduling: {re}");
+                    }
+                }
+                return match e {
+                    ScheduleRaceError::RaceFinished => Ok(Some(plain_ephemeral_response(
+                        "This race is already finished.",
+                    ))),
+                    e => Err(ErrorResponse::new(USER_FACING_ERROR, e)),
+                };
+            }
+        };
+    Ok(Some(update_message_without_buttons(content)))
+}
+
+/// replaces a message's content and removes its buttons
+fn update_message_without_buttons(content: String) -> InteractionResponse {
+    InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            components: Some(vec![]),
+            ..Default::default()
+        }),
+    }
+}
+
+/// finds the reschedule proposal `id`, along with its race and the player answering it, if the
+/// person who pressed the button is allowed to answer it right now. Otherwise returns what to
+/// tell them instead.
+async fn answerable_proposal(
+    id: &str,
+    interaction: &InteractionCreate,
+    state: &Arc<DiscordState>,
+) -> Result<Either<(RescheduleProposal, BracketRace, Player), InteractionResponse>, ErrorResponse> {
+    const USER_FACING_ERROR: &str = "Error answering reschedule request.";
+    let proposal_id = id
+        .parse::<i32>()
+        .map_err(|_| ErrorResponse::new(USER_FACING_ERROR, format!("Bad proposal id {id}")))?;
+    let uid = interaction.author_id().ok_or(ErrorResponse::new(
+        USER_FACING_ERROR,
+        "No user found on reschedule answer",
+    ))?;
+    let mut conn = state
+        .diesel_cxn()
+        .await
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    let proposal = RescheduleProposal::get_by_id(proposal_id, &mut conn)
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    if proposal.race_deleted.is_some() {
+        return Ok(Either::Right(update_message_without_buttons(
+            "This request is no longer open: the race has been cancelled.".to_string(),
+        )));
+    }
+    let race = BracketRace::get_by_id(proposal.bracket_race_id, &mut conn)
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    let player = match Player::get_by_discord_id(&uid.to_string(), &mut conn)
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+    {
+        Some(p) => p,
+        None => {
+            return Ok(Either::Right(plain_ephemeral_response(
+                "You do not seem to be registered.",
+            )));
+        }
+    };
+    if !race
+        .includes_player(&player, &mut conn)
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+    {
+        return Ok(Either::Right(plain_ephemeral_response(
+            "Only the players in this race can answer this.",
+        )));
+    }
+    if player.discord_id == proposal.proposer_discord_id {
+        return Ok(Either::Right(plain_ephemeral_response(
+            "You can't answer your own request.",
+        )));
+    }
+    let proposal_state = proposal
+        .state()
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    if proposal_state != ProposalState::Pending {
+        let why = match proposal_state {
+            ProposalState::Superseded => "a newer time has been proposed",
+            ProposalState::Overridden => "an admin has rescheduled the race",
+            ProposalState::Cancelled => "the race has been cancelled",
+            _ => "it has already been answered",
+        };
+        return Ok(Either::Right(update_message_without_buttons(format!(
+            "This request is no longer open: {why}."
+        ))));
+    }
+    if race
+        .state()
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?
+        == BracketRaceState::Finished
+    {
+        return Ok(Either::Right(update_message_without_buttons(
+            "This race is already finished.".to_string(),
+        )));
+    }
+    Ok(Either::Left((proposal, race, player)))
+}
+
+/// saves an answer to a reschedule proposal. Returns false (and saves nothing) if someone else
+/// answered it first.
+async fn resolve_proposal(
+    proposal: &mut RescheduleProposal,
+    answer: ProposalState,
+    responder: &Player,
+    state: &Arc<DiscordState>,
+) -> Result<bool, ErrorResponse> {
+    const USER_FACING_ERROR: &str = "Error answering reschedule request.";
+    let audit = NewAuditEvent::new(
+        &Actor::Discord(responder.discord_id.clone()),
+        "answer_reschedule",
+        AuditEntity::RescheduleProposal,
+        proposal.id,
+    )
+    .before(proposal);
+    let mut conn = state
+        .diesel_cxn()
+        .await
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    let resolved = proposal
+        .resolve(answer, responder.discord_id.clone(), &mut conn)
+        .map_err(|e| ErrorResponse::new(USER_FACING_ERROR, e))?;
+    if resolved {
+        audit.after(proposal).record(&mut conn);
+    }
+    Ok(resolved)
+}
+
+fn already_answered() -> InteractionResponse {
+    update_message_without_buttons(
+        "This request is no longer open: it has already been answered.".to_string(),
+    )
+}
+
+async fn handle_accept_reschedule(
+    id: &str,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    let (mut proposal, race, player) = match answerable_proposal(id, &interaction, state).await? {
+        Either::Left(found) => found,
+        Either::Right(resp) => {
+            return Ok(Some(resp));
+        }
+    };
+    let when = proposal.proposed_time().ok_or(ErrorResponse::new(
+        "Error answering reschedule request.",
+        format!("Bad proposed time on reschedule proposal {}", proposal.id),
+    ))?;
+    if when <= Utc::now() {
+        return Ok(Some(plain_ephemeral_response(
+            "That time has already passed. You can suggest another time instead.",
+        )));
+    }
+    // claim the proposal before moving the race, so it can't be accepted twice
+    if !resolve_proposal(&mut proposal, ProposalState::Accepted, &player, state).await? {
+        return Ok(Some(already_answered()));
+    }
+    let actor = Actor::Discord(player.discord_id.clone());
+    let scheduled = discord::schedule_race(race, when, &actor, state).await;
+    let content = match scheduled {
+        Ok(s) => s,
+        Err(e) => {
+            // the race didn't move, so the proposal wasn't really accepted
+            let reopened = match state.diesel_cxn().await {
+                Ok(mut conn) => match proposal.reopen(&mut conn) {
+                    Ok(reopened) => reopened,
+                    Err(re) => {
+                        warn!("Error reopening reschedule proposal {}: {re}", proposal.id);
+                        false
+                    }
+                },
+                Err(re) => {
+                    warn!("Error reopening reschedule proposal {}: {re}", proposal.id);
+                    false
+                }
+            };
+            return match e {
+                ScheduleRaceError::RaceFinished => Ok(Some(update_message_without_buttons(
+                    "This race is already finished.".to_string(),
+                ))),
+                e if reopened => Err(ErrorResponse::new(
+                    "Error rescheduling race. The request is still open, so you can try again.",
+                    e,
+                )),
+                e => Err(ErrorResponse::new(
+                    "Error rescheduling race, and the request couldn't be reopened. Please ask \
+                    an admin to move the race.",
+                    e,
+                )),
+            };
+        }
+    };
+    discord::notify_proposer(
+        &proposal,
+        &format!("{} accepted your new time. {content}", player.name),
+        state,
+    )
+    .await;
+    Ok(Some(update_message_without_buttons(content)))
+}
+
+async fn handle_decline_reschedule(
+    id: &str,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    let (mut proposal, race, player) = match answerable_proposal(id, &interaction, state).await? {
+        Either::Left(found) => found,
+        Either::Right(resp) => {
+            return Ok(Some(resp));
+        }
+    };
+    if !resolve_proposal(&mut proposal, ProposalState::Declined, &player, state).await? {
+        return Ok(Some(already_answered()));
+    }
+    let title = {
+        let mut conn = state
+            .diesel_cxn()
+            .await
+            .map_err(|e| ErrorResponse::new("Error declining reschedule request.", e))?;
+        race.title(&mut conn)
+            .map_err(|e| ErrorResponse::new("Error declining reschedule request.", e))?
+    };
+    discord::notify_proposer(
+        &proposal,
+        &format!(
+            "{} declined moving {title}, so it's staying where it was.",
+            player.name
+        ),
+        state,
+    )
+    .await;
+    Ok(Some(update_message_without_buttons(format!(
+        "Declined. {title} is staying where it was."
+    ))))
+}
+
+fn handle_counter_reschedule_button(id: &str) -> InteractionResponse {
+    create_modal(
+        &format!("{CUSTOM_ID_COUNTER_RESCHEDULE_MODAL}:{id}"),
+        "When would you like to race instead?",
+        "Suggest another time",
+        vec![Component::TextInput(TextInput {
+            id: None,
+            custom_id: CUSTOM_ID_COUNTER_RESCHEDULE_MODAL_INPUT.to_string(),
+            label: Some("yyyy/mm/dd h:mm am/pm, US/Eastern".to_string()),
+            max_length: Some(30),
+            min_length: Some(1),
+            placeholder: Some("2022/10/28 8:30 pm".to_string()),
+            required: Some(true),
+            style: TextInputStyle::Short,
+            value: None,
+        })],
+    )
+}
+
+/// answers a reschedule proposal with a different time, which the original proposer then has to
+/// accept in turn
+async fn handle_counter_reschedule_modal(
+    id: &str,
+    mut interaction_data: ModalInteractionData,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<Option<InteractionResponse>, ErrorResponse> {
+    let (mut proposal, race, player) = match answerable_proposal(id, &interaction, state).await? {
+        Either::Left(found) => found,
+        Either::Right(resp) => {
+            return Ok(Some(resp));
+        }
+    };
+    let text = get_field_from_modal_components(
+        std::mem::take(&mut interaction_data.components),
+        CUSTOM_ID_COUNTER_RESCHEDULE_MODAL_INPUT,
+    )
+    .ok_or(ErrorResponse::new(
+        "Error suggesting another time.",
+        "Error getting time from counter proposal modal.",
+    ))?;
+    let when = match parse_scheduling_text(&text) {
+        Ok(w) => w,
+        Err(e) => {
+            return Ok(Some(plain_ephemeral_response(e)));
+        }
+    };
+    if when <= Utc::now() {
+        return Ok(Some(plain_ephemeral_response(
+            "That time has already passed.",
+        )));
+    }
+    if !resolve_proposal(&mut proposal, ProposalState::Countered, &player, state).await? {
+        return Ok(Some(already_answered()));
+    }
+    let content = discord::propose_reschedule(&race, &player, when, state)
+        .await
+        .map_err(|e| ErrorResponse::new("Error suggesting another time.", e))?;
+    Ok(Some(update_message_without_buttons(content)))
+}
+
 async fn handle_button_interaction(
     interaction_data: MessageComponentInteractionData,
     interaction: Box<InteractionCreate>,
@@ -523,10 +1070,28 @@ async fn handle_button_interaction(
         CUSTOM_ID_FORFEIT_RUN => Ok(Some(handle_async_run_forfeit_button())),
         CUSTOM_ID_FINISH_RUN => handle_async_run_finish(interaction, state).await,
         CUSTOM_ID_VOD_READY => Ok(Some(handle_async_vod_ready())),
-        _ => {
-            info!("Unhandled button: {:?}", interaction_data);
-            Ok(None)
-        }
+        other => match other.split_once(':') {
+            Some((CUSTOM_ID_VERIFY_QUALIFIER, id)) => {
+                handle_verify_qualifier(id, interaction, state).await
+            }
+            Some((CUSTOM_ID_REJECT_QUALIFIER, id)) => Ok(Some(handle_reject_qualifier_button(id))),
+            Some((CUSTOM_ID_CONFIRM_SLOT, slot)) => {
+                handle_confirm_slot(slot, interaction, state).await
+            }
+            Some((CUSTOM_ID_ACCEPT_RESCHEDULE, id)) => {
+                handle_accept_reschedule(id, interaction, state).await
+            }
+            Some((CUSTOM_ID_DECLINE_RESCHEDULE, id)) => {
+                handle_decline_reschedule(id, interaction, state).await
+            }
+            Some((CUSTOM_ID_COUNTER_RESCHEDULE, id)) => {
+                Ok(Some(handle_counter_reschedule_button(id)))
+            }
+            _ => {
+                info!("Unhandled button: {:?}", interaction_data);
+                Ok(None)
+            }
+        },
     }
 }
 
@@ -543,10 +1108,20 @@ async fn handle_modal_submission(
         CUSTOM_ID_FORFEIT_MODAL => {
             handle_run_forfeit_modal(interaction_data, interaction, state).await
         }
-        _ => {
-            info!("Unhandled modal: {:?}", interaction_data);
-            Ok(None)
-        }
+        other => match other.split_once(':') {
+            Some((CUSTOM_ID_REJECT_QUALIFIER_MODAL, id)) => {
+                let id = id.to_string();
+                handle_reject_qualifier_modal(&id, interaction_data, interaction, state).await
+            }
+            Some((CUSTOM_ID_COUNTER_RESCHEDULE_MODAL, id)) => {
+                let id = id.to_string();
+                handle_counter_reschedule_modal(&id, interaction_data, interaction, state).await
+            }
+            _ => {
+                info!("Unhandled modal: {:?}", interaction_data);
+                Ok(None)
+            }
+        },
     }
 }
 
@@ -148,18 +148,27 @@ async fn sync_race_status(
         })
         .collect::<HashMap<_, _>>();
 
+    // { guild_id: [RaceEvent] } for races that don't exist anymore (e.g. they were cancelled)
+    let mut orphaned_race_events_by_guild = RaceEvent::get_orphaned(conn)?
+        .into_iter()
+        .into_group_map_by(|re| re.guild_id.clone());
+
     // for each guild we're syncing events to, do the syncing
     // the list of guilds to sync is just "whichever ones the bot is currently added to"
     for guild_filters in helper_bot.guild_criteria().await? {
         let race_events_by_bri_id = race_events_by_guild
             .remove(&guild_filters.guild_id().to_string())
             .unwrap_or(HashMap::new());
+        let orphaned_race_events = orphaned_race_events_by_guild
+            .remove(&guild_filters.guild_id().to_string())
+            .unwrap_or_default();
 
         if let Err(e) = sync_events_in_a_guild(
             guild_filters,
             helper_bot,
             &race_infos,
             race_events_by_bri_id,
+            orphaned_race_events,
             conn,
         )
         .await
@@ -174,6 +183,9 @@ async fn sync_race_status(
 /// and compares it with the actually-existing events in a guild, and tries to reconcile the differences.
 ///
 /// The `race_events_by_bri_id` allow us to track existing events for races through changes
+///
+/// The `orphaned_race_events` are for races that no longer exist; their events are ended and then the
+/// `RaceEvent`s are deleted
 // XXX probably we could include race id #s in the event info somewhere and use that to track them over time...
 // that would be very vulnerable to users modifying events, though.
 async fn sync_events_in_a_guild(
@@ -181,11 +193,40 @@ async fn sync_events_in_a_guild(
     helper_bot: &Arc<HelperBot>,
     race_infos: &[RaceInfoBundle],
     mut race_events_by_bri_id: HashMap<i32, RaceEvent>,
+    orphaned_race_events: Vec<RaceEvent>,
     conn: &mut SqliteConnection,
 ) -> Result<(), NMGLeagueBotError> {
     let mut existing_events =
         get_existing_events_by_id(guild_filters.guild_id(), helper_bot).await?;
 
+    for race_event in orphaned_race_events {
+        let existing_event = race_event
+            .get_scheduled_event_id()
+            .map(|gse_id| existing_events.remove(&gse_id))
+            .flatten();
+        match update_discord_events(
+            guild_filters.guild_id(),
+            &RaceEventContentAndStatus::NoEvent,
+            existing_event.as_ref(),
+            helper_bot,
+        )
+        .await
+        {
+            Ok(_) => {
+                let id = race_event.id;
+                if let Err(e) = race_event.delete(conn) {
+                    warn!("Error deleting orphaned RaceEvent {id}: {e}");
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Error ending event for orphaned RaceEvent {}: {e}",
+                    race_event.id
+                );
+            }
+        }
+    }
+
     for bundle in race_infos {
         // just hardcode that main guild must sync everything
         let interesting = guild_filters.guild_id() == CONFIG.guild_id
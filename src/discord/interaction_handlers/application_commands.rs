@@ -1,10 +1,15 @@
 use crate::discord::components::action_row;
 use crate::discord::constants::{
-    ADD_PLAYER_TO_BRACKET_CMD, CANCEL_ASYNC_CMD, CHECK_USER_INFO_CMD, COMMENTATORS_CMD,
-    CREATE_ASYNC_CMD, CREATE_BRACKET_CMD, CREATE_PLAYER_CMD, CREATE_SEASON_CMD, FINISH_BRACKET_CMD,
-    GENERATE_PAIRINGS_CMD, REPORT_RACE_CMD, RESCHEDULE_RACE_CMD, SCHEDULE_RACE_CMD,
-    SEE_UNSCHEDULED_RACES_CMD, SET_RESTREAM_CMD, SET_SEASON_STATE_CMD, SUBMIT_QUALIFIER_CMD,
-    UPDATE_FINISHED_RACE_CMD, UPDATE_USER_INFO_CMD, USER_PROFILE_CMD,
+    ADD_PLAYER_TO_BRACKET_CMD, AVAILABILITY_CMD, CANCEL_ASYNC_CMD, CHECK_USER_INFO_CMD,
+    COMMENTATORS_CMD, CREATE_API_TOKEN_CMD, CREATE_ASYNC_CMD, CREATE_BRACKET_CMD,
+    CREATE_PLAYER_CMD, CREATE_SEASON_CMD, DISPUTES_CMD, DISPUTE_RACE_CMD,
+    DROP_PLAYER_FROM_BRACKET_CMD, FINISH_BRACKET_CMD, GENERATE_PAIRINGS_CMD, OVERDUE_RACES_CMD,
+    REPORT_PLACEMENT_RACE_CMD, REPORT_RACE_CMD, RESCHEDULE_RACE_CMD, RESOLVE_DISPUTE_CMD,
+    REVIEW_QUALIFIERS_CMD, REVOKE_API_TOKEN_CMD, ROLLBACK_ROUND_CMD, SCHEDULE_RACE_CMD,
+    SEE_UNSCHEDULED_RACES_CMD, SET_BRACKET_BEST_OF_CMD, SET_BRACKET_SCORING_CMD,
+    SET_QUALIFIER_RULES_CMD, SET_RESTREAM_CMD, SET_ROUND_DEADLINES_CMD, SET_SEASON_STATE_CMD,
+    SUBMIT_QUALIFIER_CMD, SUGGEST_TIMES_CMD, UPDATE_FINISHED_RACE_CMD, UPDATE_USER_INFO_CMD,
+    USER_PROFILE_CMD,
 };
 
 use crate::discord::discord_state::DiscordOperations;
@@ -13,7 +18,9 @@ use crate::discord::interactions_utils::{
     autocomplete_result, button_component, get_subcommand_options, interaction_to_custom_id,
     plain_ephemeral_response, plain_interaction_response, update_resp_to_plain_content,
 };
-use crate::discord::{self, notify_racer, ErrorResponse, ScheduleRaceError};
+use crate::discord::{
+    self, notify_racer, slot_buttons, ErrorResponse, ScheduleRaceError, MAX_SUGGESTED_TIMES,
+};
 use crate::{find_opt, get_focused_opt, get_opt_s};
 use nmg_league_bot::models::asyncs::race::{AsyncRace, NewAsyncRace, RaceState};
 use nmg_league_bot::models::asyncs::race_run::AsyncRaceRun;
@@ -28,19 +35,38 @@ use diesel::SqliteConnection;
 use either::Either;
 use log::{info, warn};
 use nmg_league_bot::config::CONFIG;
-use nmg_league_bot::models::bracket_races::BracketRace;
-use nmg_league_bot::models::brackets::{Bracket, BracketType, NewBracket};
+use nmg_league_bot::models::api_tokens::{ApiToken, NewApiToken};
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
+use nmg_league_bot::models::availability::{
+    suggested_times, AvailabilityWindow, NewAvailabilityWindow,
+};
+use nmg_league_bot::models::bracket_races::{BracketRace, PlayerResult};
+use nmg_league_bot::models::bracket_rounds::{
+    awaiting_admin_decision, BracketRound, MissedDeadlinePolicy,
+};
+use nmg_league_bot::models::brackets::{
+    create_bracket, Bracket, BracketError, BracketOptions, BracketType,
+};
 use nmg_league_bot::models::player::{NewPlayer, Player};
 use nmg_league_bot::models::player_bracket_entries::NewPlayerBracketEntry;
-use nmg_league_bot::models::qualifer_submission::NewQualifierSubmission;
+use nmg_league_bot::models::qualifer_submission::{NewQualifierSubmission, QualifierSubmission};
+use nmg_league_bot::models::qualifier_rules::QualifierAggregation;
+use nmg_league_bot::models::race_disputes::{self, DisputeState, RaceDispute};
+use nmg_league_bot::models::reschedule_proposals;
+use nmg_league_bot::models::scoring::{parse_placement_points, parse_tiebreakers};
 use nmg_league_bot::models::season::{NewSeason, Season, SeasonState};
+use nmg_league_bot::models::webhooks::{fire_event, LeagueEvent};
 use nmg_league_bot::utils::{parse_race_result, ResultCollapse, ResultErrToString};
-use nmg_league_bot::worker_funcs::{trigger_race_finish, RaceFinishError, RaceFinishOptions};
+use nmg_league_bot::worker_funcs::{
+    drop_player_from_bracket, rollback_round, trigger_placement_race_finish, trigger_race_finish,
+    DroppedPlayerRaces, PlacementRaceFinishOptions, RaceFinishError, RaceFinishOptions,
+};
 use nmg_league_bot::{utils, BracketRaceState, BracketRaceStateError, NMGLeagueBotError};
 use racetime_api::endpoint::Query;
 use racetime_api::endpoints::UserSearch;
 use racetime_api::types::UserSearchResult;
 use regex::{Regex, RegexBuilder};
+use serde_json::json;
 use std::ops::DerefMut;
 use std::sync::Arc;
 use twilight_http::request::application::interaction::UpdateResponse;
@@ -189,6 +215,35 @@ pub async fn handle_application_interaction(
         USER_PROFILE_CMD => {
             return handle_user_profile(ac, interaction, state).await;
         }
+        AVAILABILITY_CMD => {
+            return match interaction.kind {
+                InteractionType::ApplicationCommand => {
+                    handle_availability_command(ac, interaction, state)
+                        .await
+                        .map(Some)
+                        .map_err(|e| ErrorResponse::new("Error updating your availability.", e))
+                }
+                InteractionType::ApplicationCommandAutocomplete => timezone_autocomplete(ac)
+                    .map(Some)
+                    .map_err(|e| ErrorResponse::new("Error finding time zones.", e)),
+                _ => Err(ErrorResponse::new(
+                    "Weird internal error, sorry",
+                    format!("Unexpected InteractionType for {}", AVAILABILITY_CMD),
+                )),
+            };
+        }
+        SUGGEST_TIMES_CMD => {
+            return handle_suggest_times(ac, interaction, state)
+                .await
+                .map(Some)
+                .map_err(|e| ErrorResponse::new("Error finding times to race.", e));
+        }
+        DISPUTE_RACE_CMD => {
+            return handle_dispute_race(ac, interaction, state)
+                .await
+                .map(Some)
+                .map_err(|e| ErrorResponse::new("Error disputing that race.", e));
+        }
 
         _ => {}
     };
@@ -203,26 +258,36 @@ pub async fn handle_application_interaction(
             return Err(ErrorResponse::new("Error running command", s));
         }
     };
+    let actor = discord::interaction_actor(&interaction, "Error running command")?;
 
     // admin commands
     match ac.name.as_str() {
         CREATE_PLAYER_CMD => admin_command_wrapper(
-            handle_create_player(ac, interaction, state)
+            handle_create_player(ac, interaction, &actor, state)
                 .await
                 .map(|i| Some(i)),
         ),
         ADD_PLAYER_TO_BRACKET_CMD => admin_command_wrapper(
-            handle_add_player_to_bracket(ac, interaction, state)
+            handle_add_player_to_bracket(ac, interaction, &actor, state)
                 .await
                 .map(|i| Some(i)),
         ),
         // admin commands
-        CREATE_ASYNC_CMD => {
-            admin_command_wrapper(handle_create_race(ac, state).await.map(Option::from))
-        }
-        GENERATE_PAIRINGS_CMD => {
-            admin_command_wrapper(handle_generate_pairings(ac, state).await.map(Option::from))
-        }
+        CREATE_ASYNC_CMD => admin_command_wrapper(
+            handle_create_race(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        GENERATE_PAIRINGS_CMD => admin_command_wrapper(
+            handle_generate_pairings(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        ROLLBACK_ROUND_CMD => admin_command_wrapper(
+            handle_rollback_round(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
         RESCHEDULE_RACE_CMD => {
             match interaction.kind {
                 InteractionType::ApplicationCommand => {
@@ -241,27 +306,83 @@ pub async fn handle_application_interaction(
             }
         }
 
-        CANCEL_ASYNC_CMD => admin_command_wrapper(handle_cancel_race(ac, interaction, state).await),
-        CREATE_SEASON_CMD => {
-            admin_command_wrapper(handle_create_season(ac, state).await.map(Option::from))
+        CANCEL_ASYNC_CMD => {
+            admin_command_wrapper(handle_cancel_race(ac, interaction, &actor, state).await)
         }
-        SET_SEASON_STATE_CMD => {
-            admin_command_wrapper(handle_set_season_state(ac, state).await.map(Option::from))
+        CREATE_SEASON_CMD => admin_command_wrapper(
+            handle_create_season(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        SET_SEASON_STATE_CMD => admin_command_wrapper(
+            handle_set_season_state(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        SET_QUALIFIER_RULES_CMD => admin_command_wrapper(
+            handle_set_qualifier_rules(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        REVIEW_QUALIFIERS_CMD => {
+            admin_command_wrapper(handle_review_qualifiers(state).await.map(Option::from))
         }
 
-        CREATE_BRACKET_CMD => {
-            admin_command_wrapper(handle_create_bracket(ac, state).await.map(Option::from))
-        }
-        FINISH_BRACKET_CMD => {
-            admin_command_wrapper(handle_finish_bracket(ac, state).await.map(Option::from))
-        }
-        REPORT_RACE_CMD => {
-            admin_command_wrapper(handle_report_race(ac, state).await.map(Option::from))
+        CREATE_BRACKET_CMD => admin_command_wrapper(
+            handle_create_bracket(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        FINISH_BRACKET_CMD => admin_command_wrapper(
+            handle_finish_bracket(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        SET_BRACKET_SCORING_CMD => admin_command_wrapper(
+            handle_set_bracket_scoring(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        SET_BRACKET_BEST_OF_CMD => admin_command_wrapper(
+            handle_set_bracket_best_of(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        SET_ROUND_DEADLINES_CMD => admin_command_wrapper(
+            handle_set_round_deadlines(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        OVERDUE_RACES_CMD => {
+            admin_command_wrapper(handle_overdue_races(state).await.map(Option::from))
         }
+        DROP_PLAYER_FROM_BRACKET_CMD => admin_command_wrapper(
+            handle_drop_player_from_bracket(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        REPORT_RACE_CMD => admin_command_wrapper(
+            handle_report_race(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        REPORT_PLACEMENT_RACE_CMD => admin_command_wrapper(
+            handle_report_placement_race(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
 
-        UPDATE_FINISHED_RACE_CMD => {
-            admin_command_wrapper(handle_rereport_race(ac, state).await.map(Option::from))
-        }
+        UPDATE_FINISHED_RACE_CMD => admin_command_wrapper(
+            handle_rereport_race(ac, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        DISPUTES_CMD => admin_command_wrapper(handle_disputes(state).await.map(Option::from)),
+        RESOLVE_DISPUTE_CMD => admin_command_wrapper(
+            handle_resolve_dispute(ac, interaction, &actor, state)
+                .await
+                .map(Option::from),
+        ),
 
         SEE_UNSCHEDULED_RACES_CMD => admin_command_wrapper(
             handle_see_unscheduled_races(ac, state)
@@ -271,7 +392,7 @@ pub async fn handle_application_interaction(
 
         COMMENTATORS_CMD => admin_command_wrapper(match interaction.kind {
             InteractionType::ApplicationCommand => {
-                handle_commentator_command(ac, interaction, state)
+                handle_commentator_command(ac, interaction, &actor, state)
                     .await
                     .map(Some)
             }
@@ -286,9 +407,22 @@ pub async fn handle_application_interaction(
             )))),
         }),
 
+        CREATE_API_TOKEN_CMD => admin_command_wrapper(
+            handle_create_api_token(ac, interaction, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+        REVOKE_API_TOKEN_CMD => admin_command_wrapper(
+            handle_revoke_api_token(ac, interaction, &actor, state)
+                .await
+                .map(Option::from),
+        ),
+
         SET_RESTREAM_CMD => admin_command_wrapper(match interaction.kind {
             InteractionType::ApplicationCommand => {
-                handle_set_restream(ac, interaction, state).await.map(Some)
+                handle_set_restream(ac, interaction, &actor, state)
+                    .await
+                    .map(Some)
             }
             InteractionType::ApplicationCommandAutocomplete => {
                 scheduled_races_autocomplete(ac, interaction, state)
@@ -358,6 +492,28 @@ fn datetime_from_options(
         .ok_or("Invalid datetime")
 }
 
+/// parses times typed out as text, like `2022/10/28 8:30 pm`, as US/Eastern (same as the
+/// scheduling commands' options)
+pub(crate) fn parse_scheduling_text(s: &str) -> Result<DateTime<chrono_tz::Tz>, &'static str> {
+    static TIME_REGEX: Lazy<Result<Regex, regex::Error>> = Lazy::new(|| {
+        RegexBuilder::new(r"^\s*(\d{4}/\d{1,2}/\d{1,2})\s+(\d{1,2}):(\d{2})\s*(am|pm)\s*$")
+            .case_insensitive(true)
+            .build()
+    });
+    const BAD_FORMAT: &str = "Invalid time. Expected something like `2022/10/28 8:30 pm`.";
+    let caps = TIME_REGEX
+        .as_ref()
+        .map_err(|_| "Internal error parsing time")?
+        .captures(s)
+        .ok_or(BAD_FORMAT)?;
+    let hour: i64 = caps[2].parse().map_err(|_| BAD_FORMAT)?;
+    let minute: i64 = caps[3].parse().map_err(|_| BAD_FORMAT)?;
+    if !(1..=12).contains(&hour) || minute > 59 {
+        return Err(BAD_FORMAT);
+    }
+    datetime_from_options(&caps[1], hour, minute, &caps[4].to_uppercase())
+}
+
 fn get_datetime_from_scheduling_cmd(
     options: &mut Vec<CommandDataOption>,
 ) -> Result<DateTime<chrono_tz::Tz>, &'static str> {
@@ -525,7 +681,22 @@ async fn _handle_schedule_race_cmd(
         },
     };
 
-    match discord::schedule_race(the_race, dt, &state).await {
+    let already_scheduled = the_race.state().map_err(|e| {
+        ErrorResponse::new(
+            BLAND_USER_FACING_ERROR,
+            format!("Error getting race state: {e}"),
+        )
+    })? == BracketRaceState::Scheduled;
+    drop(cxn);
+    // once a race is scheduled, moving it needs the opponent to agree
+    if already_scheduled {
+        return discord::propose_reschedule(&the_race, &player, dt, &state)
+            .await
+            .map(UpdateResponseBag::new_content)
+            .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e));
+    }
+
+    match discord::schedule_race(the_race, dt, &Actor::discord(user.id), &state).await {
         Ok(s) => Ok(UpdateResponseBag::new_content(s)),
         Err(ScheduleRaceError::RaceFinished) => Ok(UpdateResponseBag::new_content(
             "Your race for this round is already finished.",
@@ -598,6 +769,7 @@ async fn scheduled_races_autocomplete(
 async fn handle_commentator_command(
     mut ac: Box<CommandData>,
     _interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let (cmd_s, mut subcommand_opts) =
@@ -618,6 +790,7 @@ async fn handle_commentator_command(
     let mut conn = state.diesel_cxn().await.map_err_to_string()?;
     let race = BracketRace::get_by_id(race_id as i32, &mut conn).map_err_to_string()?;
     let mut info = race.info(&mut conn).map_err_to_string()?;
+    let before = info.commentator_signups(&mut conn).map_err_to_string()?;
     match cmd {
         Cmd::Add => match info
             .new_commentator_signup(user, &mut conn)
@@ -640,6 +813,15 @@ async fn handle_commentator_command(
             _ => {}
         },
     }
+    NewAuditEvent::new(
+        actor,
+        &format!("commentators {cmd_s}"),
+        AuditEntity::BracketRace,
+        race.id,
+    )
+    .before(&before)
+    .after(&info.commentator_signups(&mut conn).map_err_to_string()?)
+    .record(&mut conn);
 
     // it would be nice to also update the restream request message
     // and maybe send pings to the new comm? but i think it's not very important and it seems annoying to implement
@@ -652,6 +834,7 @@ async fn handle_commentator_command(
 async fn handle_set_restream(
     mut ac: Box<CommandData>,
     _interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let race_id = get_opt_s!("race", &mut ac.options, Integer)?;
@@ -659,6 +842,8 @@ async fn handle_set_restream(
     let mut conn = state.diesel_cxn().await.map_err_to_string()?;
     let race = BracketRace::get_by_id(race_id as i32, &mut conn).map_err_to_string()?;
     let mut info = race.info(&mut conn).map_err_to_string()?;
+    let audit = NewAuditEvent::new(actor, SET_RESTREAM_CMD, AuditEntity::BracketRace, race.id)
+        .before(&info);
 
     if channel == "none" {
         info.restream_channel = None;
@@ -680,6 +865,7 @@ async fn handle_set_restream(
         }
     }
     info.update(&mut conn).map_err_to_string()?;
+    audit.after(&info).record(&mut conn);
 
     // it would be nice to also update the restream request message
     // and maybe send pings to the new comm? but i think it's not very important and it seems annoying to implement
@@ -724,8 +910,16 @@ async fn handle_update_user_info(
         .await
         .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?;
 
-    let (mut player, _created) = Player::get_or_create_from_discord_user(user, cxn.deref_mut())
+    let actor = Actor::discord(user.id);
+    let (mut player, created) = Player::get_or_create_from_discord_user(user, cxn.deref_mut())
         .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?;
+    let audit = NewAuditEvent::new(&actor, UPDATE_USER_INFO_CMD, AuditEntity::Player, player.id);
+    // newly created players didn't exist before
+    let audit = if created {
+        audit
+    } else {
+        audit.before(&player)
+    };
     let mut save = false;
     let mut user_messages = vec![];
     let mut internal_errors = vec![];
@@ -782,6 +976,9 @@ async fn handle_update_user_info(
             .update(cxn.deref_mut())
             .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?;
     }
+    if save || created {
+        audit.after(&player).record(cxn.deref_mut());
+    }
     let user_resp = user_messages.join(" ");
     if internal_errors.is_empty() {
         Ok(Some(format_player(
@@ -1030,12 +1227,18 @@ async fn handle_submit_qualifier(
         .await
         .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?;
 
+    let actor = Actor::discord(user.id);
     let (player, created) = match Player::get_or_create_from_discord_user(user, cxn.deref_mut()) {
         Ok(p) => p,
         Err(e) => {
             return Err(ErrorResponse::new(BLAND_USER_FACING_ERROR, e));
         }
     };
+    if created {
+        NewAuditEvent::new(&actor, SUBMIT_QUALIFIER_CMD, AuditEntity::Player, player.id)
+            .after(&player)
+            .record(cxn.deref_mut());
+    }
 
     let update_pls_suffix = if created {
         format!(" I would really appreciate it if you would run the `/{UPDATE_USER_INFO_CMD}` command and provide your \
@@ -1054,14 +1257,43 @@ async fn handle_submit_qualifier(
             return Err(ErrorResponse::new(BLAND_USER_FACING_ERROR, e));
         }
     };
+    let rules = current_season
+        .qualifier_rules()
+        .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?;
     let nqs = NewQualifierSubmission::new(&player, &current_season, secs, vod);
-    nqs.save(cxn.deref_mut())
-        .map(|_| {
-            Some(plain_interaction_response(format!(
-                "Thanks for your submission!{update_pls_suffix}"
-            )))
-        })
-        .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))
+    let submission = match nqs
+        .save_within_limit(rules.max_attempts, cxn.deref_mut())
+        .map_err(|e| ErrorResponse::new(BLAND_USER_FACING_ERROR, e))?
+    {
+        Some(s) => s,
+        None => {
+            return Ok(Some(plain_interaction_response(format!(
+                "You've already submitted {} qualifiers, which is the limit this season.",
+                rules.max_attempts.unwrap_or(0)
+            ))));
+        }
+    };
+    NewAuditEvent::new(
+        &actor,
+        SUBMIT_QUALIFIER_CMD,
+        AuditEntity::QualifierSubmission,
+        submission.id,
+    )
+    .after(&submission)
+    .record(cxn.deref_mut());
+    fire_event(
+        LeagueEvent::QualifierSubmitted {
+            qualifier_id: submission.id,
+            season_ordinal: current_season.ordinal,
+            player_id: player.id,
+            time: submission.reported_time,
+        },
+        cxn.deref_mut(),
+    );
+    Ok(Some(plain_interaction_response(format!(
+        "Thanks for your submission! It will count once an admin has checked \
+         your VoD.{update_pls_suffix}"
+    ))))
 }
 
 fn active_season_with_quals_open(
@@ -1081,17 +1313,29 @@ fn active_season_with_quals_open(
 
 async fn handle_reschedule_race_cmd(
     ac: Box<CommandData>,
-    _interaction: Box<InteractionCreate>,
+    interaction: Box<InteractionCreate>,
     state: Arc<DiscordState>,
 ) -> Result<UpdateResponseBag, ErrorResponse> {
-    Ok(match _handle_reschedule_race_cmd(ac, state).await {
-        Ok(u) => u,
-        Err(e) => UpdateResponseBag::new_content(e),
-    })
+    let admin_id = interaction
+        .author_id()
+        .ok_or(ErrorResponse::new(
+            "Internal error. Sorry.",
+            "No author on reschedule_race command",
+        ))?
+        .to_string();
+    Ok(
+        match _handle_reschedule_race_cmd(ac, admin_id, state).await {
+            Ok(u) => u,
+            Err(e) => UpdateResponseBag::new_content(e),
+        },
+    )
 }
 
+/// admins can move races directly, without the players agreeing. This is recorded in the race's
+/// reschedule history, and overrides anything the players had proposed.
 async fn _handle_reschedule_race_cmd(
     mut ac: Box<CommandData>,
+    admin_id: String,
     state: Arc<DiscordState>,
 ) -> Result<UpdateResponseBag, String> {
     let race_id = get_opt_s!("race_id", &mut ac.options, Integer)?;
@@ -1106,15 +1350,34 @@ async fn _handle_reschedule_race_cmd(
         }
     };
     let when = get_datetime_from_scheduling_cmd(&mut ac.options).map_err_to_string()?;
-    discord::schedule_race(race, when, &state)
+    let previous_time = race
+        .info(cxn.deref_mut())
+        .map_err_to_string()?
+        .scheduled_for;
+    drop(cxn);
+    let race_id = race.id;
+    let actor = Actor::Discord(admin_id.clone());
+    let message = discord::schedule_race(race, when.clone(), &actor, &state)
         .await
-        .map(|s| UpdateResponseBag::new_content(s))
-        .map_err_to_string()
+        .map_err_to_string()?;
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    if let Err(e) = reschedule_proposals::record_admin_override(
+        race_id,
+        admin_id,
+        &when,
+        previous_time,
+        cxn.deref_mut(),
+    ) {
+        // the race has been moved at this point, so don't act like it failed
+        warn!("Error recording admin reschedule of race {race_id}: {e}");
+    }
+    Ok(UpdateResponseBag::new_content(message))
 }
 
 async fn handle_create_player(
     mut ac: Box<CommandData>,
     _interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let discord_user = get_opt_s!("user", &mut ac.options, User)?;
@@ -1139,7 +1402,12 @@ async fn handle_create_player(
     );
 
     match np.save(cxn.deref_mut()) {
-        Ok(_) => Ok(plain_interaction_response("Player added!")),
+        Ok(player) => {
+            NewAuditEvent::new(actor, CREATE_PLAYER_CMD, AuditEntity::Player, player.id)
+                .after(&player)
+                .record(cxn.deref_mut());
+            Ok(plain_interaction_response("Player added!"))
+        }
         Err(e) => Err(e.to_string()),
     }
 }
@@ -1147,11 +1415,12 @@ async fn handle_create_player(
 async fn handle_add_player_to_bracket(
     ac: Box<CommandData>,
     interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     match interaction.kind {
         InteractionType::ApplicationCommand => {
-            handle_add_player_to_bracket_submit(ac, interaction, state).await
+            handle_add_player_to_bracket_submit(ac, interaction, actor, state).await
         }
         InteractionType::ApplicationCommandAutocomplete => {
             handle_add_player_to_bracket_autocomplete(ac, interaction, state).await
@@ -1166,6 +1435,7 @@ async fn handle_add_player_to_bracket(
 async fn handle_add_player_to_bracket_submit(
     mut ac: Box<CommandData>,
     _interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let discord_id = get_opt_s!("user", &mut ac.options, User)?;
@@ -1179,6 +1449,16 @@ async fn handle_add_player_to_bracket_submit(
             return Err(format!("Error getting or creating player: {e}"));
         }
     };
+    if created {
+        NewAuditEvent::new(
+            actor,
+            ADD_PLAYER_TO_BRACKET_CMD,
+            AuditEntity::Player,
+            player.id,
+        )
+        .after(&player)
+        .record(cxn.deref_mut());
+    }
 
     let update_pls_suffix = if created {
         format!(" This player was freshly created and has no user info!")
@@ -1200,14 +1480,19 @@ async fn handle_add_player_to_bracket_submit(
         ))?;
 
     let npbe = NewPlayerBracketEntry::new(&bracket, &player);
-    npbe.save(cxn.deref_mut())
-        .map(|_| {
-            plain_interaction_response(format!(
-                "{} added to {}.{update_pls_suffix}",
-                player.name, bracket.name
-            ))
-        })
-        .map_err(|e| e.to_string())
+    npbe.save(cxn.deref_mut()).map_err(|e| e.to_string())?;
+    NewAuditEvent::new(
+        actor,
+        ADD_PLAYER_TO_BRACKET_CMD,
+        AuditEntity::Bracket,
+        bracket.id,
+    )
+    .after(&json!({ "player_id": player.id }))
+    .record(cxn.deref_mut());
+    Ok(plain_interaction_response(format!(
+        "{} added to {}.{update_pls_suffix}",
+        player.name, bracket.name
+    )))
 }
 
 async fn get_bracket_autocompletes(
@@ -1264,6 +1549,7 @@ async fn handle_add_player_to_bracket_autocomplete(
 
 async fn handle_create_race(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let p1 = get_opt_s!("p1", &mut ac.options, User)?;
@@ -1284,6 +1570,9 @@ async fn handle_create_race(
     let race = new_race
         .save(cxn.deref_mut())
         .map_err(|e| format!("Error saving race: {}", e))?;
+    NewAuditEvent::new(actor, CREATE_ASYNC_CMD, AuditEntity::AsyncRace, race.id)
+        .after(&race)
+        .record(cxn.deref_mut());
 
     let (mut r1, mut r2) = race
         .select_racers(p1.clone(), p2.clone(), &mut cxn)
@@ -1328,6 +1617,7 @@ async fn handle_create_race(
 async fn handle_cancel_race(
     mut ac: Box<CommandData>,
     interaction: Box<InteractionCreate>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<Option<InteractionResponse>, String> {
     let race_id = get_opt_s!("race_id", &mut ac.options, Integer)?;
@@ -1367,12 +1657,12 @@ async fn handle_cancel_race(
     };
 
     if !r1.state.is_pre_start() || !r2.state.is_pre_start() {
-        handle_cancel_race_started(interaction, race, r1, r2, state)
+        handle_cancel_race_started(interaction, race, r1, r2, actor, state)
             .await
             .ok();
         Ok(None)
     } else {
-        actually_cancel_race(race, r1, r2, state)
+        actually_cancel_race(race, r1, r2, actor, state)
             .await
             .map(|_| Some(plain_interaction_response("Race cancelled.")))
     }
@@ -1385,6 +1675,7 @@ async fn handle_cancel_race_started(
     race: AsyncRace,
     r1: AsyncRaceRun,
     r2: AsyncRaceRun,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<(), String> {
     let mut resp =
@@ -1414,7 +1705,7 @@ async fn handle_cancel_race_started(
             // creating an "update response"
             let cid = interaction_to_custom_id(&cmp);
             let resp = match cid {
-                Some(REALLY_CANCEL_ID) => actually_cancel_race(race, r1, r2, state)
+                Some(REALLY_CANCEL_ID) => actually_cancel_race(race, r1, r2, actor, state)
                     .await
                     .map(|()| "Race cancelled.".to_string())
                     .collapse(),
@@ -1466,12 +1757,22 @@ async fn actually_cancel_race(
     race: AsyncRace,
     r1: AsyncRaceRun,
     r2: AsyncRaceRun,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<(), String> {
     let mut conn = state.diesel_cxn().await.map_err(|e| e.to_string())?;
     race.cancel(&mut conn)
         .await
         .map_err(|e| format!("Error cancelling race: {}", e))?;
+    let audit =
+        NewAuditEvent::new(actor, CANCEL_ASYNC_CMD, AuditEntity::AsyncRace, race.id).before(&race);
+    match AsyncRace::get_by_id(race.id, &mut conn) {
+        Ok(cancelled) => audit.after(&cancelled).record(&mut conn),
+        Err(e) => {
+            warn!("Error fetching cancelled race {}: {e}", race.id);
+            audit.record(&mut conn);
+        }
+    }
 
     let (r1_update, r2_update) = tokio::join!(
         update_cancelled_race_message(r1, state),
@@ -1533,6 +1834,7 @@ fn update_interaction_message_to_plain_text<'a>(
 
 async fn handle_set_season_state(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let season_ordinal = get_opt_s!("season_ordinal", &mut ac.options, Integer)?;
@@ -1542,15 +1844,88 @@ async fn handle_set_season_state(
     let mut cxn = state.diesel_cxn().await.map_err(|e| e.to_string())?;
     let mut season =
         Season::get_by_ordinal(season_ordinal as i32, cxn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(actor, SET_SEASON_STATE_CMD, AuditEntity::Season, season.id)
+        .before(&season);
     season
         .set_state(new_state, cxn.deref_mut())
         .map_err_to_string()?;
     season.update(cxn.deref_mut()).map_err_to_string()?;
+    audit.after(&season).record(cxn.deref_mut());
     Ok(plain_interaction_response("Update successful."))
 }
 
+async fn handle_set_qualifier_rules(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let season_ordinal = get_opt_s!("season_ordinal", &mut ac.options, Integer)?;
+    let aggregation = get_opt_s!("aggregation", &mut ac.options, String).ok();
+    let average_of = get_opt_s!("average_of", &mut ac.options, Integer).ok();
+    let max_attempts = get_opt_s!("max_attempts", &mut ac.options, Integer).ok();
+
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut season =
+        Season::get_by_ordinal(season_ordinal as i32, cxn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(
+        actor,
+        SET_QUALIFIER_RULES_CMD,
+        AuditEntity::Season,
+        season.id,
+    )
+    .before(&season);
+    let mut rules = season.qualifier_rules().map_err_to_string()?;
+    match (aggregation.as_deref(), average_of) {
+        (Some("AverageOfBest"), Some(n)) => {
+            rules.aggregation = QualifierAggregation::AverageOfBest(n as u32);
+        }
+        (Some("AverageOfBest"), None) => {
+            return Err("Please say how many times to average.".to_string());
+        }
+        (Some(_), Some(_)) => {
+            return Err("Only AverageOfBest averages times.".to_string());
+        }
+        (Some(a), None) => {
+            rules.aggregation = serde_json::from_str(&format!("\"{a}\"")).map_err_to_string()?;
+        }
+        (None, Some(n)) => {
+            if !matches!(rules.aggregation, QualifierAggregation::AverageOfBest(_)) {
+                return Err("Only AverageOfBest averages times.".to_string());
+            }
+            rules.aggregation = QualifierAggregation::AverageOfBest(n as u32);
+        }
+        (None, None) => {}
+    }
+    if let Some(m) = max_attempts {
+        rules.max_attempts = if m == 0 { None } else { Some(m as u32) };
+    }
+    season.set_qualifier_rules(&rules).map_err_to_string()?;
+    season.update(cxn.deref_mut()).map_err_to_string()?;
+    audit.after(&season).record(cxn.deref_mut());
+    let attempts = rules
+        .max_attempts
+        .map(|m| m.to_string())
+        .unwrap_or("unlimited".to_string());
+    Ok(plain_interaction_response(format!(
+        "Season {} qualifiers updated. Scoring: {:?}. Attempts: {attempts}.",
+        season.ordinal, rules.aggregation
+    )))
+}
+
+async fn handle_review_qualifiers(
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let mut data = discord::next_pending_qualifier(None, state).await?;
+    data.flags = Some(MessageFlags::EPHEMERAL);
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(data),
+    })
+}
+
 async fn handle_create_season(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let format = get_opt_s!("format", &mut ac.options, String)?;
@@ -1560,6 +1935,9 @@ async fn handle_create_season(
     let ns = NewSeason::new(format, category, goal, cxn.deref_mut()).map_err_to_string()?;
 
     let s = ns.save(cxn.deref_mut()).map_err(|e| e.to_string())?;
+    NewAuditEvent::new(actor, CREATE_SEASON_CMD, AuditEntity::Season, s.id)
+        .after(&s)
+        .record(cxn.deref_mut());
     Ok(plain_interaction_response(format!(
         "Season {} created!",
         s.ordinal
@@ -1581,10 +1959,17 @@ async fn handle_see_unscheduled_races(
     for br in unscheduled {
         let (p1, p2) = br.players(cxn.deref_mut()).map_err_to_string()?;
         let bname = br.bracket(cxn.deref_mut()).map_err_to_string()?.name;
+        let value = match suggested_times(&br, Utc::now(), 1, cxn.deref_mut())
+            .map_err_to_string()?
+            .first()
+        {
+            Some(t) => format!("{bname}\nEveryone is available <t:{}:f>", t.timestamp()),
+            None => bname,
+        };
         let field = EmbedField {
             inline: false,
             name: format!("{} vs {}", p1.name, p2.name),
-            value: bname,
+            value,
         };
         fields.push(field);
     }
@@ -1612,32 +1997,253 @@ async fn handle_see_unscheduled_races(
     Ok(ir)
 }
 
+/// the most races `/suggest_times` shows at once: each gets a row of buttons, and messages can only
+/// have 5 rows
+const MAX_SUGGESTED_RACES: usize = 5;
+
+async fn handle_availability_command(
+    mut ac: Box<CommandData>,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let (cmd, mut opts) =
+        get_subcommand_options(std::mem::take(&mut ac.options)).map_err_to_string()?;
+    let uid = interaction
+        .author_id()
+        .ok_or("No user found on availability command")?;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let player = match Player::get_by_discord_id(&uid.to_string(), &mut conn).map_err_to_string()? {
+        Some(p) => p,
+        None => {
+            return Ok(plain_ephemeral_response(
+                "You do not seem to be registered.",
+            ));
+        }
+    };
+    match cmd.as_str() {
+        "add" => {
+            let day = get_opt_s!("day", &mut opts, Integer)?;
+            let start = get_opt_s!("start", &mut opts, String)?;
+            let end = get_opt_s!("end", &mut opts, String)?;
+            let timezone = get_opt_s!("timezone", &mut opts, String)?;
+            match NewAvailabilityWindow::parse(player.id, day as i32, &start, &end, &timezone) {
+                Ok(new) => {
+                    let window = new.save(&mut conn).map_err_to_string()?;
+                    NewAuditEvent::new(
+                        &Actor::discord(uid),
+                        "availability add",
+                        AuditEntity::AvailabilityWindow,
+                        window.id,
+                    )
+                    .after(&window)
+                    .record(&mut conn);
+                    Ok(plain_ephemeral_response(format!(
+                        "Added {}.",
+                        window.describe()
+                    )))
+                }
+                Err(e) => Ok(plain_ephemeral_response(e.to_string())),
+            }
+        }
+        "list" => {
+            let windows =
+                AvailabilityWindow::for_player(player.id, &mut conn).map_err_to_string()?;
+            if windows.is_empty() {
+                return Ok(plain_ephemeral_response(
+                    "You haven't set any availability. Add some with `/availability add`.",
+                ));
+            }
+            let lines = windows
+                .iter()
+                .map(|w| format!("* {}", w.describe()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Ok(plain_ephemeral_response(format!(
+                "You're available:\n{lines}\n\nYou can also change this at {}/availability",
+                CONFIG.website_url
+            )))
+        }
+        "clear" => {
+            let windows =
+                AvailabilityWindow::for_player(player.id, &mut conn).map_err_to_string()?;
+            let n =
+                AvailabilityWindow::clear_for_player(player.id, &mut conn).map_err_to_string()?;
+            for window in windows {
+                NewAuditEvent::new(
+                    &Actor::discord(uid),
+                    "availability clear",
+                    AuditEntity::AvailabilityWindow,
+                    window.id,
+                )
+                .before(&window)
+                .record(&mut conn);
+            }
+            Ok(plain_ephemeral_response(format!(
+                "Removed {n} availability windows."
+            )))
+        }
+        _ => Err(format!("Unknown availability command `{cmd}`")),
+    }
+}
+
+fn timezone_autocomplete(mut ac: Box<CommandData>) -> Result<InteractionResponse, String> {
+    let (_cmd, mut opts) =
+        get_subcommand_options(std::mem::take(&mut ac.options)).map_err_to_string()?;
+    let typed = get_focused_opt!("timezone", &mut opts, String)
+        .map_err_to_string()?
+        .to_lowercase();
+    let choices = chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(|name| name.to_lowercase().contains(&typed))
+        .take(25)
+        .map(|name| CommandOptionChoice {
+            name: name.to_string(),
+            name_localizations: None,
+            value: CommandOptionChoiceValue::String(name.to_string()),
+        })
+        .collect();
+    Ok(autocomplete_result(choices))
+}
+
+/// suggests times that everyone is available for each of the player's unscheduled races, with
+/// buttons that schedule them. The message is public so that anyone in the race can click them.
+async fn handle_suggest_times(
+    mut ac: Box<CommandData>,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let uid = interaction
+        .author_id()
+        .ok_or("No user found on suggest_times command")?;
+    let opponent = find_opt!("opponent", &mut ac.options, User).map_err_to_string()?;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let player = match Player::get_by_discord_id(&uid.to_string(), &mut conn).map_err_to_string()? {
+        Some(p) => p,
+        None => {
+            return Ok(plain_ephemeral_response(
+                "You do not seem to be registered.",
+            ));
+        }
+    };
+    let opponent = match opponent {
+        Some(o) => {
+            match Player::get_by_discord_id(&o.to_string(), &mut conn).map_err_to_string()? {
+                Some(p) => Some(p),
+                None => {
+                    return Ok(plain_ephemeral_response(
+                        "That opponent could not be found.",
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+    let mut races = vec![];
+    for race in
+        BracketRace::get_unfinished_races_for_player(&player, &mut conn).map_err_to_string()?
+    {
+        if race.state().map_err_to_string()? != BracketRaceState::New {
+            continue;
+        }
+        if let Some(opp) = &opponent {
+            if !race.includes_player(opp, &mut conn).map_err_to_string()? {
+                continue;
+            }
+        }
+        races.push(race);
+    }
+    if races.is_empty() {
+        return Ok(plain_ephemeral_response(
+            "You don't have any unscheduled races.",
+        ));
+    }
+
+    let now = Utc::now();
+    let mut lines = vec![];
+    let mut rows = vec![];
+    for race in races.iter().take(MAX_SUGGESTED_RACES) {
+        let title = race.title(&mut conn).map_err_to_string()?;
+        let times =
+            suggested_times(race, now, MAX_SUGGESTED_TIMES, &mut conn).map_err_to_string()?;
+        lines.push(format!("**{title}**"));
+        if times.is_empty() {
+            lines.push(
+                "No times when everyone is available this week. \
+                Make sure everyone has added their availability with `/availability add`."
+                    .to_string(),
+            );
+            continue;
+        }
+        for (i, t) in times.iter().enumerate() {
+            lines.push(format!("{}. <t:{}:F>", i + 1, t.timestamp()));
+        }
+        rows.push(action_row(slot_buttons(race, &title, &times)));
+    }
+    if races.len() > MAX_SUGGESTED_RACES {
+        lines.push(format!(
+            "...and {} more. Pick an opponent to see them.",
+            races.len() - MAX_SUGGESTED_RACES
+        ));
+    }
+    Ok(InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(lines.join("\n")),
+            components: Some(rows),
+            ..Default::default()
+        }),
+    })
+}
+
 async fn handle_create_bracket(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let name = get_opt_s!("name", &mut ac.options, String)?;
     let bracket_type = get_opt_s!("bracket_type", &mut ac.options, String)?;
     let bt: BracketType = serde_json::from_str(&bracket_type).map_err_to_string()?;
+    let options = BracketOptions {
+        seed_from_bracket_id: get_opt_s!("seed_from_bracket_id", &mut ac.options, Integer)
+            .ok()
+            .map(|id| id as i32),
+        bye_points: get_opt_s!("bye_points", &mut ac.options, Integer)
+            .ok()
+            .map(|p| p as i32),
+        group_size: get_opt_s!("group_size", &mut ac.options, Integer)
+            .ok()
+            .map(|s| s as i32),
+    };
     let mut conn = state.diesel_cxn().await.map_err(|e| e.to_string())?;
     let szn = Season::get_active_season(conn.deref_mut())
         .and_then(|os| os.ok_or(diesel::result::Error::NotFound))
         .map_err_to_string()?;
-    let nb = NewBracket::new(&szn, name, bt);
-    nb.save(conn.deref_mut()).map_err(|e| e.to_string())?;
+    let bracket =
+        create_bracket(&szn, name, bt, &options, conn.deref_mut()).map_err(|e| match e {
+            BracketError::Other(s) => s,
+            e => e.to_string(),
+        })?;
+    NewAuditEvent::new(actor, CREATE_BRACKET_CMD, AuditEntity::Bracket, bracket.id)
+        .after(&bracket)
+        .record(conn.deref_mut());
     Ok(plain_interaction_response("Bracket created!"))
 }
 
 async fn handle_finish_bracket(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
     let mut conn = state.diesel_cxn().await.map_err(|e| e.to_string())?;
     let mut bracket =
         Bracket::get_by_id(bracket_id as i32, conn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(actor, FINISH_BRACKET_CMD, AuditEntity::Bracket, bracket.id)
+        .before(&bracket);
     let resp = if bracket.finish(conn.deref_mut()).map_err_to_string()? {
         bracket.update(conn.deref_mut()).map_err_to_string()?;
+        audit.after(&bracket).record(conn.deref_mut());
         "Bracket finished."
     } else {
         "Unable to finish that bracket (round still in progress)."
@@ -1645,32 +2251,292 @@ async fn handle_finish_bracket(
     Ok(plain_interaction_response(resp))
 }
 
-// wow dude great function name
-async fn get_race_finish_opts_from_command_opts(
-    options: &mut Vec<CommandDataOption>,
+async fn handle_set_bracket_scoring(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
-    force: bool,
-) -> Result<RaceFinishOptions, String> {
-    let race_id = get_opt_s!("race_id", options, Integer)?;
-    let p1_res = get_opt_s!("p1_result", options, String)?;
-    let p2_res = get_opt_s!("p2_result", options, String)?;
-    let racetime_url = get_opt_s!("racetime_url", options, String).ok();
-    let r1 = parse_race_result(&p1_res).map_err_to_string()?;
+) -> Result<InteractionResponse, String> {
+    let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
+    let win_points = get_opt_s!("win_points", &mut ac.options, Integer).ok();
+    let draw_points = get_opt_s!("draw_points", &mut ac.options, Integer).ok();
+    let loss_points = get_opt_s!("loss_points", &mut ac.options, Integer).ok();
+    // discord enforces the minimum, but it's stored in seconds as an i32 either way
+    let forfeit_penalty = get_opt_s!("forfeit_penalty_minutes", &mut ac.options, Integer)
+        .ok()
+        .map(|m| {
+            i32::try_from(m)
+                .ok()
+                .filter(|m| *m >= 1)
+                .and_then(|m| m.checked_mul(60))
+                .map(|seconds| seconds as u32)
+                .ok_or(format!(
+                    "Forfeit penalty must be a reasonable number of minutes, not {m}"
+                ))
+        })
+        .transpose()?;
+    let tiebreakers = get_opt_s!("tiebreakers", &mut ac.options, String)
+        .ok()
+        .map(|s| parse_tiebreakers(&s))
+        .transpose()?;
+    let placement_points = get_opt_s!("placement_points", &mut ac.options, String)
+        .ok()
+        .map(|s| parse_placement_points(&s))
+        .transpose()?;
 
-    let r2 = parse_race_result(&p2_res).map_err_to_string()?;
-    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
-    let race = match BracketRace::get_by_id(race_id as i32, cxn.deref_mut()) {
-        Ok(r) => r,
-        Err(Error::NotFound) => {
-            return Err("That race ID does not exist".to_string());
-        }
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut bracket =
+        Bracket::get_by_id(bracket_id as i32, conn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(
+        actor,
+        SET_BRACKET_SCORING_CMD,
+        AuditEntity::Bracket,
+        bracket.id,
+    )
+    .before(&bracket);
+    let mut rules = bracket.scoring_rules().map_err_to_string()?;
+    if let Some(p) = win_points {
+        rules.points_per_win = p as i32;
+    }
+    if let Some(p) = draw_points {
+        rules.points_per_draw = p as i32;
+    }
+    if let Some(p) = loss_points {
+        rules.points_per_loss = p as i32;
+    }
+    if let Some(p) = forfeit_penalty {
+        rules.forfeit_time_penalty = p;
+    }
+    if let Some(t) = tiebreakers {
+        rules.tiebreakers = t;
+    }
+    if let Some(p) = placement_points {
+        rules.placement_points = p;
+    }
+    bracket.set_scoring_rules(&rules).map_err_to_string()?;
+    bracket.update(conn.deref_mut()).map_err_to_string()?;
+    audit.after(&bracket).record(conn.deref_mut());
+    let tiebreakers = rules
+        .tiebreakers
+        .iter()
+        .map(|t| format!("{t:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(plain_interaction_response(format!(
+        "Scoring for {} updated. Win/draw/loss: {}/{}/{} half-points. Forfeits: {} minutes. \
+        Tiebreakers: {tiebreakers}.",
+        bracket.name,
+        rules.points_per_win,
+        rules.points_per_draw,
+        rules.points_per_loss,
+        rules.forfeit_time_penalty / 60,
+    )))
+}
+
+async fn handle_set_bracket_best_of(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
+    let best_of = get_opt_s!("best_of", &mut ac.options, Integer)?;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut bracket =
+        Bracket::get_by_id(bracket_id as i32, conn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(
+        actor,
+        SET_BRACKET_BEST_OF_CMD,
+        AuditEntity::Bracket,
+        bracket.id,
+    )
+    .before(&bracket);
+    let changed = bracket
+        .set_best_of(best_of as i32, conn.deref_mut())
+        .map_err_to_string()?;
+    audit.after(&bracket).record(conn.deref_mut());
+    Ok(plain_interaction_response(format!(
+        "{} races are now best of {best_of}. Updated {changed} existing races.",
+        bracket.name
+    )))
+}
+
+/// deadlines are the end of the given day (YYYY/MM/DD), US/Eastern
+fn deadline_from_day(day: Option<String>) -> Result<Option<i64>, String> {
+    day.map(|d| {
+        datetime_from_options(&d, 11, 59, "PM")
+            .map(|dt| dt.timestamp())
+            .map_err_to_string()
+    })
+    .transpose()
+}
+
+async fn handle_set_round_deadlines(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
+    let round_num = get_opt_s!("round", &mut ac.options, Integer)?;
+    let schedule_by = deadline_from_day(get_opt_s!("schedule_by", &mut ac.options, String).ok())?;
+    let play_by = deadline_from_day(get_opt_s!("play_by", &mut ac.options, String).ok())?;
+    let policy = match get_opt_s!("missed_deadline", &mut ac.options, String) {
+        Ok(p) => serde_json::from_str(&p).map_err(|e| format!("Error parsing option: {e}"))?,
+        Err(_) => MissedDeadlinePolicy::Nothing,
+    };
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let bracket = Bracket::get_by_id(bracket_id as i32, conn.deref_mut()).map_err_to_string()?;
+    let mut round = BracketRound::get_by_round_num(bracket.id, round_num as i32, conn.deref_mut())
+        .map_err_to_string()?
+        .ok_or(format!(
+            "{} doesn't have a round {round_num}.",
+            bracket.name
+        ))?;
+    let audit = NewAuditEvent::new(
+        actor,
+        SET_ROUND_DEADLINES_CMD,
+        AuditEntity::BracketRound,
+        round.id,
+    )
+    .before(&round);
+    round
+        .set_deadlines(&bracket, schedule_by, play_by, policy)
+        .map_err_to_string()?;
+    round.update(conn.deref_mut()).map_err_to_string()?;
+    audit.after(&round).record(conn.deref_mut());
+    let describe = |t: Option<i64>| t.map(discord::long_timestamp).unwrap_or("none".to_string());
+    Ok(plain_interaction_response(format!(
+        "{} round {round_num} deadlines updated. Schedule by: {}. Play by: {}. Missed deadlines: \
+        {policy:?}.",
+        bracket.name,
+        describe(schedule_by),
+        describe(play_by)
+    )))
+}
+
+/// the admin decision queue
+async fn handle_overdue_races(state: &Arc<DiscordState>) -> Result<InteractionResponse, String> {
+    const MAX_LISTED: usize = 25;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let overdue =
+        awaiting_admin_decision(Utc::now().timestamp(), conn.deref_mut()).map_err_to_string()?;
+    if overdue.is_empty() {
+        return Ok(plain_interaction_response(
+            "No races are waiting for an admin decision.",
+        ));
+    }
+    let mut lines = vec![format!(
+        "{} race(s) missed their play-by deadline and need an admin decision. Report results \
+        with `/{REPORT_RACE_CMD}` (or `/{REPORT_PLACEMENT_RACE_CMD}`).",
+        overdue.len()
+    )];
+    for (round, race) in overdue.iter().take(MAX_LISTED) {
+        let bracket = round.bracket(conn.deref_mut()).map_err_to_string()?;
+        let due = round
+            .play_by
+            .map(discord::long_timestamp)
+            .unwrap_or_default();
+        lines.push(format!(
+            "* #{}: {} ({} round {}, due {due})",
+            race.id,
+            race.title(conn.deref_mut()).map_err_to_string()?,
+            bracket.name,
+            round.round_num
+        ));
+    }
+    if overdue.len() > MAX_LISTED {
+        lines.push(format!("...and {} more", overdue.len() - MAX_LISTED));
+    }
+    Ok(plain_interaction_response(lines.join("\n")))
+}
+
+async fn handle_drop_player_from_bracket(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let discord_id = get_opt_s!("user", &mut ac.options, User)?;
+    let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
+    let races_raw = get_opt_s!("unfinished_races", &mut ac.options, String)?;
+    let races: DroppedPlayerRaces =
+        serde_json::from_str(&races_raw).map_err(|e| format!("Error parsing option: {e}"))?;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let player = Player::get_by_discord_id(&discord_id.to_string(), conn.deref_mut())
+        .map_err_to_string()?
+        .ok_or("That user isn't a player.".to_string())?;
+    let mut bracket =
+        Bracket::get_by_id(bracket_id as i32, conn.deref_mut()).map_err_to_string()?;
+    let count = drop_player_from_bracket(
+        &mut bracket,
+        &player,
+        races,
+        conn.deref_mut(),
+        Some((state.discord_client.as_ref(), &state.channel_config)),
+    )
+    .await
+    .map_err_to_string()?;
+    NewAuditEvent::new(
+        actor,
+        DROP_PLAYER_FROM_BRACKET_CMD,
+        AuditEntity::Bracket,
+        bracket.id,
+    )
+    .after(&json!({
+        "player_id": player.id,
+        "unfinished_races": races,
+        "races_affected": count,
+    }))
+    .record(conn.deref_mut());
+    let verb = match races {
+        DroppedPlayerRaces::Forfeit => "forfeited",
+        DroppedPlayerRaces::Cancel => "cancelled",
+    };
+    Ok(plain_interaction_response(format!(
+        "{} dropped from {}. {count} unfinished race(s) {verb}.",
+        player.name, bracket.name
+    )))
+}
+
+// wow dude great function name
+async fn get_race_finish_opts_from_command_opts(
+    options: &mut Vec<CommandDataOption>,
+    state: &Arc<DiscordState>,
+    force: bool,
+) -> Result<RaceFinishOptions, String> {
+    let race_id = get_opt_s!("race_id", options, Integer)?;
+    let p1_res = get_opt_s!("p1_result", options, String)?;
+    let p2_res = get_opt_s!("p2_result", options, String)?;
+    let racetime_url = get_opt_s!("racetime_url", options, String).ok();
+    race_finish_opts(race_id as i32, &p1_res, &p2_res, racetime_url, state, force).await
+}
+
+async fn race_finish_opts(
+    race_id: i32,
+    p1_res: &str,
+    p2_res: &str,
+    racetime_url: Option<String>,
+    state: &Arc<DiscordState>,
+    force: bool,
+) -> Result<RaceFinishOptions, String> {
+    let r1 = parse_race_result(p1_res).map_err_to_string()?;
+
+    let r2 = parse_race_result(p2_res).map_err_to_string()?;
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let race = match BracketRace::get_by_id(race_id, cxn.deref_mut()) {
+        Ok(r) => r,
+        Err(Error::NotFound) => {
+            return Err("That race ID does not exist".to_string());
+        }
         Err(e) => {
             return Err(format!("Other database error: {e}"));
         }
     };
     let mut info = race.info(cxn.deref_mut()).map_err_to_string()?;
-    if let Some(rt) = racetime_url {
-        info.racetime_gg_url = Some(rt);
+    if let Some(rt) = &racetime_url {
+        info.racetime_gg_url = Some(rt.clone());
+    }
+    if race.is_placement_race() {
+        return Err(format!(
+            "That race has more than two players. Please use `/{REPORT_PLACEMENT_RACE_CMD}`."
+        ));
     }
     let (p1, p2) = race.players(cxn.deref_mut()).map_err_to_string()?;
     Ok(RaceFinishOptions {
@@ -1680,19 +2546,40 @@ async fn get_race_finish_opts_from_command_opts(
         player_1_result: r1,
         player_2: p2,
         player_2_result: r2,
+        racetime_gg_url: racetime_url,
         channel_id: state.channel_config.match_results,
         force_update: force,
     })
 }
 
+/// starts an audit event for reporting `race`'s results, to be finished with
+/// [record_race_result_audit] once they're saved
+fn race_result_audit(actor: &Actor, action: &str, race: &BracketRace) -> NewAuditEvent {
+    NewAuditEvent::new(actor, action, AuditEntity::BracketRace, race.id).before(race)
+}
+
+fn record_race_result_audit(audit: NewAuditEvent, race_id: i32, conn: &mut SqliteConnection) {
+    match BracketRace::get_by_id(race_id, conn) {
+        Ok(race) => audit.after(&race).record(conn),
+        Err(e) => {
+            warn!("Error fetching race {race_id} for the audit log: {e}");
+            audit.record(conn);
+        }
+    }
+}
+
 async fn handle_report_race(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let opts = get_race_finish_opts_from_command_opts(&mut ac.options, state, false).await?;
     let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let race_id = opts.bracket_race.id;
+    let audit = race_result_audit(actor, REPORT_RACE_CMD, &opts.bracket_race);
     trigger_race_finish(opts, cxn.deref_mut(), Some(&state.discord_client),  &state.channel_config)
         .await
+        .map(|()| record_race_result_audit(audit, race_id, cxn.deref_mut()))
         .map(|_|plain_interaction_response(format!(
             "Race has been updated. You should see a post in {}",
             state.channel_config.match_results.mention()
@@ -1706,18 +2593,128 @@ async fn handle_report_race(
         })
 }
 
+/// parses e.g. "alice 1:23:45, bob forfeit" into results for `players`, matching them by name
+fn parse_placement_results(
+    s: &str,
+    players: &[Player],
+) -> Result<Vec<(Player, PlayerResult)>, String> {
+    let mut results = vec![];
+    for entry in s.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, result) = entry
+            .rsplit_once(' ')
+            .ok_or(format!("Expected \"name result\", got \"{entry}\""))?;
+        let player = players
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or(format!("{name} isn't in that race"))?;
+        let result = parse_race_result(result.trim().to_lowercase().as_str())
+            .map_err(|e| format!("Invalid result for {name}: {e}"))?;
+        results.push((player.clone(), result));
+    }
+    Ok(results)
+}
+
+async fn handle_report_placement_race(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let race_id = get_opt_s!("race_id", &mut ac.options, Integer)?;
+    let results = get_opt_s!("results", &mut ac.options, String)?;
+    let racetime_url = get_opt_s!("racetime_url", &mut ac.options, String).ok();
+    let force = get_opt_s!("force", &mut ac.options, Boolean).unwrap_or(false);
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let race = match BracketRace::get_by_id(race_id as i32, cxn.deref_mut()) {
+        Ok(r) => r,
+        Err(Error::NotFound) => {
+            return Err("That race ID does not exist".to_string());
+        }
+        Err(e) => {
+            return Err(format!("Other database error: {e}"));
+        }
+    };
+    if !race.is_placement_race() {
+        return Err(format!(
+            "That race is head-to-head. Please use `/{REPORT_RACE_CMD}`."
+        ));
+    }
+    let players = race.all_players(cxn.deref_mut()).map_err_to_string()?;
+    let results = parse_placement_results(&results, &players)?;
+    let mut info = race.info(cxn.deref_mut()).map_err_to_string()?;
+    if let Some(rt) = racetime_url {
+        info.racetime_gg_url = Some(rt);
+        info.update(cxn.deref_mut()).map_err_to_string()?;
+    }
+    let race_id = race.id;
+    let audit = race_result_audit(actor, REPORT_PLACEMENT_RACE_CMD, &race);
+    let opts = PlacementRaceFinishOptions {
+        bracket_race: race,
+        info,
+        results,
+        channel_id: state.channel_config.match_results,
+        force_update: force,
+    };
+    trigger_placement_race_finish(
+        opts,
+        cxn.deref_mut(),
+        Some(&state.discord_client),
+        &state.channel_config,
+    )
+    .await
+    .map(|()| {
+        record_race_result_audit(audit, race_id, cxn.deref_mut());
+        plain_interaction_response(format!(
+            "Race has been updated. Once everyone has a result, you should see a post in {}",
+            state.channel_config.match_results.mention()
+        ))
+    })
+    .map_err(|e| match e {
+        RaceFinishError::BracketRaceStateError(BracketRaceStateError::InvalidState(_, _)) => {
+            "That race is already finished. Use the `force` option if you are trying to change \
+            its results."
+                .to_string()
+        }
+        e => e.to_string(),
+    })
+}
+
 async fn handle_rereport_race(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let opts = get_race_finish_opts_from_command_opts(&mut ac.options, state, true).await?;
-    if opts.bracket_race.state().map_err_to_string()? != BracketRaceState::Finished {
+    correct_race_result(opts, actor, UPDATE_FINISHED_RACE_CMD, state).await?;
+    Ok(plain_interaction_response(format!(
+        "Race has been updated. You should see a post in {}",
+        state.channel_config.match_results.mention()
+    )))
+}
+
+/// overwrites the results of a finished race (or one game of a series) and re-posts its match
+/// results. This is what `/update_finished_race` does, and how disputed results get corrected.
+async fn correct_race_result(
+    opts: RaceFinishOptions,
+    actor: &Actor,
+    action: &str,
+    state: &Arc<DiscordState>,
+) -> Result<(), String> {
+    if opts.bracket_race.is_series() {
+        if opts.racetime_gg_url.is_none() {
+            return Err(
+                "That race is a series. Please give the racetime url of the game you're correcting."
+                    .to_string(),
+            );
+        }
+    } else if opts.bracket_race.state().map_err_to_string()? != BracketRaceState::Finished {
         return Err(format!(
             "That race is not yet reported. Please use `/{REPORT_RACE_CMD}` if you are trying to \
             report an unfinished race."
         ));
     }
     let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let race_id = opts.bracket_race.id;
+    let audit = race_result_audit(actor, action, &opts.bracket_race);
     trigger_race_finish(
         opts,
         cxn.deref_mut(),
@@ -1725,17 +2722,321 @@ async fn handle_rereport_race(
         &state.channel_config,
     )
     .await
-    .map(|_| {
-        plain_interaction_response(format!(
-            "Race has been updated. You should see a post in {}",
-            state.channel_config.match_results.mention()
-        ))
-    })
+    .map(|()| record_race_result_audit(audit, race_id, cxn.deref_mut()))
     .map_err_to_string()
 }
 
+/// lets players contest the result of one of their finished races. Admins hear about it in the
+/// admin channel, and decide with `/resolve_dispute`.
+async fn handle_dispute_race(
+    mut ac: Box<CommandData>,
+    interaction: Box<InteractionCreate>,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let uid = interaction
+        .author_id()
+        .ok_or("No user found on dispute_race command")?;
+    let race_id = get_opt_s!("race_id", &mut ac.options, Integer)?;
+    let reason = get_opt_s!("reason", &mut ac.options, String)?;
+    let evidence_url = get_opt_s!("evidence_url", &mut ac.options, String)
+        .ok()
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if let Some(e) = &evidence_url {
+        // this ends up as a link on the race admin page, so only allow web links
+        match Url::parse(e) {
+            Ok(u) if u.scheme() == "https" || u.scheme() == "http" => {}
+            _ => {
+                return Ok(plain_ephemeral_response(format!(
+                    "{e} doesn't look like a link."
+                )));
+            }
+        }
+    }
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let player = match Player::get_by_discord_id(&uid.to_string(), &mut conn).map_err_to_string()? {
+        Some(p) => p,
+        None => {
+            return Ok(plain_ephemeral_response(
+                "You do not seem to be registered.",
+            ));
+        }
+    };
+    let race = match BracketRace::get_by_id(race_id as i32, &mut conn) {
+        Ok(r) => r,
+        Err(Error::NotFound) => {
+            return Ok(plain_ephemeral_response("That race ID does not exist."));
+        }
+        Err(e) => {
+            return Err(e.to_string());
+        }
+    };
+    if !race
+        .includes_player(&player, &mut conn)
+        .map_err_to_string()?
+    {
+        return Ok(plain_ephemeral_response(
+            "You can only dispute your own races.",
+        ));
+    }
+    let dispute = match race_disputes::open_dispute(
+        &race,
+        uid.to_string(),
+        reason,
+        evidence_url,
+        &mut conn,
+    ) {
+        Ok(d) => d,
+        Err(NMGLeagueBotError::StateError(e)) => {
+            return Ok(plain_ephemeral_response(format!(
+                "You can't dispute that race: {e}."
+            )));
+        }
+        Err(e) => {
+            return Err(e.to_string());
+        }
+    };
+    NewAuditEvent::new(
+        &Actor::discord(uid),
+        DISPUTE_RACE_CMD,
+        AuditEntity::RaceDispute,
+        dispute.id,
+    )
+    .after(&dispute)
+    .record(&mut conn);
+
+    let title = race.title(&mut conn).map_err_to_string()?;
+    let evidence = dispute
+        .evidence_url
+        .as_ref()
+        .map(|e| format!("\nEvidence: {e}"))
+        .unwrap_or_default();
+    let content = format!(
+        "{} disputed the result of race #{} ({title}) as dispute #{}:\n> {}{evidence}\n\
+        Use `/{RESOLVE_DISPUTE_CMD}` to keep or correct it.",
+        player.name, race.id, dispute.id, dispute.reason
+    );
+    if let Err(e) = state
+        .discord_client
+        .create_message(state.channel_config.admin)
+        .content(&content)
+        .await
+    {
+        // it's still in `/disputes`, so don't act like it failed
+        warn!("Error telling admins about dispute {}: {e}", dispute.id);
+    }
+    Ok(plain_ephemeral_response(format!(
+        "Your dispute of {title} (#{}) has been sent to the admins. You'll get a DM once they've \
+        decided.",
+        dispute.id
+    )))
+}
+
+async fn handle_disputes(state: &Arc<DiscordState>) -> Result<InteractionResponse, String> {
+    const MAX_LISTED: usize = 10;
+    const MAX_REASON_CHARS: usize = 100;
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let open = RaceDispute::open(conn.deref_mut()).map_err_to_string()?;
+    if open.is_empty() {
+        return Ok(plain_interaction_response("There are no open disputes."));
+    }
+    let mut lines = vec![format!(
+        "{} open dispute(s). Keep or correct them with `/{RESOLVE_DISPUTE_CMD}`.",
+        open.len()
+    )];
+    for dispute in open.iter().take(MAX_LISTED) {
+        let race = BracketRace::get_by_id(dispute.bracket_race_id, conn.deref_mut())
+            .map_err_to_string()?;
+        let opener = Player::get_by_discord_id(&dispute.opener_discord_id, conn.deref_mut())
+            .map_err_to_string()?
+            .map(|p| p.name)
+            .unwrap_or(dispute.opener_discord_id.clone());
+        let mut reason = dispute
+            .reason
+            .chars()
+            .take(MAX_REASON_CHARS)
+            .collect::<String>();
+        if dispute.reason.chars().count() > MAX_REASON_CHARS {
+            reason.push_str("...");
+        }
+        let evidence = dispute
+            .evidence_url
+            .as_ref()
+            .map(|e| format!(" (evidence: <{e}>)"))
+            .unwrap_or_default();
+        lines.push(format!(
+            "* #{}: race #{} ({}), disputed by {opener} {}: {reason}{evidence}",
+            dispute.id,
+            race.id,
+            race.title(conn.deref_mut()).map_err_to_string()?,
+            discord::long_timestamp(dispute.created),
+        ));
+    }
+    if open.len() > MAX_LISTED {
+        lines.push(format!("...and {} more", open.len() - MAX_LISTED));
+    }
+    Ok(plain_interaction_response(lines.join("\n")))
+}
+
+/// keeps or corrects the result of a disputed race, then DMs everyone in the race about it
+async fn handle_resolve_dispute(
+    mut ac: Box<CommandData>,
+    interaction: Box<InteractionCreate>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let admin_id = interaction
+        .author_id()
+        .ok_or("No author on resolve_dispute command")?;
+    let dispute_id = get_opt_s!("dispute_id", &mut ac.options, Integer)?;
+    let decision = match get_opt_s!("decision", &mut ac.options, String)?.as_str() {
+        "keep" => DisputeState::ResultKept,
+        "correct" => DisputeState::Corrected,
+        other => {
+            return Err(format!("Unknown decision {other}"));
+        }
+    };
+    let note = get_opt_s!("note", &mut ac.options, String)
+        .ok()
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    let mut dispute = {
+        let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+        match RaceDispute::get_by_id(dispute_id as i32, cxn.deref_mut()) {
+            Ok(d) => d,
+            Err(Error::NotFound) => {
+                return Err(format!("Dispute {dispute_id} not found."));
+            }
+            Err(e) => {
+                return Err(e.to_string());
+            }
+        }
+    };
+    if dispute.state().map_err_to_string()? != DisputeState::Open {
+        return Err(format!(
+            "Dispute #{} has already been resolved.",
+            dispute.id
+        ));
+    }
+    let correction = if decision == DisputeState::Corrected {
+        let (p1_res, p2_res) = match (
+            get_opt_s!("p1_result", &mut ac.options, String),
+            get_opt_s!("p2_result", &mut ac.options, String),
+        ) {
+            (Ok(p1), Ok(p2)) => (p1, p2),
+            _ => {
+                return Err("Correcting a result needs p1_result and p2_result.".to_string());
+            }
+        };
+        let racetime_url = get_opt_s!("racetime_url", &mut ac.options, String).ok();
+        Some(
+            race_finish_opts(
+                dispute.bracket_race_id,
+                &p1_res,
+                &p2_res,
+                racetime_url,
+                state,
+                true,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    // claim the dispute before touching the race, so it can't be resolved (or corrected) twice
+    let audit = NewAuditEvent::new(
+        actor,
+        RESOLVE_DISPUTE_CMD,
+        AuditEntity::RaceDispute,
+        dispute.id,
+    )
+    .before(&dispute);
+    let claimed = {
+        let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+        dispute
+            .resolve(decision, admin_id.to_string(), note, cxn.deref_mut())
+            .map_err_to_string()?
+    };
+    if !claimed {
+        return Err(format!(
+            "Dispute #{} has already been resolved.",
+            dispute.id
+        ));
+    }
+    if let Some(opts) = correction {
+        if let Err(e) = correct_race_result(opts, actor, RESOLVE_DISPUTE_CMD, state).await {
+            // the result wasn't corrected, so the dispute is still open
+            match state.diesel_cxn().await {
+                Ok(mut cxn) => {
+                    match dispute.reopen(cxn.deref_mut()) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            warn!("Dispute {} changed before it could be reopened", dispute.id);
+                        }
+                        Err(re) => {
+                            warn!("Error reopening dispute {}: {re}", dispute.id);
+                        }
+                    }
+                }
+                Err(re) => {
+                    warn!("Error reopening dispute {}: {re}", dispute.id);
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    audit.after(&dispute).record(cxn.deref_mut());
+    let race =
+        BracketRace::get_by_id(dispute.bracket_race_id, cxn.deref_mut()).map_err_to_string()?;
+    let title = race.title(cxn.deref_mut()).map_err_to_string()?;
+    let players = race.all_players(cxn.deref_mut()).map_err_to_string()?;
+    drop(cxn);
+
+    let outcome = if decision == DisputeState::Corrected {
+        "corrected the result"
+    } else {
+        "kept the reported result"
+    };
+    let mut content = format!("An admin looked at the dispute of {title} and {outcome}.");
+    if let Some(n) = &dispute.resolution_note {
+        content.push_str(&format!("\n> {n}"));
+    }
+    let mut undelivered = vec![];
+    for player in players {
+        let sent = match player.discord_id() {
+            Ok(uid) => discord::send_dm(uid, &content, state).await,
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = sent {
+            warn!(
+                "Error telling {} about dispute {}: {e}",
+                player.name, dispute.id
+            );
+            undelivered.push(player.name);
+        }
+    }
+    let mut message = format!("Dispute #{} resolved: {outcome}.", dispute.id);
+    if decision == DisputeState::Corrected {
+        message.push_str(&format!(
+            " You should see a post in {}.",
+            state.channel_config.match_results.mention()
+        ));
+    }
+    if !undelivered.is_empty() {
+        message.push_str(&format!(
+            " I couldn't DM {}, so you might need to tell them directly.",
+            undelivered.join(", ")
+        ));
+    }
+    Ok(plain_interaction_response(message))
+}
+
 async fn handle_generate_pairings(
     mut ac: Box<CommandData>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<InteractionResponse, String> {
     let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
@@ -1757,19 +3058,118 @@ async fn handle_generate_pairings(
         season_ordinal = szn.ordinal,
         bracket_id = b.id
     ));
+    let audit =
+        NewAuditEvent::new(actor, GENERATE_PAIRINGS_CMD, AuditEntity::Bracket, b.id).before(&b);
     match b.generate_pairings(cxn.deref_mut()) {
-        Ok(()) => Ok(plain_interaction_response(format!(
-            "Pairings generated! See them at {}{url}",
-            CONFIG.website_url,
-        ))),
+        Ok(()) => {
+            audit.after(&b).record(cxn.deref_mut());
+            Ok(plain_interaction_response(format!(
+                "Pairings generated! See them at {}{url}",
+                CONFIG.website_url,
+            )))
+        }
         Err(e) => Err(format!("Error generating pairings: {e}")),
     }
 }
 
+async fn handle_rollback_round(
+    mut ac: Box<CommandData>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let bracket_id = get_opt_s!("bracket_id", &mut ac.options, Integer)?;
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut bracket = match Bracket::get_by_id(bracket_id as i32, cxn.deref_mut()) {
+        Ok(b) => b,
+        Err(Error::NotFound) => {
+            return Err(format!("Bracket {bracket_id} not found."));
+        }
+        Err(e) => {
+            return Err(e.to_string());
+        }
+    };
+    let (round, count) = rollback_round(
+        &mut bracket,
+        cxn.deref_mut(),
+        Some((state.discord_client.as_ref(), &state.channel_config)),
+    )
+    .await
+    .map_err(|e| format!("Error rolling back round: {e}"))?;
+    NewAuditEvent::new(
+        actor,
+        ROLLBACK_ROUND_CMD,
+        AuditEntity::BracketRound,
+        round.id,
+    )
+    .before(&round)
+    .after(&json!({ "bracket_id": bracket.id, "races_deleted": count }))
+    .record(cxn.deref_mut());
+    Ok(plain_interaction_response(format!(
+        "Rolled back round {} of {} ({count} race(s) deleted). Use `/{GENERATE_PAIRINGS_CMD}` to \
+        generate it again.",
+        round.round_num, bracket.name
+    )))
+}
+
+async fn handle_create_api_token(
+    mut ac: Box<CommandData>,
+    mut interaction: Box<InteractionCreate>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let user = get_user_from_interaction(&mut interaction).ok_or("Unable to find user")?;
+    let name = get_opt_s!("name", &mut ac.options, String)?;
+    let (new_token, secret) = NewApiToken::new(user.id, name);
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let token = new_token.save(cxn.deref_mut()).map_err_to_string()?;
+    NewAuditEvent::new(actor, CREATE_API_TOKEN_CMD, AuditEntity::ApiToken, token.id)
+        .after(&token)
+        .record(cxn.deref_mut());
+    Ok(plain_ephemeral_response(format!(
+        "Created API token #{}. This is the only time it will be shown, so save it now:\n\
+        `{secret}`\n\
+        Send it as `Authorization: Bearer <token>`. It stops working if you lose the admin role, \
+        or if you `/{REVOKE_API_TOKEN_CMD}` it.",
+        token.id
+    )))
+}
+
+async fn handle_revoke_api_token(
+    mut ac: Box<CommandData>,
+    mut interaction: Box<InteractionCreate>,
+    actor: &Actor,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponse, String> {
+    let user = get_user_from_interaction(&mut interaction).ok_or("Unable to find user")?;
+    let token_id = get_opt_s!("token_id", &mut ac.options, Integer)?;
+    let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut token = match ApiToken::get_by_id(token_id as i32, cxn.deref_mut()) {
+        Ok(t) if t.discord_id == user.id.to_string() => t,
+        Ok(_) | Err(Error::NotFound) => {
+            return Err(format!("You don't have an API token #{token_id}."));
+        }
+        Err(e) => {
+            return Err(e.to_string());
+        }
+    };
+    let audit = NewAuditEvent::new(actor, REVOKE_API_TOKEN_CMD, AuditEntity::ApiToken, token.id)
+        .before(&token);
+    token.revoke();
+    token.update(cxn.deref_mut()).map_err_to_string()?;
+    audit.after(&token).record(cxn.deref_mut());
+    let remaining = ApiToken::active_for_user(user.id, cxn.deref_mut()).map_err_to_string()?;
+    Ok(plain_ephemeral_response(format!(
+        "API token #{} revoked. You have {} other active token(s).",
+        token.id,
+        remaining.len()
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::discord::interaction_handlers::application_commands::{
-        datetime_from_options, normalize_racetime_name, tolerate_twitch_links,
+        datetime_from_options, normalize_racetime_name, parse_scheduling_text,
+        tolerate_twitch_links,
     };
     use chrono::{Datelike, Timelike};
 
@@ -1791,6 +3191,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_scheduling_text() {
+        let thing = parse_scheduling_text(" 2022/10/28 8:05 PM ").unwrap();
+        assert_eq!(thing.format("%v %r").to_string(), "28-Oct-2022 08:05:00 PM");
+        assert_eq!(
+            datetime_from_options("2022/10/28", 12, 0, "AM"),
+            parse_scheduling_text("2022/10/28 12:00am")
+        );
+        assert!(parse_scheduling_text("2022/10/28 20:05").is_err());
+        assert!(parse_scheduling_text("2022/10/28 13:05 pm").is_err());
+        assert!(parse_scheduling_text("tomorrow 8:05 pm").is_err());
+    }
+
     #[test]
     fn test_normalize_racetime_name() {
         let already_good = "foxlisk#1234";
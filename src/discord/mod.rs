@@ -3,7 +3,7 @@ extern crate tokio;
 
 use crate::discord::discord_state::DiscordOperations;
 use bb8::RunError;
-use chrono::{DateTime, TimeZone};
+use chrono::{DateTime, TimeZone, Utc};
 use diesel::{ConnectionError, SqliteConnection};
 use log::{info, warn};
 use std::env::VarError;
@@ -23,13 +23,22 @@ use twilight_model::id::marker::UserMarker;
 use twilight_model::id::Id;
 use twilight_util::builder::embed::EmbedFooterBuilder;
 
-use crate::discord::constants::CUSTOM_ID_START_RUN;
+use crate::discord::constants::{
+    CUSTOM_ID_ACCEPT_RESCHEDULE, CUSTOM_ID_CONFIRM_SLOT, CUSTOM_ID_COUNTER_RESCHEDULE,
+    CUSTOM_ID_DECLINE_RESCHEDULE, CUSTOM_ID_REJECT_QUALIFIER, CUSTOM_ID_START_RUN,
+    CUSTOM_ID_VERIFY_QUALIFIER,
+};
 use nmg_league_bot::models::asyncs::race::AsyncRace;
 use nmg_league_bot::models::asyncs::race_run::AsyncRaceRun;
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
 use nmg_league_bot::models::bracket_race_infos::BracketRaceInfo;
 use nmg_league_bot::models::bracket_races::BracketRace;
 use nmg_league_bot::models::player::{MentionOptional, Player};
-use nmg_league_bot::utils::{race_to_nice_embeds, ResultErrToString};
+use nmg_league_bot::models::qualifer_submission::{QualifierSubmission, ReviewState};
+use nmg_league_bot::models::reschedule_proposals::{self, RescheduleProposal};
+use nmg_league_bot::models::season::Season;
+use nmg_league_bot::models::webhooks::{fire_event, LeagueEvent};
+use nmg_league_bot::utils::{format_hms, race_to_nice_embeds, ResultErrToString};
 
 use nmg_league_bot::config::CONFIG;
 use nmg_league_bot::worker_funcs::{
@@ -39,7 +48,9 @@ use nmg_league_bot::{ApplicationCommandOptionError, BracketRaceState, BracketRac
 use thiserror::Error;
 use twilight_model::channel::message::component::{ActionRow, ButtonStyle};
 use twilight_model::channel::message::{Component, Embed};
+use twilight_model::gateway::payload::incoming::InteractionCreate;
 use twilight_model::guild::Permissions;
+use twilight_model::http::interaction::InteractionResponseData;
 pub(crate) use webhooks::Webhooks;
 
 use crate::discord::discord_state::DiscordState;
@@ -72,15 +83,38 @@ pub mod constants {
     pub const CUSTOM_ID_USER_TIME: &str = "user_time";
     pub const CUSTOM_ID_USER_TIME_MODAL: &str = "user_time_modal";
 
+    // these are followed by `:<qualifier submission id>`
+    pub const CUSTOM_ID_VERIFY_QUALIFIER: &str = "verify_qualifier";
+    pub const CUSTOM_ID_REJECT_QUALIFIER: &str = "reject_qualifier";
+    pub const CUSTOM_ID_REJECT_QUALIFIER_MODAL: &str = "reject_qualifier_modal";
+    pub const CUSTOM_ID_REJECT_QUALIFIER_MODAL_INPUT: &str = "reject_qualifier_reason";
+
+    // followed by `:<bracket race id>:<timestamp>`
+    pub const CUSTOM_ID_CONFIRM_SLOT: &str = "confirm_slot";
+
+    // these are followed by `:<reschedule proposal id>`
+    pub const CUSTOM_ID_ACCEPT_RESCHEDULE: &str = "accept_reschedule";
+    pub const CUSTOM_ID_DECLINE_RESCHEDULE: &str = "decline_reschedule";
+    pub const CUSTOM_ID_COUNTER_RESCHEDULE: &str = "counter_reschedule";
+    pub const CUSTOM_ID_COUNTER_RESCHEDULE_MODAL: &str = "counter_reschedule_modal";
+    pub const CUSTOM_ID_COUNTER_RESCHEDULE_MODAL_INPUT: &str = "counter_reschedule_time";
+
     pub const CREATE_ASYNC_CMD: &str = "create_async";
     pub const CANCEL_ASYNC_CMD: &str = "cancel_async";
 
     pub const CREATE_SEASON_CMD: &str = "create_season";
     pub const SET_SEASON_STATE_CMD: &str = "set_season_state";
+    pub const SET_QUALIFIER_RULES_CMD: &str = "set_qualifier_rules";
+    pub const REVIEW_QUALIFIERS_CMD: &str = "review_qualifiers";
     pub const CREATE_BRACKET_CMD: &str = "create_bracket";
     pub const FINISH_BRACKET_CMD: &str = "finish_bracket";
+    pub const SET_BRACKET_SCORING_CMD: &str = "set_bracket_scoring";
+    pub const SET_BRACKET_BEST_OF_CMD: &str = "set_bracket_best_of";
+    pub const SET_ROUND_DEADLINES_CMD: &str = "set_round_deadlines";
+    pub const OVERDUE_RACES_CMD: &str = "overdue_races";
 
     pub const ADD_PLAYER_TO_BRACKET_CMD: &str = "add_player_to_bracket";
+    pub const DROP_PLAYER_FROM_BRACKET_CMD: &str = "drop_player_from_bracket";
 
     pub const CREATE_PLAYER_CMD: &str = "create_player";
     pub const SCHEDULE_RACE_CMD: &str = "schedule_race";
@@ -92,12 +126,23 @@ pub mod constants {
     pub const CHECK_USER_INFO_CMD: &str = "check_user_info";
     pub const RESCHEDULE_RACE_CMD: &str = "reschedule_race";
     pub const REPORT_RACE_CMD: &str = "report_race";
+    pub const REPORT_PLACEMENT_RACE_CMD: &str = "report_placement_race";
     pub const UPDATE_FINISHED_RACE_CMD: &str = "update_finished_race";
+    pub const DISPUTE_RACE_CMD: &str = "dispute_race";
+    pub const DISPUTES_CMD: &str = "disputes";
+    pub const RESOLVE_DISPUTE_CMD: &str = "resolve_dispute";
     pub const GENERATE_PAIRINGS_CMD: &str = "generate_pairings";
+    pub const ROLLBACK_ROUND_CMD: &str = "rollback_round";
 
     pub const SEE_UNSCHEDULED_RACES_CMD: &str = "unscheduled_races";
     pub const COMMENTATORS_CMD: &str = "commentators";
     pub const SET_RESTREAM_CMD: &str = "set_restream";
+
+    pub const AVAILABILITY_CMD: &str = "availability";
+    pub const SUGGEST_TIMES_CMD: &str = "suggest_times";
+
+    pub const CREATE_API_TOKEN_CMD: &str = "create_api_token";
+    pub const REVOKE_API_TOKEN_CMD: &str = "revoke_api_token";
 }
 
 // the functions in here aren't well organized
@@ -154,6 +199,114 @@ If anything goes wrong, tell an admin there was an issue with race `{}`",
     }
 }
 
+/// records an admin's review of a qualifier submission and DMs the player if it was rejected.
+/// The review is saved even if the DM can't be sent.
+pub(crate) async fn review_qualifier_submission(
+    submission_id: i32,
+    review_state: ReviewState,
+    reviewer: Option<Id<UserMarker>>,
+    reason: Option<String>,
+    state: &Arc<DiscordState>,
+) -> Result<QualifierSubmission, String> {
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let mut submission =
+        QualifierSubmission::get_by_id(submission_id, conn.deref_mut()).map_err_to_string()?;
+    let audit = NewAuditEvent::new(
+        // only the website running without auth has anonymous reviewers
+        &reviewer.map(Actor::discord).unwrap_or(Actor::Anonymous),
+        "review_qualifier",
+        AuditEntity::QualifierSubmission,
+        submission.id,
+    )
+    .before(&submission);
+    submission
+        .review(review_state, reviewer.map(|u| u.to_string()), reason)
+        .map_err_to_string()?;
+    submission.update(conn.deref_mut()).map_err_to_string()?;
+    audit.after(&submission).record(conn.deref_mut());
+    if review_state == ReviewState::Rejected {
+        if let Err(e) = notify_rejected_qualifier(&submission, conn.deref_mut(), state).await {
+            warn!(
+                "Error letting the player know qualifier {} was rejected: {e}",
+                submission.id
+            );
+        }
+    }
+    Ok(submission)
+}
+
+async fn notify_rejected_qualifier(
+    submission: &QualifierSubmission,
+    conn: &mut SqliteConnection,
+    state: &Arc<DiscordState>,
+) -> Result<(), String> {
+    let player = submission.player(conn).map_err_to_string()?;
+    let uid = player.discord_id().map_err_to_string()?;
+    let dm = state.get_private_channel(uid).await?;
+    let content = format!(
+        "Your qualifier submission ({}, {}) was rejected by an admin: {}
+
+If you think this was a mistake, please reach out to an admin.",
+        format_hms(submission.reported_time as u64),
+        submission.vod_link,
+        submission
+            .review_reason
+            .as_deref()
+            .unwrap_or("no reason given"),
+    );
+    state
+        .discord_client
+        .create_message(dm)
+        .content(&content)
+        .await
+        .map_err_to_string()?;
+    Ok(())
+}
+
+/// shows the oldest qualifier submission in the active season that's waiting for review, with
+/// buttons to verify or reject it. `preamble` goes above it, e.g. to confirm the last review.
+pub(crate) async fn next_pending_qualifier(
+    preamble: Option<&str>,
+    state: &Arc<DiscordState>,
+) -> Result<InteractionResponseData, String> {
+    let mut conn = state.diesel_cxn().await.map_err_to_string()?;
+    let season = Season::get_active_season(conn.deref_mut())
+        .map_err_to_string()?
+        .ok_or("There's no active season.".to_string())?;
+    let pending = QualifierSubmission::pending(season.id, conn.deref_mut()).map_err_to_string()?;
+    let mut content = preamble.map(|p| format!("{p}\n\n")).unwrap_or_default();
+    let Some(submission) = pending.first() else {
+        content.push_str("No qualifiers are waiting for review.");
+        return Ok(InteractionResponseData {
+            components: Some(vec![]),
+            ..interactions_utils::plain_interaction_data(content)
+        });
+    };
+    let player = submission.player(conn.deref_mut()).map_err_to_string()?;
+    content.push_str(&format!(
+        "{} reported {}: {}\n({} waiting for review)",
+        player.name,
+        format_hms(submission.reported_time as u64),
+        submission.vod_link,
+        pending.len()
+    ));
+    Ok(InteractionResponseData {
+        components: Some(components::action_row(vec![
+            interactions_utils::button_component(
+                "Verify",
+                format!("{CUSTOM_ID_VERIFY_QUALIFIER}:{}", submission.id),
+                ButtonStyle::Success,
+            ),
+            interactions_utils::button_component(
+                "Reject",
+                format!("{CUSTOM_ID_REJECT_QUALIFIER}:{}", submission.id),
+                ButtonStyle::Danger,
+            ),
+        ])),
+        ..interactions_utils::plain_interaction_data(content)
+    })
+}
+
 /// Takes a list of [CommandDataOption]s and tries to find the one with the given name and type. Returns
 /// the [CommandOptionValue] inside of the option.
 ///
@@ -312,7 +465,7 @@ impl Display for ErrorResponse {
 }
 
 #[derive(Error, Debug)]
-enum ScheduleRaceError {
+pub(crate) enum ScheduleRaceError {
     #[error("Connection error: {0}")]
     ConnectionError(#[from] RunError<ConnectionError>),
     #[error("Race already finished")]
@@ -331,9 +484,10 @@ impl From<serde_json::Error> for ScheduleRaceError {
 
 /// Returns a nicely formatted message to return to chat
 /// wipes out existing state about scheduling/commentating/etc
-async fn schedule_race<Tz: TimeZone>(
+pub(crate) async fn schedule_race<Tz: TimeZone>(
     mut the_race: BracketRace,
     when: DateTime<Tz>,
+    actor: &Actor,
     state: &Arc<DiscordState>,
 ) -> Result<String, ScheduleRaceError> {
     if the_race.state()? == BracketRaceState::Finished {
@@ -344,6 +498,31 @@ async fn schedule_race<Tz: TimeZone>(
 
     // TODO: jesus this is gross
     let (old_info, mut new_info) = the_race.schedule(&when, conn)?;
+    fire_event(
+        match old_info.scheduled() {
+            Some(previously) => LeagueEvent::RaceRescheduled {
+                race_id: the_race.id,
+                bracket_id: the_race.bracket_id,
+                scheduled_for: when.timestamp(),
+                previously_scheduled_for: previously.timestamp(),
+            },
+            None => LeagueEvent::RaceScheduled {
+                race_id: the_race.id,
+                bracket_id: the_race.bracket_id,
+                scheduled_for: when.timestamp(),
+            },
+        },
+        conn,
+    );
+    NewAuditEvent::new(
+        actor,
+        "schedule_race",
+        AuditEntity::BracketRace,
+        the_race.id,
+    )
+    .before(&old_info)
+    .after(&new_info)
+    .record(conn);
 
     let p1r = Player::get_by_id(the_race.player_1_id, conn);
     let p1_name = p1r
@@ -417,6 +596,235 @@ async fn schedule_race<Tz: TimeZone>(
     ))
 }
 
+/// whoever triggered an interaction, for the audit log
+pub(crate) fn interaction_actor(
+    interaction: &InteractionCreate,
+    user_facing_error: &str,
+) -> Result<Actor, ErrorResponse> {
+    interaction
+        .author_id()
+        .map(Actor::discord)
+        .ok_or(ErrorResponse::new(
+            user_facing_error,
+            "No user found on interaction",
+        ))
+}
+
+pub(crate) fn long_timestamp(t: i64) -> String {
+    MentionTimestamp::new(t as u64, Some(TimestampStyle::LongDateTime)).mention()
+}
+
+/// sends `content` to `uid` in a DM
+pub(crate) async fn send_dm(
+    uid: Id<UserMarker>,
+    content: &str,
+    state: &Arc<DiscordState>,
+) -> Result<(), String> {
+    let dm = state.get_private_channel(uid).await?;
+    state
+        .discord_client
+        .create_message(dm)
+        .content(content)
+        .await
+        .map(|_| ())
+        .map_err_to_string()
+}
+
+/// the most times suggested per race: rows only fit 5 buttons
+pub(crate) const MAX_SUGGESTED_TIMES: usize = 5;
+
+/// a button for each of `times` that schedules `race` for that time, for anyone in the race
+pub(crate) fn slot_buttons(
+    race: &BracketRace,
+    title: &str,
+    times: &[DateTime<Utc>],
+) -> Vec<Component> {
+    times
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            interactions_utils::button_component(
+                format!("{} #{}", title.chars().take(60).collect::<String>(), i + 1),
+                format!("{CUSTOM_ID_CONFIRM_SLOT}:{}:{}", race.id, t.timestamp()),
+                ButtonStyle::Primary,
+            )
+        })
+        .collect()
+}
+
+/// DMs everyone in `race` (`players`) the `times` they're all available for, with buttons that
+/// schedule it. Undeliverable DMs are only logged.
+pub(crate) async fn send_suggested_times(
+    race: &BracketRace,
+    title: &str,
+    times: &[DateTime<Utc>],
+    players: Vec<Player>,
+    state: &Arc<DiscordState>,
+) {
+    let lines = times
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("{}. {}", i + 1, long_timestamp(t.timestamp())))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let content = format!(
+        "{title} isn't scheduled yet. Everyone in it is available at these times, so pick one to \
+        schedule it:\n{lines}"
+    );
+    let buttons = slot_buttons(race, title, times);
+    for player in players {
+        let sent = match player.discord_id() {
+            Ok(uid) => match state.get_private_channel(uid).await {
+                Ok(dm) => state
+                    .discord_client
+                    .create_message(dm)
+                    .components(&[Component::ActionRow(ActionRow {
+                        id: None,
+                        components: buttons.clone(),
+                    })])
+                    .content(&content)
+                    .await
+                    .map(|_| ())
+                    .map_err_to_string(),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = sent {
+            warn!(
+                "Error sending suggested times for race {} to {}: {e}",
+                race.id, player.name
+            );
+        }
+    }
+}
+
+/// proposes moving `race`, which is already scheduled, to `when` on `proposer`'s behalf, and DMs
+/// everyone else in the race buttons to accept, decline or counter it. Returns a message for the
+/// proposer.
+pub(crate) async fn propose_reschedule<Tz: TimeZone>(
+    race: &BracketRace,
+    proposer: &Player,
+    when: DateTime<Tz>,
+    state: &Arc<DiscordState>,
+) -> Result<String, String> {
+    let (proposal, title, others) = {
+        let mut cxn = state.diesel_cxn().await.map_err_to_string()?;
+        let conn = cxn.deref_mut();
+        let info = race.info(conn).map_err_to_string()?;
+        let proposal = reschedule_proposals::propose(
+            race.id,
+            proposer.discord_id.clone(),
+            &when,
+            info.scheduled_for,
+            conn,
+        )
+        .map_err_to_string()?;
+        NewAuditEvent::new(
+            &Actor::Discord(proposer.discord_id.clone()),
+            "propose_reschedule",
+            AuditEntity::RescheduleProposal,
+            proposal.id,
+        )
+        .after(&proposal)
+        .record(conn);
+        let title = race.title(conn).map_err_to_string()?;
+        let others = race
+            .all_players(conn)
+            .map_err_to_string()?
+            .into_iter()
+            .filter(|p| p.id != proposer.id)
+            .collect::<Vec<_>>();
+        (proposal, title, others)
+    };
+    let new_t = long_timestamp(proposal.proposed_time);
+    let old_t = proposal
+        .previous_time
+        .map(|t| format!(" from {}", long_timestamp(t)))
+        .unwrap_or_default();
+    let content = format!(
+        "{} would like to move {title}{old_t} to {new_t}. It will only be moved if you accept.",
+        proposer.name
+    );
+    let buttons = vec![
+        interactions_utils::button_component(
+            "Accept",
+            format!("{CUSTOM_ID_ACCEPT_RESCHEDULE}:{}", proposal.id),
+            ButtonStyle::Success,
+        ),
+        interactions_utils::button_component(
+            "Decline",
+            format!("{CUSTOM_ID_DECLINE_RESCHEDULE}:{}", proposal.id),
+            ButtonStyle::Danger,
+        ),
+        interactions_utils::button_component(
+            "Suggest another time",
+            format!("{CUSTOM_ID_COUNTER_RESCHEDULE}:{}", proposal.id),
+            ButtonStyle::Secondary,
+        ),
+    ];
+    let mut undelivered = vec![];
+    for other in others {
+        let sent = match other.discord_id() {
+            Ok(uid) => match state.get_private_channel(uid).await {
+                Ok(dm) => state
+                    .discord_client
+                    .create_message(dm)
+                    .components(&[Component::ActionRow(ActionRow {
+                        id: None,
+                        components: buttons.clone(),
+                    })])
+                    .content(&content)
+                    .await
+                    .map(|_| ())
+                    .map_err_to_string(),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = sent {
+            warn!(
+                "Error sending reschedule proposal {} to {}: {e}",
+                proposal.id, other.name
+            );
+            undelivered.push(other.name);
+        }
+    }
+    let mut message = format!(
+        "Asked to move {title} to {new_t}. It will be rescheduled once your opponent accepts."
+    );
+    if !undelivered.is_empty() {
+        message.push_str(&format!(
+            " I couldn't DM {}, so you might need to ask them directly, or ask an admin to \
+            reschedule it.",
+            undelivered.join(", ")
+        ));
+    }
+    Ok(message)
+}
+
+/// tells whoever proposed moving a race what happened to their proposal. Failures are only logged,
+/// since by now the answer has been recorded.
+pub(crate) async fn notify_proposer(
+    proposal: &RescheduleProposal,
+    content: &str,
+    state: &Arc<DiscordState>,
+) {
+    let uid = match proposal.proposer_discord_id.parse::<Id<UserMarker>>() {
+        Ok(u) => u,
+        Err(e) => {
+            warn!("Bad proposer on reschedule proposal {}: {e}", proposal.id);
+            return;
+        }
+    };
+    if let Err(e) = send_dm(uid, content, state).await {
+        warn!(
+            "Error telling proposer about reschedule proposal {}: {e}",
+            proposal.id
+        );
+    }
+}
+
 /// "commentary opportunities"
 async fn create_commportunities_post(
     info: &BracketRaceInfo,
@@ -19,7 +19,9 @@ use crate::discord::discord_state::{DiscordState, DiscordStateError};
 use crate::discord::{
     clear_commportunities_message, clear_tentative_commentary_assignment_message,
 };
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
 use nmg_league_bot::models::bracket_race_infos::BracketRaceInfo;
+use nmg_league_bot::models::webhooks::{fire_event, LeagueEvent};
 use nmg_league_bot::utils::race_to_nice_embeds;
 
 use super::{comm_ids_and_names, embed_with_title};
@@ -69,8 +71,20 @@ async fn _handle_reaction_remove(
     if let Some(mut info) =
         BracketRaceInfo::get_by_commportunities_message_id(reaction.message_id, cxn.deref_mut())?
     {
+        let before = info.commentator_signups(cxn.deref_mut())?;
         let res = info.remove_commentator(reaction.user_id, cxn.deref_mut())?;
         debug!("{} comms removed", res);
+        if res > 0 {
+            NewAuditEvent::new(
+                &Actor::discord(reaction.user_id),
+                "withdraw_commentary",
+                AuditEntity::BracketRace,
+                info.bracket_race_id,
+            )
+            .before(&before)
+            .after(&info.commentator_signups(cxn.deref_mut())?)
+            .record(cxn.deref_mut());
+        }
     } else {
         debug!("Uninteresting reaction removal");
     }
@@ -155,11 +169,18 @@ async fn handle_commportunities_reaction(
 // we are satisfied with the assigned commentators
 async fn handle_commentary_confirmation(
     mut info: BracketRaceInfo,
-    _reaction: Box<ReactionAdd>,
+    reaction: Box<ReactionAdd>,
     state: &Arc<DiscordState>,
 ) -> Result<(), ReactionAddError> {
     let mut cxn = state.diesel_cxn().await?;
     let conn = cxn.deref_mut();
+    let audit = NewAuditEvent::new(
+        &Actor::discord(reaction.user_id),
+        "confirm_commentators",
+        AuditEntity::BracketRace,
+        info.bracket_race_id,
+    )
+    .before(&info);
     let names: Vec<String> = comm_ids_and_names(&info, state, conn)
         .await?
         .into_iter()
@@ -199,6 +220,7 @@ async fn handle_commentary_confirmation(
     }
 
     info.update(conn)?;
+    audit.after(&info).record(conn);
     Ok(())
 }
 
@@ -253,8 +275,18 @@ async fn handle_commentary_signup(
 ) -> Result<(), ReactionAddError> {
     let mut cxn = state.diesel_cxn().await?;
 
+    let before = info.commentator_signups(cxn.deref_mut())?;
     if info.new_commentator_signup(reaction.user_id, cxn.deref_mut())? {
         let commentators = info.commentator_signups(cxn.deref_mut())?;
+        NewAuditEvent::new(
+            &Actor::discord(reaction.user_id),
+            "sign_up_for_commentary",
+            AuditEntity::BracketRace,
+            info.bracket_race_id,
+        )
+        .before(&before)
+        .after(&commentators)
+        .record(cxn.deref_mut());
         let ids = commentators
             .into_iter()
             .map(|c| c.discord_id())
@@ -337,6 +369,13 @@ async fn handle_restream_request_reaction(
         }
     };
     let url = format!("https://twitch.tv/{chan}");
+    let audit = NewAuditEvent::new(
+        &Actor::discord(reaction.user_id),
+        "set_restream",
+        AuditEntity::BracketRace,
+        info.bracket_race_id,
+    )
+    .before(&info);
     info.restream_channel = Some(url.clone());
 
     let mut cxn = state.diesel_cxn().await?;
@@ -347,6 +386,14 @@ async fn handle_restream_request_reaction(
             .await?
             .into_iter()
             .unzip();
+    fire_event(
+        LeagueEvent::CommentatorsAssigned {
+            race_id: info.bracket_race_id,
+            commentator_discord_ids: comm_ids.iter().map(|id| id.to_string()).collect(),
+            restream_channel: Some(url.clone()),
+        },
+        conn,
+    );
 
     let mut fields = race_to_nice_embeds(&info, conn)?;
     fields.push(EmbedField {
@@ -420,6 +467,7 @@ async fn handle_restream_request_reaction(
         warn!("Error clearing tentative commentary assignment: {e}");
     }
     info.update(conn)?;
+    audit.after(&info).record(conn);
 
     Ok(())
 }
@@ -6,6 +6,7 @@ extern crate swiss_pairings;
 use std::num::ParseIntError;
 
 use crate::config::CONFIG;
+use crate::models::brackets::BracketError;
 use bb8::RunError;
 use diesel::ConnectionError;
 #[cfg(feature = "racetime_bot")]
@@ -39,6 +40,7 @@ pub struct ChannelConfig {
     pub zsr: Id<ChannelMarker>,
     pub commentary_discussion: Id<ChannelMarker>,
     pub match_results: Id<ChannelMarker>,
+    pub admin: Id<ChannelMarker>,
 }
 
 impl ChannelConfig {
@@ -52,12 +54,15 @@ impl ChannelConfig {
         let commentary_discussion = CONFIG.commentary_discussion_channel_id;
 
         let match_results = CONFIG.match_results_channel_id;
+
+        let admin = CONFIG.admin_channel_id;
         Self {
             commportunities,
             sirius_inbox,
             zsr,
             commentary_discussion,
             match_results,
+            admin,
         }
     }
 }
@@ -87,7 +92,7 @@ pub enum RaceEventError {
     MissingPlayer(i32),
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug, schemars::JsonSchema)]
 pub enum BracketRaceState {
     New,
     Scheduled,
@@ -102,6 +107,8 @@ pub enum BracketRaceStateError {
     ParseError(#[from] serde_json::Error),
     #[error("Cannot finish race without both players' results")]
     MissingResult,
+    #[error("Player {0} is not in this race")]
+    PlayerNotInRace(i32),
     #[error("Database error: {0}")]
     DatabaseError(#[from] diesel::result::Error),
 }
@@ -175,6 +182,9 @@ pub enum NMGLeagueBotError {
     #[error("Unable to parse finish time")]
     ParseFinishTimeError,
 
+    #[error("Bracket error: {0}")]
+    BracketError(#[from] BracketError),
+
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -117,6 +117,21 @@ async fn main() {
         state.clone(),
     ));
 
+    tokio::spawn(workers::webhook_worker::cron(
+        shutdown_send.subscribe(),
+        state.clone(),
+    ));
+
+    tokio::spawn(workers::round_deadlines_worker::cron(
+        shutdown_send.subscribe(),
+        state.clone(),
+    ));
+
+    tokio::spawn(workers::ratings_worker::cron(
+        shutdown_send.subscribe(),
+        state.clone(),
+    ));
+
     #[cfg(feature = "helper_bot")]
     tokio::spawn(helper_bot::launch(
         shutdown_send.subscribe(),
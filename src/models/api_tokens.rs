@@ -0,0 +1,158 @@
+//! Tokens for the write API.
+//!
+//! Each token belongs to a discord user and is only good while they're an admin (that part is
+//! checked by the website, since it needs discord). The token itself is only ever shown when it's
+//! created: we only store its sha256 hash.
+
+use crate::schema::api_tokens;
+use crate::utils::{epoch_timestamp, hex_string};
+use crate::{save_fn, update_fn};
+use diesel::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::num::ParseIntError;
+use std::str::FromStr;
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone)]
+#[diesel(treat_none_as_null = true)]
+pub struct ApiToken {
+    pub id: i32,
+    pub discord_id: String,
+    pub name: String,
+    #[serde(skip)]
+    token_hash: String,
+    pub created: i64,
+    pub last_used: Option<i64>,
+    pub revoked: bool,
+}
+
+fn hash_token(token: &str) -> String {
+    hex_string(&Sha256::digest(token.as_bytes()))
+}
+
+impl ApiToken {
+    pub fn discord_id(&self) -> Result<Id<UserMarker>, ParseIntError> {
+        Id::from_str(&self.discord_id)
+    }
+
+    /// the unrevoked token with this secret, if there is one
+    pub fn get_by_secret(
+        secret: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Self>, diesel::result::Error> {
+        api_tokens::table
+            .filter(api_tokens::token_hash.eq(hash_token(secret)))
+            .filter(api_tokens::revoked.eq(false))
+            .first(conn)
+            .optional()
+    }
+
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> Result<Self, diesel::result::Error> {
+        api_tokens::table.find(id).first(conn)
+    }
+
+    /// this user's unrevoked tokens, oldest first
+    pub fn active_for_user(
+        discord_id: Id<UserMarker>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Self>, diesel::result::Error> {
+        api_tokens::table
+            .filter(api_tokens::discord_id.eq(discord_id.to_string()))
+            .filter(api_tokens::revoked.eq(false))
+            .order(api_tokens::id.asc())
+            .load(conn)
+    }
+
+    /// records that this token was just used, unless it's been revoked since it was loaded.
+    /// Returns false if it has been, in which case it shouldn't be accepted.
+    /// persists self
+    pub fn mark_used(&mut self, conn: &mut SqliteConnection) -> QueryResult<bool> {
+        let now = epoch_timestamp() as i64;
+        let updated = diesel::update(
+            api_tokens::table
+                .find(self.id)
+                .filter(api_tokens::revoked.eq(false)),
+        )
+        .set(api_tokens::last_used.eq(Some(now)))
+        .execute(conn)?;
+        if updated == 0 {
+            return Ok(false);
+        }
+        self.last_used = Some(now);
+        Ok(true)
+    }
+
+    /// does *not* persist self
+    pub fn revoke(&mut self) {
+        self.revoked = true;
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=api_tokens)]
+pub struct NewApiToken {
+    discord_id: String,
+    name: String,
+    token_hash: String,
+    created: i64,
+}
+
+impl NewApiToken {
+    /// returns the new token and its secret. The secret can't be recovered once this is saved, so
+    /// it has to be handed to the user now.
+    pub fn new<S: Into<String>>(discord_id: Id<UserMarker>, name: S) -> (Self, String) {
+        let secret = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        (
+            Self {
+                discord_id: discord_id.to_string(),
+                name: name.into(),
+                token_hash: hash_token(&secret),
+                created: epoch_timestamp() as i64,
+            },
+            secret,
+        )
+    }
+
+    save_fn!(api_tokens::table, ApiToken);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::api_tokens::{ApiToken, NewApiToken};
+    use crate::test_utils::setup_db;
+    use twilight_model::id::Id;
+
+    #[test]
+    fn test_tokens() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (new_token, secret) = NewApiToken::new(Id::new(1234), "scheduler");
+        let mut token = new_token.save(&mut db)?;
+        assert_ne!(secret, token.token_hash);
+        assert_eq!(
+            Some(token.id),
+            ApiToken::get_by_secret(&secret, &mut db)?.map(|t| t.id)
+        );
+        assert!(ApiToken::get_by_secret("nope", &mut db)?.is_none());
+        assert_eq!(1, ApiToken::active_for_user(Id::new(1234), &mut db)?.len());
+        let mut stale = ApiToken::get_by_id(token.id, &mut db)?;
+        assert!(stale.mark_used(&mut db)?);
+        assert!(stale.last_used.is_some());
+
+        token.revoke();
+        token.update(&mut db)?;
+        assert!(ApiToken::get_by_secret(&secret, &mut db)?.is_none());
+        assert!(ApiToken::active_for_user(Id::new(1234), &mut db)?.is_empty());
+        // using a copy that was loaded before the revoke doesn't bring the token back
+        assert!(!stale.mark_used(&mut db)?);
+        assert!(ApiToken::get_by_id(token.id, &mut db)?.revoked);
+        Ok(())
+    }
+}
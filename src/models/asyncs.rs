@@ -30,7 +30,7 @@ pub mod race {
         on_start_message: Option<String>,
     }
 
-    #[derive(Queryable, Clone, Identifiable)]
+    #[derive(Queryable, Clone, Identifiable, Serialize)]
     #[diesel(table_name = crate::schema::races)]
     pub struct AsyncRace {
         pub id: i32,
@@ -357,7 +357,7 @@ pub mod race_run {
         }
     }
 
-    #[derive(Clone, Queryable, Identifiable)]
+    #[derive(Clone, Queryable, Identifiable, Serialize)]
     #[diesel(table_name = crate::schema::race_runs)]
     pub struct AsyncRaceRun {
         pub id: i32,
@@ -554,6 +554,10 @@ pub mod race_run {
             self.message_id = Some(message_id.to_string());
         }
 
+        pub fn race_id(&self) -> i32 {
+            self.race_id
+        }
+
         pub fn get_race(
             &self,
             conn: &mut SqliteConnection,
@@ -0,0 +1,279 @@
+//! A record of who changed what, for every mutating admin and player action.
+//!
+//! Each [AuditEvent] says which [Actor] did something (an `action`, usually the name of the
+//! command or route) to which entity, with the entity as JSON before and after when that makes
+//! sense. Events are written with [NewAuditEvent::record], which never fails: losing an audit
+//! event isn't worth failing the action over, so errors are only logged.
+
+use crate::save_fn;
+use crate::schema::audit_events;
+use crate::utils::epoch_timestamp;
+use diesel::prelude::*;
+use enum_iterator::Sequence;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
+
+/// whoever did something. Stored as `discord:<user id>`, `api_token:<token id>`, `system` or
+/// `anonymous`, which is also what [AuditFilter::actor] is matched against.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Actor {
+    /// someone using the discord bot or the website (which logs in with discord)
+    Discord(String),
+    /// an admin using the API
+    ApiToken(i32),
+    /// the bot itself, e.g. workers enforcing deadlines
+    System,
+    /// someone using the website while it's running without auth (which is only for development)
+    Anonymous,
+}
+
+impl Actor {
+    pub fn discord(id: Id<UserMarker>) -> Self {
+        Self::Discord(id.to_string())
+    }
+}
+
+impl Display for Actor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Actor::Discord(id) => write!(f, "discord:{id}"),
+            Actor::ApiToken(id) => write!(f, "api_token:{id}"),
+            Actor::System => write!(f, "system"),
+            Actor::Anonymous => write!(f, "anonymous"),
+        }
+    }
+}
+
+impl FromStr for Actor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("discord", id)) if !id.is_empty() => Ok(Self::Discord(id.to_string())),
+            Some(("api_token", id)) => id
+                .parse()
+                .map(Self::ApiToken)
+                .map_err(|e| format!("Invalid API token id {id}: {e}")),
+            None if s == "system" => Ok(Self::System),
+            None if s == "anonymous" => Ok(Self::Anonymous),
+            _ => Err(format!("Unknown actor {s}")),
+        }
+    }
+}
+
+/// the kinds of things audit events are about
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Sequence)]
+pub enum AuditEntity {
+    Season,
+    Bracket,
+    BracketRound,
+    BracketRace,
+    Player,
+    AsyncRace,
+    QualifierSubmission,
+    ApiToken,
+    WebhookSubscription,
+    AvailabilityWindow,
+    RescheduleProposal,
+    RaceDispute,
+}
+
+fn serialized_entity(entity: AuditEntity) -> String {
+    // serializing a unit variant can't fail
+    serde_json::to_string(&entity).unwrap_or_default()
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Clone)]
+pub struct AuditEvent {
+    pub id: i32,
+    pub created: i64,
+    /// serialized [Actor]
+    actor: String,
+    pub action: String,
+    /// serialized [AuditEntity]
+    entity_type: String,
+    pub entity_id: String,
+    pub before_json: Option<String>,
+    pub after_json: Option<String>,
+}
+
+/// what to look for with [AuditEvent::search]. Everything that's set has to match.
+#[derive(Debug, Default, Clone)]
+pub struct AuditFilter {
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<String>,
+    pub actor: Option<Actor>,
+    /// epoch timestamps, inclusive
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl AuditEvent {
+    pub fn actor(&self) -> Result<Actor, String> {
+        Actor::from_str(&self.actor)
+    }
+
+    pub fn entity_type(&self) -> Result<AuditEntity, serde_json::Error> {
+        serde_json::from_str(&self.entity_type)
+    }
+
+    /// matching events, newest first
+    pub fn search(
+        filter: &AuditFilter,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = audit_events::table.into_boxed();
+        if let Some(entity_type) = filter.entity_type {
+            query = query.filter(audit_events::entity_type.eq(serialized_entity(entity_type)));
+        }
+        if let Some(entity_id) = &filter.entity_id {
+            query = query.filter(audit_events::entity_id.eq(entity_id.clone()));
+        }
+        if let Some(actor) = &filter.actor {
+            query = query.filter(audit_events::actor.eq(actor.to_string()));
+        }
+        if let Some(since) = filter.since {
+            query = query.filter(audit_events::created.ge(since));
+        }
+        if let Some(until) = filter.until {
+            query = query.filter(audit_events::created.le(until));
+        }
+        query.order(audit_events::id.desc()).limit(limit).load(conn)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = audit_events)]
+pub struct NewAuditEvent {
+    created: i64,
+    actor: String,
+    action: String,
+    entity_type: String,
+    entity_id: String,
+    before_json: Option<String>,
+    after_json: Option<String>,
+}
+
+fn to_json<T: Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_string(value) {
+        Ok(s) => Some(s),
+        Err(e) => {
+            warn!("Error serializing value for audit event: {e}");
+            None
+        }
+    }
+}
+
+impl NewAuditEvent {
+    pub fn new<I: ToString>(
+        actor: &Actor,
+        action: &str,
+        entity_type: AuditEntity,
+        entity_id: I,
+    ) -> Self {
+        Self {
+            created: epoch_timestamp() as i64,
+            actor: actor.to_string(),
+            action: action.to_string(),
+            entity_type: serialized_entity(entity_type),
+            entity_id: entity_id.to_string(),
+            before_json: None,
+            after_json: None,
+        }
+    }
+
+    /// the entity before the change
+    pub fn before<T: Serialize>(mut self, before: &T) -> Self {
+        self.before_json = to_json(before);
+        self
+    }
+
+    /// the entity after the change
+    pub fn after<T: Serialize>(mut self, after: &T) -> Self {
+        self.after_json = to_json(after);
+        self
+    }
+
+    save_fn!(audit_events::table, AuditEvent);
+
+    /// saves the event, logging (but otherwise ignoring) any error
+    pub fn record(self, conn: &mut SqliteConnection) {
+        if let Err(e) = self.save(conn) {
+            warn!("Error recording audit event {self:?}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::audit_events::{Actor, AuditEntity, AuditEvent, AuditFilter, NewAuditEvent};
+    use crate::test_utils::setup_db;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_actor_round_trip() {
+        for actor in [
+            Actor::Discord("1234".to_string()),
+            Actor::ApiToken(5),
+            Actor::System,
+            Actor::Anonymous,
+        ] {
+            assert_eq!(Ok(actor.clone()), Actor::from_str(&actor.to_string()));
+        }
+        assert!(Actor::from_str("discord:").is_err());
+        assert!(Actor::from_str("api_token:abc").is_err());
+        assert!(Actor::from_str("someone").is_err());
+    }
+
+    #[test]
+    fn test_search() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let admin = Actor::Discord("10".to_string());
+        NewAuditEvent::new(&admin, "set_season_state", AuditEntity::Season, 1)
+            .before(&json!({"state": "Created"}))
+            .after(&json!({"state": "QualifiersOpen"}))
+            .record(&mut db);
+        NewAuditEvent::new(&admin, "cancel_async", AuditEntity::AsyncRace, 1).record(&mut db);
+        NewAuditEvent::new(&Actor::ApiToken(3), "create_season", AuditEntity::Season, 2)
+            .record(&mut db);
+
+        let all = AuditEvent::search(&AuditFilter::default(), 10, &mut db)?;
+        assert_eq!(
+            vec!["create_season", "cancel_async", "set_season_state"],
+            all.iter().map(|e| e.action.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Ok(Actor::ApiToken(3)), all[0].actor());
+        assert_eq!(AuditEntity::AsyncRace, all[1].entity_type()?);
+        assert_eq!(
+            Some(r#"{"state":"QualifiersOpen"}"#.to_string()),
+            all[2].after_json
+        );
+        assert_eq!(None, all[1].before_json);
+
+        // entity ids are only unique per entity type
+        let season_1 = AuditFilter {
+            entity_type: Some(AuditEntity::Season),
+            entity_id: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(1, AuditEvent::search(&season_1, 10, &mut db)?.len());
+        let by_admin = AuditFilter {
+            actor: Some(admin),
+            ..Default::default()
+        };
+        assert_eq!(2, AuditEvent::search(&by_admin, 10, &mut db)?.len());
+        assert_eq!(1, AuditEvent::search(&by_admin, 1, &mut db)?.len());
+        let later = AuditFilter {
+            since: Some(all[0].created + 1),
+            ..Default::default()
+        };
+        assert!(AuditEvent::search(&later, 10, &mut db)?.is_empty());
+        Ok(())
+    }
+}
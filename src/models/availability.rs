@@ -0,0 +1,414 @@
+//! Weekly windows when players are usually free to race, and finding times when everyone in a
+//! race is.
+//!
+//! Windows are stored in the player's own time zone, so "Tuesdays 19:00-23:00" stays 19:00-23:00
+//! for them across daylight saving changes.
+
+use crate::models::bracket_races::BracketRace;
+use crate::schema::availability_windows;
+use crate::{delete_fn, save_fn};
+use chrono::{DateTime, Datelike, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use serde::Serialize;
+use thiserror::Error;
+
+/// how long a race is assumed to take when suggesting times for it
+pub const SUGGESTED_RACE_MINUTES: i64 = 120;
+/// how far ahead to look for times to suggest
+pub const SUGGESTION_DAYS: i64 = 7;
+/// suggested times are at least this far away, so there's time to set the race up
+const SUGGESTION_LEAD_MINUTES: i64 = 60;
+/// suggested times start on these boundaries
+const SLOT_MINUTES: i64 = 15;
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// indexed by [AvailabilityWindow::weekday]
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Error, Debug)]
+pub enum AvailabilityError {
+    #[error("Unknown time zone {0:?}. Use a name like America/New_York.")]
+    UnknownTimezone(String),
+    #[error("Couldn't understand the time {0:?}. Try something like 19:30 or 7:30pm.")]
+    InvalidTime(String),
+    #[error("Unknown day {0:?}")]
+    InvalidWeekday(String),
+    #[error("Availability has to end after it starts")]
+    EmptyWindow,
+}
+
+pub fn parse_timezone(s: &str) -> Result<Tz, AvailabilityError> {
+    s.trim()
+        .parse()
+        .map_err(|_| AvailabilityError::UnknownTimezone(s.to_string()))
+}
+
+/// parses a day name ("Monday", "tue", ...) into an index into [WEEKDAYS]
+pub fn parse_weekday(s: &str) -> Result<i32, AvailabilityError> {
+    let wd: Weekday = s
+        .trim()
+        .parse()
+        .map_err(|_| AvailabilityError::InvalidWeekday(s.to_string()))?;
+    Ok(wd.num_days_from_monday() as i32)
+}
+
+/// parses a time of day (`19:30`, `7:30pm`, `7pm`, or `24:00` for the end of the day) into
+/// minutes after midnight
+pub fn parse_time_of_day(s: &str) -> Result<i32, AvailabilityError> {
+    let err = || AvailabilityError::InvalidTime(s.to_string());
+    let lower = s.trim().to_lowercase();
+    let (rest, pm) = if let Some(r) = lower.strip_suffix("pm") {
+        (r.trim(), Some(true))
+    } else if let Some(r) = lower.strip_suffix("am") {
+        (r.trim(), Some(false))
+    } else {
+        (lower.as_str(), None)
+    };
+    let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hour: i32 = h.parse().map_err(|_| err())?;
+    let minute: i32 = m.parse().map_err(|_| err())?;
+    if !(0..60).contains(&minute) {
+        return Err(err());
+    }
+    let hour = match pm {
+        Some(pm) => {
+            if !(1..=12).contains(&hour) {
+                return Err(err());
+            }
+            hour % 12 + if pm { 12 } else { 0 }
+        }
+        None => hour,
+    };
+    let total = hour * 60 + minute;
+    if total > MINUTES_PER_DAY {
+        return Err(err());
+    }
+    Ok(total)
+}
+
+/// formats minutes after midnight as `HH:MM`
+pub fn format_time_of_day(minutes: i32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Clone)]
+#[diesel(table_name = availability_windows)]
+pub struct AvailabilityWindow {
+    pub id: i32,
+    pub player_id: i32,
+    /// index into [WEEKDAYS]
+    pub weekday: i32,
+    /// minutes after local midnight
+    pub start_minute: i32,
+    /// minutes after local midnight; up to 1440, for windows that go until midnight
+    pub end_minute: i32,
+    /// IANA name
+    pub timezone: String,
+}
+
+impl AvailabilityWindow {
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> QueryResult<Self> {
+        availability_windows::table.find(id).first(conn)
+    }
+
+    pub fn for_player(player_id: i32, conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        availability_windows::table
+            .filter(availability_windows::player_id.eq(player_id))
+            .order((
+                availability_windows::weekday,
+                availability_windows::start_minute,
+            ))
+            .load(conn)
+    }
+
+    /// deletes all of the player's windows, returning how many there were
+    pub fn clear_for_player(player_id: i32, conn: &mut SqliteConnection) -> QueryResult<usize> {
+        diesel::delete(
+            availability_windows::table.filter(availability_windows::player_id.eq(player_id)),
+        )
+        .execute(conn)
+    }
+
+    delete_fn!(availability_windows::table);
+
+    pub fn weekday(&self) -> Option<Weekday> {
+        WEEKDAYS.get(self.weekday as usize).copied()
+    }
+
+    /// e.g. "Tue 19:00-23:00 (America/New_York)"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {}-{} ({})",
+            self.weekday()
+                .map(|w| w.to_string())
+                .unwrap_or("???".to_string()),
+            format_time_of_day(self.start_minute),
+            format_time_of_day(self.end_minute),
+            self.timezone
+        )
+    }
+
+    /// the parts of `[from, until)` that this window covers, in order. Windows that start or end
+    /// in a daylight saving gap are skipped for that day.
+    fn occurrences(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let (tz, weekday) = match (parse_timezone(&self.timezone), self.weekday()) {
+            (Ok(tz), Some(wd)) => (tz, wd),
+            _ => {
+                return vec![];
+            }
+        };
+        let mut out = vec![];
+        // local days and UTC days don't line up, so look one day past either end
+        let mut day = from.with_timezone(&tz).date_naive() - TimeDelta::days(1);
+        let last = until.with_timezone(&tz).date_naive() + TimeDelta::days(1);
+        while day <= last {
+            if day.weekday() == weekday {
+                let at = |minute: i32| {
+                    let naive = day.and_hms_opt(0, 0, 0)? + TimeDelta::minutes(minute as i64);
+                    tz.from_local_datetime(&naive)
+                        .earliest()
+                        .map(|d| d.with_timezone(&Utc))
+                };
+                if let (Some(start), Some(end)) = (at(self.start_minute), at(self.end_minute)) {
+                    let start = start.max(from);
+                    let end = end.min(until);
+                    if start < end {
+                        out.push((start, end));
+                    }
+                }
+            }
+            day = day + TimeDelta::days(1);
+        }
+        out
+    }
+}
+
+/// sorts intervals and joins the ones that overlap or touch
+fn merged(
+    mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    intervals.sort();
+    let mut out: Vec<(DateTime<Utc>, DateTime<Utc>)> = vec![];
+    for (start, end) in intervals {
+        match out.last_mut() {
+            Some(last) if start <= last.1 => {
+                last.1 = last.1.max(end);
+            }
+            _ => out.push((start, end)),
+        }
+    }
+    out
+}
+
+/// times in `[from, until)` when everyone in `windows` (one list per player) is available for at
+/// least `length`: the earliest one (on a [SLOT_MINUTES] boundary) in each stretch of shared
+/// availability, up to `max` of them. Empty if anyone has no windows at all.
+pub fn common_times(
+    windows: &[Vec<AvailabilityWindow>],
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    length: TimeDelta,
+    max: usize,
+) -> Vec<DateTime<Utc>> {
+    let mut shared: Option<Vec<(DateTime<Utc>, DateTime<Utc>)>> = None;
+    for player_windows in windows {
+        let free = merged(
+            player_windows
+                .iter()
+                .flat_map(|w| w.occurrences(from, until))
+                .collect(),
+        );
+        shared = Some(match shared {
+            None => free,
+            Some(so_far) => {
+                let mut both = vec![];
+                for a in &so_far {
+                    for b in &free {
+                        let start = a.0.max(b.0);
+                        let end = a.1.min(b.1);
+                        if start < end {
+                            both.push((start, end));
+                        }
+                    }
+                }
+                both
+            }
+        });
+    }
+    let slot = SLOT_MINUTES * 60;
+    shared
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let rounded = (start.timestamp() + slot - 1).div_euclid(slot) * slot;
+            let start = Utc.timestamp_opt(rounded, 0).single()?;
+            (start + length <= end).then_some(start)
+        })
+        .take(max)
+        .collect()
+}
+
+/// up to `max` times in the next [SUGGESTION_DAYS] days that everyone in `race` is available for
+/// a [SUGGESTED_RACE_MINUTES] race
+pub fn suggested_times(
+    race: &BracketRace,
+    now: DateTime<Utc>,
+    max: usize,
+    conn: &mut SqliteConnection,
+) -> QueryResult<Vec<DateTime<Utc>>> {
+    let mut windows = vec![];
+    for player in race.all_players(conn)? {
+        windows.push(AvailabilityWindow::for_player(player.id, conn)?);
+    }
+    Ok(common_times(
+        &windows,
+        now + TimeDelta::minutes(SUGGESTION_LEAD_MINUTES),
+        now + TimeDelta::days(SUGGESTION_DAYS),
+        TimeDelta::minutes(SUGGESTED_RACE_MINUTES),
+        max,
+    ))
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = availability_windows)]
+pub struct NewAvailabilityWindow {
+    player_id: i32,
+    weekday: i32,
+    start_minute: i32,
+    end_minute: i32,
+    timezone: String,
+}
+
+impl NewAvailabilityWindow {
+    /// `weekday` is an index into [WEEKDAYS]; `start_minute` and `end_minute` are minutes after
+    /// midnight in `timezone`
+    pub fn new(
+        player_id: i32,
+        weekday: i32,
+        start_minute: i32,
+        end_minute: i32,
+        timezone: Tz,
+    ) -> Result<Self, AvailabilityError> {
+        if !(0..WEEKDAYS.len() as i32).contains(&weekday) {
+            return Err(AvailabilityError::InvalidWeekday(weekday.to_string()));
+        }
+        if start_minute < 0 || end_minute > MINUTES_PER_DAY || start_minute >= end_minute {
+            return Err(AvailabilityError::EmptyWindow);
+        }
+        Ok(Self {
+            player_id,
+            weekday,
+            start_minute,
+            end_minute,
+            timezone: timezone.name().to_string(),
+        })
+    }
+
+    /// like [Self::new], from what a player typed in
+    pub fn parse(
+        player_id: i32,
+        weekday: i32,
+        start: &str,
+        end: &str,
+        timezone: &str,
+    ) -> Result<Self, AvailabilityError> {
+        Self::new(
+            player_id,
+            weekday,
+            parse_time_of_day(start)?,
+            parse_time_of_day(end)?,
+            parse_timezone(timezone)?,
+        )
+    }
+
+    save_fn!(availability_windows::table, AvailabilityWindow);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::availability::{
+        common_times, parse_time_of_day, parse_weekday, AvailabilityWindow, NewAvailabilityWindow,
+    };
+    use crate::models::player::NewPlayer;
+    use crate::test_utils::setup_db;
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(19 * 60 + 30, parse_time_of_day("19:30").unwrap());
+        assert_eq!(19 * 60 + 30, parse_time_of_day("7:30 PM").unwrap());
+        assert_eq!(0, parse_time_of_day("12am").unwrap());
+        assert_eq!(12 * 60, parse_time_of_day("12pm").unwrap());
+        assert_eq!(24 * 60, parse_time_of_day("24:00").unwrap());
+        assert!(parse_time_of_day("24:30").is_err());
+        assert!(parse_time_of_day("13pm").is_err());
+        assert!(parse_time_of_day("noon").is_err());
+        assert_eq!(1, parse_weekday("Tuesday").unwrap());
+        assert!(parse_weekday("Someday").is_err());
+    }
+
+    #[test]
+    fn test_common_times() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let p1 = NewPlayer::new("p1", "1", None, None, None).save(&mut db)?;
+        let p2 = NewPlayer::new("p2", "2", None, None, None).save(&mut db)?;
+        // Tuesdays 19:00-23:00 in New York is 23:00-03:00 UTC (in October)
+        NewAvailabilityWindow::new(p1.id, 1, 19 * 60, 23 * 60, chrono_tz::America::New_York)?
+            .save(&mut db)?;
+        // Wednesdays 01:00-02:00 and 02:00-05:00 in London is 00:00-04:00 UTC, until the clocks
+        // go back on the 25th
+        NewAvailabilityWindow::new(p2.id, 2, 60, 120, chrono_tz::Europe::London)?.save(&mut db)?;
+        NewAvailabilityWindow::new(p2.id, 2, 120, 300, chrono_tz::Europe::London)?.save(&mut db)?;
+        assert!(NewAvailabilityWindow::new(p2.id, 2, 300, 300, chrono_tz::UTC).is_err());
+
+        let windows = vec![
+            AvailabilityWindow::for_player(p1.id, &mut db)?,
+            AvailabilityWindow::for_player(p2.id, &mut db)?,
+        ];
+        // Monday, October 19th 2026
+        let from = Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap();
+        let until = from + TimeDelta::days(14);
+        let times = common_times(&windows, from, until, TimeDelta::hours(2), 5);
+        assert_eq!(
+            vec![
+                Utc.with_ymd_and_hms(2026, 10, 21, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 10, 28, 1, 0, 0).unwrap(),
+            ],
+            times
+        );
+        // four hours don't fit
+        assert!(common_times(&windows, from, until, TimeDelta::hours(4), 5).is_empty());
+        // starts partway through a window get rounded up
+        let late = Utc.with_ymd_and_hms(2026, 10, 21, 0, 50, 0).unwrap();
+        assert_eq!(
+            vec![Utc.with_ymd_and_hms(2026, 10, 21, 1, 0, 0).unwrap()],
+            common_times(&windows, late, until, TimeDelta::hours(2), 1)
+        );
+        // nobody is available with someone who has no windows
+        assert!(common_times(
+            &[windows[0].clone(), vec![]],
+            from,
+            until,
+            TimeDelta::hours(1),
+            5
+        )
+        .is_empty());
+
+        assert_eq!(2, AvailabilityWindow::clear_for_player(p2.id, &mut db)?);
+        assert!(AvailabilityWindow::for_player(p2.id, &mut db)?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::models::bracket_rounds::BracketRound;
+use crate::models::brackets::Bracket;
+use crate::models::player::Player;
+use crate::save_fn;
+use crate::schema::bracket_byes;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A player sitting out a round of a Swiss bracket (because there's an odd number of players).
+/// Worth the bracket's `bye_points`.
+#[derive(Queryable, Identifiable, Debug, Serialize, Clone, Selectable)]
+pub struct BracketBye {
+    pub id: i32,
+    pub bracket_id: i32,
+    pub round_id: i32,
+    pub player_id: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=bracket_byes)]
+pub struct NewBracketBye {
+    bracket_id: i32,
+    round_id: i32,
+    player_id: i32,
+}
+
+impl NewBracketBye {
+    pub fn new(bracket: &Bracket, round: &BracketRound, player: &Player) -> Self {
+        Self {
+            bracket_id: bracket.id,
+            round_id: round.id,
+            player_id: player.id,
+        }
+    }
+
+    save_fn!(bracket_byes::table, BracketBye);
+}
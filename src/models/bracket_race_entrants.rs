@@ -0,0 +1,117 @@
+use crate::models::bracket_races::{BracketRace, PlayerResult};
+use crate::models::player::Player;
+use crate::schema::bracket_race_entrants;
+use crate::{save_fn, update_fn};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// One entrant in a placement race (see [BracketRace::is_placement_race]). Head-to-head races
+/// don't have any of these; their players live on the race itself.
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone, Selectable)]
+pub struct BracketRaceEntrant {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub player_id: i32,
+    pub result: Option<String>,
+    /// 1-indexed; only set once every entrant has a result
+    pub placement: Option<i32>,
+}
+
+impl BracketRaceEntrant {
+    pub fn result(&self) -> Option<Result<PlayerResult, serde_json::Error>> {
+        self.result.as_ref().map(|s| serde_json::from_str(s))
+    }
+
+    /// all the entrants of all of these races, by race id, in the order they were added
+    pub fn for_races(
+        race_ids: &[i32],
+        conn: &mut SqliteConnection,
+    ) -> Result<HashMap<i32, Vec<Self>>, diesel::result::Error> {
+        use itertools::Itertools;
+        Ok(bracket_race_entrants::table
+            .filter(bracket_race_entrants::bracket_race_id.eq_any(race_ids))
+            .order(bracket_race_entrants::id.asc())
+            .load::<Self>(conn)?
+            .into_iter()
+            .into_group_map_by(|e| e.bracket_race_id))
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=bracket_race_entrants)]
+pub struct NewBracketRaceEntrant {
+    bracket_race_id: i32,
+    player_id: i32,
+}
+
+impl NewBracketRaceEntrant {
+    pub fn new(race: &BracketRace, player: &Player) -> Self {
+        Self {
+            bracket_race_id: race.id,
+            player_id: player.id,
+        }
+    }
+
+    save_fn!(bracket_race_entrants::table, BracketRaceEntrant);
+}
+
+/// 1-indexed placement for each (player id, result), in the same order as `results`.
+///
+/// Finishers are placed by time; players with the same time share a placement and the next
+/// placement is skipped (1, 2, 2, 4). Forfeits all share the placement after the last finisher.
+pub fn placements(results: &[(i32, PlayerResult)]) -> Vec<(i32, i32)> {
+    let time = |r: &PlayerResult| match r {
+        PlayerResult::Forfeit => None,
+        PlayerResult::Finish(t) => Some(*t),
+    };
+    results
+        .iter()
+        .map(|(id, result)| {
+            let beaten_by = results
+                .iter()
+                .filter(|(_, other)| match (time(result), time(other)) {
+                    (None, None) => false,
+                    (None, Some(_)) => true,
+                    (Some(_), None) => false,
+                    (Some(mine), Some(theirs)) => theirs < mine,
+                })
+                .count();
+            (*id, beaten_by as i32 + 1)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_race_entrants::placements;
+    use crate::models::bracket_races::PlayerResult::*;
+
+    #[test]
+    fn test_placements() {
+        assert_eq!(
+            vec![(1, 3), (2, 1), (3, 2)],
+            placements(&[(1, Finish(300)), (2, Finish(100)), (3, Finish(200))])
+        );
+    }
+
+    #[test]
+    fn test_placements_with_ties_and_forfeits() {
+        assert_eq!(
+            vec![(1, 1), (2, 2), (3, 4), (4, 2), (5, 4)],
+            placements(&[
+                (1, Finish(100)),
+                (2, Finish(200)),
+                (3, Forfeit),
+                (4, Finish(200)),
+                (5, Forfeit)
+            ])
+        );
+        assert_eq!(
+            vec![(1, 1), (2, 1)],
+            placements(&[(1, Forfeit), (2, Forfeit)])
+        );
+    }
+}
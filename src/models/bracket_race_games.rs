@@ -0,0 +1,138 @@
+use crate::models::bracket_races::{BracketRace, Outcome, PlayerResult};
+use crate::schema::bracket_race_games;
+use crate::{save_fn, update_fn};
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// One game of a best-of-N [BracketRace]. Single-game races don't have any of these; their results
+/// live on the race itself.
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone, Selectable)]
+pub struct BracketRaceGame {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    /// 1-indexed
+    pub game_num: i32,
+    pub player_1_result: String,
+    pub player_2_result: String,
+    pub outcome: String,
+    pub racetime_gg_url: Option<String>,
+}
+
+impl BracketRaceGame {
+    pub fn player_1_result(&self) -> Result<PlayerResult, serde_json::Error> {
+        serde_json::from_str(&self.player_1_result)
+    }
+
+    pub fn player_2_result(&self) -> Result<PlayerResult, serde_json::Error> {
+        serde_json::from_str(&self.player_2_result)
+    }
+
+    pub fn outcome(&self) -> Result<Outcome, serde_json::Error> {
+        serde_json::from_str(&self.outcome)
+    }
+
+    /// replaces this game's results
+    /// does *not* persist self
+    pub fn set_results(
+        &mut self,
+        p1: &PlayerResult,
+        p2: &PlayerResult,
+    ) -> Result<(), serde_json::Error> {
+        self.player_1_result = serde_json::to_string(p1)?;
+        self.player_2_result = serde_json::to_string(p2)?;
+        self.outcome = serde_json::to_string(&Outcome::from((p1, p2)))?;
+        Ok(())
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=bracket_race_games)]
+pub struct NewBracketRaceGame {
+    bracket_race_id: i32,
+    game_num: i32,
+    player_1_result: String,
+    player_2_result: String,
+    outcome: String,
+    racetime_gg_url: Option<String>,
+}
+
+impl NewBracketRaceGame {
+    pub fn new(
+        race: &BracketRace,
+        game_num: i32,
+        p1: &PlayerResult,
+        p2: &PlayerResult,
+        racetime_gg_url: Option<String>,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            bracket_race_id: race.id,
+            game_num,
+            player_1_result: serde_json::to_string(p1)?,
+            player_2_result: serde_json::to_string(p2)?,
+            outcome: serde_json::to_string(&Outcome::from((p1, p2)))?,
+            racetime_gg_url,
+        })
+    }
+
+    save_fn!(bracket_race_games::table, BracketRaceGame);
+}
+
+/// (player 1 game wins, player 2 game wins). Tied games don't count for anyone.
+pub fn series_score(outcomes: &[Outcome]) -> (i32, i32) {
+    outcomes
+        .iter()
+        .fold((0, 0), |(p1, p2), outcome| match outcome {
+            Outcome::Tie => (p1, p2),
+            Outcome::P1Win => (p1 + 1, p2),
+            Outcome::P2Win => (p1, p2 + 1),
+        })
+}
+
+/// The outcome of a best-of-`best_of` series with these game outcomes, or None if it's still
+/// going. A series is over once someone has won a majority of the games, or once all `best_of`
+/// games have been played (which can only be undecided if some games were tied).
+pub fn series_outcome(best_of: i32, outcomes: &[Outcome]) -> Option<Outcome> {
+    let (p1, p2) = series_score(outcomes);
+    let needed = best_of / 2 + 1;
+    if p1 >= needed {
+        Some(Outcome::P1Win)
+    } else if p2 >= needed {
+        Some(Outcome::P2Win)
+    } else if outcomes.len() as i32 >= best_of {
+        Some(if p1 > p2 {
+            Outcome::P1Win
+        } else if p2 > p1 {
+            Outcome::P2Win
+        } else {
+            Outcome::Tie
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_race_games::series_outcome;
+    use crate::models::bracket_races::Outcome::*;
+
+    #[test]
+    fn test_series_outcome() {
+        assert_eq!(None, series_outcome(3, &[]));
+        assert_eq!(None, series_outcome(3, &[P1Win]));
+        assert_eq!(Some(P1Win), series_outcome(3, &[P1Win, P1Win]));
+        assert_eq!(None, series_outcome(3, &[P1Win, P2Win]));
+        assert_eq!(Some(P2Win), series_outcome(3, &[P1Win, P2Win, P2Win]));
+        assert_eq!(Some(P1Win), series_outcome(1, &[P1Win]));
+    }
+
+    #[test]
+    fn test_series_outcome_with_tied_games() {
+        // a tied game doesn't count towards anyone's majority
+        assert_eq!(None, series_outcome(3, &[Tie, P1Win]));
+        assert_eq!(Some(P1Win), series_outcome(3, &[Tie, P1Win, Tie]));
+        assert_eq!(Some(Tie), series_outcome(3, &[Tie, P1Win, P2Win]));
+    }
+}
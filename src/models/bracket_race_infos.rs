@@ -28,6 +28,8 @@ pub struct BracketRaceInfo {
     pub tentative_commentary_assignment_message_id: Option<String>,
     pub commentary_assignment_message_id: Option<String>,
     pub restream_channel: Option<String>,
+    /// when everyone in the race was DMed times they're all available for
+    pub times_suggested: Option<i64>,
 }
 
 impl BracketRaceInfo {
@@ -249,8 +251,8 @@ impl NewBracketRaceInfo {
 
 #[derive(Queryable, Debug, Serialize)]
 pub struct CommentatorSignup {
-    id: i32,
-    bracket_race_info_id: i32,
+    pub id: i32,
+    pub bracket_race_info_id: i32,
     pub discord_id: String,
 }
 
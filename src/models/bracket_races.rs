@@ -1,7 +1,10 @@
+use crate::models::bracket_race_entrants::{placements, BracketRaceEntrant, NewBracketRaceEntrant};
+use crate::models::bracket_race_games::{series_outcome, BracketRaceGame, NewBracketRaceGame};
 use crate::models::bracket_race_infos::BracketRaceInfo;
 use crate::models::bracket_rounds::BracketRound;
 use crate::models::brackets::Bracket;
 use crate::models::player::Player;
+use crate::models::reschedule_proposals::RescheduleProposal;
 use crate::save_fn;
 use crate::schema::bracket_races;
 use crate::update_fn;
@@ -12,12 +15,13 @@ use crate::NMGLeagueBotError;
 use chrono::{DateTime, Duration, TimeZone};
 use diesel::prelude::*;
 use diesel::SqliteConnection;
+use schemars::JsonSchema;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use swiss_pairings::MatchResult;
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, JsonSchema)]
 pub enum PlayerResult {
     Forfeit,
     /// finish time in seconds
@@ -40,14 +44,19 @@ impl Display for PlayerResult {
 impl PlayerResult {
     /// finish time if given, 3:00:00 if forfeit
     pub fn time(&self) -> u32 {
+        self.time_with_forfeit_penalty(Duration::hours(3).num_seconds() as u32)
+    }
+
+    /// finish time if given, `penalty` (in seconds) if forfeit
+    pub fn time_with_forfeit_penalty(&self, penalty: u32) -> u32 {
         match self {
-            Self::Forfeit => Duration::hours(3).num_seconds() as u32,
+            Self::Forfeit => penalty,
             Self::Finish(t) => t.clone(),
         }
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Eq, PartialEq, JsonSchema)]
 pub enum Outcome {
     Tie,
     P1Win,
@@ -86,6 +95,12 @@ pub struct BracketRace {
     pub player_1_result: Option<String>,
     pub player_2_result: Option<String>,
     pub outcome: Option<String>,
+    /// races with `best_of` > 1 are series of games: see [BracketRace::add_game]
+    pub best_of: i32,
+    /// placement races have any number of entrants and are scored by placement rather than by
+    /// outcome: see [BracketRace::add_placement_results]. `player_1_id` and `player_2_id` are the
+    /// first two entrants.
+    pub placement_race: bool,
 }
 
 impl BracketRace {}
@@ -122,12 +137,17 @@ impl BracketRace {
         player: &Player,
         conn: &mut SqliteConnection,
     ) -> Result<Vec<BracketRace>, NMGLeagueBotError> {
+        use crate::schema::bracket_race_entrants;
+        let entered = bracket_race_entrants::table
+            .filter(bracket_race_entrants::player_id.eq(player.id))
+            .select(bracket_race_entrants::bracket_race_id);
         bracket_races::table
             .filter(bracket_races::state.ne(serde_json::to_string(&BracketRaceState::Finished)?))
             .filter(
                 bracket_races::player_1_id
                     .eq(player.id)
-                    .or(bracket_races::player_2_id.eq(player.id)),
+                    .or(bracket_races::player_2_id.eq(player.id))
+                    .or(bracket_races::id.eq_any(entered)),
             )
             .load(conn)
             .map_err(From::from)
@@ -154,6 +174,10 @@ impl BracketRace {
 
     /// Returns true if the race is definitely complete, false if it is incomplete or there is a data error.
     pub fn is_complete(&self) -> bool {
+        if self.placement_race {
+            // placement races don't have an outcome
+            return self.state().ok() == Some(BracketRaceState::Finished);
+        }
         match self.outcome() {
             Ok(Some(_)) => true,
             _ => false,
@@ -192,6 +216,70 @@ impl BracketRace {
         self.player_1_id == player.id || self.player_2_id == player.id
     }
 
+    /// like [Self::involves_player], but also checks all the entrants of placement races
+    pub fn includes_player(
+        &self,
+        player: &Player,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, diesel::result::Error> {
+        if self.placement_race {
+            Ok(self
+                .race_entrants(conn)?
+                .iter()
+                .any(|e| e.player_id == player.id))
+        } else {
+            Ok(self.involves_player(player))
+        }
+    }
+
+    /// every player in this race: the entrants, in order, for placement races, and
+    /// (Player 1, Player 2) otherwise
+    pub fn all_players(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Player>, diesel::result::Error> {
+        if !self.placement_race {
+            let (p1, p2) = self.players(conn)?;
+            return Ok(vec![p1, p2]);
+        }
+        let entrants = self.race_entrants(conn)?;
+        let mut players = crate::schema::players::table
+            .filter(
+                crate::schema::players::id
+                    .eq_any(entrants.iter().map(|e| e.player_id).collect::<Vec<_>>()),
+            )
+            .load::<Player>(conn)?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect::<HashMap<_, _>>();
+        entrants
+            .iter()
+            .map(|e| {
+                players
+                    .remove(&e.player_id)
+                    .ok_or(diesel::result::Error::NotFound)
+            })
+            .collect()
+    }
+
+    /// true if this race has more than two entrants, scored by placement
+    pub fn is_placement_race(&self) -> bool {
+        self.placement_race
+    }
+
+    /// the entrants of a placement race, in the order they were added (always empty for other
+    /// races)
+    pub fn race_entrants(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRaceEntrant>, diesel::result::Error> {
+        use crate::schema::bracket_race_entrants;
+        bracket_race_entrants::table
+            .filter(bracket_race_entrants::bracket_race_id.eq(self.id))
+            .order(bracket_race_entrants::id.asc())
+            .load(conn)
+    }
+
     pub fn bracket(&self, conn: &mut SqliteConnection) -> Result<Bracket, diesel::result::Error> {
         Bracket::get_by_id(self.bracket_id, conn)
     }
@@ -199,8 +287,51 @@ impl BracketRace {
     /// this hits the db (twice!) to find players, so uh. i guess if that matters to you don't call it
     /// has users names instead of mentions, because mentions don't work in embeds
     pub fn title(&self, conn: &mut SqliteConnection) -> Result<String, diesel::result::Error> {
-        let (p1, p2) = self.players(conn)?;
-        Ok(format!("{} vs {}", p1.name, p2.name))
+        let players = self.all_players(conn)?;
+        Ok(players
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" vs "))
+    }
+
+    /// claims a new race for scheduling by marking it scheduled, so two people picking a time for
+    /// it at once can't both schedule it. Returns false (and changes nothing) if it wasn't new in
+    /// the database. [Self::schedule] still has to set the time.
+    pub fn claim_for_scheduling(
+        &mut self,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, NMGLeagueBotError> {
+        let changed = diesel::update(
+            bracket_races::table
+                .find(self.id)
+                .filter(bracket_races::state.eq(serde_json::to_string(&BracketRaceState::New)?)),
+        )
+        .set(bracket_races::state.eq(serde_json::to_string(&BracketRaceState::Scheduled)?))
+        .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.set_state(BracketRaceState::Scheduled);
+        Ok(true)
+    }
+
+    /// undoes [Self::claim_for_scheduling], for when the race couldn't actually be scheduled
+    pub fn release_scheduling_claim(
+        &mut self,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, NMGLeagueBotError> {
+        let changed =
+            diesel::update(bracket_races::table.find(self.id).filter(
+                bracket_races::state.eq(serde_json::to_string(&BracketRaceState::Scheduled)?),
+            ))
+            .set(bracket_races::state.eq(serde_json::to_string(&BracketRaceState::New)?))
+            .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.set_state(BracketRaceState::New);
+        Ok(true)
     }
 
     /// returns (old_info, new_info) (before and after the update from this method
@@ -258,6 +389,155 @@ impl BracketRace {
         Ok(())
     }
 
+    /// true if this race is a best-of-N series rather than a single game
+    pub fn is_series(&self) -> bool {
+        self.best_of > 1
+    }
+
+    /// the games played so far, in order (always empty for races that aren't series)
+    pub fn games(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRaceGame>, diesel::result::Error> {
+        use crate::schema::bracket_race_games;
+        bracket_race_games::table
+            .filter(bracket_race_games::bracket_race_id.eq(self.id))
+            .order(bracket_race_games::game_num.asc())
+            .load(conn)
+    }
+
+    /// true if anything about this race has been played: it's finished, or a player, game, or
+    /// placement race entrant has a result
+    pub fn has_results(&self, conn: &mut SqliteConnection) -> Result<bool, diesel::result::Error> {
+        if self.is_complete()
+            || self.player_1_result.is_some()
+            || self.player_2_result.is_some()
+            || !self.games(conn)?.is_empty()
+        {
+            return Ok(true);
+        }
+        Ok(self.race_entrants(conn)?.iter().any(|e| e.result.is_some()))
+    }
+
+    /// records one game of a best-of-N series, and updates the outcome & state from the game wins
+    /// so far. The series is finished once it's decided.
+    ///
+    /// If a game in the same racetime room has already been recorded, that game's results are
+    /// replaced if `force` is true, and nothing happens (returning None) otherwise. Any other game
+    /// is the next one in the series, which must be unfinished.
+    ///
+    /// unlike [Self::add_results], this persists both self and the game
+    pub fn add_game(
+        &mut self,
+        p1: &PlayerResult,
+        p2: &PlayerResult,
+        racetime_gg_url: Option<String>,
+        force: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<BracketRaceGame>, BracketRaceStateError> {
+        conn.transaction(|c| {
+            let mut games = self.games(c)?;
+            let existing = racetime_gg_url.as_ref().and_then(|url| {
+                games
+                    .iter()
+                    .position(|g| g.racetime_gg_url.as_ref() == Some(url))
+            });
+            let game = match existing {
+                Some(_) if !force => {
+                    return Ok(None);
+                }
+                Some(idx) => {
+                    games[idx].set_results(p1, p2)?;
+                    games[idx].update(c)?;
+                    games[idx].clone()
+                }
+                None => {
+                    let state = self.state()?;
+                    if state == BracketRaceState::Finished {
+                        return Err(BracketRaceStateError::InvalidState(
+                            vec![BracketRaceState::New, BracketRaceState::Scheduled],
+                            state,
+                        ));
+                    }
+                    let game_num = games.len() as i32 + 1;
+                    let game = NewBracketRaceGame::new(self, game_num, p1, p2, racetime_gg_url)?
+                        .save(c)?;
+                    games.push(game.clone());
+                    game
+                }
+            };
+
+            let outcomes = games
+                .iter()
+                .map(BracketRaceGame::outcome)
+                .collect::<Result<Vec<_>, _>>()?;
+            match series_outcome(self.best_of, &outcomes) {
+                Some(outcome) => {
+                    self.outcome = Some(serde_json::to_string(&outcome)?);
+                    self.set_state(BracketRaceState::Finished);
+                }
+                None => {
+                    // only possible if a rereport un-decided the series
+                    self.outcome = None;
+                    if self.state()? == BracketRaceState::Finished {
+                        self.set_state(BracketRaceState::Scheduled);
+                    }
+                }
+            }
+            self.update(c)?;
+            Ok(Some(game))
+        })
+    }
+
+    /// records results for some of the entrants of a placement race (as (player id, result)).
+    /// Once every entrant has a result, everyone's placement is set and the race is finished.
+    ///
+    /// only works on races in the New or Scheduled state unless force is true. like
+    /// [Self::add_game], this persists both self and the entrants
+    pub fn add_placement_results(
+        &mut self,
+        results: &[(i32, PlayerResult)],
+        force: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), BracketRaceStateError> {
+        let state = self.state()?;
+        if !force && state == BracketRaceState::Finished {
+            return Err(BracketRaceStateError::InvalidState(
+                vec![BracketRaceState::New, BracketRaceState::Scheduled],
+                state,
+            ));
+        }
+        conn.transaction(|c| {
+            let mut entrants = self.race_entrants(c)?;
+            for (player_id, result) in results {
+                let entrant = entrants
+                    .iter_mut()
+                    .find(|e| e.player_id == *player_id)
+                    .ok_or(BracketRaceStateError::PlayerNotInRace(*player_id))?;
+                entrant.result = Some(serde_json::to_string(result)?);
+            }
+
+            let all_results = entrants
+                .iter()
+                .filter_map(|e| e.result().map(|r| r.map(|r| (e.player_id, r))))
+                .collect::<Result<Vec<_>, _>>()?;
+            if all_results.len() == entrants.len() {
+                let placed = placements(&all_results)
+                    .into_iter()
+                    .collect::<HashMap<_, _>>();
+                for entrant in entrants.iter_mut() {
+                    entrant.placement = placed.get(&entrant.player_id).cloned();
+                }
+                self.set_state(BracketRaceState::Finished);
+            }
+            for entrant in &entrants {
+                entrant.update(c)?;
+            }
+            self.update(c)?;
+            Ok(())
+        })
+    }
+
     pub fn player_1_result(&self) -> Option<Result<PlayerResult, serde_json::Error>> {
         self.player_1_result
             .as_ref()
@@ -286,6 +566,90 @@ impl BracketRace {
         Ok(())
     }
 
+    /// records a forfeit for `player` and a win for their opponent, who doesn't get a result.
+    /// This is for races that aren't going to happen because `player` dropped out of the bracket.
+    /// does *not* persist self
+    pub fn forfeit_player(&mut self, player: &Player) -> Result<(), BracketRaceStateError> {
+        let state = self.state()?;
+        if state == BracketRaceState::Finished {
+            return Err(BracketRaceStateError::InvalidState(
+                vec![BracketRaceState::New, BracketRaceState::Scheduled],
+                state,
+            ));
+        }
+        let forfeit = Some(serde_json::to_string(&PlayerResult::Forfeit)?);
+        let outcome = if self.player_1_id == player.id {
+            self.player_1_result = forfeit;
+            Outcome::P2Win
+        } else if self.player_2_id == player.id {
+            self.player_2_result = forfeit;
+            Outcome::P1Win
+        } else {
+            return Err(BracketRaceStateError::PlayerNotInRace(player.id));
+        };
+        self.outcome = Some(serde_json::to_string(&outcome)?);
+        self.set_state(BracketRaceState::Finished);
+        Ok(())
+    }
+
+    /// whether this race was finished by [Self::forfeit_player]: one player forfeited, and the
+    /// other doesn't have a result
+    pub fn is_drop_forfeit(&self) -> bool {
+        matches!(
+            (self.player_1_result(), self.player_2_result()),
+            (Some(Ok(PlayerResult::Forfeit)), None) | (None, Some(Ok(PlayerResult::Forfeit)))
+        )
+    }
+
+    /// records a forfeit for both players, which is a tie. This is for races that weren't played
+    /// in time.
+    /// does *not* persist self
+    pub fn double_forfeit(&mut self) -> Result<(), BracketRaceStateError> {
+        let state = self.state()?;
+        if state == BracketRaceState::Finished {
+            return Err(BracketRaceStateError::InvalidState(
+                vec![BracketRaceState::New, BracketRaceState::Scheduled],
+                state,
+            ));
+        }
+        let forfeit = Some(serde_json::to_string(&PlayerResult::Forfeit)?);
+        self.player_1_result = forfeit.clone();
+        self.player_2_result = forfeit;
+        self.outcome = Some(serde_json::to_string(&Outcome::Tie)?);
+        self.set_state(BracketRaceState::Finished);
+        Ok(())
+    }
+
+    /// deletes this race, along with its info, entrants, and any commentator signups for it. Its
+    /// reschedule proposals are kept as history (see [RescheduleProposal::race_deleted]).
+    ///
+    /// N.B. this doesn't touch any of our Discord messages about the race. Discord events are
+    /// cleaned up by the helper bot once the race info is gone.
+    pub fn delete(self, conn: &mut SqliteConnection) -> Result<usize, diesel::result::Error> {
+        use crate::schema::{bracket_race_entrants, bracket_race_infos, commentator_signups};
+        conn.transaction(|c| {
+            diesel::delete(
+                bracket_race_entrants::table
+                    .filter(bracket_race_entrants::bracket_race_id.eq(self.id)),
+            )
+            .execute(c)?;
+            RescheduleProposal::race_deleted(self.id, c)?;
+            let info_ids = bracket_race_infos::table
+                .filter(bracket_race_infos::bracket_race_id.eq(self.id))
+                .select(bracket_race_infos::id);
+            diesel::delete(
+                commentator_signups::table
+                    .filter(commentator_signups::bracket_race_info_id.eq_any(info_ids)),
+            )
+            .execute(c)?;
+            diesel::delete(
+                bracket_race_infos::table.filter(bracket_race_infos::bracket_race_id.eq(self.id)),
+            )
+            .execute(c)?;
+            diesel::delete(bracket_races::table.find(self.id)).execute(c)
+        })
+    }
+
     pub fn try_into_match_result(&self) -> Result<MatchResult<'_, i32>, MatchResultError> {
         if self
             .state()
@@ -330,6 +694,8 @@ pub struct NewBracketRace {
     pub player_1_result: Option<String>,
     pub player_2_result: Option<String>,
     pub outcome: Option<String>,
+    pub best_of: i32,
+    pub placement_race: bool,
 }
 
 pub fn insert_bulk(
@@ -358,8 +724,37 @@ impl NewBracketRace {
             player_1_result: None,
             player_2_result: None,
             outcome: None,
+            best_of: bracket.best_of,
+            placement_race: false,
         }
     }
 
     save_fn!(bracket_races::table, BracketRace);
 }
+
+/// creates a placement race between all of `players`, along with its entrants
+/// `players` must have at least two players in it (this returns NotFound otherwise)
+pub fn create_placement_race(
+    bracket: &Bracket,
+    round: &BracketRound,
+    players: &[&Player],
+    conn: &mut SqliteConnection,
+) -> Result<BracketRace, diesel::result::Error> {
+    let (p1, p2) = match players {
+        [p1, p2, ..] => (*p1, *p2),
+        _ => {
+            return Err(diesel::result::Error::NotFound);
+        }
+    };
+    conn.transaction(|c| {
+        let mut nbr = NewBracketRace::new(bracket, round, p1, p2);
+        nbr.placement_race = true;
+        // placement races are always single games
+        nbr.best_of = 1;
+        let race = nbr.save(c)?;
+        for player in players {
+            NewBracketRaceEntrant::new(&race, player).save(c)?;
+        }
+        Ok(race)
+    })
+}
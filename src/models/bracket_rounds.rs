@@ -1,18 +1,242 @@
-use crate::models::bracket_races::{BracketRace};
-use crate::models::brackets::Bracket;
-use crate::{save_fn, BracketRaceState};
+use crate::models::bracket_byes::BracketBye;
+use crate::models::bracket_races::{BracketRace, PlayerResult};
+use crate::models::brackets::{Bracket, BracketError, BracketState};
 use crate::schema::bracket_rounds;
+use crate::{save_fn, update_fn, BracketRaceState, NMGLeagueBotError};
 use diesel::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Debug)]
-#[allow(unused)]
+/// how long before a round's scheduling deadline players with unscheduled races are reminded
+pub const SCHEDULE_REMINDER_LEAD_SECONDS: i64 = 48 * 60 * 60;
+
+/// what happens to a round's races that still haven't been played by its play-by deadline.
+/// Admins are told about them regardless.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Sequence)]
+pub enum MissedDeadlinePolicy {
+    /// nothing beyond telling admins
+    Nothing,
+    /// everyone in the race forfeits. Elimination brackets need a winner, so they can't use this.
+    DoubleForfeit,
+    /// the races wait in the admin decision queue (`/overdue_races`) until an admin reports a
+    /// result for them
+    AdminDecision,
+}
+
+/// the things the deadline worker does for a round, in order
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DeadlineStep {
+    /// the scheduling deadline is coming up: DM players whose races aren't scheduled
+    RemindUnscheduled,
+    /// the scheduling deadline passed: tell admins about races that still aren't scheduled
+    EscalateUnscheduled,
+    /// the play-by deadline passed: tell admins about unplayed races and apply the round's
+    /// [MissedDeadlinePolicy]
+    EscalateUnplayed,
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize)]
+#[diesel(treat_none_as_null = true)]
 pub struct BracketRound {
     pub id: i32,
     pub round_num: i32,
     bracket_id: i32,
+    /// epoch timestamp races should be scheduled by
+    pub schedule_by: Option<i64>,
+    /// epoch timestamp races should be played by
+    pub play_by: Option<i64>,
+    /// serialized [MissedDeadlinePolicy]
+    missed_deadline_policy: String,
+    schedule_reminders_sent: bool,
+    schedule_by_escalated: bool,
+    play_by_escalated: bool,
+}
+
+pub(crate) fn serialized_policy(policy: MissedDeadlinePolicy) -> String {
+    // serializing a unit variant can't fail
+    serde_json::to_string(&policy).unwrap_or_default()
 }
 
 impl BracketRound {
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> QueryResult<Self> {
+        bracket_rounds::table.find(id).first(conn)
+    }
+
+    pub fn get_by_round_num(
+        bracket_id: i32,
+        round_num: i32,
+        conn: &mut SqliteConnection,
+    ) -> QueryResult<Option<Self>> {
+        bracket_rounds::table
+            .filter(bracket_rounds::bracket_id.eq(bracket_id))
+            .filter(bracket_rounds::round_num.eq(round_num))
+            .first(conn)
+            .optional()
+    }
+
+    /// rounds of started brackets that the deadline worker might still have something to do for
+    pub fn with_open_deadlines(conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        use crate::schema::brackets;
+        let started = serde_json::to_string(&BracketState::Started).unwrap_or_default();
+        bracket_rounds::table
+            .inner_join(brackets::table)
+            .filter(brackets::state.eq(started))
+            .filter(
+                bracket_rounds::schedule_by
+                    .is_not_null()
+                    .and(
+                        bracket_rounds::schedule_reminders_sent
+                            .eq(false)
+                            .or(bracket_rounds::schedule_by_escalated.eq(false)),
+                    )
+                    .or(bracket_rounds::play_by
+                        .is_not_null()
+                        .and(bracket_rounds::play_by_escalated.eq(false))),
+            )
+            .select(bracket_rounds::all_columns)
+            .load(conn)
+    }
+
+    pub fn bracket(&self, conn: &mut SqliteConnection) -> Result<Bracket, diesel::result::Error> {
+        Bracket::get_by_id(self.bracket_id, conn)
+    }
+
+    pub fn missed_deadline_policy(&self) -> Result<MissedDeadlinePolicy, serde_json::Error> {
+        serde_json::from_str(&self.missed_deadline_policy)
+    }
+
+    /// replaces this round's deadlines (`None` removes one). The worker starts over for the new
+    /// deadlines, so players can get reminded again.
+    /// does *not* persist self
+    pub fn set_deadlines(
+        &mut self,
+        bracket: &Bracket,
+        schedule_by: Option<i64>,
+        play_by: Option<i64>,
+        policy: MissedDeadlinePolicy,
+    ) -> Result<(), BracketError> {
+        if bracket.id != self.bracket_id {
+            return Err(BracketError::Other(format!(
+                "Round {} isn't in bracket {}",
+                self.id, bracket.id
+            )));
+        }
+        if let (Some(s), Some(p)) = (schedule_by, play_by) {
+            if s > p {
+                return Err(BracketError::Other(
+                    "The scheduling deadline can't be after the play-by deadline".to_string(),
+                ));
+            }
+        }
+        if policy == MissedDeadlinePolicy::DoubleForfeit && bracket.bracket_type()?.is_elimination()
+        {
+            return Err(BracketError::Other(
+                "Elimination brackets need a winner for every race, so they can't use double forfeits"
+                    .to_string(),
+            ));
+        }
+        self.schedule_by = schedule_by;
+        self.play_by = play_by;
+        self.missed_deadline_policy = serialized_policy(policy);
+        self.schedule_reminders_sent = false;
+        self.schedule_by_escalated = false;
+        self.play_by_escalated = false;
+        Ok(())
+    }
+
+    /// the deadline steps that are due at `now` and haven't been done yet, in order
+    pub fn pending_deadline_steps(&self, now: i64) -> Vec<DeadlineStep> {
+        let mut steps = vec![];
+        if let Some(schedule_by) = self.schedule_by {
+            // no point reminding people about a deadline that's already passed
+            if !self.schedule_reminders_sent
+                && now >= schedule_by - SCHEDULE_REMINDER_LEAD_SECONDS
+                && now < schedule_by
+            {
+                steps.push(DeadlineStep::RemindUnscheduled);
+            }
+            if !self.schedule_by_escalated && now >= schedule_by {
+                steps.push(DeadlineStep::EscalateUnscheduled);
+            }
+        }
+        if let Some(play_by) = self.play_by {
+            if !self.play_by_escalated && now >= play_by {
+                steps.push(DeadlineStep::EscalateUnplayed);
+            }
+        }
+        steps
+    }
+
+    /// records that `step` was done, so it won't be done again.
+    /// does *not* persist self
+    pub fn mark_done(&mut self, step: DeadlineStep) {
+        match step {
+            DeadlineStep::RemindUnscheduled => self.schedule_reminders_sent = true,
+            DeadlineStep::EscalateUnscheduled => {
+                // if we're past the deadline we don't want to send reminders anymore either
+                self.schedule_reminders_sent = true;
+                self.schedule_by_escalated = true;
+            }
+            DeadlineStep::EscalateUnplayed => self.play_by_escalated = true,
+        }
+    }
+
+    /// this round's races that haven't been scheduled yet
+    pub fn unscheduled_races(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRace>, diesel::result::Error> {
+        use crate::schema::bracket_races;
+        let s = serde_json::to_string(&BracketRaceState::New).unwrap_or_default();
+        bracket_races::table
+            .filter(bracket_races::round_id.eq(self.id))
+            .filter(bracket_races::state.eq(s))
+            .load(conn)
+    }
+
+    pub fn unfinished_races(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRace>, diesel::result::Error> {
+        use crate::schema::bracket_races;
+        let s = serde_json::to_string(&BracketRaceState::Finished).unwrap_or_default();
+        bracket_races::table
+            .filter(bracket_races::round_id.eq(self.id))
+            .filter(bracket_races::state.ne(s))
+            .load(conn)
+    }
+
+    /// applies this round's [MissedDeadlinePolicy] to its unfinished races, returning the ones it
+    /// gave a result to
+    pub fn apply_missed_deadline_policy(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRace>, NMGLeagueBotError> {
+        if self.missed_deadline_policy()? != MissedDeadlinePolicy::DoubleForfeit {
+            return Ok(vec![]);
+        }
+        conn.transaction(|c| {
+            let mut forfeited = vec![];
+            for mut race in self.unfinished_races(c)? {
+                if race.is_placement_race() {
+                    let results = race
+                        .race_entrants(c)?
+                        .iter()
+                        .map(|e| (e.player_id, PlayerResult::Forfeit))
+                        .collect::<Vec<_>>();
+                    race.add_placement_results(&results, false, c)?;
+                } else {
+                    race.double_forfeit()?;
+                    race.update(c)?;
+                }
+                forfeited.push(race);
+            }
+            Ok(forfeited)
+        })
+    }
+
+    update_fn! {}
+
     pub fn races(
         &self,
         conn: &mut SqliteConnection,
@@ -23,6 +247,16 @@ impl BracketRound {
             .load(conn)
     }
 
+    pub fn byes(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketBye>, diesel::result::Error> {
+        use crate::schema::bracket_byes;
+        bracket_byes::table
+            .filter(bracket_byes::round_id.eq(self.id))
+            .load(conn)
+    }
+
     pub fn all_races_finished(
         &self,
         conn: &mut SqliteConnection,
@@ -55,3 +289,142 @@ impl NewBracketRound {
 
     save_fn!(bracket_rounds::table, BracketRound);
 }
+
+/// the admin decision queue: unfinished races in rounds of started brackets whose play-by deadline
+/// has passed and which leave missed deadlines to admins, oldest deadline first
+pub fn awaiting_admin_decision(
+    now: i64,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<(BracketRound, BracketRace)>, diesel::result::Error> {
+    use crate::schema::{bracket_races, brackets};
+    let started = serde_json::to_string(&BracketState::Started).unwrap_or_default();
+    let finished = serde_json::to_string(&BracketRaceState::Finished).unwrap_or_default();
+    bracket_races::table
+        .inner_join(bracket_rounds::table.inner_join(brackets::table))
+        .filter(brackets::state.eq(started))
+        .filter(bracket_rounds::play_by.lt(now))
+        .filter(
+            bracket_rounds::missed_deadline_policy
+                .eq(serialized_policy(MissedDeadlinePolicy::AdminDecision)),
+        )
+        .filter(bracket_races::state.ne(finished))
+        .order((bracket_rounds::play_by.asc(), bracket_races::id.asc()))
+        .select((bracket_rounds::all_columns, bracket_races::all_columns))
+        .load(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::Outcome;
+    use crate::models::bracket_races::PlayerResult;
+    use crate::models::bracket_rounds::{
+        awaiting_admin_decision, BracketRound, DeadlineStep, MissedDeadlinePolicy, NewBracketRound,
+        SCHEDULE_REMINDER_LEAD_SECONDS,
+    };
+    use crate::models::brackets::{BracketType, NewBracket};
+    use crate::models::season::Season;
+    use crate::test_utils::{bracket_with_players, setup_db};
+    use crate::BracketRaceState;
+
+    #[test]
+    fn test_round_deadlines() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::Swiss, 4, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let mut round = bracket.current_round(&mut db)?.unwrap();
+        let (schedule_by, play_by) = (1_000_000, 2_000_000);
+        assert!(round
+            .set_deadlines(
+                &bracket,
+                Some(play_by),
+                Some(schedule_by),
+                MissedDeadlinePolicy::Nothing
+            )
+            .is_err());
+        round.set_deadlines(
+            &bracket,
+            Some(schedule_by),
+            Some(play_by),
+            MissedDeadlinePolicy::DoubleForfeit,
+        )?;
+        round.update(&mut db)?;
+        assert_eq!(1, BracketRound::with_open_deadlines(&mut db)?.len());
+
+        assert!(round
+            .pending_deadline_steps(schedule_by - SCHEDULE_REMINDER_LEAD_SECONDS - 1)
+            .is_empty());
+        assert_eq!(
+            vec![DeadlineStep::RemindUnscheduled],
+            round.pending_deadline_steps(schedule_by - 1)
+        );
+        // a worker that was down for a while catches up, but doesn't send late reminders
+        assert_eq!(
+            vec![
+                DeadlineStep::EscalateUnscheduled,
+                DeadlineStep::EscalateUnplayed
+            ],
+            round.pending_deadline_steps(play_by)
+        );
+        round.mark_done(DeadlineStep::EscalateUnscheduled);
+        round.mark_done(DeadlineStep::EscalateUnplayed);
+        assert!(round.pending_deadline_steps(play_by).is_empty());
+        round.update(&mut db)?;
+        assert_eq!(0, BracketRound::with_open_deadlines(&mut db)?.len());
+
+        let mut races = round.races(&mut db)?;
+        races[0].add_results(
+            Some(&PlayerResult::Finish(5000)),
+            Some(&PlayerResult::Forfeit),
+            false,
+        )?;
+        races[0].update(&mut db)?;
+        assert_eq!(1, round.unfinished_races(&mut db)?.len());
+        assert_eq!(1, round.unscheduled_races(&mut db)?.len());
+
+        // the finished race is left alone
+        let forfeited = round.apply_missed_deadline_policy(&mut db)?;
+        assert_eq!(1, forfeited.len());
+        assert_eq!(races[1].id, forfeited[0].id);
+        assert_eq!(Some(Outcome::Tie), forfeited[0].outcome()?);
+        assert_eq!(BracketRaceState::Finished, forfeited[0].state()?);
+        assert!(round.unfinished_races(&mut db)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_admin_decision_queue() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::Swiss, 2, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let mut round = bracket.current_round(&mut db)?.unwrap();
+        round.set_deadlines(
+            &bracket,
+            None,
+            Some(100),
+            MissedDeadlinePolicy::AdminDecision,
+        )?;
+        round.update(&mut db)?;
+
+        assert!(awaiting_admin_decision(100, &mut db)?.is_empty());
+        let queue = awaiting_admin_decision(101, &mut db)?;
+        assert_eq!(1, queue.len());
+        assert_eq!(round.id, queue[0].0.id);
+        // admins decide these themselves
+        assert!(round.apply_missed_deadline_policy(&mut db)?.is_empty());
+
+        // somebody has to win elimination races
+        let season = Season::get_by_id(bracket.season_id, &mut db)?;
+        let elimination =
+            NewBracket::new(&season, "top 8", BracketType::SingleElimination).save(&mut db)?;
+        let mut elimination_round = NewBracketRound::new(&elimination, 1).save(&mut db)?;
+        assert!(elimination_round
+            .set_deadlines(
+                &elimination,
+                None,
+                Some(100),
+                MissedDeadlinePolicy::DoubleForfeit
+            )
+            .is_err());
+        Ok(())
+    }
+}
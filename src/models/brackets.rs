@@ -1,9 +1,20 @@
+use crate::models::bracket_byes::{BracketBye, NewBracketBye};
+use crate::models::bracket_race_games::BracketRaceGame;
+use crate::models::bracket_race_infos::BracketRaceInfo;
 use crate::models::bracket_races::{
-    insert_bulk, BracketRace, MatchResultError, NewBracketRace, Outcome,
+    create_placement_race, insert_bulk, BracketRace, MatchResultError, NewBracketRace, Outcome,
 };
 use crate::models::bracket_rounds::{BracketRound, NewBracketRound};
+use crate::models::cancelled_races::CancelledRace;
+use crate::models::elimination::{
+    elimination_rounds, pair_key, EliminationResults, EliminationRound,
+};
 use crate::models::player::Player;
+use crate::models::player_bracket_entries::PlayerBracketEntry;
+use crate::models::qualifer_submission::QualifierSubmission;
+use crate::models::scoring::{rank, GameResult, Record, ScoringRules};
 use crate::models::season::Season;
+use crate::models::webhooks::{fire_event, LeagueEvent};
 use crate::schema::brackets;
 use crate::{save_fn, update_fn, BracketRaceStateError, NMGLeagueBotError};
 use diesel::prelude::*;
@@ -13,27 +24,44 @@ use enum_iterator::Sequence;
 use itertools::Itertools;
 use log::{debug, warn};
 use rand::thread_rng;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
-use swiss_pairings::{PairingError, TourneyConfig};
+use swiss_pairings::{MatchResult, PairingError};
 use thiserror::Error;
 
 use rand::seq::SliceRandom;
 
 use super::bracket_races::PlayerResult;
 
-#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug, JsonSchema)]
 pub enum BracketState {
     Unstarted,
     Started,
     Finished,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug, Sequence)]
+#[derive(
+    serde::Serialize, serde::Deserialize, Eq, PartialEq, Debug, Sequence, Clone, Copy, JsonSchema,
+)]
 pub enum BracketType {
     Swiss,
     RoundRobin,
+    SingleElimination,
+    DoubleElimination,
+    /// players are split into groups of up to [Bracket::group_size], and each group plays one
+    /// placement race per round
+    Group,
+}
+
+impl BracketType {
+    pub fn is_elimination(&self) -> bool {
+        match self {
+            BracketType::Swiss | BracketType::RoundRobin | BracketType::Group => false,
+            BracketType::SingleElimination | BracketType::DoubleElimination => true,
+        }
+    }
 }
 
 #[derive(Queryable, Identifiable, Debug, AsChangeset, Serialize, Deserialize, Selectable)]
@@ -46,6 +74,24 @@ pub struct Bracket {
     bracket_type: String,
     /// set for backfilled brackets to give a little context on the bracket pages
     pub backfill_note: Option<String>,
+    /// elimination brackets are seeded from this bracket's standings, if set, or from qualifier
+    /// times otherwise
+    pub seed_source_bracket_id: Option<i32>,
+    /// what a bye counts as: 0 for a loss, 1 for a draw, or 2 for a win (i.e. the points it's worth
+    /// with the default scoring rules)
+    pub bye_points: i32,
+    // see [Bracket::scoring_rules]
+    points_per_win: i32,
+    points_per_draw: i32,
+    points_per_loss: i32,
+    forfeit_time_penalty: i32,
+    tiebreakers: String,
+    /// how many games new races in this bracket are: see [BracketRace::add_game]
+    pub best_of: i32,
+    /// for group brackets: the most players in each group (i.e. in each placement race)
+    pub group_size: i32,
+    // see [Bracket::scoring_rules]
+    placement_points: String,
 }
 
 impl Bracket {}
@@ -54,7 +100,7 @@ impl Bracket {}
 pub enum BracketError {
     #[error("Invalid bracket state")]
     InvalidState,
-    #[error("Cannot generate pairings with odd player counts. Add a bye?")]
+    #[error("Cannot generate pairings with an odd player count when nobody has had a bye yet")]
     OddPlayerCount,
     #[error("Database error: {0}")]
     DBError(#[from] diesel::result::Error),
@@ -87,6 +133,192 @@ impl From<String> for BracketError {
     }
 }
 
+/// Stands in for "nobody" when we tell the pairing algorithm about byes: a bye is recorded as a
+/// match against this id.
+const BYE_PLAYER_ID: i32 = -1;
+/// The pairing algorithm works out who's playing from the results it's given, and needs an even
+/// number of them. When byes and dropped players make that odd, this one evens it out (see
+/// [generate_next_round_pairings_swiss]).
+const FILLER_PLAYER_ID: i32 = -2;
+
+/// what a bye counts as, given [Bracket::bye_points]
+fn bye_result(bye_points: i32) -> GameResult {
+    match bye_points {
+        0 => GameResult::Loss,
+        1 => GameResult::Draw,
+        _ => GameResult::Win,
+    }
+}
+
+fn bye_match_result(player_id: &i32, bye_points: i32) -> MatchResult<'_, i32> {
+    match bye_result(bye_points) {
+        GameResult::Loss => MatchResult::Player2Win {
+            p1: player_id,
+            p2: &BYE_PLAYER_ID,
+        },
+        GameResult::Draw => MatchResult::Draw {
+            p1: player_id,
+            p2: &BYE_PLAYER_ID,
+        },
+        GameResult::Win => MatchResult::Player1Win {
+            p1: player_id,
+            p2: &BYE_PLAYER_ID,
+        },
+    }
+}
+
+/// every active player's points and past opponents, the way the pairing algorithm counts them
+struct PairingHistory {
+    points: HashMap<i32, i32>,
+    opponents: HashMap<i32, HashSet<i32>>,
+}
+
+impl PairingHistory {
+    fn new(
+        races: &[&BracketRace],
+        byes: &[&BracketBye],
+        rules: &ScoringRules,
+        bye_points: i32,
+    ) -> Result<Self, BracketError> {
+        let mut history = Self {
+            points: Default::default(),
+            opponents: Default::default(),
+        };
+        for race in races {
+            let (p1, p2) = match race.outcome()?.ok_or(MatchResultError::RaceNotFinished)? {
+                Outcome::Tie => (GameResult::Draw, GameResult::Draw),
+                Outcome::P1Win => (GameResult::Win, GameResult::Loss),
+                Outcome::P2Win => (GameResult::Loss, GameResult::Win),
+            };
+            history.record(race.player_1_id, race.player_2_id, rules.points(p1));
+            history.record(race.player_2_id, race.player_1_id, rules.points(p2));
+        }
+        for bye in byes {
+            history.record(
+                bye.player_id,
+                BYE_PLAYER_ID,
+                rules.points(bye_result(bye_points)),
+            );
+        }
+        Ok(history)
+    }
+
+    fn record(&mut self, player_id: i32, opponent_id: i32, points: i32) {
+        *self.points.entry(player_id).or_default() += points;
+        self.opponents
+            .entry(player_id)
+            .or_default()
+            .insert(opponent_id);
+    }
+
+    fn points(&self, player_id: i32) -> i32 {
+        self.points.get(&player_id).cloned().unwrap_or(0)
+    }
+
+    fn have_played(&self, a: i32, b: i32) -> bool {
+        self.opponents.get(&a).map_or(false, |o| o.contains(&b))
+    }
+}
+
+/// pairs up `players` (best first) without any rematches, if that's possible
+fn pair_without_rematches(players: &[i32], history: &PairingHistory) -> Option<Vec<(i32, i32)>> {
+    let (first, rest) = match players.split_first() {
+        Some(split) => split,
+        None => {
+            return Some(vec![]);
+        }
+    };
+    for (i, other) in rest.iter().enumerate() {
+        if history.have_played(*first, *other) {
+            continue;
+        }
+        let mut remaining = rest.to_vec();
+        remaining.remove(i);
+        if let Some(mut pairs) = pair_without_rematches(&remaining, history) {
+            pairs.insert(0, (*first, *other));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// turns the pairing algorithm's `suggested` pairings into real ones: players it paired against
+/// a stand-in ([BYE_PLAYER_ID], [FILLER_PLAYER_ID], or anyone else who isn't `active`) are paired
+/// against each other instead, except for whoever gets this round's bye (if anyone needs one).
+///
+/// Returns the pairings and who gets the bye.
+fn pairings_for_active_players(
+    suggested: Vec<(i32, i32)>,
+    active: &HashSet<i32>,
+    history: &PairingHistory,
+) -> Result<(Vec<(i32, i32)>, Option<i32>), BracketError> {
+    let mut pairings = vec![];
+    // (player, whether the algorithm gave them the bye)
+    let mut unpaired = vec![];
+    let mut seen = HashSet::new();
+    for (p1, p2) in suggested {
+        seen.insert(p1);
+        seen.insert(p2);
+        match (active.contains(&p1), active.contains(&p2)) {
+            (true, true) => pairings.push((p1, p2)),
+            (true, false) => unpaired.push((p1, p2 == BYE_PLAYER_ID)),
+            (false, true) => unpaired.push((p2, p1 == BYE_PLAYER_ID)),
+            (false, false) => {}
+        }
+    }
+    unpaired.extend(
+        active
+            .iter()
+            .filter(|p| !seen.contains(*p))
+            .map(|p| (*p, false)),
+    );
+
+    let bye = if active.len() % 2 == 1 {
+        let had_bye = |p: &i32| history.have_played(*p, BYE_PLAYER_ID);
+        let bye = unpaired
+            .iter()
+            .find(|(p, got_bye)| *got_bye && !had_bye(p))
+            .or_else(|| {
+                unpaired
+                    .iter()
+                    .filter(|(p, _)| !had_bye(p))
+                    .min_by_key(|(p, _)| (history.points(*p), *p))
+            })
+            .map(|(p, _)| *p)
+            .ok_or(BracketError::Other(
+                "Everyone who could sit out this round has already had a bye".to_string(),
+            ))?;
+        unpaired.retain(|(p, _)| *p != bye);
+        Some(bye)
+    } else {
+        None
+    };
+
+    let by_points = |players: Vec<i32>| {
+        players
+            .into_iter()
+            .sorted_by_key(|p| (-history.points(*p), *p))
+            .collect::<Vec<_>>()
+    };
+    let unpaired = by_points(unpaired.into_iter().map(|(p, _)| p).collect());
+    if let Some(rest) = pair_without_rematches(&unpaired, history) {
+        pairings.extend(rest);
+        return Ok((pairings, bye));
+    }
+    // the leftovers have all played each other, so start over with everyone
+    let everyone = by_points(
+        active
+            .iter()
+            .filter(|p| Some(**p) != bye)
+            .cloned()
+            .collect(),
+    );
+    let pairings = pair_without_rematches(&everyone, history).ok_or(BracketError::Other(
+        format!("Cannot pair players {everyone:?} without rematches"),
+    ))?;
+    Ok((pairings, bye))
+}
+
 fn generate_next_round_pairings_swiss(
     bracket: &Bracket,
     conn: &mut SqliteConnection,
@@ -94,44 +326,101 @@ fn generate_next_round_pairings_swiss(
     let rounds = bracket.rounds(conn)?;
 
     let mut round_races = vec![];
+    let mut round_byes = vec![];
 
     let mut highest_round_num = 0;
     for round in rounds {
         round_races.push(round.races(conn)?);
+        round_byes.push(round.byes(conn)?);
         assert!(round.round_num > highest_round_num);
         highest_round_num = round.round_num;
     }
+    let mut players: HashMap<_, _> =
+        HashMap::from_iter(bracket.active_players(conn)?.into_iter().map(|p| (p.id, p)));
+    let active = players.keys().cloned().collect::<HashSet<_>>();
+
+    // every result counts, whether or not anyone needs a bye this round and whether or not the
+    // other player is still around: whoever ends up paired against a stand-in (a bye or a dropped
+    // player) is sorted out afterwards
+    let mut seen = HashSet::new();
     let mut pairing_rounds = vec![];
-    for races in &round_races {
+    for (races, byes) in round_races.iter().zip(round_byes.iter()) {
         let mut this_round = vec![];
         for race in races {
             this_round.push(race.try_into_match_result()?);
+            seen.insert(race.player_1_id);
+            seen.insert(race.player_2_id);
+        }
+        for bye in byes {
+            this_round.push(bye_match_result(&bye.player_id, bracket.bye_points));
+            seen.insert(bye.player_id);
+            seen.insert(BYE_PLAYER_ID);
         }
         pairing_rounds.push(this_round);
     }
+    if seen.len() % 2 == 1 {
+        let stand_in = seen
+            .iter()
+            .find(|id| !active.contains(*id))
+            // an odd number of players, none of whom has ever sat out
+            .ok_or(BracketError::OddPlayerCount)?;
+        if let Some(first) = pairing_rounds.first_mut() {
+            first.push(MatchResult::Draw {
+                p1: &FILLER_PLAYER_ID,
+                p2: stand_in,
+            });
+        }
+    }
     debug!("{:?}", pairing_rounds);
-    let cfg = TourneyConfig {
-        points_per_win: 2,
-        points_per_loss: 0,
-        points_per_draw: 1,
-        error_on_repeated_opponent: true,
+    let cfg = bracket.scoring_rules()?.tourney_config();
+    // the stand-ins can make an otherwise fine round impossible for the pairing algorithm (e.g.
+    // if everyone left has already played one of them), in which case we pair by points ourselves
+    let suggested = match swiss_pairings::swiss_pairings(
+        &pairing_rounds,
+        &cfg,
+        Some(Duration::from_millis(5000)),
+    ) {
+        Ok((pairings, _standings)) => pairings,
+        Err(e) => {
+            warn!(
+                "Error pairing bracket {}, pairing by points instead: {e:?}",
+                bracket.id
+            );
+            vec![]
+        }
     };
-    let (pairings, _standings) =
-        swiss_pairings::swiss_pairings(&pairing_rounds, &cfg, Some(Duration::from_millis(5000)))?;
-    debug!("{:?}", pairings);
+    debug!("{:?}", suggested);
 
-    let mut players: HashMap<_, _> =
-        HashMap::from_iter(bracket.players(conn)?.into_iter().map(|p| (p.id, p)));
+    let history = PairingHistory::new(
+        &round_races.iter().flatten().collect::<Vec<_>>(),
+        &round_byes.iter().flatten().collect::<Vec<_>>(),
+        &bracket.scoring_rules()?,
+        bracket.bye_points,
+    )?;
+    let (pairings, bye) = pairings_for_active_players(
+        suggested.into_iter().map(|(p1, p2)| (*p1, *p2)).collect(),
+        &active,
+        &history,
+    )?;
 
     let nr = NewBracketRound::new(&bracket, highest_round_num + 1);
     let new_round = nr.save(conn)?;
+    if let Some(player_id) = bye {
+        let player = players
+            .remove(&player_id)
+            .ok_or(BracketError::Other(format!(
+                "Cannot find player {}",
+                player_id
+            )))?;
+        NewBracketBye::new(bracket, &new_round, &player).save(conn)?;
+    }
     let mut new_races = vec![];
     for (p1_id, p2_id) in pairings {
         let p1 = players
-            .remove(p1_id)
+            .remove(&p1_id)
             .ok_or(BracketError::Other(format!("Cannot find player {}", p1_id)))?;
         let p2 = players
-            .remove(p2_id)
+            .remove(&p2_id)
             .ok_or(BracketError::Other(format!("Cannot find player {}", p2_id)))?;
         let new_race = NewBracketRace::new(bracket, &new_round, &p1, &p2);
         new_races.push(new_race);
@@ -153,9 +442,68 @@ fn generate_next_round_pairings(
         BracketType::RoundRobin => Err(BracketError::RoundRobinError(
             "Round Robin pairings already generated".to_string(),
         )),
+        BracketType::SingleElimination | BracketType::DoubleElimination => {
+            generate_next_round_pairings_elimination(bracket, conn)
+        }
+        BracketType::Group => generate_next_round_pairings_group(bracket, conn),
     }
 }
 
+fn generate_next_round_pairings_elimination(
+    bracket: &Bracket,
+    conn: &mut SqliteConnection,
+) -> Result<(), BracketError> {
+    let next_round_num = bracket
+        .current_round(conn)?
+        .map(|r| r.round_num)
+        .unwrap_or(0)
+        + 1;
+    let round = bracket
+        .elimination_rounds(conn)?
+        .into_iter()
+        .find(|r| r.round_num == next_round_num)
+        .ok_or(BracketError::Other(
+            "There are no more rounds to play in this bracket".to_string(),
+        ))?;
+    let mut pairings = vec![];
+    for m in round.matches.iter().filter(|m| !m.is_walkover()) {
+        let pairing = m.players().ok_or(BracketError::Other(format!(
+            "Round {} isn't finished yet",
+            next_round_num - 1
+        )))?;
+        pairings.push(pairing);
+    }
+
+    let players: HashMap<_, _> =
+        HashMap::from_iter(bracket.players(conn)?.into_iter().map(|p| (p.id, p)));
+    let new_round = NewBracketRound::new(bracket, next_round_num).save(conn)?;
+    let mut new_races = vec![];
+    for (p1_id, p2_id) in pairings {
+        let p1 = players
+            .get(&p1_id)
+            .ok_or(BracketError::Other(format!("Cannot find player {}", p1_id)))?;
+        let p2 = players
+            .get(&p2_id)
+            .ok_or(BracketError::Other(format!("Cannot find player {}", p2_id)))?;
+        new_races.push(NewBracketRace::new(bracket, &new_round, p1, p2));
+    }
+    insert_bulk(&new_races, conn)?;
+
+    // players who have dropped out can still turn up here (e.g. on the losers side of a double
+    // elimination bracket); they forfeit straight away
+    let dropped = bracket.dropped_player_ids(conn)?;
+    for mut race in new_round.races(conn)? {
+        let dropped_id = [race.player_1_id, race.player_2_id]
+            .into_iter()
+            .find(|id| dropped.contains(id));
+        if let Some(player) = dropped_id.and_then(|id| players.get(&id)) {
+            race.forfeit_player(player)?;
+            race.update(conn)?;
+        }
+    }
+    Ok(())
+}
+
 fn generate_initial_pairings_swiss(
     bracket: &mut Bracket,
     conn: &mut SqliteConnection,
@@ -163,7 +511,7 @@ fn generate_initial_pairings_swiss(
     let new_round = NewBracketRound::new(bracket, 1);
     let round = new_round.save(conn)?;
 
-    let mut players = bracket.players(conn)?;
+    let mut players = bracket.active_players(conn)?;
     players.as_mut_slice().shuffle(&mut thread_rng());
     let mut nbrs = vec![];
     while players.len() > 1 {
@@ -172,8 +520,9 @@ fn generate_initial_pairings_swiss(
         let nbr = NewBracketRace::new(bracket, &round, &p1, &p2);
         nbrs.push(nbr);
     }
-    if !players.is_empty() {
-        return Err(BracketError::OddPlayerCount);
+    // odd one out gets a bye
+    if let Some(p) = players.pop() {
+        NewBracketBye::new(bracket, &round, &p).save(conn)?;
     }
     insert_bulk(&nbrs, conn)?;
 
@@ -189,7 +538,7 @@ fn generate_pairings_round_robin(
     round: &BracketRound,
     conn: &mut SqliteConnection,
 ) -> Result<(), BracketError> {
-    let mut players = bracket.players(conn)?;
+    let mut players = bracket.active_players(conn)?;
     players.sort_unstable_by_key(|p| p.id);
 
     fn sorted_tuple<'a>(ps: (&'a Player, &'a Player)) -> (&'a Player, &'a Player) {
@@ -250,6 +599,163 @@ fn generate_initial_pairings_round_robin(
     Ok(())
 }
 
+/// every player in the bracket, best seed first
+/// players missing from the seed source (e.g. no qualifier submitted) go last, ordered by id
+fn generate_seeds(
+    bracket: &Bracket,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<i32>, BracketError> {
+    let ordering: Vec<i32> = match bracket.seed_source_bracket_id {
+        Some(source_id) => Bracket::get_by_id(source_id, conn)?
+            .standings(conn)?
+            .into_iter()
+            .map(|p| p.id)
+            .collect(),
+        None => {
+            let season = Season::get_by_id(bracket.season_id, conn)?;
+            QualifierSubmission::scores(season.id, &season.qualifier_rules()?, conn)?
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        }
+    };
+    let positions: HashMap<i32, usize> = ordering
+        .into_iter()
+        .enumerate()
+        .map(|(idx, id)| (id, idx))
+        .collect();
+    Ok(bracket
+        .active_players(conn)?
+        .into_iter()
+        .map(|p| p.id)
+        .sorted_by_key(|id| (positions.get(id).cloned().unwrap_or(usize::MAX), *id))
+        .collect())
+}
+
+/// saves `seeds` (best first) as the players' seeds in this bracket
+fn save_seeds(
+    bracket: &Bracket,
+    seeds: &[i32],
+    conn: &mut SqliteConnection,
+) -> Result<(), diesel::result::Error> {
+    use crate::schema::player_bracket_entry as pbes;
+    for (idx, player_id) in seeds.iter().enumerate() {
+        diesel::update(
+            pbes::table
+                .filter(pbes::bracket_id.eq(bracket.id))
+                .filter(pbes::player_id.eq(*player_id)),
+        )
+        .set(pbes::seed.eq(Some(idx as i32 + 1)))
+        .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn generate_initial_pairings_elimination(
+    bracket: &mut Bracket,
+    conn: &mut SqliteConnection,
+) -> Result<(), BracketError> {
+    let seeds = generate_seeds(bracket, conn)?;
+    if seeds.len() < 2 {
+        return Err(BracketError::Other(
+            "Elimination brackets need at least two players".to_string(),
+        ));
+    }
+    save_seeds(bracket, &seeds, conn)?;
+    generate_next_round_pairings_elimination(bracket, conn)?;
+
+    bracket
+        .set_state(BracketState::Started)
+        .map_err(|e| e.to_string())?;
+    bracket.update(conn)?;
+    Ok(())
+}
+
+/// splits `seeds` (best first) into as few groups of at most `group_size` as possible, snaking
+/// through the groups so they're as even as possible
+/// e.g. seeds 1-8 in groups of 4: [1, 4, 5, 8], [2, 3, 6, 7]
+fn snake_groups(seeds: &[i32], group_size: usize) -> Vec<Vec<i32>> {
+    let num_groups = (seeds.len() + group_size - 1) / group_size;
+    let mut groups = vec![vec![]; num_groups];
+    for (idx, id) in seeds.iter().enumerate() {
+        let row = idx / num_groups;
+        let col = idx % num_groups;
+        let group = if row % 2 == 0 {
+            col
+        } else {
+            num_groups - 1 - col
+        };
+        groups[group].push(*id);
+    }
+    groups
+}
+
+/// creates a placement race in `round` for each group with at least two players left in it
+fn generate_group_races(
+    bracket: &Bracket,
+    round: &BracketRound,
+    conn: &mut SqliteConnection,
+) -> Result<(), BracketError> {
+    let dropped = bracket.dropped_player_ids(conn)?;
+    let players: HashMap<_, _> =
+        HashMap::from_iter(bracket.players(conn)?.into_iter().map(|p| (p.id, p)));
+    for group in snake_groups(&bracket.seeds(conn)?, bracket.group_size.max(2) as usize) {
+        let group_players = group
+            .iter()
+            .filter(|id| !dropped.contains(id))
+            .map(|id| {
+                players
+                    .get(id)
+                    .ok_or(BracketError::Other(format!("Cannot find player {}", id)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if group_players.len() < 2 {
+            continue;
+        }
+        create_placement_race(bracket, round, &group_players, conn)?;
+    }
+    Ok(())
+}
+
+fn generate_initial_pairings_group(
+    bracket: &mut Bracket,
+    conn: &mut SqliteConnection,
+) -> Result<(), BracketError> {
+    let seeds = generate_seeds(bracket, conn)?;
+    if seeds.len() < 2 {
+        return Err(BracketError::Other(
+            "Group brackets need at least two players".to_string(),
+        ));
+    }
+    save_seeds(bracket, &seeds, conn)?;
+    let round = NewBracketRound::new(bracket, 1).save(conn)?;
+    generate_group_races(bracket, &round, conn)?;
+
+    bracket
+        .set_state(BracketState::Started)
+        .map_err(|e| e.to_string())?;
+    bracket.update(conn)?;
+    Ok(())
+}
+
+/// every round of a group bracket has the same groups as the first round, minus anyone who dropped
+fn generate_next_round_pairings_group(
+    bracket: &Bracket,
+    conn: &mut SqliteConnection,
+) -> Result<(), BracketError> {
+    let current = bracket
+        .current_round(conn)?
+        .ok_or(BracketError::InvalidState)?;
+    if !current.all_races_finished(conn)? {
+        return Err(BracketError::Other(format!(
+            "Round {} isn't finished yet",
+            current.round_num
+        )));
+    }
+    let round = NewBracketRound::new(bracket, current.round_num + 1).save(conn)?;
+    generate_group_races(bracket, &round, conn)
+}
+
 fn generate_initial_pairings(
     bracket: &mut Bracket,
     conn: &mut SqliteConnection,
@@ -264,6 +770,10 @@ fn generate_initial_pairings(
     match bracket.bracket_type()? {
         BracketType::Swiss => generate_initial_pairings_swiss(bracket, conn),
         BracketType::RoundRobin => generate_initial_pairings_round_robin(bracket, conn),
+        BracketType::SingleElimination | BracketType::DoubleElimination => {
+            generate_initial_pairings_elimination(bracket, conn)
+        }
+        BracketType::Group => generate_initial_pairings_group(bracket, conn),
     }
 }
 
@@ -289,6 +799,65 @@ impl Bracket {
         Ok(())
     }
 
+    /// how results are scored and ties are broken, for both pairings and standings
+    pub fn scoring_rules(&self) -> Result<ScoringRules, serde_json::Error> {
+        Ok(ScoringRules {
+            points_per_win: self.points_per_win,
+            points_per_draw: self.points_per_draw,
+            points_per_loss: self.points_per_loss,
+            forfeit_time_penalty: self.forfeit_time_penalty.max(0) as u32,
+            tiebreakers: serde_json::from_str(&self.tiebreakers)?,
+            placement_points: serde_json::from_str(&self.placement_points)?,
+        })
+    }
+
+    /// does *not* persist self
+    pub fn set_scoring_rules(&mut self, rules: &ScoringRules) -> Result<(), BracketError> {
+        rules.validate()?;
+        self.points_per_win = rules.points_per_win;
+        self.points_per_draw = rules.points_per_draw;
+        self.points_per_loss = rules.points_per_loss;
+        self.forfeit_time_penalty = rules.forfeit_time_penalty as i32;
+        self.tiebreakers = serde_json::to_string(&rules.tiebreakers)?;
+        self.placement_points = serde_json::to_string(&rules.placement_points)?;
+        Ok(())
+    }
+
+    /// sets how many games this bracket's races are, including existing races that haven't had any
+    /// results reported yet. Returns the number of existing races that changed.
+    /// persists self
+    pub fn set_best_of(
+        &mut self,
+        best_of: i32,
+        conn: &mut SqliteConnection,
+    ) -> Result<usize, BracketError> {
+        if best_of < 1 || best_of % 2 == 0 {
+            return Err(
+                format!("Races must be best of an odd number of games, not {best_of}").into(),
+            );
+        }
+        conn.transaction(|c| {
+            self.best_of = best_of;
+            self.update(c)?;
+            let mut changed = 0;
+            for mut race in self.bracket_races(c)? {
+                if race.best_of == best_of
+                    || race.is_placement_race()
+                    || race.player_1_result.is_some()
+                    || race.player_2_result.is_some()
+                    || race.is_complete()
+                    || !race.games(c)?.is_empty()
+                {
+                    continue;
+                }
+                race.best_of = best_of;
+                race.update(c)?;
+                changed += 1;
+            }
+            Ok(changed)
+        })
+    }
+
     /// sets this bracket's state to finished, if there are no unfinished rounds
     pub fn finish(&mut self, cxn: &mut SqliteConnection) -> Result<bool, NMGLeagueBotError> {
         for r in self.rounds(cxn)? {
@@ -315,6 +884,65 @@ impl Bracket {
             .load(conn)
     }
 
+    /// players who haven't dropped out of this bracket
+    pub fn active_players(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Player>, diesel::result::Error> {
+        use crate::schema::player_bracket_entry as pbes;
+        use crate::schema::players;
+        pbes::table
+            .filter(pbes::bracket_id.eq(self.id))
+            .filter(pbes::dropped_at.is_null())
+            .inner_join(players::table)
+            .select(players::all_columns)
+            .load(conn)
+    }
+
+    pub fn dropped_player_ids(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<HashSet<i32>, diesel::result::Error> {
+        use crate::schema::player_bracket_entry as pbes;
+        let ids: Vec<i32> = pbes::table
+            .filter(pbes::bracket_id.eq(self.id))
+            .filter(pbes::dropped_at.is_not_null())
+            .select(pbes::player_id)
+            .load(conn)?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// marks `player` as having dropped out of this bracket, so they won't be in any future
+    /// pairings. Their past results stay in the standings.
+    ///
+    /// Returns their unfinished races in this bracket, which the caller should either forfeit
+    /// ([BracketRace::forfeit_player]) or cancel ([BracketRace::delete])
+    pub fn drop_player(
+        &self,
+        player: &Player,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketRace>, BracketError> {
+        if self.is_finished()? {
+            return Err(BracketError::InvalidState);
+        }
+        let mut entry = PlayerBracketEntry::get(self, player, conn)?;
+        if entry.is_dropped() {
+            return Err(BracketError::Other(format!(
+                "{} has already dropped out of this bracket",
+                player.name
+            )));
+        }
+        entry.mark_dropped();
+        entry.update(conn)?;
+        let mut races = vec![];
+        for race in self.bracket_races(conn)? {
+            if !race.is_complete() && race.includes_player(player, conn)? {
+                races.push(race);
+            }
+        }
+        Ok(races)
+    }
+
     pub fn rounds(
         &self,
         conn: &mut SqliteConnection,
@@ -326,6 +954,16 @@ impl Bracket {
             .load(conn)
     }
 
+    pub fn byes(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BracketBye>, diesel::result::Error> {
+        use crate::schema::bracket_byes;
+        bracket_byes::table
+            .filter(bracket_byes::bracket_id.eq(self.id))
+            .load(conn)
+    }
+
     /// returns all BracketRaces for this bracket (unordered)
     pub fn bracket_races(
         &self,
@@ -345,10 +983,204 @@ impl Bracket {
     pub fn generate_pairings(&mut self, conn: &mut SqliteConnection) -> Result<(), BracketError> {
         let state = self.state().map_err(|_| BracketError::InvalidState)?;
         match state {
-            BracketState::Unstarted => conn.transaction(|c| generate_initial_pairings(self, c)),
-            BracketState::Started => conn.transaction(|c| generate_next_round_pairings(self, c)),
-            BracketState::Finished => Err(BracketError::InvalidState),
+            BracketState::Unstarted => conn.transaction(|c| generate_initial_pairings(self, c))?,
+            BracketState::Started => conn.transaction(|c| generate_next_round_pairings(self, c))?,
+            BracketState::Finished => return Err(BracketError::InvalidState),
+        };
+        if let Some(round) = self.current_round(conn)? {
+            fire_event(
+                LeagueEvent::PairingsGenerated {
+                    bracket_id: self.id,
+                    round_num: round.round_num,
+                },
+                conn,
+            );
+        }
+        Ok(())
+    }
+
+    /// undoes [Self::generate_pairings]: deletes the latest round along with its byes and races,
+    /// so it can be generated again (e.g. after fixing who's in the bracket). Rolling back the
+    /// first round puts the bracket back to unstarted, and forgets any seeds.
+    ///
+    /// Refuses if anything in the round has been played. Round robin brackets generate all of
+    /// their races at once, so they can't be rolled back a round at a time.
+    ///
+    /// Scheduled races are recorded as [CancelledRace]s. Returns the deleted round and its races'
+    /// infos; the caller should clean up the infos' Discord messages, while Discord events are
+    /// cleaned up by the helper bot.
+    /// persists self
+    pub fn rollback_round(
+        &mut self,
+        conn: &mut SqliteConnection,
+    ) -> Result<(BracketRound, Vec<BracketRaceInfo>), NMGLeagueBotError> {
+        if self.state()? != BracketState::Started {
+            return Err(BracketError::InvalidState.into());
+        }
+        if self.bracket_type()? == BracketType::RoundRobin {
+            return Err(BracketError::RoundRobinError(
+                "Round robin pairings are generated all at once, so rounds can't be rolled back"
+                    .to_string(),
+            )
+            .into());
+        }
+        conn.transaction(|c| -> Result<_, NMGLeagueBotError> {
+            let round = self.current_round(c)?.ok_or(BracketError::Other(
+                "There are no rounds to roll back".to_string(),
+            ))?;
+            let races = round.races(c)?;
+            for race in &races {
+                if race.has_results(c)? {
+                    return Err(BracketError::Other(format!(
+                        "Race {} in round {} already has results",
+                        race.id, round.round_num
+                    ))
+                    .into());
+                }
+            }
+            let mut infos = vec![];
+            for race in races {
+                let info = race.info(c)?;
+                CancelledRace::record(&race, &info, c)?;
+                infos.push(info);
+                race.delete(c)?;
+            }
+            {
+                use crate::schema::{bracket_byes, bracket_rounds};
+                diesel::delete(bracket_byes::table.filter(bracket_byes::round_id.eq(round.id)))
+                    .execute(c)?;
+                diesel::delete(bracket_rounds::table.find(round.id)).execute(c)?;
+            }
+            if round.round_num == 1 {
+                use crate::schema::player_bracket_entry as pbes;
+                diesel::update(pbes::table.filter(pbes::bracket_id.eq(self.id)))
+                    .set(pbes::seed.eq(None::<i32>))
+                    .execute(c)?;
+                self.set_state(BracketState::Unstarted)?;
+                self.update(c)?;
+            }
+            Ok((round, infos))
+        })
+    }
+
+    /// for elimination brackets: if every race in the latest round is finished, generates the
+    /// next round. Returns true if a round was generated.
+    ///
+    /// N.B. results can't change who won once the following round exists (see
+    /// [Self::check_winner_can_change])
+    pub fn advance_elimination(
+        &mut self,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, BracketError> {
+        if !self.bracket_type()?.is_elimination() || self.state()? != BracketState::Started {
+            return Ok(false);
+        }
+        let current = match self.current_round(conn)? {
+            Some(r) => r,
+            None => return Ok(false),
+        };
+        if !current.all_races_finished(conn)? {
+            return Ok(false);
+        }
+        if !self
+            .elimination_rounds(conn)?
+            .iter()
+            .any(|r| r.round_num > current.round_num)
+        {
+            // that was the final
+            return Ok(false);
+        }
+        self.generate_pairings(conn)?;
+        // the new round might already be finished, if it was all forfeits by dropped players
+        self.advance_elimination(conn)?;
+        Ok(true)
+    }
+
+    /// for elimination brackets: errors if the round after `race`'s has already been generated,
+    /// since changing who won `race` would leave the wrong player in it. Rolling that round back
+    /// (see [Self::rollback_round]) makes `race` correctable again.
+    pub fn check_winner_can_change(
+        &self,
+        race: &BracketRace,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), BracketError> {
+        if !self.bracket_type()?.is_elimination() {
+            return Ok(());
+        }
+        let round = BracketRound::get_by_id(race.round_id, conn)?;
+        match self.current_round(conn)? {
+            Some(current) if current.round_num > round.round_num => {
+                Err(BracketError::Other(format!(
+                    "Round {} has already been generated from race {}'s result. Roll it back \
+                    before changing who won.",
+                    round.round_num + 1,
+                    race.id
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// player ids of this bracket's seeded entrants, best seed first
+    pub fn seeds(&self, conn: &mut SqliteConnection) -> Result<Vec<i32>, diesel::result::Error> {
+        use crate::schema::player_bracket_entry as pbes;
+        pbes::table
+            .filter(pbes::bracket_id.eq(self.id))
+            .filter(pbes::seed.is_not_null())
+            .order(pbes::seed.asc())
+            .select(pbes::player_id)
+            .load(conn)
+    }
+
+    /// the full tree of an elimination bracket, including rounds that haven't been generated yet
+    pub fn elimination_rounds(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<EliminationRound>, BracketError> {
+        let double_elimination = match self.bracket_type()? {
+            BracketType::SingleElimination => false,
+            BracketType::DoubleElimination => true,
+            BracketType::Swiss | BracketType::RoundRobin | BracketType::Group => {
+                return Err(BracketError::InvalidState);
+            }
+        };
+        let seeds = self.seeds(conn)?;
+        let seed_positions: HashMap<i32, usize> = seeds
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (*id, idx))
+            .collect();
+        let round_nums: HashMap<i32, i32> = self
+            .rounds(conn)?
+            .into_iter()
+            .map(|r| (r.id, r.round_num))
+            .collect();
+
+        let mut results = EliminationResults::default();
+        for race in self.bracket_races(conn)? {
+            let winner = match race.outcome()? {
+                Some(Outcome::P1Win) => race.player_1_id,
+                Some(Outcome::P2Win) => race.player_2_id,
+                // someone has to advance, so ties go to the higher seed
+                Some(Outcome::Tie) => {
+                    std::cmp::min_by_key(race.player_1_id, race.player_2_id, |id| {
+                        seed_positions.get(id).cloned().unwrap_or(usize::MAX)
+                    })
+                }
+                None => continue,
+            };
+            let round_num = round_nums
+                .get(&race.round_id)
+                .ok_or(BracketError::Other(format!(
+                    "Cannot find round {} for race {}",
+                    race.round_id, race.id
+                )))?;
+            results
+                .entry(*round_num)
+                .or_default()
+                .insert(pair_key(race.player_1_id, race.player_2_id), winner);
         }
+        Ok(elimination_rounds(&seeds, double_elimination, &results))
     }
 
     pub fn current_round(
@@ -371,42 +1203,83 @@ impl Bracket {
             }
             BracketState::Started | BracketState::Finished => {}
         }
+        let rules = self.scoring_rules()?;
         let rounds = self.rounds(conn)?;
         let mut races = vec![];
         let is_rr = match self.bracket_type()? {
-            BracketType::Swiss => false,
+            BracketType::Swiss
+            | BracketType::SingleElimination
+            | BracketType::DoubleElimination
+            | BracketType::Group => false,
             BracketType::RoundRobin => true,
         };
 
         struct StandingsRace {
             player_1_id: i32,
             player_2_id: i32,
-            player_1_result: PlayerResult,
-            player_2_result: PlayerResult,
+            /// one result per game. Empty for the opponent of a player who forfeited by dropping out
+            player_1_results: Vec<PlayerResult>,
+            player_2_results: Vec<PlayerResult>,
             outcome: Outcome,
         }
 
-        impl TryFrom<BracketRace> for StandingsRace {
-            type Error = BracketError;
-
-            fn try_from(value: BracketRace) -> Result<Self, Self::Error> {
-                let player_1_result = value
-                    .player_1_result()
-                    .ok_or(BracketError::InvalidState)??;
-                let player_2_result = value
-                    .player_2_result()
-                    .ok_or(BracketError::InvalidState)??;
-                let outcome = value.outcome()?.ok_or(BracketError::InvalidState)?;
+        impl StandingsRace {
+            fn new(race: BracketRace, conn: &mut SqliteConnection) -> Result<Self, BracketError> {
+                let games = if race.is_series() {
+                    race.games(conn)?
+                } else {
+                    vec![]
+                };
+                // series only have race-level results if someone forfeited by dropping out
+                let mut player_1_results = games
+                    .iter()
+                    .map(BracketRaceGame::player_1_result)
+                    .collect::<Result<Vec<_>, _>>()?;
+                player_1_results.extend(race.player_1_result().transpose()?);
+                let mut player_2_results = games
+                    .iter()
+                    .map(BracketRaceGame::player_2_result)
+                    .collect::<Result<Vec<_>, _>>()?;
+                player_2_results.extend(race.player_2_result().transpose()?);
+                if player_1_results.is_empty() && player_2_results.is_empty() {
+                    return Err(BracketError::InvalidState);
+                }
+                let outcome = race.outcome()?.ok_or(BracketError::InvalidState)?;
                 Ok(Self {
-                    player_1_id: value.player_1_id,
-                    player_2_id: value.player_2_id,
-                    player_1_result,
-                    player_2_result,
+                    player_1_id: race.player_1_id,
+                    player_2_id: race.player_2_id,
+                    player_1_results,
+                    player_2_results,
                     outcome,
                 })
             }
         }
 
+        struct StandingsEntrant {
+            player_id: i32,
+            result: PlayerResult,
+            placement: i32,
+        }
+
+        /// the entrants of a finished placement race
+        fn standings_entrants(
+            race: &BracketRace,
+            conn: &mut SqliteConnection,
+        ) -> Result<Vec<StandingsEntrant>, BracketError> {
+            race.race_entrants(conn)?
+                .into_iter()
+                .map(|e| -> Result<StandingsEntrant, BracketError> {
+                    Ok(StandingsEntrant {
+                        player_id: e.player_id,
+                        result: e.result().ok_or(BracketError::InvalidState)??,
+                        placement: e.placement.ok_or(BracketError::InvalidState)?,
+                    })
+                })
+                .collect()
+        }
+        let mut placement_races = vec![];
+
+        let mut byes: Vec<BracketBye> = vec![];
         for round in rounds {
             let round_races = round.races(conn)?;
             if !is_rr && !round_races.iter().all(BracketRace::is_complete) {
@@ -417,11 +1290,20 @@ impl Bracket {
                 break;
             }
 
+            let (round_placement_races, round_races): (Vec<_>, Vec<_>) = round_races
+                .into_iter()
+                .partition(BracketRace::is_placement_race);
+            for race in round_placement_races {
+                if let Ok(entrants) = standings_entrants(&race, conn) {
+                    placement_races.push(entrants);
+                }
+            }
             races.extend(
                 round_races
                     .into_iter()
-                    .filter_map(|r| StandingsRace::try_from(r).ok()),
+                    .filter_map(|r| StandingsRace::new(r, conn).ok()),
             );
+            byes.extend(round.byes(conn)?);
         }
 
         let mut info: HashMap<i32, PlayerInfoBuilder> = Default::default();
@@ -429,35 +1311,83 @@ impl Bracket {
             let StandingsRace {
                 player_1_id,
                 player_2_id,
-                player_1_result,
-                player_2_result,
+                player_1_results,
+                player_2_results,
                 outcome,
             } = race;
-            let (p1_adjust, p2_adjust) = match outcome {
-                Outcome::Tie => (1, 1),
-                Outcome::P1Win => (2, 0),
-                Outcome::P2Win => (0, 2),
+            let (p1_game_result, p2_game_result) = match outcome {
+                Outcome::Tie => (GameResult::Draw, GameResult::Draw),
+                Outcome::P1Win => (GameResult::Win, GameResult::Loss),
+                Outcome::P2Win => (GameResult::Loss, GameResult::Win),
             };
             let p1_i_b = info
                 .entry(race.player_1_id)
                 .or_insert(PlayerInfoBuilder::new(race.player_1_id));
-            p1_i_b.results.push(player_1_result);
-            p1_i_b.points += p1_adjust;
-            p1_i_b.opponents.push(player_2_id);
+            p1_i_b.results.extend(player_1_results);
+            p1_i_b.points += rules.points(p1_game_result);
+            p1_i_b.games.push((player_2_id, p1_game_result));
 
             let p2_i_b = info
                 .entry(race.player_2_id)
                 .or_insert(PlayerInfoBuilder::new(race.player_2_id));
-            p2_i_b.results.push(player_2_result);
-            p2_i_b.points += p2_adjust;
-            p2_i_b.opponents.push(player_1_id);
+            p2_i_b.results.extend(player_2_results);
+            p2_i_b.points += rules.points(p2_game_result);
+            p2_i_b.games.push((player_1_id, p2_game_result));
+        }
+        // placement races count as a game against each of the other entrants, for tiebreakers
+        for entrants in placement_races {
+            let num_entrants = entrants.len() as i32;
+            for entrant in &entrants {
+                let i_b = info
+                    .entry(entrant.player_id)
+                    .or_insert(PlayerInfoBuilder::new(entrant.player_id));
+                i_b.results.push(entrant.result.clone());
+                i_b.points += rules.placement_points(entrant.placement, num_entrants);
+                for other in entrants.iter().filter(|o| o.player_id != entrant.player_id) {
+                    let result = match entrant.placement.cmp(&other.placement) {
+                        std::cmp::Ordering::Less => GameResult::Win,
+                        std::cmp::Ordering::Equal => GameResult::Draw,
+                        std::cmp::Ordering::Greater => GameResult::Loss,
+                    };
+                    i_b.games.push((other.player_id, result));
+                }
+            }
+        }
+        for bye in byes {
+            let i_b = info
+                .entry(bye.player_id)
+                .or_insert(PlayerInfoBuilder::new(bye.player_id));
+            i_b.points += rules.points(bye_result(self.bye_points));
+            i_b.byes += 1;
+        }
+        for id in self.dropped_player_ids(conn)? {
+            if let Some(i_b) = info.get_mut(&id) {
+                i_b.dropped = true;
+            }
         }
         let points: HashMap<i32, i32> = info.values().map(|p| (p.id, p.points)).collect();
 
-        Ok(info
+        let mut players: HashMap<i32, PlayerInfo> = info
             .into_values()
-            .map(|builder| builder.build(&points))
-            .sorted_by_cached_key(|p| (-p.points, -p.opponent_points, p.time_adjusted(), p.id))
+            .map(|builder| {
+                (
+                    builder.id,
+                    builder.build(&points, rules.forfeit_time_penalty),
+                )
+            })
+            .collect();
+        let records = players
+            .values()
+            .map(|p| Record {
+                id: p.id,
+                points: p.points,
+                games: p.games.clone(),
+                average_time: p.avg_time_adjusted(),
+            })
+            .collect::<Vec<_>>();
+        Ok(rank(&records, &rules)
+            .into_iter()
+            .filter_map(|id| players.remove(&id))
             .collect())
     }
 }
@@ -465,8 +1395,10 @@ impl Bracket {
 struct PlayerInfoBuilder {
     id: i32,
     points: i32,
-    opponents: Vec<i32>,
+    games: Vec<(i32, GameResult)>,
     results: Vec<PlayerResult>,
+    byes: i32,
+    dropped: bool,
 }
 
 impl PlayerInfoBuilder {
@@ -474,16 +1406,18 @@ impl PlayerInfoBuilder {
         Self {
             id,
             points: 0,
-            opponents: vec![],
+            games: vec![],
             results: vec![],
+            byes: 0,
+            dropped: false,
         }
     }
 
-    fn build(self, scores: &HashMap<i32, i32>) -> PlayerInfo {
+    fn build(self, scores: &HashMap<i32, i32>, forfeit_time_penalty: u32) -> PlayerInfo {
         let score = self
-            .opponents
+            .games
             .iter()
-            .map(|opponent_id| {
+            .map(|(opponent_id, _)| {
                 if let Some(score) = scores.get(opponent_id) {
                     score.clone()
                 } else {
@@ -497,7 +1431,11 @@ impl PlayerInfoBuilder {
             id: self.id,
             points: self.points,
             opponent_points: score,
+            games: self.games,
             results: self.results,
+            forfeit_time_penalty,
+            byes: self.byes,
+            dropped: self.dropped,
         }
     }
 }
@@ -508,16 +1446,31 @@ pub struct PlayerInfo {
     pub points: i32,
     /// see [points]
     pub opponent_points: i32,
+    /// (opponent id, result) for every race played
+    games: Vec<(i32, GameResult)>,
     results: Vec<PlayerResult>,
+    /// from the bracket's [ScoringRules]
+    forfeit_time_penalty: u32,
+    /// number of byes (already included in `points`)
+    pub byes: i32,
+    /// true if this player has dropped out of the bracket
+    pub dropped: bool,
 }
 
 impl PlayerInfo {
-    /// total time of all races, with forfeits counting as 3 hours (for use in sorting)
+    /// total time of all races, with forfeits counting as the bracket's forfeit time penalty
     fn time_adjusted(&self) -> u32 {
-        self.results.iter().map(|r| r.time()).sum()
+        self.results
+            .iter()
+            .map(|r| r.time_with_forfeit_penalty(self.forfeit_time_penalty))
+            .sum()
     }
 
     pub fn avg_time_adjusted(&self) -> f32 {
+        if self.results.is_empty() {
+            // e.g. someone who's only had a bye so far
+            return 0.0;
+        }
         self.time_adjusted() as f32 / self.results.len() as f32
     }
 
@@ -563,9 +1516,87 @@ impl NewBracket {
     save_fn!(brackets::table, Bracket);
 }
 
+/// The settings a bracket can be created with besides its name and type. Each one only makes
+/// sense for some bracket types: see [create_bracket].
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BracketOptions {
+    /// elimination brackets only
+    pub seed_from_bracket_id: Option<i32>,
+    /// Swiss brackets only
+    pub bye_points: Option<i32>,
+    /// group brackets only
+    pub group_size: Option<i32>,
+}
+
+/// creates a new bracket in `season`, after checking that `options` make sense for
+/// `bracket_type`. Elimination brackets can only be seeded from a bracket in the same season.
+pub fn create_bracket<S: Into<String>>(
+    season: &Season,
+    name: S,
+    bracket_type: BracketType,
+    options: &BracketOptions,
+    conn: &mut SqliteConnection,
+) -> Result<Bracket, BracketError> {
+    if options.seed_from_bracket_id.is_some() && !bracket_type.is_elimination() {
+        return Err(BracketError::Other(
+            "Only elimination brackets are seeded.".to_string(),
+        ));
+    }
+    if options.bye_points.is_some() && bracket_type != BracketType::Swiss {
+        return Err(BracketError::Other(
+            "Only Swiss brackets have byes.".to_string(),
+        ));
+    }
+    if options.group_size.is_some() && bracket_type != BracketType::Group {
+        return Err(BracketError::Other(
+            "Only group brackets have groups.".to_string(),
+        ));
+    }
+    if let Some(source_id) = options.seed_from_bracket_id {
+        let source = match Bracket::get_by_id(source_id, conn) {
+            Ok(b) => b,
+            Err(Error::NotFound) => {
+                return Err(BracketError::Other(format!(
+                    "Bracket {source_id} not found."
+                )));
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+        if source.season_id != season.id {
+            return Err(BracketError::Other(
+                "Brackets can only be seeded from a bracket in the same season.".to_string(),
+            ));
+        }
+    }
+    conn.transaction(|conn| {
+        let mut bracket = NewBracket::new(season, name, bracket_type).save(conn)?;
+        if options.seed_from_bracket_id.is_some()
+            || options.bye_points.is_some()
+            || options.group_size.is_some()
+        {
+            bracket.seed_source_bracket_id = options.seed_from_bracket_id;
+            if let Some(points) = options.bye_points {
+                bracket.bye_points = points;
+            }
+            if let Some(size) = options.group_size {
+                bracket.group_size = size;
+            }
+            bracket.update(conn)?;
+        }
+        Ok(bracket)
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::models::brackets::BracketState;
+    use crate::models::bracket_races::PlayerResult;
+    use crate::models::brackets::{
+        create_bracket, snake_groups, BracketOptions, BracketState, BracketType,
+    };
+    use crate::models::season::NewSeason;
+    use crate::test_utils::{bracket_with_players, setup_db};
     use rocket::serde::json::serde_json;
     #[derive(Eq, PartialEq, Debug)]
     struct P {
@@ -587,4 +1618,308 @@ mod tests {
             serde_json::from_str(r#""Unstarted""#).unwrap()
         );
     }
+
+    #[test]
+    fn test_snake_groups() {
+        assert_eq!(
+            vec![vec![1, 4, 5, 8], vec![2, 3, 6, 7]],
+            snake_groups(&[1, 2, 3, 4, 5, 6, 7, 8], 4)
+        );
+        assert_eq!(
+            vec![vec![1, 6, 7], vec![2, 5], vec![3, 4]],
+            snake_groups(&[1, 2, 3, 4, 5, 6, 7], 3)
+        );
+    }
+
+    #[test]
+    fn test_group_bracket_placement_races() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, players) = bracket_with_players(BracketType::Group, 6, &mut db)?;
+        bracket.group_size = 3;
+        bracket.update(&mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let races = bracket.bracket_races(&mut db)?;
+        assert_eq!(2, races.len());
+        for mut race in races {
+            assert!(race.is_placement_race());
+            let results = race
+                .race_entrants(&mut db)?
+                .iter()
+                .enumerate()
+                .map(|(idx, e)| (e.player_id, PlayerResult::Finish(100 * (idx as u32 + 1))))
+                .collect::<Vec<_>>();
+            assert_eq!(3, results.len());
+            race.add_placement_results(&results[..2], false, &mut db)?;
+            assert!(!race.is_complete());
+            race.add_placement_results(&results[2..], false, &mut db)?;
+            assert!(race.is_complete());
+        }
+
+        let standings = bracket.standings(&mut db)?;
+        assert_eq!(6, standings.len());
+        // first place in a group of 3 beats two players
+        assert_eq!(4, standings[0].points);
+        assert_eq!(0, standings[5].points);
+
+        bracket.drop_player(&players[0], &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let round_2 = bracket.current_round(&mut db)?.unwrap();
+        let mut entrants = 0;
+        for race in round_2.races(&mut db)? {
+            assert!(!race.includes_player(&players[0], &mut db)?);
+            entrants += race.race_entrants(&mut db)?.len();
+        }
+        assert_eq!(5, entrants);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_bracket_options() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let season = NewSeason::new("Any% NMG", "alttp", "Any% NMG", &mut db)?.save(&mut db)?;
+        let swiss = create_bracket(
+            &season,
+            "swiss",
+            BracketType::Swiss,
+            &BracketOptions {
+                bye_points: Some(0),
+                ..Default::default()
+            },
+            &mut db,
+        )?;
+        assert_eq!(0, swiss.bye_points);
+
+        let seeded = BracketOptions {
+            seed_from_bracket_id: Some(swiss.id),
+            ..Default::default()
+        };
+        assert!(create_bracket(&season, "bad", BracketType::Swiss, &seeded, &mut db).is_err());
+        let elim = create_bracket(
+            &season,
+            "top 8",
+            BracketType::SingleElimination,
+            &seeded,
+            &mut db,
+        )?;
+        assert_eq!(Some(swiss.id), elim.seed_source_bracket_id);
+
+        let other_season =
+            NewSeason::new("Any% NMG", "alttp", "Any% NMG", &mut db)?.save(&mut db)?;
+        assert!(create_bracket(
+            &other_season,
+            "top 8",
+            BracketType::SingleElimination,
+            &seeded,
+            &mut db
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_odd_player_count_gets_bye() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::Swiss, 3, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        assert_eq!(1, bracket.bracket_races(&mut db)?.len());
+        assert_eq!(1, bracket.byes(&mut db)?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dropped_player_not_paired() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, players) = bracket_with_players(BracketType::Swiss, 4, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        for mut race in bracket.bracket_races(&mut db)? {
+            race.add_results(
+                Some(&PlayerResult::Finish(100)),
+                Some(&PlayerResult::Finish(200)),
+                false,
+            )?;
+            race.update(&mut db)?;
+        }
+
+        let dropped = &players[0];
+        assert!(bracket.drop_player(dropped, &mut db)?.is_empty());
+        assert!(bracket.drop_player(dropped, &mut db).is_err());
+        assert_eq!(3, bracket.active_players(&mut db)?.len());
+
+        bracket.generate_pairings(&mut db)?;
+        let round_2 = bracket.current_round(&mut db)?.unwrap();
+        let races = round_2.races(&mut db)?;
+        assert_eq!(1, races.len());
+        assert!(!races[0].involves_player(dropped));
+        let byes = round_2.byes(&mut db)?;
+        assert_eq!(1, byes.len());
+        assert_ne!(dropped.id, byes[0].player_id);
+
+        let mut race = races.into_iter().next().unwrap();
+        race.add_results(
+            Some(&PlayerResult::Finish(100)),
+            Some(&PlayerResult::Forfeit),
+            false,
+        )?;
+        race.update(&mut db)?;
+        let standings = bracket.standings(&mut db)?;
+        assert_eq!(4, standings.len());
+        assert!(
+            standings
+                .iter()
+                .find(|p| p.id == dropped.id)
+                .unwrap()
+                .dropped
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_earlier_byes_count_without_a_new_one() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, players) = bracket_with_players(BracketType::Swiss, 5, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let races = bracket.bracket_races(&mut db)?;
+        let winners = races.iter().map(|r| r.player_1_id).collect::<Vec<_>>();
+        for mut race in races {
+            race.add_results(
+                Some(&PlayerResult::Finish(100)),
+                Some(&PlayerResult::Finish(200)),
+                false,
+            )?;
+            race.update(&mut db)?;
+        }
+        let bye_player = bracket.byes(&mut db)?[0].player_id;
+        let dropped = players.iter().find(|p| p.id == winners[0]).unwrap();
+        bracket.drop_player(dropped, &mut db)?;
+
+        // the bye is worth a win, so whoever had it should play the remaining winner
+        bracket.generate_pairings(&mut db)?;
+        let round_2 = bracket.current_round(&mut db)?.unwrap();
+        assert!(round_2.byes(&mut db)?.is_empty());
+        let races = round_2.races(&mut db)?;
+        assert_eq!(2, races.len());
+        let bye_players_race = races
+            .iter()
+            .find(|r| r.player_1_id == bye_player || r.player_2_id == bye_player)
+            .unwrap();
+        assert!(
+            bye_players_race.player_1_id == winners[1]
+                || bye_players_race.player_2_id == winners[1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wins_over_dropped_players_count_for_pairing() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, players) = bracket_with_players(BracketType::Swiss, 6, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let races = bracket.bracket_races(&mut db)?;
+        let round_1 = races
+            .iter()
+            .map(|r| (r.player_1_id, r.player_2_id))
+            .collect::<Vec<_>>();
+        for mut race in races {
+            race.add_results(
+                Some(&PlayerResult::Finish(100)),
+                Some(&PlayerResult::Finish(200)),
+                false,
+            )?;
+            race.update(&mut db)?;
+        }
+        // one winner whose opponent drops, one winner who drops, and everyone else stays: that
+        // leaves two winners and two losers, and an even number of players
+        for id in [round_1[0].1, round_1[1].0] {
+            let p = players.iter().find(|p| p.id == id).unwrap();
+            bracket.drop_player(p, &mut db)?;
+        }
+        assert_eq!(4, bracket.active_players(&mut db)?.len());
+
+        bracket.generate_pairings(&mut db)?;
+        let round_2 = bracket.current_round(&mut db)?.unwrap();
+        assert!(round_2.byes(&mut db)?.is_empty());
+        let races = round_2.races(&mut db)?;
+        assert_eq!(2, races.len());
+        let winners = [round_1[0].0, round_1[2].0];
+        assert!(races
+            .iter()
+            .any(|r| winners.contains(&r.player_1_id) && winners.contains(&r.player_2_id)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_round() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::Swiss, 5, &mut db)?;
+        // nothing to roll back yet
+        assert!(bracket.rollback_round(&mut db).is_err());
+
+        bracket.generate_pairings(&mut db)?;
+        let (round, infos) = bracket.rollback_round(&mut db)?;
+        assert_eq!(1, round.round_num);
+        assert_eq!(2, infos.len());
+        assert_eq!(BracketState::Unstarted, bracket.state()?);
+        assert!(bracket.rounds(&mut db)?.is_empty());
+        assert!(bracket.bracket_races(&mut db)?.is_empty());
+        assert!(bracket.byes(&mut db)?.is_empty());
+
+        bracket.generate_pairings(&mut db)?;
+        for mut race in bracket.bracket_races(&mut db)? {
+            race.add_results(
+                Some(&PlayerResult::Finish(100)),
+                Some(&PlayerResult::Finish(200)),
+                false,
+            )?;
+            race.update(&mut db)?;
+        }
+        bracket.generate_pairings(&mut db)?;
+        let mut round_2_race = bracket
+            .current_round(&mut db)?
+            .unwrap()
+            .races(&mut db)?
+            .remove(0);
+        // one player's result is enough to count as played
+        round_2_race.add_results(Some(&PlayerResult::Finish(100)), None, false)?;
+        round_2_race.update(&mut db)?;
+        assert!(bracket.rollback_round(&mut db).is_err());
+        assert_eq!(2, bracket.rounds(&mut db)?.len());
+
+        round_2_race.player_1_result = None;
+        round_2_race.update(&mut db)?;
+        let (round, _) = bracket.rollback_round(&mut db)?;
+        assert_eq!(2, round.round_num);
+        assert_eq!(BracketState::Started, bracket.state()?);
+        assert_eq!(1, bracket.rounds(&mut db)?.len());
+        assert_eq!(1, bracket.byes(&mut db)?.len());
+        assert_eq!(2, bracket.bracket_races(&mut db)?.len());
+
+        // and the round can be generated again
+        bracket.generate_pairings(&mut db)?;
+        assert_eq!(2, bracket.current_round(&mut db)?.unwrap().round_num);
+        Ok(())
+    }
+
+    #[test]
+    fn test_elimination_winner_cant_change_after_next_round() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::SingleElimination, 4, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        for mut race in bracket.bracket_races(&mut db)? {
+            race.add_results(
+                Some(&PlayerResult::Finish(100)),
+                Some(&PlayerResult::Finish(200)),
+                false,
+            )?;
+            race.update(&mut db)?;
+        }
+        let race = bracket.bracket_races(&mut db)?.remove(0);
+        bracket.check_winner_can_change(&race, &mut db)?;
+
+        assert!(bracket.advance_elimination(&mut db)?);
+        assert!(bracket.check_winner_can_change(&race, &mut db).is_err());
+
+        bracket.rollback_round(&mut db)?;
+        bracket.check_winner_can_change(&race, &mut db)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,359 @@
+//! iCalendar feeds of scheduled races, for subscribing to from calendar apps.
+//!
+//! Every race that has been scheduled is an event, including finished ones, so calendars keep
+//! their history. Each race keeps the same UID while it exists, so rescheduling it moves the
+//! existing event. Cancelled races (see [CancelledRace]) stay in the feed as cancelled events with
+//! UIDs of their own, since a deleted race's id can be given to a new race.
+
+use crate::models::bracket_race_entrants::BracketRaceEntrant;
+use crate::models::bracket_race_infos::{BracketRaceInfo, CommentatorSignup};
+use crate::models::bracket_races::BracketRace;
+use crate::models::brackets::Bracket;
+use crate::models::cancelled_races::CancelledRace;
+use crate::models::player::Player;
+use crate::schema::{
+    bracket_race_entrants, bracket_race_infos, bracket_races, brackets, cancelled_races,
+    commentator_signups, players,
+};
+use crate::NMGLeagueBotError;
+use chrono::{TimeZone, Utc};
+use diesel::prelude::*;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// how long calendar events are. This matches the helper bot's Discord events.
+const RACE_DURATION_SECS: i64 = 100 * 60;
+
+/// which races a feed has in it
+#[derive(Debug, Clone, Copy)]
+pub enum CalendarScope {
+    /// by season id
+    Season(i32),
+    Bracket(i32),
+    /// every race this player has been in, across every season
+    Player(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarRace {
+    pub bracket_race_id: i32,
+    /// "<bracket>: <player> vs <player>"
+    pub title: String,
+    pub scheduled_for: i64,
+    pub restream_channel: Option<String>,
+    /// discord ids of the race's commentators. Only restreamed races have commentators: signups
+    /// for races that aren't restreamed don't mean anything yet.
+    pub commentator_discord_ids: Vec<String>,
+    pub racetime_gg_url: Option<String>,
+    /// the [CancelledRace]'s id, if this race was cancelled. Race ids can be reused after a race
+    /// is deleted, so cancelled races are their own events.
+    pub cancellation_id: Option<i32>,
+}
+
+type RaceRow = (BracketRace, BracketRaceInfo, Bracket);
+
+/// every scheduled (or cancelled after being scheduled) race in `scope`, in order of when they're
+/// scheduled for
+pub fn calendar_races(
+    scope: CalendarScope,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<CalendarRace>, NMGLeagueBotError> {
+    let mut query = bracket_races::table
+        .inner_join(bracket_race_infos::table)
+        .inner_join(brackets::table)
+        .filter(bracket_race_infos::scheduled_for.is_not_null())
+        .into_boxed();
+    let mut cancelled_query = cancelled_races::table
+        .inner_join(brackets::table)
+        .select(cancelled_races::all_columns)
+        .into_boxed();
+    match scope {
+        CalendarScope::Season(id) => {
+            query = query.filter(brackets::season_id.eq(id));
+            cancelled_query = cancelled_query.filter(brackets::season_id.eq(id));
+        }
+        CalendarScope::Bracket(id) => {
+            query = query.filter(brackets::id.eq(id));
+            cancelled_query = cancelled_query.filter(cancelled_races::bracket_id.eq(id));
+        }
+        CalendarScope::Player(id) => {
+            let placement_race_ids = bracket_race_entrants::table
+                .filter(bracket_race_entrants::player_id.eq(id))
+                .select(bracket_race_entrants::bracket_race_id)
+                .load::<i32>(conn)?;
+            query = query.filter(
+                bracket_races::player_1_id
+                    .eq(id)
+                    .or(bracket_races::player_2_id.eq(id))
+                    .or(bracket_races::id.eq_any(placement_race_ids)),
+            );
+        }
+    }
+    let rows: Vec<RaceRow> = query.load(conn)?;
+    let mut cancelled: Vec<CancelledRace> = cancelled_query.load(conn)?;
+    if let CalendarScope::Player(id) = scope {
+        cancelled.retain(|c| c.player_ids().map(|ids| ids.contains(&id)).unwrap_or(false));
+    }
+
+    let race_ids = rows.iter().map(|(r, _, _)| r.id).collect::<Vec<_>>();
+    let info_ids = rows.iter().map(|(_, i, _)| i.id).collect::<Vec<_>>();
+    let mut entrants = BracketRaceEntrant::for_races(&race_ids, conn)?;
+    let player_ids = rows
+        .iter()
+        .flat_map(|(r, _, _)| [r.player_1_id, r.player_2_id])
+        .chain(entrants.values().flatten().map(|e| e.player_id))
+        .unique()
+        .collect::<Vec<_>>();
+    let names: HashMap<i32, String> = players::table
+        .filter(players::id.eq_any(player_ids))
+        .load::<Player>(conn)?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let mut signups = commentator_signups::table
+        .filter(commentator_signups::bracket_race_info_id.eq_any(info_ids))
+        .order(commentator_signups::id.asc())
+        .load::<CommentatorSignup>(conn)?
+        .into_iter()
+        .into_group_map_by(|s| s.bracket_race_info_id);
+
+    let name = |id: i32| names.get(&id).map(String::as_str).unwrap_or("<unknown>");
+    let mut out = rows
+        .into_iter()
+        .filter_map(|(race, info, bracket)| {
+            let player_names = if race.placement_race {
+                entrants
+                    .remove(&race.id)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|e| name(e.player_id))
+                    .collect::<Vec<_>>()
+            } else {
+                vec![name(race.player_1_id), name(race.player_2_id)]
+            };
+            let signups = signups.remove(&info.id).unwrap_or_default();
+            Some(CalendarRace {
+                bracket_race_id: race.id,
+                title: format!("{}: {}", bracket.name, player_names.join(" vs ")),
+                scheduled_for: info.scheduled_for?,
+                commentator_discord_ids: if info.restream_channel.is_some() {
+                    signups.into_iter().map(|s| s.discord_id).collect()
+                } else {
+                    vec![]
+                },
+                restream_channel: info.restream_channel,
+                racetime_gg_url: info.racetime_gg_url,
+                cancellation_id: None,
+            })
+        })
+        .chain(cancelled.into_iter().map(|c| CalendarRace {
+            cancellation_id: Some(c.id),
+            bracket_race_id: c.bracket_race_id,
+            title: c.title,
+            scheduled_for: c.scheduled_for,
+            restream_channel: None,
+            commentator_discord_ids: vec![],
+            racetime_gg_url: None,
+        }))
+        .collect::<Vec<_>>();
+    out.sort_by_key(|r| (r.scheduled_for, r.bracket_race_id));
+    Ok(out)
+}
+
+/// escapes a TEXT value (RFC 5545 section 3.3.11)
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\r', "\\n")
+        .replace('\n', "\\n")
+}
+
+/// folds a content line into lines of at most 75 octets (RFC 5545 section 3.1), without splitting
+/// any characters
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            // the leading space counts
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out
+}
+
+fn ics_timestamp(ts: i64) -> String {
+    Utc.timestamp_opt(ts, 0)
+        .earliest()
+        .map(|t| t.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// renders `races` as an iCalendar feed called `name`.
+///
+/// `commentator_names` maps discord ids to display names; commentators who aren't in it are
+/// listed as "unknown".
+pub fn to_ics(
+    name: &str,
+    races: &[CalendarRace],
+    commentator_names: &HashMap<String, String>,
+    now: i64,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//NMG League//NMG League Bot//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+    for race in races {
+        let mut description = vec![];
+        if let Some(channel) = &race.restream_channel {
+            description.push(format!("Restream: {channel}"));
+        }
+        if !race.commentator_discord_ids.is_empty() {
+            let names = race
+                .commentator_discord_ids
+                .iter()
+                .map(|id| {
+                    commentator_names
+                        .get(id)
+                        .map(String::as_str)
+                        .unwrap_or("unknown")
+                })
+                .join(" and ");
+            description.push(format!("Commentary: {names}"));
+        }
+        if let Some(url) = &race.racetime_gg_url {
+            description.push(format!("racetime.gg: {url}"));
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(match race.cancellation_id {
+            Some(id) => format!("UID:cancelled-race-{id}@nmg-league"),
+            None => format!("UID:bracket-race-{}@nmg-league", race.bracket_race_id),
+        });
+        lines.push(format!("DTSTAMP:{}", ics_timestamp(now)));
+        lines.push(format!("DTSTART:{}", ics_timestamp(race.scheduled_for)));
+        lines.push(format!(
+            "DTEND:{}",
+            ics_timestamp(race.scheduled_for + RACE_DURATION_SECS)
+        ));
+        lines.push(format!("SUMMARY:{}", escape_text(&race.title)));
+        if let Some(location) = race
+            .restream_channel
+            .as_ref()
+            .or(race.racetime_gg_url.as_ref())
+        {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(url) = &race.racetime_gg_url {
+            lines.push(format!("URL:{url}"));
+        }
+        if !description.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_text(&description.join("\n"))
+            ));
+        }
+        lines.push(format!(
+            "STATUS:{}",
+            if race.cancellation_id.is_some() {
+                "CANCELLED"
+            } else {
+                "CONFIRMED"
+            }
+        ));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|l| fold_line(l))
+        .map(|l| l + "\r\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::calendar::{escape_text, fold_line, to_ics, CalendarRace};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_fold_line() {
+        let short = "SUMMARY:short";
+        assert_eq!(short, fold_line(short));
+        let long = format!("DESCRIPTION:{}", "é".repeat(50));
+        let folded = fold_line(&long);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(long, folded.replace("\r\n ", ""));
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!("a\\, b\\; c\\nd\\ne\\nf", escape_text("a, b; c\r\nd\re\nf"));
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let races = vec![
+            CalendarRace {
+                bracket_race_id: 12,
+                title: "Dark World: one vs two".to_string(),
+                scheduled_for: 1700000000,
+                restream_channel: Some("https://twitch.tv/zeldaspeedruns".to_string()),
+                commentator_discord_ids: vec!["1".to_string(), "2".to_string()],
+                racetime_gg_url: None,
+                cancellation_id: None,
+            },
+            CalendarRace {
+                // a cancelled race whose id was given to the race above
+                bracket_race_id: 12,
+                title: "Dark World: three vs four".to_string(),
+                scheduled_for: 1700003600,
+                restream_channel: None,
+                commentator_discord_ids: vec![],
+                racetime_gg_url: None,
+                cancellation_id: Some(3),
+            },
+        ];
+        let names = HashMap::from([("1".to_string(), "comm, one".to_string())]);
+        let ics = to_ics("Season 12", &races, &names, 1699990000);
+        assert!(ics.split("\r\n").all(|l| l.len() <= 75));
+        let unfolded = ics.replace("\r\n ", "");
+        let lines = unfolded.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(Some(&"BEGIN:VCALENDAR"), lines.first());
+        assert_eq!(
+            vec![
+                "UID:bracket-race-12@nmg-league",
+                "UID:cancelled-race-3@nmg-league"
+            ],
+            lines
+                .iter()
+                .filter(|l| l.starts_with("UID:"))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert!(lines.contains(&"DTSTART:20231114T221320Z"));
+        assert!(lines.contains(&"DTEND:20231114T235320Z"));
+        assert!(lines.contains(&"LOCATION:https://twitch.tv/zeldaspeedruns"));
+        assert!(lines.contains(
+            &"DESCRIPTION:Restream: https://twitch.tv/zeldaspeedruns\\nCommentary: comm\\, one and unknown"
+        ));
+        assert_eq!(
+            vec!["STATUS:CONFIRMED", "STATUS:CANCELLED"],
+            lines
+                .iter()
+                .filter(|l| l.starts_with("STATUS:"))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&"END:VCALENDAR"), lines.iter().rev().nth(1));
+    }
+}
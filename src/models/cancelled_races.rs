@@ -0,0 +1,108 @@
+//! Races that had been scheduled when they were cancelled.
+//!
+//! Cancelled races are deleted (see [BracketRace::delete]), which would make them silently vanish
+//! from calendar feeds. These keep enough of them around to publish the cancellation instead.
+
+use crate::models::bracket_race_infos::BracketRaceInfo;
+use crate::models::bracket_races::BracketRace;
+use crate::schema::cancelled_races;
+use crate::utils::epoch_timestamp;
+use crate::{save_fn, NMGLeagueBotError};
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Clone)]
+pub struct CancelledRace {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub bracket_id: i32,
+    /// serialized list of player ids
+    #[serde(skip)]
+    player_ids: String,
+    pub title: String,
+    pub scheduled_for: i64,
+    pub cancelled: i64,
+}
+
+impl CancelledRace {
+    pub fn player_ids(&self) -> Result<Vec<i32>, serde_json::Error> {
+        serde_json::from_str(&self.player_ids)
+    }
+
+    /// remembers that `race` was cancelled, if it had been scheduled; races that were never
+    /// scheduled were never on anyone's calendar. Call this before deleting the race.
+    pub fn record(
+        race: &BracketRace,
+        info: &BracketRaceInfo,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Self>, NMGLeagueBotError> {
+        let scheduled_for = match info.scheduled_for {
+            Some(s) => s,
+            None => {
+                return Ok(None);
+            }
+        };
+        let bracket = race.bracket(conn)?;
+        let players = race.all_players(conn)?;
+        let title = format!(
+            "{}: {}",
+            bracket.name,
+            players
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" vs ")
+        );
+        let new = NewCancelledRace {
+            bracket_race_id: race.id,
+            bracket_id: race.bracket_id,
+            player_ids: serde_json::to_string(&players.iter().map(|p| p.id).collect::<Vec<_>>())?,
+            title,
+            scheduled_for,
+            cancelled: epoch_timestamp() as i64,
+        };
+        Ok(Some(new.save(conn)?))
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=cancelled_races)]
+struct NewCancelledRace {
+    bracket_race_id: i32,
+    bracket_id: i32,
+    player_ids: String,
+    title: String,
+    scheduled_for: i64,
+    cancelled: i64,
+}
+
+impl NewCancelledRace {
+    save_fn!(cancelled_races::table, CancelledRace);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::NewBracketRace;
+    use crate::models::bracket_rounds::NewBracketRound;
+    use crate::models::brackets::BracketType;
+    use crate::models::cancelled_races::CancelledRace;
+    use crate::test_utils::{bracket_with_players, setup_db};
+
+    #[test]
+    fn test_record_reused_race_id() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (bracket, players) = bracket_with_players(BracketType::Swiss, 2, &mut db)?;
+        let round = NewBracketRound::new(&bracket, 1).save(&mut db)?;
+        let race = NewBracketRace::new(&bracket, &round, &players[0], &players[1]).save(&mut db)?;
+        let mut info = race.info(&mut db)?;
+        assert!(CancelledRace::record(&race, &info, &mut db)?.is_none());
+
+        info.scheduled_for = Some(1700000000);
+        let first = CancelledRace::record(&race, &info, &mut db)?.unwrap();
+        // sqlite hands a deleted race's id to the next race, which can be cancelled too
+        let second = CancelledRace::record(&race, &info, &mut db)?.unwrap();
+        assert_eq!(first.bracket_race_id, second.bracket_race_id);
+        assert_ne!(first.id, second.id);
+        Ok(())
+    }
+}
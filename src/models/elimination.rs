@@ -0,0 +1,289 @@
+//! Layout for single- and double-elimination brackets.
+//!
+//! The only thing we store about the shape of an elimination bracket is each entrant's seed: the
+//! tree is replayed from the seeds plus the results of the races that exist so far. The races in
+//! bracket round N are always exactly the non-walkover matches of the [EliminationRound] with
+//! `round_num` N.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Slot {
+    Player(i32),
+    /// nobody is here; whoever is on the other side of the match advances automatically
+    Bye,
+    /// depends on a race that isn't finished (or doesn't exist yet)
+    Tbd,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EliminationSide {
+    Winners,
+    Losers,
+    GrandFinal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EliminationMatch {
+    pub side: EliminationSide,
+    pub slot_1: Slot,
+    pub slot_2: Slot,
+}
+
+impl EliminationMatch {
+    /// matches against a bye don't get a race
+    pub fn is_walkover(&self) -> bool {
+        self.slot_1 == Slot::Bye || self.slot_2 == Slot::Bye
+    }
+
+    /// both players' ids, if they're both known
+    pub fn players(&self) -> Option<(i32, i32)> {
+        match (self.slot_1, self.slot_2) {
+            (Slot::Player(p1), Slot::Player(p2)) => Some((p1, p2)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EliminationRound {
+    pub round_num: i32,
+    pub matches: Vec<EliminationMatch>,
+}
+
+/// winners of finished races: round number -> [pair_key] of the two players -> winner's id
+pub type EliminationResults = HashMap<i32, HashMap<(i32, i32), i32>>;
+
+pub fn pair_key(p1: i32, p2: i32) -> (i32, i32) {
+    if p1 < p2 {
+        (p1, p2)
+    } else {
+        (p2, p1)
+    }
+}
+
+/// 1-indexed seeds in bracket order, arranged so the top seeds meet as late as possible
+/// e.g. for 8 slots: 1, 8, 4, 5, 2, 7, 3, 6
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let pair_sum = order.len() * 2 + 1;
+        order = order.into_iter().flat_map(|s| [s, pair_sum - s]).collect();
+    }
+    order
+}
+
+fn pair_up(slots: &[Slot], side: EliminationSide) -> Vec<EliminationMatch> {
+    slots
+        .chunks_exact(2)
+        .map(|c| EliminationMatch {
+            side,
+            slot_1: c[0],
+            slot_2: c[1],
+        })
+        .collect()
+}
+
+/// returns (winner, loser)
+fn resolve(
+    slot_1: Slot,
+    slot_2: Slot,
+    round_results: Option<&HashMap<(i32, i32), i32>>,
+) -> (Slot, Slot) {
+    match (slot_1, slot_2) {
+        (Slot::Bye, other) | (other, Slot::Bye) => (other, Slot::Bye),
+        (Slot::Player(p1), Slot::Player(p2)) => {
+            match round_results.and_then(|r| r.get(&pair_key(p1, p2))) {
+                Some(w) if *w == p1 => (slot_1, slot_2),
+                Some(w) if *w == p2 => (slot_2, slot_1),
+                _ => (Slot::Tbd, Slot::Tbd),
+            }
+        }
+        _ => (Slot::Tbd, Slot::Tbd),
+    }
+}
+
+/// lays out every round of an elimination bracket. Rounds that have been played are filled in
+/// from `results`; anything downstream of an unfinished race is [Slot::Tbd].
+///
+/// `seeds` are the entrants' player ids, best seed first. The field is padded out to a power of
+/// two with byes, which go to the top seeds.
+///
+/// Double elimination brackets end with a single grand final (there's no bracket reset). Players
+/// dropping out of the winners side wait in line for the losers side to catch up, so the winners
+/// side may finish a few rounds early.
+pub fn elimination_rounds(
+    seeds: &[i32],
+    double_elimination: bool,
+    results: &EliminationResults,
+) -> Vec<EliminationRound> {
+    let size = seeds.len().next_power_of_two().max(2);
+    let mut winners: Vec<Slot> = seed_order(size)
+        .into_iter()
+        .map(|s| {
+            seeds
+                .get(s - 1)
+                .map(|id| Slot::Player(*id))
+                .unwrap_or(Slot::Bye)
+        })
+        .collect();
+    let mut losers: Vec<Slot> = vec![];
+    // groups of players who have dropped out of the winners side but not yet joined the losers side
+    let mut dropped: VecDeque<Vec<Slot>> = Default::default();
+    let mut grand_final_scheduled = false;
+    let mut rounds = vec![];
+
+    loop {
+        let mut matches = vec![];
+        if winners.len() > 1 {
+            matches.extend(pair_up(&winners, EliminationSide::Winners));
+        }
+        if double_elimination {
+            if losers.is_empty() {
+                // the first group to drop out of the winners side just play each other
+                if let Some(group) = dropped.pop_front() {
+                    losers = group;
+                    matches.extend(pair_up(&losers, EliminationSide::Losers));
+                }
+            } else if dropped.front().map(Vec::len) == Some(losers.len()) {
+                // the incoming group is reversed so that players don't (usually) get an
+                // immediate rematch
+                if let Some(group) = dropped.pop_front() {
+                    matches.extend(losers.iter().zip(group.iter().rev()).map(|(l, d)| {
+                        EliminationMatch {
+                            side: EliminationSide::Losers,
+                            slot_1: *l,
+                            slot_2: *d,
+                        }
+                    }));
+                }
+            } else if losers.len() > 1 {
+                matches.extend(pair_up(&losers, EliminationSide::Losers));
+            }
+
+            if matches.is_empty()
+                && !grand_final_scheduled
+                && winners.len() == 1
+                && losers.len() == 1
+                && dropped.is_empty()
+            {
+                matches.push(EliminationMatch {
+                    side: EliminationSide::GrandFinal,
+                    slot_1: winners[0],
+                    slot_2: losers[0],
+                });
+                grand_final_scheduled = true;
+            }
+        }
+        if matches.is_empty() {
+            break;
+        }
+
+        // steps made up entirely of walkovers don't get a bracket round of their own
+        let round_num = if matches.iter().any(|m| !m.is_walkover()) {
+            Some(rounds.len() as i32 + 1)
+        } else {
+            None
+        };
+        let round_results = round_num.and_then(|n| results.get(&n));
+        let mut next_winners = vec![];
+        let mut next_losers = vec![];
+        let mut newly_dropped = vec![];
+        for m in &matches {
+            let (winner, loser) = resolve(m.slot_1, m.slot_2, round_results);
+            match m.side {
+                EliminationSide::Winners => {
+                    next_winners.push(winner);
+                    newly_dropped.push(loser);
+                }
+                EliminationSide::Losers => {
+                    next_losers.push(winner);
+                }
+                EliminationSide::GrandFinal => {}
+            }
+        }
+        if !next_winners.is_empty() {
+            winners = next_winners;
+            if double_elimination {
+                dropped.push_back(newly_dropped);
+            }
+        }
+        if !next_losers.is_empty() {
+            losers = next_losers;
+        }
+        if let Some(round_num) = round_num {
+            rounds.push(EliminationRound { round_num, matches });
+        }
+    }
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::elimination::{
+        elimination_rounds, pair_key, seed_order, EliminationResults, EliminationSide, Slot,
+    };
+
+    #[test]
+    fn test_seed_order() {
+        assert_eq!(vec![1, 2], seed_order(2));
+        assert_eq!(vec![1, 8, 4, 5, 2, 7, 3, 6], seed_order(8));
+    }
+
+    #[test]
+    fn test_single_elimination() {
+        let seeds = vec![10, 20, 30, 40];
+        let mut results = EliminationResults::default();
+        let first = elimination_rounds(&seeds, false, &results);
+        assert_eq!(2, first.len());
+        assert_eq!(Some((10, 40)), first[0].matches[0].players());
+        assert_eq!(Some((20, 30)), first[0].matches[1].players());
+        assert_eq!(Slot::Tbd, first[1].matches[0].slot_1);
+
+        let round_1 = results.entry(1).or_default();
+        round_1.insert(pair_key(10, 40), 40);
+        round_1.insert(pair_key(20, 30), 20);
+        let rounds = elimination_rounds(&seeds, false, &results);
+        assert_eq!(Some((40, 20)), rounds[1].matches[0].players());
+    }
+
+    #[test]
+    fn test_byes_go_to_top_seeds() {
+        let rounds = elimination_rounds(&[1, 2, 3], false, &Default::default());
+        assert_eq!(2, rounds.len());
+        assert!(rounds[0].matches[0].is_walkover());
+        assert_eq!(Some((2, 3)), rounds[0].matches[1].players());
+        assert_eq!(Slot::Player(1), rounds[1].matches[0].slot_1);
+        assert_eq!(Slot::Tbd, rounds[1].matches[0].slot_2);
+    }
+
+    #[test]
+    fn test_double_elimination_layout() {
+        let rounds = elimination_rounds(&[1, 2, 3, 4, 5, 6, 7, 8], true, &Default::default());
+        let sides = rounds
+            .iter()
+            .map(|r| {
+                r.matches
+                    .iter()
+                    .map(|m| m.side)
+                    .filter(|s| *s != EliminationSide::Winners)
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![0, 2, 2, 1, 1, 1], sides);
+        assert_eq!(
+            EliminationSide::GrandFinal,
+            rounds.last().unwrap().matches[0].side
+        );
+    }
+
+    #[test]
+    fn test_double_elimination_two_players() {
+        let mut results = EliminationResults::default();
+        results.entry(1).or_default().insert(pair_key(1, 2), 2);
+        let rounds = elimination_rounds(&[1, 2], true, &results);
+        assert_eq!(2, rounds.len());
+        assert_eq!(Some((2, 1)), rounds[1].matches[0].players());
+    }
+}
@@ -0,0 +1,355 @@
+//! Every bracket race two players have had against each other, across every season.
+//!
+//! Everything here is from the point of view of the first player asked about: "player 1" is
+//! always them, whichever slot they had in the race itself.
+
+use crate::models::bracket_race_entrants::BracketRaceEntrant;
+use crate::models::bracket_race_games::BracketRaceGame;
+use crate::models::bracket_race_infos::BracketRaceInfo;
+use crate::models::bracket_races::{BracketRace, Outcome, PlayerResult};
+use crate::models::bracket_rounds::BracketRound;
+use crate::models::brackets::Bracket;
+use crate::models::season::Season;
+use crate::schema::{
+    bracket_race_entrants, bracket_race_games, bracket_race_infos, bracket_races, bracket_rounds,
+    brackets, seasons,
+};
+use diesel::prelude::*;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// one game of a best-of-N series between the two players
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct HeadToHeadGame {
+    pub game_num: i32,
+    pub player_1_result: Option<PlayerResult>,
+    pub player_2_result: Option<PlayerResult>,
+    pub racetime_gg_url: Option<String>,
+}
+
+/// one bracket race the two players were both in
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct HeadToHeadRace {
+    pub race_id: i32,
+    pub season_ordinal: i32,
+    pub bracket_name: String,
+    pub round_num: i32,
+    pub placement_race: bool,
+    /// always None for series: see `games`
+    pub player_1_result: Option<PlayerResult>,
+    /// always None for series: see `games`
+    pub player_2_result: Option<PlayerResult>,
+    /// None until the race is finished. For placement races, whoever placed higher won.
+    pub outcome: Option<Outcome>,
+    pub racetime_gg_url: Option<String>,
+    /// empty unless this race is a series
+    pub games: Vec<HeadToHeadGame>,
+}
+
+impl HeadToHeadRace {
+    /// player 1's time minus player 2's time, in seconds, for every game they both finished
+    pub fn time_deltas(&self) -> Vec<i64> {
+        if self.games.is_empty() {
+            time_delta(&self.player_1_result, &self.player_2_result)
+                .into_iter()
+                .collect()
+        } else {
+            self.games
+                .iter()
+                .filter_map(|g| time_delta(&g.player_1_result, &g.player_2_result))
+                .collect()
+        }
+    }
+}
+
+fn time_delta(p1: &Option<PlayerResult>, p2: &Option<PlayerResult>) -> Option<i64> {
+    match (p1, p2) {
+        (Some(PlayerResult::Finish(a)), Some(PlayerResult::Finish(b))) => {
+            Some(*a as i64 - *b as i64)
+        }
+        _ => None,
+    }
+}
+
+/// the aggregate over every finished race
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct HeadToHeadRecord {
+    pub player_1_wins: i32,
+    pub player_2_wins: i32,
+    pub ties: i32,
+    /// average of player 1's time minus player 2's time, in seconds, over every game they both
+    /// finished (so negative means player 1 is usually faster). None if there are no such games.
+    pub average_time_delta: Option<f64>,
+}
+
+impl HeadToHeadRecord {
+    pub fn from_races(races: &[HeadToHeadRace]) -> Self {
+        let mut record = Self::default();
+        for race in races {
+            match race.outcome {
+                Some(Outcome::P1Win) => record.player_1_wins += 1,
+                Some(Outcome::P2Win) => record.player_2_wins += 1,
+                Some(Outcome::Tie) => record.ties += 1,
+                None => {}
+            }
+        }
+        let deltas = races
+            .iter()
+            .flat_map(HeadToHeadRace::time_deltas)
+            .collect::<Vec<_>>();
+        if !deltas.is_empty() {
+            record.average_time_delta =
+                Some(deltas.iter().sum::<i64>() as f64 / deltas.len() as f64);
+        }
+        record
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct HeadToHead {
+    pub player_1_id: i32,
+    pub player_2_id: i32,
+    /// oldest first
+    pub races: Vec<HeadToHeadRace>,
+    pub record: HeadToHeadRecord,
+}
+
+fn flip(outcome: Outcome) -> Outcome {
+    match outcome {
+        Outcome::Tie => Outcome::Tie,
+        Outcome::P1Win => Outcome::P2Win,
+        Outcome::P2Win => Outcome::P1Win,
+    }
+}
+
+type RaceRow = (BracketRace, (BracketRound, (Bracket, Season)));
+
+/// turns a race (as stored) into one from `player_1_id`'s point of view
+fn head_to_head_race(
+    player_1_id: i32,
+    player_2_id: i32,
+    (race, (round, (bracket, season))): RaceRow,
+    info: Option<BracketRaceInfo>,
+    games: Vec<BracketRaceGame>,
+    entrants: Vec<BracketRaceEntrant>,
+) -> HeadToHeadRace {
+    let mut out = HeadToHeadRace {
+        race_id: race.id,
+        season_ordinal: season.ordinal,
+        bracket_name: bracket.name,
+        round_num: round.round_num,
+        placement_race: race.placement_race,
+        player_1_result: None,
+        player_2_result: None,
+        outcome: None,
+        racetime_gg_url: info.and_then(|i| i.racetime_gg_url),
+        games: vec![],
+    };
+    if race.placement_race {
+        let find = |id| entrants.iter().find(|e| e.player_id == id);
+        let (e1, e2) = match (find(player_1_id), find(player_2_id)) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => {
+                return out;
+            }
+        };
+        out.player_1_result = e1.result().and_then(Result::ok);
+        out.player_2_result = e2.result().and_then(Result::ok);
+        if let (Some(p1), Some(p2)) = (e1.placement, e2.placement) {
+            out.outcome = Some(match p1.cmp(&p2) {
+                std::cmp::Ordering::Less => Outcome::P1Win,
+                std::cmp::Ordering::Equal => Outcome::Tie,
+                std::cmp::Ordering::Greater => Outcome::P2Win,
+            });
+        }
+        return out;
+    }
+
+    let swapped = race.player_1_id != player_1_id;
+    let orient = |a, b| if swapped { (b, a) } else { (a, b) };
+    out.outcome = race
+        .outcome()
+        .ok()
+        .flatten()
+        .map(|o| if swapped { flip(o) } else { o });
+    if race.is_series() {
+        out.games = games
+            .into_iter()
+            .map(|g| {
+                let (p1, p2) = orient(g.player_1_result().ok(), g.player_2_result().ok());
+                HeadToHeadGame {
+                    game_num: g.game_num,
+                    player_1_result: p1,
+                    player_2_result: p2,
+                    racetime_gg_url: g.racetime_gg_url,
+                }
+            })
+            .collect();
+    } else {
+        let (p1, p2) = orient(
+            race.player_1_result().and_then(Result::ok),
+            race.player_2_result().and_then(Result::ok),
+        );
+        out.player_1_result = p1;
+        out.player_2_result = p2;
+    }
+    out
+}
+
+/// every bracket race between these two players, from `player_1_id`'s point of view, along with
+/// their overall record. Includes placement races they were both entrants in.
+///
+/// Nobody has a head to head with themselves, so that's always empty.
+pub fn head_to_head(
+    player_1_id: i32,
+    player_2_id: i32,
+    conn: &mut SqliteConnection,
+) -> Result<HeadToHead, diesel::result::Error> {
+    if player_1_id == player_2_id {
+        return Ok(HeadToHead {
+            player_1_id,
+            player_2_id,
+            races: vec![],
+            record: Default::default(),
+        });
+    }
+    let placement_races_for = |player_id: i32, conn: &mut SqliteConnection| {
+        bracket_race_entrants::table
+            .filter(bracket_race_entrants::player_id.eq(player_id))
+            .select(bracket_race_entrants::bracket_race_id)
+            .load::<i32>(conn)
+            .map(|ids| ids.into_iter().collect::<HashSet<_>>())
+    };
+    let shared_placement_races = placement_races_for(player_1_id, conn)?
+        .intersection(&placement_races_for(player_2_id, conn)?)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let races: Vec<RaceRow> = bracket_races::table
+        .inner_join(bracket_rounds::table.inner_join(brackets::table.inner_join(seasons::table)))
+        .filter(
+            bracket_races::placement_race
+                .eq(false)
+                .and(
+                    bracket_races::player_1_id
+                        .eq(player_1_id)
+                        .and(bracket_races::player_2_id.eq(player_2_id))
+                        .or(bracket_races::player_1_id
+                            .eq(player_2_id)
+                            .and(bracket_races::player_2_id.eq(player_1_id))),
+                )
+                .or(bracket_races::id.eq_any(&shared_placement_races)),
+        )
+        .order((
+            seasons::ordinal.asc(),
+            brackets::id.asc(),
+            bracket_rounds::round_num.asc(),
+            bracket_races::id.asc(),
+        ))
+        .load(conn)?;
+    let race_ids = races.iter().map(|(r, _)| r.id).collect::<Vec<_>>();
+
+    let mut infos: HashMap<i32, BracketRaceInfo> = bracket_race_infos::table
+        .filter(bracket_race_infos::bracket_race_id.eq_any(&race_ids))
+        .load::<BracketRaceInfo>(conn)?
+        .into_iter()
+        .map(|i| (i.bracket_race_id, i))
+        .collect();
+    let mut games = bracket_race_games::table
+        .filter(bracket_race_games::bracket_race_id.eq_any(&race_ids))
+        .order(bracket_race_games::game_num.asc())
+        .load::<BracketRaceGame>(conn)?
+        .into_iter()
+        .into_group_map_by(|g| g.bracket_race_id);
+    let mut entrants = BracketRaceEntrant::for_races(&shared_placement_races, conn)?;
+
+    let races = races
+        .into_iter()
+        .map(|row| {
+            let id = row.0.id;
+            head_to_head_race(
+                player_1_id,
+                player_2_id,
+                row,
+                infos.remove(&id),
+                games.remove(&id).unwrap_or_default(),
+                entrants.remove(&id).unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Ok(HeadToHead {
+        player_1_id,
+        player_2_id,
+        record: HeadToHeadRecord::from_races(&races),
+        races,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::Outcome;
+    use crate::models::bracket_races::PlayerResult::{Finish, Forfeit};
+    use crate::models::head_to_head::{HeadToHeadGame, HeadToHeadRace, HeadToHeadRecord};
+
+    fn race(outcome: Option<Outcome>) -> HeadToHeadRace {
+        HeadToHeadRace {
+            race_id: 1,
+            season_ordinal: 1,
+            bracket_name: "".to_string(),
+            round_num: 1,
+            placement_race: false,
+            player_1_result: None,
+            player_2_result: None,
+            outcome,
+            racetime_gg_url: None,
+            games: vec![],
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut won = race(Some(Outcome::P1Win));
+        won.player_1_result = Some(Finish(5000));
+        won.player_2_result = Some(Finish(5100));
+        let mut forfeited = race(Some(Outcome::P2Win));
+        forfeited.player_1_result = Some(Forfeit);
+        forfeited.player_2_result = Some(Finish(6000));
+        let mut series = race(Some(Outcome::P1Win));
+        series.games = vec![
+            HeadToHeadGame {
+                game_num: 1,
+                player_1_result: Some(Finish(5200)),
+                player_2_result: Some(Finish(5000)),
+                racetime_gg_url: None,
+            },
+            HeadToHeadGame {
+                game_num: 2,
+                player_1_result: Some(Finish(5000)),
+                player_2_result: Some(Finish(5300)),
+                racetime_gg_url: None,
+            },
+        ];
+        let unfinished = race(None);
+
+        let record = HeadToHeadRecord::from_races(&[won, forfeited, series, unfinished]);
+        assert_eq!(
+            HeadToHeadRecord {
+                player_1_wins: 2,
+                player_2_wins: 1,
+                ties: 0,
+                // -100, 200, -300
+                average_time_delta: Some(-200.0 / 3.0),
+            },
+            record
+        );
+    }
+
+    #[test]
+    fn test_record_without_finishes() {
+        let record = HeadToHeadRecord::from_races(&[race(Some(Outcome::Tie))]);
+        assert_eq!(1, record.ties);
+        assert_eq!(None, record.average_time_delta);
+    }
+}
@@ -1,14 +1,33 @@
+pub mod api_tokens;
 pub mod asyncs;
+pub mod audit_events;
+pub mod availability;
+pub mod bracket_byes;
+pub mod bracket_race_entrants;
+pub mod bracket_race_games;
 pub mod bracket_race_infos;
 pub mod bracket_races;
 pub mod bracket_rounds;
 pub mod brackets;
+pub mod calendar;
+pub mod cancelled_races;
+pub mod elimination;
 pub mod guild_race_criteria;
+pub mod head_to_head;
 pub mod player;
 pub mod player_bracket_entries;
 pub mod qualifer_submission;
+pub mod qualifier_rules;
+pub mod qualifier_tiers;
+pub mod race_disputes;
 pub mod race_events;
+pub mod ratings;
+pub mod reschedule_proposals;
+pub mod scoring;
 pub mod season;
+pub mod season_archive;
+pub mod stats;
+pub mod webhooks;
 
 // TODO: should this be a derive macro?
 /// creates a function named `save()` that takes a &SqliteConnection
@@ -2,6 +2,7 @@ use crate::schema::players::{self};
 use crate::{save_fn, update_fn};
 use diesel::prelude::*;
 use diesel::SqliteConnection;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::num::ParseIntError;
@@ -15,7 +16,16 @@ pub trait MentionOptional {
     fn mention_maybe(&self) -> Option<String>;
 }
 
-#[derive(Queryable, Debug, Clone, Identifiable, AsChangeset, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Queryable,
+    Debug,
+    Clone,
+    Identifiable,
+    AsChangeset,
+    serde::Serialize,
+    serde::Deserialize,
+    JsonSchema,
+)]
 pub struct Player {
     pub id: i32,
     /// display name
@@ -1,15 +1,52 @@
 use crate::models::brackets::Bracket;
-use diesel::prelude::{Insertable, Queryable};
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::prelude::*;
 
 use crate::models::player::Player;
-use crate::save_fn;
 use crate::schema::player_bracket_entry;
+use crate::{save_fn, update_fn};
 
-#[derive(Queryable, Debug)]
+#[derive(Queryable, Identifiable, AsChangeset, Debug)]
+#[diesel(table_name=player_bracket_entry)]
 pub struct PlayerBracketEntry {
     pub id: i32,
     pub bracket_id: i32,
     pub player_id: i32,
+    /// 1-indexed seed, for bracket types that care about seeding (set when the bracket starts)
+    pub seed: Option<i32>,
+    /// timestamp of when this player dropped out of the bracket, if they have
+    pub dropped_at: Option<i64>,
+}
+
+impl PlayerBracketEntry {
+    pub fn get(
+        bracket: &Bracket,
+        player: &Player,
+        conn: &mut SqliteConnection,
+    ) -> Result<Self, diesel::result::Error> {
+        player_bracket_entry::table
+            .filter(player_bracket_entry::bracket_id.eq(bracket.id))
+            .filter(player_bracket_entry::player_id.eq(player.id))
+            .first(conn)
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.dropped_at.is_some()
+    }
+
+    pub fn dropped_at(&self) -> Option<DateTime<Utc>> {
+        self.dropped_at
+            .map(|t| Utc.timestamp_opt(t, 0).earliest())
+            .flatten()
+    }
+
+    /// marks this player as having dropped out of the bracket as of now
+    /// does *not* persist self
+    pub fn mark_dropped(&mut self) {
+        self.dropped_at = Some(Utc::now().timestamp());
+    }
+
+    update_fn! {}
 }
 
 #[derive(Insertable)]
@@ -1,26 +1,140 @@
 use diesel::prelude::*;
 use diesel::RunQueryDsl;
+use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
 use crate::models::player::Player;
+use crate::models::qualifier_rules::QualifierRules;
 use crate::models::season::Season;
 use crate::schema::qualifier_submissions;
+use crate::utils::epoch_timestamp;
 use crate::{delete_fn, save_fn, update_fn, NMGLeagueBotError};
+use enum_iterator::Sequence;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Sequence, JsonSchema)]
+pub enum ReviewState {
+    /// submitted, but no admin has checked it against the VoD yet
+    Pending,
+    Verified,
+    Rejected,
+}
 
 #[derive(Queryable, Debug, Serialize, Identifiable, AsChangeset, Deserialize)]
+#[diesel(treat_none_as_null = true)]
 pub struct QualifierSubmission {
     pub id: i32,
-    player_id: i32,
-    season_id: i32,
-    reported_time: i32,
-    vod_link: String,
+    pub player_id: i32,
+    pub season_id: i32,
+    pub reported_time: i32,
+    pub vod_link: String,
+    /// serialized [ReviewState]
+    review_state: String,
+    pub reviewer_discord_id: Option<String>,
+    /// why it was rejected, if it was
+    pub review_reason: Option<String>,
+    pub reviewed_at: Option<i64>,
 }
 
 impl QualifierSubmission {
     pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> Result<Self, diesel::result::Error> {
         qualifier_submissions::table.find(id).first(conn)
     }
+
+    /// (player id, score) for every player in the given season who has a qualifier score under
+    /// `rules`, best first. Ties go to whoever submitted first. Only verified submissions count.
+    pub fn scores(
+        season_id: i32,
+        rules: &QualifierRules,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<(i32, i32)>, diesel::result::Error> {
+        let submissions: Vec<(i32, i32, i32)> = qualifier_submissions::table
+            .filter(qualifier_submissions::season_id.eq(season_id))
+            .filter(qualifier_submissions::review_state.eq(serialized_state(ReviewState::Verified)))
+            .order(qualifier_submissions::id.asc())
+            .select((
+                qualifier_submissions::id,
+                qualifier_submissions::player_id,
+                qualifier_submissions::reported_time,
+            ))
+            .load(conn)?;
+        Ok(submissions
+            .into_iter()
+            .into_group_map_by(|(_, player_id, _)| *player_id)
+            .into_iter()
+            .filter_map(|(player_id, subs)| {
+                let times = subs.iter().map(|(_, _, time)| *time).collect::<Vec<_>>();
+                let first_id = subs.first()?.0;
+                rules
+                    .score(&times)
+                    .map(|score| (score, first_id, player_id))
+            })
+            .sorted()
+            .map(|(score, _, player_id)| (player_id, score))
+            .collect())
+    }
+
+    /// how many qualifiers this player has submitted in the given season. Rejected submissions
+    /// don't count.
+    pub fn count_for_player(
+        season_id: i32,
+        player_id: i32,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, diesel::result::Error> {
+        qualifier_submissions::table
+            .filter(qualifier_submissions::season_id.eq(season_id))
+            .filter(qualifier_submissions::player_id.eq(player_id))
+            .filter(qualifier_submissions::review_state.ne(serialized_state(ReviewState::Rejected)))
+            .count()
+            .get_result(conn)
+    }
+
+    /// submissions in the given season that are waiting to be reviewed, oldest first
+    pub fn pending(
+        season_id: i32,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Self>, diesel::result::Error> {
+        qualifier_submissions::table
+            .filter(qualifier_submissions::season_id.eq(season_id))
+            .filter(qualifier_submissions::review_state.eq(serialized_state(ReviewState::Pending)))
+            .order(qualifier_submissions::id.asc())
+            .load(conn)
+    }
+
+    pub fn review_state(&self) -> Result<ReviewState, serde_json::Error> {
+        serde_json::from_str(&self.review_state)
+    }
+
+    /// records an admin's review of this submission. `reason` is only kept for rejections.
+    /// does *not* persist self
+    pub fn review(
+        &mut self,
+        state: ReviewState,
+        reviewer_discord_id: Option<String>,
+        reason: Option<String>,
+    ) -> Result<(), serde_json::Error> {
+        self.review_state = serde_json::to_string(&state)?;
+        self.reviewer_discord_id = reviewer_discord_id;
+        self.review_reason = match state {
+            ReviewState::Rejected => reason,
+            ReviewState::Pending | ReviewState::Verified => None,
+        };
+        self.reviewed_at = match state {
+            ReviewState::Pending => None,
+            ReviewState::Verified | ReviewState::Rejected => Some(epoch_timestamp() as i64),
+        };
+        Ok(())
+    }
+
+    pub fn player(&self, conn: &mut SqliteConnection) -> Result<Player, diesel::result::Error> {
+        Player::get_by_id(self.player_id, conn)?.ok_or(diesel::result::Error::NotFound)
+    }
+}
+
+fn serialized_state(state: ReviewState) -> String {
+    // serializing a unit variant can't fail
+    serde_json::to_string(&state).unwrap_or_default()
 }
 
 impl QualifierSubmission {
@@ -53,4 +167,51 @@ impl NewQualifierSubmission {
         }
     }
     save_fn!(qualifier_submissions::table, QualifierSubmission);
+
+    /// saves this submission unless the player has already submitted `max_attempts` qualifiers
+    /// this season, in which case returns None. The count and the insert are one transaction, so
+    /// two submissions at once can't both get in under the limit.
+    pub fn save_within_limit(
+        &self,
+        max_attempts: Option<u32>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<QualifierSubmission>, diesel::result::Error> {
+        conn.immediate_transaction(|c| {
+            if let Some(max) = max_attempts {
+                let submitted =
+                    QualifierSubmission::count_for_player(self.season_id, self.player_id, c)?;
+                if submitted >= max as i64 {
+                    return Ok(None);
+                }
+            }
+            self.save(c).map(Some)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::qualifer_submission::{NewQualifierSubmission, ReviewState};
+    use crate::models::season::NewSeason;
+    use crate::test_utils::{new_players, setup_db};
+
+    #[test]
+    fn test_save_within_limit() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let season = NewSeason::new("Any% NMG", "alttp", "Any% NMG", &mut db)?.save(&mut db)?;
+        let players = new_players(1, &mut db)?;
+        let nqs = NewQualifierSubmission::new(&players[0], &season, 5000, "vod".to_string());
+        let mut first = nqs.save_within_limit(Some(2), &mut db)?.unwrap();
+        assert!(nqs.save_within_limit(Some(2), &mut db)?.is_some());
+        assert!(nqs.save_within_limit(Some(2), &mut db)?.is_none());
+        // no limit, no problem
+        assert!(nqs.save_within_limit(None, &mut db)?.is_some());
+
+        // rejected submissions don't count towards the limit
+        first.review(ReviewState::Rejected, None, Some("no vod".to_string()))?;
+        first.update(&mut db)?;
+        assert!(nqs.save_within_limit(Some(3), &mut db)?.is_some());
+        assert!(nqs.save_within_limit(Some(3), &mut db)?.is_none());
+        Ok(())
+    }
 }
@@ -0,0 +1,156 @@
+//! Per-season rules for how a player's qualifier score is computed from their submissions, and
+//! how many submissions they get.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum QualifierAggregation {
+    /// the player's best time
+    #[default]
+    Best,
+    /// the average of the player's best N times. Players with fewer than N submissions don't have
+    /// a score yet.
+    AverageOfBest(u32),
+    /// only the player's most recent submission counts
+    Last,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Default)]
+pub struct QualifierRules {
+    pub aggregation: QualifierAggregation,
+    /// how many times each player may submit a qualifier; `None` is unlimited
+    pub max_attempts: Option<u32>,
+}
+
+impl QualifierRules {
+    /// checks that these rules make sense. Returns a description of the problem if they don't.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == Some(0) {
+            return Err("Players need at least one attempt".to_string());
+        }
+        if let QualifierAggregation::AverageOfBest(n) = self.aggregation {
+            if n == 0 {
+                return Err("Can't average zero times".to_string());
+            }
+            if self.max_attempts.map(|m| m < n).unwrap_or(false) {
+                return Err(format!(
+                    "Can't average the best {n} times with only {} attempts",
+                    self.max_attempts.unwrap_or(0)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// indexes into `times` of the submissions that make up the score, or `None` if they don't
+    /// add up to a score.
+    ///
+    /// `times` should be one player's submissions, oldest first. If the player has more
+    /// submissions than they're allowed (e.g. because the cap was lowered), only the oldest ones
+    /// count.
+    pub fn counted_attempts(&self, times: &[i32]) -> Option<Vec<usize>> {
+        let allowed = self
+            .max_attempts
+            .map(|m| times.len().min(m as usize))
+            .unwrap_or(times.len());
+        let times = &times[..allowed];
+        if times.is_empty() {
+            return None;
+        }
+        let mut fastest_first = (0..times.len()).collect::<Vec<_>>();
+        fastest_first.sort_by_key(|idx| (times[*idx], *idx));
+        match self.aggregation {
+            QualifierAggregation::Best => Some(vec![fastest_first[0]]),
+            QualifierAggregation::Last => Some(vec![times.len() - 1]),
+            QualifierAggregation::AverageOfBest(n) => {
+                if times.len() < n as usize {
+                    None
+                } else {
+                    fastest_first.truncate(n as usize);
+                    Some(fastest_first)
+                }
+            }
+        }
+    }
+
+    /// the player's score (in seconds, rounded) from all their submissions' times, oldest first
+    pub fn score(&self, times: &[i32]) -> Option<i32> {
+        let counted = self.counted_attempts(times)?;
+        let total: i64 = counted.iter().map(|idx| times[*idx] as i64).sum();
+        let count = counted.len() as i64;
+        Some(((total + count / 2) / count) as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::qualifier_rules::{QualifierAggregation, QualifierRules};
+
+    fn rules(aggregation: QualifierAggregation, max_attempts: Option<u32>) -> QualifierRules {
+        QualifierRules {
+            aggregation,
+            max_attempts,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(QualifierRules::default().validate().is_ok());
+        assert!(rules(QualifierAggregation::Best, Some(0))
+            .validate()
+            .is_err());
+        assert!(rules(QualifierAggregation::AverageOfBest(0), None)
+            .validate()
+            .is_err());
+        assert!(rules(QualifierAggregation::AverageOfBest(3), Some(2))
+            .validate()
+            .is_err());
+        assert!(rules(QualifierAggregation::AverageOfBest(2), Some(3))
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_score() {
+        let times = [300, 100, 200, 400];
+        assert_eq!(Some(100), QualifierRules::default().score(&times));
+        assert_eq!(
+            Some(400),
+            rules(QualifierAggregation::Last, None).score(&times)
+        );
+        assert_eq!(
+            Some(150),
+            rules(QualifierAggregation::AverageOfBest(2), None).score(&times)
+        );
+        assert_eq!(
+            None,
+            rules(QualifierAggregation::AverageOfBest(5), None).score(&times)
+        );
+        assert_eq!(None, QualifierRules::default().score(&[]));
+    }
+
+    #[test]
+    fn test_attempts_past_the_cap_dont_count() {
+        let times = [300, 100, 200, 400];
+        assert_eq!(
+            Some(150),
+            rules(QualifierAggregation::AverageOfBest(2), Some(3)).score(&times)
+        );
+        assert_eq!(
+            Some(vec![1, 0]),
+            rules(QualifierAggregation::AverageOfBest(2), Some(2)).counted_attempts(&times)
+        );
+        assert_eq!(
+            Some(200),
+            rules(QualifierAggregation::Last, Some(3)).score(&times)
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(
+            Some(101),
+            rules(QualifierAggregation::AverageOfBest(2), None).score(&[100, 101])
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::models::brackets::{Bracket, BracketType, NewBracket};
+use crate::models::player::Player;
+use crate::models::player_bracket_entries::NewPlayerBracketEntry;
+use crate::models::qualifer_submission::QualifierSubmission;
+use crate::models::ratings::PlayerRating;
+use crate::models::season::Season;
+use crate::schema::{brackets, player_bracket_entry};
+use crate::utils::format_hms;
+use diesel::prelude::*;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TierError {
+    #[error("Database error: {0}")]
+    DBError(#[from] diesel::result::Error),
+    #[error("{0}")]
+    InvalidSplit(String),
+    #[error("{0} is already in a bracket this season")]
+    AlreadyInBracket(String),
+    #[error("Serialization error (probably from invalid db state): {0}")]
+    SerializationError(#[from] serde_json::Error),
+}
+
+/// How to split the qualifier leaderboard between brackets
+#[derive(Debug, Clone, PartialEq)]
+pub enum TierSplit {
+    /// brackets are as close to the same size as possible; the higher brackets get any extras
+    EqualSizes,
+    /// the (1-indexed) rank of the last player in each bracket except the last one. e.g. with
+    /// three brackets, `[16, 32]` puts ranks 1-16 in the first bracket, 17-32 in the second, and
+    /// everyone else in the third
+    Cutoffs(Vec<usize>),
+}
+
+impl TierSplit {
+    /// parses a comma separated list of cutoffs; an empty string means [TierSplit::EqualSizes]
+    pub fn parse(s: &str) -> Result<Self, TierError> {
+        if s.trim().is_empty() {
+            return Ok(Self::EqualSizes);
+        }
+        s.split(',')
+            .map(|c| {
+                c.trim()
+                    .parse::<usize>()
+                    .map_err(|_| TierError::InvalidSplit(format!("Invalid cutoff: {c}")))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::Cutoffs)
+    }
+}
+
+/// How to rank the qualified players before splitting them into brackets. Either way, only
+/// players with a qualifier score are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+pub enum SeedOrder {
+    QualifierScore,
+    /// by current rating (see [crate::models::ratings]), best first. Unrated players go after
+    /// everyone else, in qualifier order.
+    Rating,
+}
+
+/// how many players go in each of `num_tiers` brackets, best bracket first
+fn tier_sizes(
+    num_players: usize,
+    num_tiers: usize,
+    split: &TierSplit,
+) -> Result<Vec<usize>, TierError> {
+    if num_tiers == 0 {
+        return Err(TierError::InvalidSplit(
+            "Need at least one bracket".to_string(),
+        ));
+    }
+    match split {
+        TierSplit::EqualSizes => {
+            let base = num_players / num_tiers;
+            let extra = num_players % num_tiers;
+            Ok((0..num_tiers)
+                .map(|i| if i < extra { base + 1 } else { base })
+                .collect())
+        }
+        TierSplit::Cutoffs(cutoffs) => {
+            if cutoffs.len() + 1 != num_tiers {
+                return Err(TierError::InvalidSplit(format!(
+                    "{num_tiers} brackets need {} cutoffs",
+                    num_tiers - 1
+                )));
+            }
+            let mut sizes = Vec::with_capacity(num_tiers);
+            let mut last = 0;
+            for cutoff in cutoffs {
+                if *cutoff <= last || *cutoff >= num_players {
+                    return Err(TierError::InvalidSplit(format!(
+                        "Cutoffs must be increasing and less than the number of qualified players \
+                        ({num_players})"
+                    )));
+                }
+                sizes.push(cutoff - last);
+                last = *cutoff;
+            }
+            sizes.push(num_players - last);
+            Ok(sizes)
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TierPlayer {
+    /// 1-indexed rank on the season's qualifier leaderboard
+    pub rank: usize,
+    pub player_id: i32,
+    pub name: String,
+    /// qualifier score (see [crate::models::qualifier_rules::QualifierRules]), in seconds
+    pub score: i32,
+    /// `score` formatted as h:mm:ss
+    pub score_display: String,
+    /// current rating, rounded, if the player has one
+    pub rating: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Tier {
+    pub name: String,
+    pub players: Vec<TierPlayer>,
+}
+
+/// A proposed split of a season's qualified players into brackets. Nothing is saved until
+/// [TierPlan::commit] is called, so this can be shown as a preview first.
+#[derive(Serialize, Debug)]
+pub struct TierPlan {
+    pub season_id: i32,
+    pub bracket_type: BracketType,
+    pub order: SeedOrder,
+    pub tiers: Vec<Tier>,
+}
+
+impl TierPlan {
+    /// ranks every qualified player by `order` and splits them into one bracket per name in
+    /// `names`, best bracket first
+    pub fn new(
+        season: &Season,
+        names: &[String],
+        split: &TierSplit,
+        bracket_type: BracketType,
+        order: SeedOrder,
+        conn: &mut SqliteConnection,
+    ) -> Result<Self, TierError> {
+        let mut scores = QualifierSubmission::scores(season.id, &season.qualifier_rules()?, conn)?;
+        let mut players = Player::by_id(Some(scores.iter().map(|(id, _)| *id).collect()), conn)?;
+        let ratings = PlayerRating::current(conn)?;
+        let rating = |player_id: &i32| ratings.get(player_id).map(|r| r.rating.round() as i32);
+        if order == SeedOrder::Rating {
+            // stable, so unrated players stay in qualifier order
+            scores.sort_by_key(|(player_id, _)| Reverse(rating(player_id)));
+        }
+        let sizes = tier_sizes(scores.len(), names.len(), split)?;
+        let mut ranked = scores
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, (player_id, score))| {
+                players.remove(&player_id).map(|p| TierPlayer {
+                    rank: idx + 1,
+                    player_id,
+                    name: p.name,
+                    score,
+                    score_display: format_hms(score as u64),
+                    rating: rating(&player_id),
+                })
+            });
+        let tiers = names
+            .iter()
+            .zip(sizes)
+            .map(|(name, size)| Tier {
+                name: name.clone(),
+                players: ranked.by_ref().take(size).collect(),
+            })
+            .collect();
+        Ok(Self {
+            season_id: season.id,
+            bracket_type,
+            order,
+            tiers,
+        })
+    }
+
+    /// creates the planned brackets and adds everyone to them. Fails without creating anything
+    /// if any of the players is already in one of this season's brackets.
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<Vec<Bracket>, TierError> {
+        conn.transaction(|conn| {
+            let season = Season::get_by_id(self.season_id, conn)?;
+            let player_ids = self
+                .tiers
+                .iter()
+                .flat_map(|t| t.players.iter().map(|p| p.player_id))
+                .collect::<Vec<_>>();
+            let already_entered: Vec<i32> = player_bracket_entry::table
+                .inner_join(brackets::table)
+                .filter(brackets::season_id.eq(self.season_id))
+                .filter(player_bracket_entry::player_id.eq_any(player_ids))
+                .select(player_bracket_entry::player_id)
+                .load(conn)?;
+            if let Some(p) = self
+                .tiers
+                .iter()
+                .flat_map(|t| t.players.iter())
+                .find(|p| already_entered.contains(&p.player_id))
+            {
+                return Err(TierError::AlreadyInBracket(p.name.clone()));
+            }
+
+            let mut created = Vec::with_capacity(self.tiers.len());
+            for tier in &self.tiers {
+                let bracket = NewBracket::new(&season, &tier.name, self.bracket_type).save(conn)?;
+                for p in &tier.players {
+                    NewPlayerBracketEntry {
+                        bracket_id: bracket.id,
+                        player_id: p.player_id,
+                    }
+                    .save(conn)?;
+                }
+                created.push(bracket);
+            }
+            Ok(created)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_rounds::NewBracketRound;
+    use crate::models::brackets::BracketType;
+    use crate::models::qualifer_submission::{NewQualifierSubmission, ReviewState};
+    use crate::models::qualifier_tiers::{tier_sizes, SeedOrder, TierError, TierPlan, TierSplit};
+    use crate::models::ratings::NewPlayerRating;
+    use crate::models::season::NewSeason;
+    use crate::schema::player_ratings;
+    use crate::test_utils::{new_players, setup_db};
+    use diesel::prelude::*;
+
+    #[test]
+    fn test_tier_sizes() {
+        assert_eq!(
+            vec![4, 3, 3],
+            tier_sizes(10, 3, &TierSplit::EqualSizes).unwrap()
+        );
+        assert_eq!(
+            vec![2, 5, 3],
+            tier_sizes(10, 3, &TierSplit::Cutoffs(vec![2, 7])).unwrap()
+        );
+        assert!(tier_sizes(10, 3, &TierSplit::Cutoffs(vec![2])).is_err());
+        assert!(tier_sizes(10, 3, &TierSplit::Cutoffs(vec![7, 2])).is_err());
+        assert!(tier_sizes(10, 2, &TierSplit::Cutoffs(vec![10])).is_err());
+        assert!(tier_sizes(10, 0, &TierSplit::EqualSizes).is_err());
+    }
+
+    #[test]
+    fn test_parse_split() {
+        assert_eq!(TierSplit::EqualSizes, TierSplit::parse(" ").unwrap());
+        assert_eq!(
+            TierSplit::Cutoffs(vec![16, 32]),
+            TierSplit::parse("16, 32").unwrap()
+        );
+        assert!(TierSplit::parse("16,x").is_err());
+    }
+
+    #[test]
+    fn test_plan_and_commit() -> Result<(), anyhow::Error> {
+        let mut db = setup_db()?;
+        let season = NewSeason::new("Any% NMG", "alttp", "Any% NMG", &mut db)?.save(&mut db)?;
+        let players = new_players(5, &mut db)?;
+        // p1 is slowest; p3 has two attempts and only their best one counts
+        let mut submissions = vec![];
+        for (p, time) in players.iter().zip([500, 100, 400, 300, 200]) {
+            submissions.push(NewQualifierSubmission::new(
+                p,
+                &season,
+                time,
+                "".to_string(),
+            ));
+        }
+        submissions.push(NewQualifierSubmission::new(
+            &players[2],
+            &season,
+            150,
+            "".to_string(),
+        ));
+        for submission in submissions {
+            let mut q = submission.save(&mut db)?;
+            q.review(ReviewState::Verified, None, None)?;
+            q.update(&mut db)?;
+        }
+        // unreviewed submissions don't count
+        NewQualifierSubmission::new(&players[0], &season, 50, "".to_string()).save(&mut db)?;
+
+        let names = vec!["Dark World".to_string(), "Light World".to_string()];
+        let plan = TierPlan::new(
+            &season,
+            &names,
+            &TierSplit::EqualSizes,
+            BracketType::Swiss,
+            SeedOrder::QualifierScore,
+            &mut db,
+        )?;
+        let tiers = plan
+            .tiers
+            .iter()
+            .map(|t| {
+                t.players
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![vec!["p2", "p3", "p5"], vec!["p4", "p1"]], tiers);
+        assert_eq!(150, plan.tiers[0].players[1].score);
+        assert_eq!(5, plan.tiers[1].players[1].rank);
+        // previewing doesn't create anything
+        assert!(season.brackets(&mut db)?.is_empty());
+
+        let brackets = plan.commit(&mut db)?;
+        assert_eq!(2, brackets.len());
+        assert_eq!("Light World", brackets[1].name);
+        assert_eq!(2, brackets[1].players(&mut db)?.len());
+
+        // everyone's already in a bracket now
+        assert!(matches!(
+            plan.commit(&mut db),
+            Err(TierError::AlreadyInBracket(_))
+        ));
+        assert_eq!(2, season.brackets(&mut db)?.len());
+
+        // rated players go first when seeding by rating
+        let round = NewBracketRound::new(&brackets[0], 1).save(&mut db)?;
+        diesel::insert_into(player_ratings::table)
+            .values(&NewPlayerRating {
+                player_id: players[0].id,
+                round_id: round.id,
+                rating: 1600.0,
+                deviation: 100.0,
+                volatility: 0.06,
+                games: 1,
+            })
+            .execute(&mut db)?;
+        let plan = TierPlan::new(
+            &season,
+            &names,
+            &TierSplit::EqualSizes,
+            BracketType::Swiss,
+            SeedOrder::Rating,
+            &mut db,
+        )?;
+        assert_eq!("p1", plan.tiers[0].players[0].name);
+        assert_eq!(Some(1600), plan.tiers[0].players[0].rating);
+        assert_eq!("p2", plan.tiers[0].players[1].name);
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! Players contesting the reported result of a finished race.
+//!
+//! Disputes wait in an admin queue until an admin resolves them, either by keeping the result or
+//! by correcting it (the same way `/update_finished_race` does). Resolved disputes are kept, so
+//! this is also the history of a race's contested results.
+
+use crate::models::bracket_races::BracketRace;
+use crate::schema::race_disputes;
+use crate::utils::epoch_timestamp;
+use crate::{save_fn, update_fn, BracketRaceState, NMGLeagueBotError};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DisputeState {
+    /// waiting for an admin
+    Open,
+    /// an admin decided the reported result was right
+    ResultKept,
+    /// an admin changed the result
+    Corrected,
+}
+
+fn serialized_state(state: DisputeState) -> String {
+    // serializing a unit variant can't fail
+    serde_json::to_string(&state).unwrap_or_default()
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone)]
+#[diesel(treat_none_as_null = true)]
+pub struct RaceDispute {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub opener_discord_id: String,
+    pub reason: String,
+    pub evidence_url: Option<String>,
+    /// serialized [DisputeState]
+    state: String,
+    pub created: i64,
+    pub resolver_discord_id: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved: Option<i64>,
+}
+
+impl RaceDispute {
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> QueryResult<Self> {
+        race_disputes::table.find(id).first(conn)
+    }
+
+    /// the admin queue: every open dispute, oldest first
+    pub fn open(conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        race_disputes::table
+            .filter(race_disputes::state.eq(serialized_state(DisputeState::Open)))
+            .order(race_disputes::id.asc())
+            .load(conn)
+    }
+
+    /// every dispute of the race, oldest first
+    pub fn for_race(bracket_race_id: i32, conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        race_disputes::table
+            .filter(race_disputes::bracket_race_id.eq(bracket_race_id))
+            .order(race_disputes::id.asc())
+            .load(conn)
+    }
+
+    pub fn state(&self) -> Result<DisputeState, serde_json::Error> {
+        serde_json::from_str(&self.state)
+    }
+
+    /// records an admin's decision. Only open disputes can be resolved, and decisions are final:
+    /// this only changes the dispute if it's still open in the database, and returns whether it was
+    /// (so two admins can't both resolve it).
+    pub fn resolve(
+        &mut self,
+        decision: DisputeState,
+        resolver_discord_id: String,
+        note: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, NMGLeagueBotError> {
+        if decision == DisputeState::Open {
+            return Err(NMGLeagueBotError::StateError(
+                "Open isn't a decision about a dispute".to_string(),
+            ));
+        }
+        let resolved = epoch_timestamp() as i64;
+        let changed = diesel::update(
+            race_disputes::table
+                .find(self.id)
+                .filter(race_disputes::state.eq(serialized_state(DisputeState::Open))),
+        )
+        .set((
+            race_disputes::state.eq(serialized_state(decision)),
+            race_disputes::resolver_discord_id.eq(Some(resolver_discord_id.clone())),
+            race_disputes::resolution_note.eq(note.clone()),
+            race_disputes::resolved.eq(Some(resolved)),
+        ))
+        .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.state = serialized_state(decision);
+        self.resolver_discord_id = Some(resolver_discord_id);
+        self.resolution_note = note;
+        self.resolved = Some(resolved);
+        Ok(true)
+    }
+
+    /// undoes [Self::resolve] correcting the race, for when the correction couldn't actually be
+    /// made. Like resolving, this only changes the dispute if it's still corrected in the
+    /// database, and returns whether it was.
+    pub fn reopen(&mut self, conn: &mut SqliteConnection) -> QueryResult<bool> {
+        let changed = diesel::update(
+            race_disputes::table
+                .find(self.id)
+                .filter(race_disputes::state.eq(serialized_state(DisputeState::Corrected))),
+        )
+        .set((
+            race_disputes::state.eq(serialized_state(DisputeState::Open)),
+            race_disputes::resolver_discord_id.eq(None::<String>),
+            race_disputes::resolution_note.eq(None::<String>),
+            race_disputes::resolved.eq(None::<i64>),
+        ))
+        .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.state = serialized_state(DisputeState::Open);
+        self.resolver_discord_id = None;
+        self.resolution_note = None;
+        self.resolved = None;
+        Ok(true)
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = race_disputes)]
+pub struct NewRaceDispute {
+    bracket_race_id: i32,
+    opener_discord_id: String,
+    reason: String,
+    evidence_url: Option<String>,
+    state: String,
+    created: i64,
+}
+
+impl NewRaceDispute {
+    save_fn!(race_disputes::table, RaceDispute);
+}
+
+/// disputes `race`'s result. Only finished head-to-head races can be disputed, and each race only
+/// has one open dispute at a time.
+pub fn open_dispute(
+    race: &BracketRace,
+    opener_discord_id: String,
+    reason: String,
+    evidence_url: Option<String>,
+    conn: &mut SqliteConnection,
+) -> Result<RaceDispute, NMGLeagueBotError> {
+    if race.is_placement_race() {
+        return Err(NMGLeagueBotError::StateError(format!(
+            "Race {} has more than two players, so an admin will have to look at it",
+            race.id
+        )));
+    }
+    if race.state()? != BracketRaceState::Finished {
+        return Err(NMGLeagueBotError::StateError(format!(
+            "Race {} hasn't finished yet",
+            race.id
+        )));
+    }
+    conn.transaction(|c| {
+        let open = RaceDispute::for_race(race.id, c)?
+            .into_iter()
+            .find(|d| matches!(d.state(), Ok(DisputeState::Open)));
+        if let Some(d) = open {
+            return Err(NMGLeagueBotError::StateError(format!(
+                "Race {} already has an open dispute (#{})",
+                race.id, d.id
+            )));
+        }
+        NewRaceDispute {
+            bracket_race_id: race.id,
+            opener_discord_id,
+            reason,
+            evidence_url,
+            state: serialized_state(DisputeState::Open),
+            created: epoch_timestamp() as i64,
+        }
+        .save(c)
+        .map_err(From::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::PlayerResult;
+    use crate::models::brackets::BracketType;
+    use crate::models::race_disputes::{open_dispute, DisputeState, RaceDispute};
+    use crate::test_utils::{bracket_with_players, setup_db};
+
+    #[test]
+    fn test_dispute_lifecycle() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (mut bracket, _) = bracket_with_players(BracketType::Swiss, 2, &mut db)?;
+        bracket.generate_pairings(&mut db)?;
+        let mut race = bracket.bracket_races(&mut db)?.remove(0);
+
+        // unfinished races don't have a result to dispute
+        assert!(open_dispute(&race, "0".to_string(), "why".to_string(), None, &mut db).is_err());
+        race.add_results(
+            Some(&PlayerResult::Finish(5000)),
+            Some(&PlayerResult::Forfeit),
+            false,
+        )?;
+        race.update(&mut db)?;
+
+        let evidence = Some("https://example.com/vod".to_string());
+        let mut dispute = open_dispute(
+            &race,
+            "1".to_string(),
+            "I didn't forfeit".to_string(),
+            evidence.clone(),
+            &mut db,
+        )?;
+        assert_eq!(DisputeState::Open, dispute.state()?);
+        assert_eq!(evidence, dispute.evidence_url);
+        assert!(open_dispute(&race, "0".to_string(), "me too".to_string(), None, &mut db).is_err());
+        assert_eq!(1, RaceDispute::open(&mut db)?.len());
+
+        assert!(dispute
+            .resolve(DisputeState::Open, "99".to_string(), None, &mut db)
+            .is_err());
+        let mut stale = dispute.clone();
+        assert!(dispute.resolve(
+            DisputeState::ResultKept,
+            "99".to_string(),
+            Some("the vod shows a forfeit".to_string()),
+            &mut db,
+        )?);
+        // decisions are final, even for an admin who loaded the dispute before it was resolved
+        assert!(!stale.resolve(DisputeState::Corrected, "98".to_string(), None, &mut db)?);
+        assert!(RaceDispute::open(&mut db)?.is_empty());
+        assert_eq!(
+            DisputeState::ResultKept,
+            RaceDispute::get_by_id(dispute.id, &mut db)?.state()?
+        );
+        // only corrections can be undone
+        assert!(!dispute.reopen(&mut db)?);
+        assert_eq!(DisputeState::ResultKept, dispute.state()?);
+
+        // once the first one's resolved, the race can be disputed again
+        let mut second = open_dispute(&race, "1".to_string(), "really".to_string(), None, &mut db)?;
+        assert!(second.resolve(DisputeState::Corrected, "99".to_string(), None, &mut db)?);
+        assert!(second.reopen(&mut db)?);
+        assert_eq!(DisputeState::Open, second.state()?);
+        assert!(!second.reopen(&mut db)?);
+        assert_eq!(
+            vec![DisputeState::ResultKept, DisputeState::Open],
+            RaceDispute::for_race(race.id, &mut db)?
+                .iter()
+                .map(|d| d.state())
+                .collect::<Result<Vec<_>, _>>()?
+        );
+        Ok(())
+    }
+}
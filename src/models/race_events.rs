@@ -7,7 +7,11 @@ use twilight_model::id::{
     Id,
 };
 
-use crate::{save_fn, schema::race_events, update_fn};
+use crate::{
+    delete_fn, save_fn,
+    schema::{bracket_race_infos, race_events},
+    update_fn,
+};
 
 use super::bracket_race_infos::{BracketRaceInfo, BracketRaceInfoId};
 
@@ -36,6 +40,19 @@ impl RaceEvent {
             .order_by(race_events::dsl::guild_id)
             .load(conn)
     }
+
+    /// returns all `RaceEvent`s whose BracketRaceInfo no longer exists (e.g. because the race was
+    /// cancelled)
+    ///
+    /// result is sorted by guild id
+    pub fn get_orphaned(conn: &mut SqliteConnection) -> Result<Vec<Self>, diesel::result::Error> {
+        race_events::table
+            .left_join(bracket_race_infos::table)
+            .filter(bracket_race_infos::id.nullable().is_null())
+            .select(race_events::all_columns)
+            .order_by(race_events::dsl::guild_id)
+            .load(conn)
+    }
 }
 
 impl RaceEvent {
@@ -48,6 +65,7 @@ impl RaceEvent {
     }
 
     update_fn!();
+    delete_fn!(race_events::table);
 }
 
 #[derive(Insertable)]
@@ -0,0 +1,565 @@
+//! Glicko-2 ratings (<http://www.glicko.net/glicko/glicko2.pdf>) for every player, computed by
+//! replaying every finished bracket race from every season.
+//!
+//! Each bracket round is a rating period. Placement races count as a game between every pair of
+//! entrants, and best-of-N series count as a single game. Ratings are never edited in place:
+//! [recompute_ratings] throws them all away and replays everything, so fixing an old result fixes
+//! everyone's rating too. That's too slow to do after every race, so finishing a race just calls
+//! [mark_ratings_stale], and the ratings worker recomputes them at most once per tick.
+
+use crate::models::bracket_race_entrants::BracketRaceEntrant;
+use crate::models::bracket_races::{BracketRace, Outcome, PlayerResult};
+use crate::models::bracket_rounds::BracketRound;
+use crate::models::brackets::Bracket;
+use crate::models::season::Season;
+use crate::schema::{bracket_races, bracket_rounds, brackets, player_ratings, seasons};
+use diesel::prelude::*;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// converts between the Glicko scale (1500 +/- 350) and the Glicko-2 scale
+const GLICKO2_SCALE: f64 = 173.7178;
+/// how much volatility can change between periods. Glickman suggests 0.3 to 1.2.
+const TAU: f64 = 0.5;
+/// convergence tolerance for the volatility calculation
+const EPSILON: f64 = 0.000001;
+
+/// A rating, on the original Glicko scale
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected_score(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64 {
+    1.0 / (1.0 + (-g(opponent_phi) * (mu - opponent_mu)).exp())
+}
+
+impl Glicko2Rating {
+    fn mu(&self) -> f64 {
+        (self.rating - 1500.0) / GLICKO2_SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / GLICKO2_SCALE
+    }
+
+    /// this rating after `periods` rating periods without any games: only the deviation changes
+    pub fn inactive(&self, periods: u32) -> Self {
+        let phi = self.phi();
+        let phi = (phi * phi + periods as f64 * self.volatility * self.volatility).sqrt();
+        Self {
+            deviation: phi * GLICKO2_SCALE,
+            ..*self
+        }
+    }
+
+    /// this rating after a rating period with these games. Each game is the opponent's rating
+    /// at the start of the period and our score (1 for a win, 0.5 for a draw, 0 for a loss).
+    pub fn update(&self, games: &[(Glicko2Rating, f64)]) -> Self {
+        if games.is_empty() {
+            return self.inactive(1);
+        }
+        let mu = self.mu();
+        let phi = self.phi();
+        let sigma = self.volatility;
+
+        let mut v_inv = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in games {
+            let g = g(opponent.phi());
+            let e = expected_score(mu, opponent.mu(), opponent.phi());
+            v_inv += g * g * e * (1.0 - e);
+            improvement += g * (score - e);
+        }
+        let v = 1.0 / v_inv;
+        let delta = v * improvement;
+
+        // step 5: the new volatility, by the Illinois algorithm
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denom = phi * phi + v + ex;
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * denom * denom)
+                - (x - a) / (TAU * TAU)
+        };
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > EPSILON {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+        let new_sigma = (big_a / 2.0).exp();
+
+        let phi_star = (phi * phi + new_sigma * new_sigma).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+        Self {
+            rating: new_mu * GLICKO2_SCALE + 1500.0,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility: new_sigma,
+        }
+    }
+}
+
+/// one game between two players: `score` is `player_a`'s (1 for a win, 0.5 for a draw, 0 for a
+/// loss)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatedGame {
+    pub player_a: i32,
+    pub player_b: i32,
+    pub score: f64,
+}
+
+/// a bracket round's worth of games
+#[derive(Debug)]
+pub struct RatingPeriod {
+    pub round_id: i32,
+    pub season_ordinal: i32,
+    pub games: Vec<RatedGame>,
+}
+
+/// the games in a finished race. Races that everyone forfeited don't count, and neither do races
+/// forfeited by someone dropping out of the bracket, since nobody actually raced.
+fn games_for_race(race: &BracketRace, entrants: &[BracketRaceEntrant]) -> Vec<RatedGame> {
+    if race.is_drop_forfeit() {
+        return vec![];
+    }
+    if race.is_placement_race() {
+        let placed = entrants
+            .iter()
+            .filter_map(|e| {
+                let forfeit = matches!(e.result(), Some(Ok(PlayerResult::Forfeit)));
+                e.placement.map(|p| (e.player_id, p, forfeit))
+            })
+            .collect::<Vec<_>>();
+        if placed.iter().all(|(_, _, forfeit)| *forfeit) {
+            return vec![];
+        }
+        return placed
+            .iter()
+            .tuple_combinations()
+            .map(|((a, a_place, _), (b, b_place, _))| RatedGame {
+                player_a: *a,
+                player_b: *b,
+                score: match a_place.cmp(b_place) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                },
+            })
+            .collect();
+    }
+    if let (Some(Ok(PlayerResult::Forfeit)), Some(Ok(PlayerResult::Forfeit))) =
+        (race.player_1_result(), race.player_2_result())
+    {
+        return vec![];
+    }
+    let score = match race.outcome() {
+        Ok(Some(Outcome::P1Win)) => 1.0,
+        Ok(Some(Outcome::P2Win)) => 0.0,
+        Ok(Some(Outcome::Tie)) => 0.5,
+        Ok(None) | Err(_) => {
+            return vec![];
+        }
+    };
+    vec![RatedGame {
+        player_a: race.player_1_id,
+        player_b: race.player_2_id,
+        score,
+    }]
+}
+
+/// every bracket round with any finished races in it, oldest first. Rounds are ordered by season,
+/// then bracket (brackets seeded from other brackets are always created after them), then round
+/// number.
+pub fn rating_periods(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<RatingPeriod>, diesel::result::Error> {
+    let races: Vec<(BracketRace, (BracketRound, (Bracket, Season)))> = bracket_races::table
+        .inner_join(bracket_rounds::table.inner_join(brackets::table.inner_join(seasons::table)))
+        .order((
+            seasons::ordinal.asc(),
+            brackets::id.asc(),
+            bracket_rounds::round_num.asc(),
+            bracket_races::id.asc(),
+        ))
+        .load(conn)?;
+    let races = races
+        .into_iter()
+        .filter(|(race, _)| race.is_complete())
+        .collect::<Vec<_>>();
+    let placement_race_ids = races
+        .iter()
+        .filter(|(race, _)| race.is_placement_race())
+        .map(|(race, _)| race.id)
+        .collect::<Vec<_>>();
+    let mut entrants = BracketRaceEntrant::for_races(&placement_race_ids, conn)?;
+
+    let mut periods: Vec<RatingPeriod> = vec![];
+    for (race, (round, (_, season))) in races {
+        let games = games_for_race(&race, &entrants.remove(&race.id).unwrap_or_default());
+        match periods.last_mut() {
+            Some(p) if p.round_id == round.id => {
+                p.games.extend(games);
+            }
+            _ => {
+                periods.push(RatingPeriod {
+                    round_id: round.id,
+                    season_ordinal: season.ordinal,
+                    games,
+                });
+            }
+        }
+    }
+    Ok(periods)
+}
+
+/// replays `periods` (oldest first) and returns every player's new rating after each period they
+/// played in.
+///
+/// Only players who race in a period are updated in it. Players who sat out whole seasons have
+/// their deviation grow by one period per season missed when they come back.
+pub fn replay(periods: &[RatingPeriod]) -> Vec<NewPlayerRating> {
+    let mut ratings: HashMap<i32, (Glicko2Rating, i32)> = Default::default();
+    let mut out = vec![];
+    for period in periods {
+        let mut games_by_player: HashMap<i32, Vec<(i32, f64)>> = Default::default();
+        for game in &period.games {
+            games_by_player
+                .entry(game.player_a)
+                .or_default()
+                .push((game.player_b, game.score));
+            games_by_player
+                .entry(game.player_b)
+                .or_default()
+                .push((game.player_a, 1.0 - game.score));
+        }
+        // everyone in the period is rated against their opponents' ratings from before it
+        let before: HashMap<i32, Glicko2Rating> = games_by_player
+            .keys()
+            .map(|id| {
+                let rating = match ratings.get(id) {
+                    Some((r, last_season)) if period.season_ordinal - last_season > 1 => {
+                        r.inactive((period.season_ordinal - last_season - 1) as u32)
+                    }
+                    Some((r, _)) => *r,
+                    None => Default::default(),
+                };
+                (*id, rating)
+            })
+            .collect();
+        for (player_id, games) in games_by_player.into_iter().sorted_by_key(|(id, _)| *id) {
+            let rated = games
+                .iter()
+                .map(|(opponent, score)| (before[opponent], *score))
+                .collect::<Vec<_>>();
+            let new = before[&player_id].update(&rated);
+            ratings.insert(player_id, (new, period.season_ordinal));
+            out.push(NewPlayerRating {
+                player_id,
+                round_id: period.round_id,
+                rating: new.rating,
+                deviation: new.deviation,
+                volatility: new.volatility,
+                games: games.len() as i32,
+            });
+        }
+    }
+    out
+}
+
+/// whether anything has changed since ratings were last recomputed. Starts out true, so they're
+/// recomputed once at startup in case a change was missed.
+static RATINGS_STALE: AtomicBool = AtomicBool::new(true);
+
+/// asks for ratings to be recomputed the next time the ratings worker runs
+pub fn mark_ratings_stale() {
+    RATINGS_STALE.store(true, Ordering::SeqCst);
+}
+
+/// [recompute_ratings], if they've been marked stale since the last time. Returns how many
+/// ratings were saved, or None if nothing needed doing.
+pub fn recompute_ratings_if_stale(
+    conn: &mut SqliteConnection,
+) -> Result<Option<usize>, diesel::result::Error> {
+    if !RATINGS_STALE.swap(false, Ordering::SeqCst) {
+        return Ok(None);
+    }
+    match recompute_ratings(conn) {
+        Ok(n) => Ok(Some(n)),
+        Err(e) => {
+            // try again next time
+            mark_ratings_stale();
+            Err(e)
+        }
+    }
+}
+
+/// throws away every saved rating and recomputes them all from the race results. Returns how many
+/// ratings were saved.
+pub fn recompute_ratings(conn: &mut SqliteConnection) -> Result<usize, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let new_ratings = replay(&rating_periods(conn)?);
+        diesel::delete(player_ratings::table).execute(conn)?;
+        let mut saved = 0;
+        // sqlite limits how many values can go in a single statement
+        for chunk in new_ratings.chunks(1000) {
+            saved += diesel::insert_into(player_ratings::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        Ok(saved)
+    })
+}
+
+#[derive(Queryable, Identifiable, Debug, Serialize, Clone)]
+pub struct PlayerRating {
+    pub id: i32,
+    pub player_id: i32,
+    /// the bracket round this rating is as of the end of
+    pub round_id: i32,
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    /// how many games this player had in that round
+    pub games: i32,
+}
+
+/// One point in a player's rating history, with enough context to label it
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct RatingHistoryEntry {
+    /// the rating's id. Later ratings have higher ids.
+    pub id: i32,
+    pub season_ordinal: i32,
+    pub bracket_name: String,
+    pub round_num: i32,
+    pub rating: f64,
+    pub deviation: f64,
+}
+
+impl PlayerRating {
+    /// everyone's most recent rating, by player id
+    pub fn current(
+        conn: &mut SqliteConnection,
+    ) -> Result<HashMap<i32, PlayerRating>, diesel::result::Error> {
+        // ratings are inserted in chronological order, so later ones overwrite earlier ones here
+        Ok(player_ratings::table
+            .order(player_ratings::id.asc())
+            .load::<Self>(conn)?
+            .into_iter()
+            .map(|r| (r.player_id, r))
+            .collect())
+    }
+
+    pub fn current_for_player(
+        player_id: i32,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<PlayerRating>, diesel::result::Error> {
+        player_ratings::table
+            .filter(player_ratings::player_id.eq(player_id))
+            .order(player_ratings::id.desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// every rating this player has had, oldest first
+    pub fn history_for_player(
+        player_id: i32,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<RatingHistoryEntry>, diesel::result::Error> {
+        let rows: Vec<(PlayerRating, (BracketRound, (Bracket, Season)))> = player_ratings::table
+            .inner_join(
+                bracket_rounds::table.inner_join(brackets::table.inner_join(seasons::table)),
+            )
+            .filter(player_ratings::player_id.eq(player_id))
+            .order(player_ratings::id.asc())
+            .load(conn)?;
+        Ok(rows
+            .into_iter()
+            .map(|(rating, (round, (bracket, season)))| RatingHistoryEntry {
+                id: rating.id,
+                season_ordinal: season.ordinal,
+                bracket_name: bracket.name,
+                round_num: round.round_num,
+                rating: rating.rating,
+                deviation: rating.deviation,
+            })
+            .collect())
+    }
+}
+
+#[derive(Insertable, Debug, PartialEq)]
+#[diesel(table_name=player_ratings)]
+pub struct NewPlayerRating {
+    pub player_id: i32,
+    pub round_id: i32,
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub games: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::{insert_bulk, NewBracketRace, PlayerResult};
+    use crate::models::bracket_rounds::NewBracketRound;
+    use crate::models::brackets::BracketType;
+    use crate::models::ratings::{games_for_race, replay, Glicko2Rating, RatedGame, RatingPeriod};
+    use crate::test_utils::{bracket_with_players, setup_db};
+
+    fn rating(rating: f64, deviation: f64) -> Glicko2Rating {
+        Glicko2Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        }
+    }
+
+    /// the worked example from Glickman's paper
+    #[test]
+    fn test_glicko2_example() {
+        let updated = rating(1500.0, 200.0).update(&[
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ]);
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{updated:?}");
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{updated:?}");
+        assert!(
+            (updated.volatility - 0.05999).abs() < 0.00001,
+            "{updated:?}"
+        );
+    }
+
+    #[test]
+    fn test_inactive_only_changes_deviation() {
+        let r = rating(1600.0, 50.0);
+        let updated = r.update(&[]);
+        assert_eq!(r.rating, updated.rating);
+        assert!(updated.deviation > r.deviation);
+        assert_eq!(updated, r.inactive(1));
+    }
+
+    #[test]
+    fn test_replay() {
+        let game = |player_a, player_b, score| RatedGame {
+            player_a,
+            player_b,
+            score,
+        };
+        let periods = vec![
+            RatingPeriod {
+                round_id: 1,
+                season_ordinal: 1,
+                games: vec![game(1, 2, 1.0), game(3, 4, 0.5)],
+            },
+            RatingPeriod {
+                round_id: 2,
+                season_ordinal: 1,
+                games: vec![game(1, 3, 1.0)],
+            },
+            // 2 sat out season 2
+            RatingPeriod {
+                round_id: 3,
+                season_ordinal: 3,
+                games: vec![game(2, 4, 0.0)],
+            },
+        ];
+        let ratings = replay(&periods);
+        let expected = vec![
+            (1, 1),
+            (2, 1),
+            (3, 1),
+            (4, 1),
+            (1, 2),
+            (3, 2),
+            (2, 3),
+            (4, 3),
+        ];
+        assert_eq!(
+            expected,
+            ratings
+                .iter()
+                .map(|r| (r.player_id, r.round_id))
+                .collect::<Vec<_>>()
+        );
+        // both players were unrated, so the winner gains what the loser loses
+        assert!((ratings[0].rating - 1500.0 + (ratings[1].rating - 1500.0)).abs() < 0.001);
+        assert!(ratings[0].rating > 1500.0);
+        assert!((ratings[2].rating - 1500.0).abs() < 0.001);
+        assert!(ratings[4].rating > ratings[0].rating);
+        assert_eq!(1, ratings[4].games);
+
+        // coming back after a season off is less certain than it would have been
+        let mut no_break = periods;
+        no_break[2].season_ordinal = 2;
+        assert!(ratings[6].deviation > replay(&no_break)[6].deviation);
+    }
+
+    #[test]
+    fn test_drop_forfeits_dont_count() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (bracket, players) = bracket_with_players(BracketType::Swiss, 2, &mut db)?;
+        let round = NewBracketRound::new(&bracket, 1).save(&mut db)?;
+        let (p1, p2) = (&players[0], &players[1]);
+        insert_bulk(
+            &vec![NewBracketRace::new(&bracket, &round, p1, p2)],
+            &mut db,
+        )?;
+        let race = bracket.bracket_races(&mut db)?.remove(0);
+
+        let mut dropped = race.clone();
+        dropped.forfeit_player(p2)?;
+        assert!(dropped.is_drop_forfeit());
+        assert!(games_for_race(&dropped, &[]).is_empty());
+
+        // a forfeit in a race that was actually run still counts
+        let mut played = race;
+        played.add_results(
+            Some(&PlayerResult::Finish(5000)),
+            Some(&PlayerResult::Forfeit),
+            false,
+        )?;
+        assert!(!played.is_drop_forfeit());
+        assert_eq!(1, games_for_race(&played, &[]).len());
+        Ok(())
+    }
+}
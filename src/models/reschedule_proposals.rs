@@ -0,0 +1,326 @@
+//! Requests to move races that are already scheduled.
+//!
+//! Players can't move a scheduled race on their own: they propose a new time, and it's only
+//! rescheduled once someone else in the race accepts. Admins can still move races directly, which
+//! is recorded here too, so this is the whole history of a race's rescheduling.
+
+use crate::schema::reschedule_proposals;
+use crate::utils::epoch_timestamp;
+use crate::{save_fn, update_fn, NMGLeagueBotError};
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProposalState {
+    /// waiting for someone else in the race to answer
+    Pending,
+    Accepted,
+    Declined,
+    /// answered with a different time, which is a proposal of its own
+    Countered,
+    /// replaced by a newer proposal for the same race
+    Superseded,
+    /// made moot by an admin moving the race directly
+    Overridden,
+    /// not really a proposal: an admin moved the race directly
+    AdminOverride,
+    /// the race was deleted while this was pending
+    Cancelled,
+}
+
+fn serialized_state(state: ProposalState) -> String {
+    // serializing a unit variant can't fail
+    serde_json::to_string(&state).unwrap_or_default()
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone)]
+#[diesel(treat_none_as_null = true)]
+pub struct RescheduleProposal {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub proposer_discord_id: String,
+    pub proposed_time: i64,
+    pub previous_time: Option<i64>,
+    /// serialized [ProposalState]
+    state: String,
+    pub created: i64,
+    pub responder_discord_id: Option<String>,
+    pub resolved: Option<i64>,
+    /// when the race was deleted. Race ids get reused, so after this `bracket_race_id` may be
+    /// some other race's.
+    pub race_deleted: Option<i64>,
+}
+
+impl RescheduleProposal {
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> QueryResult<Self> {
+        reschedule_proposals::table.find(id).first(conn)
+    }
+
+    /// every proposal for the race, oldest first
+    pub fn for_race(bracket_race_id: i32, conn: &mut SqliteConnection) -> QueryResult<Vec<Self>> {
+        reschedule_proposals::table
+            .filter(reschedule_proposals::bracket_race_id.eq(bracket_race_id))
+            .filter(reschedule_proposals::race_deleted.is_null())
+            .order(reschedule_proposals::id.asc())
+            .load(conn)
+    }
+
+    /// marks the race's pending proposals as `new_state`, returning how many there were
+    fn close_pending(
+        bracket_race_id: i32,
+        new_state: ProposalState,
+        conn: &mut SqliteConnection,
+    ) -> QueryResult<usize> {
+        diesel::update(
+            reschedule_proposals::table
+                .filter(reschedule_proposals::bracket_race_id.eq(bracket_race_id))
+                .filter(reschedule_proposals::race_deleted.is_null())
+                .filter(reschedule_proposals::state.eq(serialized_state(ProposalState::Pending))),
+        )
+        .set((
+            reschedule_proposals::state.eq(serialized_state(new_state)),
+            reschedule_proposals::resolved.eq(Some(epoch_timestamp() as i64)),
+        ))
+        .execute(conn)
+    }
+
+    /// keeps the race's proposals as history when it's deleted: pending ones are cancelled, and
+    /// they're all marked as belonging to a deleted race
+    pub fn race_deleted(bracket_race_id: i32, conn: &mut SqliteConnection) -> QueryResult<usize> {
+        conn.transaction(|c| {
+            Self::close_pending(bracket_race_id, ProposalState::Cancelled, c)?;
+            diesel::update(
+                reschedule_proposals::table
+                    .filter(reschedule_proposals::bracket_race_id.eq(bracket_race_id))
+                    .filter(reschedule_proposals::race_deleted.is_null()),
+            )
+            .set(reschedule_proposals::race_deleted.eq(Some(epoch_timestamp() as i64)))
+            .execute(c)
+        })
+    }
+
+    pub fn state(&self) -> Result<ProposalState, serde_json::Error> {
+        serde_json::from_str(&self.state)
+    }
+
+    pub fn proposed_time(&self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.proposed_time, 0).single()
+    }
+
+    /// records someone answering this proposal. Only pending proposals can be answered, and
+    /// answers are final: this only changes the proposal if it's still pending in the database,
+    /// and returns whether it was (so two people can't both answer it).
+    pub fn resolve(
+        &mut self,
+        answer: ProposalState,
+        responder_discord_id: String,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, NMGLeagueBotError> {
+        if !matches!(
+            answer,
+            ProposalState::Accepted | ProposalState::Declined | ProposalState::Countered
+        ) {
+            return Err(NMGLeagueBotError::StateError(format!(
+                "{answer:?} isn't an answer to a proposal"
+            )));
+        }
+        let resolved = epoch_timestamp() as i64;
+        let changed = diesel::update(
+            reschedule_proposals::table
+                .find(self.id)
+                .filter(reschedule_proposals::state.eq(serialized_state(ProposalState::Pending))),
+        )
+        .set((
+            reschedule_proposals::state.eq(serialized_state(answer)),
+            reschedule_proposals::responder_discord_id.eq(Some(responder_discord_id.clone())),
+            reschedule_proposals::resolved.eq(Some(resolved)),
+        ))
+        .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.state = serialized_state(answer);
+        self.responder_discord_id = Some(responder_discord_id);
+        self.resolved = Some(resolved);
+        Ok(true)
+    }
+
+    /// undoes [Self::resolve] accepting this proposal, for when the race couldn't actually be
+    /// moved. Like resolving, this only changes the proposal if it's still accepted in the
+    /// database, and returns whether it was.
+    pub fn reopen(&mut self, conn: &mut SqliteConnection) -> QueryResult<bool> {
+        let changed = diesel::update(
+            reschedule_proposals::table
+                .find(self.id)
+                .filter(reschedule_proposals::state.eq(serialized_state(ProposalState::Accepted))),
+        )
+        .set((
+            reschedule_proposals::state.eq(serialized_state(ProposalState::Pending)),
+            reschedule_proposals::responder_discord_id.eq(None::<String>),
+            reschedule_proposals::resolved.eq(None::<i64>),
+        ))
+        .execute(conn)?;
+        if changed == 0 {
+            return Ok(false);
+        }
+        self.state = serialized_state(ProposalState::Pending);
+        self.responder_discord_id = None;
+        self.resolved = None;
+        Ok(true)
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = reschedule_proposals)]
+pub struct NewRescheduleProposal {
+    bracket_race_id: i32,
+    proposer_discord_id: String,
+    proposed_time: i64,
+    previous_time: Option<i64>,
+    state: String,
+    created: i64,
+}
+
+impl NewRescheduleProposal {
+    fn new<T: TimeZone>(
+        bracket_race_id: i32,
+        proposer_discord_id: String,
+        when: &DateTime<T>,
+        previous_time: Option<i64>,
+        state: ProposalState,
+    ) -> Self {
+        Self {
+            bracket_race_id,
+            proposer_discord_id,
+            proposed_time: when.timestamp(),
+            previous_time,
+            state: serialized_state(state),
+            created: epoch_timestamp() as i64,
+        }
+    }
+
+    save_fn!(reschedule_proposals::table, RescheduleProposal);
+}
+
+/// proposes moving a race from `previous_time` to `when`. Any other pending proposals for the race
+/// are superseded by this one.
+pub fn propose<T: TimeZone>(
+    bracket_race_id: i32,
+    proposer_discord_id: String,
+    when: &DateTime<T>,
+    previous_time: Option<i64>,
+    conn: &mut SqliteConnection,
+) -> QueryResult<RescheduleProposal> {
+    conn.transaction(|c| {
+        RescheduleProposal::close_pending(bracket_race_id, ProposalState::Superseded, c)?;
+        NewRescheduleProposal::new(
+            bracket_race_id,
+            proposer_discord_id,
+            when,
+            previous_time,
+            ProposalState::Pending,
+        )
+        .save(c)
+    })
+}
+
+/// records an admin moving a race directly, which overrides any pending proposals for it
+pub fn record_admin_override<T: TimeZone>(
+    bracket_race_id: i32,
+    admin_discord_id: String,
+    when: &DateTime<T>,
+    previous_time: Option<i64>,
+    conn: &mut SqliteConnection,
+) -> QueryResult<RescheduleProposal> {
+    conn.transaction(|c| {
+        RescheduleProposal::close_pending(bracket_race_id, ProposalState::Overridden, c)?;
+        NewRescheduleProposal::new(
+            bracket_race_id,
+            admin_discord_id,
+            when,
+            previous_time,
+            ProposalState::AdminOverride,
+        )
+        .save(c)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::reschedule_proposals::{
+        propose, record_admin_override, ProposalState, RescheduleProposal,
+    };
+    use crate::test_utils::setup_db;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_proposal_history() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        // sqlite doesn't enforce foreign keys here, so these races don't need to exist
+        let t = |h| Utc.with_ymd_and_hms(2026, 10, 20, h, 0, 0).unwrap();
+
+        let first = propose(1, "10".to_string(), &t(18), Some(1000), &mut db)?;
+        let second = propose(1, "10".to_string(), &t(19), Some(1000), &mut db)?;
+        let mut other_race = propose(2, "10".to_string(), &t(20), None, &mut db)?;
+        let first = RescheduleProposal::get_by_id(first.id, &mut db)?;
+        assert_eq!(ProposalState::Superseded, first.state()?);
+        assert_eq!(ProposalState::Pending, second.state()?);
+
+        let mut stale = other_race.clone();
+        assert!(other_race.resolve(ProposalState::Accepted, "11".to_string(), &mut db)?);
+        // answers are final, even for someone who loaded the proposal before it was answered
+        assert!(!stale.resolve(ProposalState::Declined, "11".to_string(), &mut db)?);
+        assert_eq!(
+            ProposalState::Accepted,
+            RescheduleProposal::get_by_id(other_race.id, &mut db)?.state()?
+        );
+        assert!(other_race.reopen(&mut db)?);
+        assert_eq!(ProposalState::Pending, other_race.state()?);
+        assert!(!other_race.reopen(&mut db)?);
+        assert!(stale.resolve(ProposalState::Declined, "11".to_string(), &mut db)?);
+        let mut fresh = propose(2, "11".to_string(), &t(21), None, &mut db)?;
+        assert!(fresh
+            .resolve(ProposalState::Pending, "10".to_string(), &mut db)
+            .is_err());
+
+        record_admin_override(1, "99".to_string(), &t(22), Some(1000), &mut db)?;
+        let history = RescheduleProposal::for_race(1, &mut db)?;
+        assert_eq!(
+            vec![
+                ProposalState::Superseded,
+                ProposalState::Overridden,
+                ProposalState::AdminOverride
+            ],
+            history
+                .iter()
+                .map(|p| p.state())
+                .collect::<Result<Vec<_>, _>>()?
+        );
+        assert_eq!(Some(t(22)), history[2].proposed_time());
+        Ok(())
+    }
+
+    #[test]
+    fn test_race_deleted() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let t = |h| Utc.with_ymd_and_hms(2026, 10, 20, h, 0, 0).unwrap();
+        let pending = propose(1, "10".to_string(), &t(18), Some(1000), &mut db)?;
+        assert_eq!(1, RescheduleProposal::race_deleted(1, &mut db)?);
+
+        let pending = RescheduleProposal::get_by_id(pending.id, &mut db)?;
+        assert_eq!(ProposalState::Cancelled, pending.state()?);
+        assert!(pending.race_deleted.is_some());
+        // a new race with the same id starts with a clean history
+        assert!(RescheduleProposal::for_race(1, &mut db)?.is_empty());
+        propose(1, "11".to_string(), &t(19), None, &mut db)?;
+        assert_eq!(1, RescheduleProposal::for_race(1, &mut db)?.len());
+        assert_eq!(
+            ProposalState::Cancelled,
+            RescheduleProposal::get_by_id(pending.id, &mut db)?.state()?
+        );
+        Ok(())
+    }
+}
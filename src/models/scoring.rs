@@ -0,0 +1,335 @@
+//! Per-bracket scoring rules: how many points each result is worth, and how players who are tied
+//! on points get ordered in the standings. The same rules are used for pairing Swiss rounds.
+//!
+//! Like everywhere else, points are stored doubled (i.e. the default 2/1/0 scoring is displayed as
+//! 1/0.5/0).
+
+use enum_iterator::Sequence;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use swiss_pairings::TourneyConfig;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash, Sequence, JsonSchema)]
+pub enum Tiebreaker {
+    /// sum of opponents' points
+    Buchholz,
+    /// sum of the points of opponents you beat, plus half the points of opponents you drew
+    SonnebornBerger,
+    /// points scored in races against the other players who are still tied
+    HeadToHead,
+    /// Buchholz without the best and worst opponents (if there are at least 3)
+    MedianBuchholz,
+    /// average time, with forfeits counting as the forfeit time penalty (lower is better)
+    AverageTime,
+}
+
+impl Display for Tiebreaker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Tiebreaker::Buchholz => "opponent points",
+            Tiebreaker::SonnebornBerger => "Sonneborn-Berger",
+            Tiebreaker::HeadToHead => "head-to-head",
+            Tiebreaker::MedianBuchholz => "median opponent points",
+            Tiebreaker::AverageTime => "average time",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// parses a comma separated list of tiebreakers, e.g. "HeadToHead, Buchholz"
+pub fn parse_tiebreakers(s: &str) -> Result<Vec<Tiebreaker>, String> {
+    let mut tiebreakers = vec![];
+    for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let tiebreaker: Tiebreaker = serde_json::from_str(&format!("\"{name}\""))
+            .map_err(|_| format!("Unknown tiebreaker {name}"))?;
+        tiebreakers.push(tiebreaker);
+    }
+    Ok(tiebreakers)
+}
+
+/// parses a comma separated list of points for 1st, 2nd, ... place, e.g. "10, 6, 4, 2". "none"
+/// clears the list (i.e. uses the default placement points)
+pub fn parse_placement_points(s: &str) -> Result<Vec<i32>, String> {
+    if s.trim().eq_ignore_ascii_case("none") {
+        return Ok(vec![]);
+    }
+    s.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().map_err(|_| format!("Invalid points {p}")))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum GameResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, JsonSchema)]
+pub struct ScoringRules {
+    pub points_per_win: i32,
+    pub points_per_draw: i32,
+    pub points_per_loss: i32,
+    /// in seconds: what a forfeit counts as for time-based tiebreakers
+    pub forfeit_time_penalty: u32,
+    /// applied in order to players who are tied on points
+    pub tiebreakers: Vec<Tiebreaker>,
+    /// points for 1st, 2nd, ... place in placement races. Placements past the end of the list are
+    /// worth nothing. If this is empty, see [ScoringRules::placement_points]
+    #[serde(default)]
+    pub placement_points: Vec<i32>,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self {
+            points_per_win: 2,
+            points_per_draw: 1,
+            points_per_loss: 0,
+            forfeit_time_penalty: 3 * 60 * 60,
+            tiebreakers: vec![Tiebreaker::Buchholz, Tiebreaker::AverageTime],
+            placement_points: vec![],
+        }
+    }
+}
+
+impl ScoringRules {
+    /// checks that these rules make sense. Returns a description of the problem if they don't.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points_per_loss < 0 {
+            return Err("Points can't be negative".to_string());
+        }
+        if !(self.points_per_win >= self.points_per_draw
+            && self.points_per_draw >= self.points_per_loss)
+        {
+            return Err(
+                "Wins can't be worth less than draws, or draws less than losses".to_string(),
+            );
+        }
+        if self.forfeit_time_penalty == 0 {
+            return Err("Forfeit time penalty must be positive".to_string());
+        }
+        if self.tiebreakers.iter().collect::<HashSet<_>>().len() != self.tiebreakers.len() {
+            return Err("Tiebreakers can't be repeated".to_string());
+        }
+        if self.placement_points.iter().any(|p| *p < 0) {
+            return Err("Points can't be negative".to_string());
+        }
+        if self.placement_points.windows(2).any(|w| w[0] < w[1]) {
+            return Err("Placements can't be worth more than better placements".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn points(&self, result: GameResult) -> i32 {
+        match result {
+            GameResult::Win => self.points_per_win,
+            GameResult::Draw => self.points_per_draw,
+            GameResult::Loss => self.points_per_loss,
+        }
+    }
+
+    /// points for finishing in (1-indexed) `placement` in a placement race with `entrants`
+    /// entrants. Without a placement points table, that's a win against everyone you beat.
+    pub fn placement_points(&self, placement: i32, entrants: i32) -> i32 {
+        if self.placement_points.is_empty() {
+            self.points_per_win * (entrants - placement).max(0)
+        } else {
+            self.placement_points
+                .get((placement - 1).max(0) as usize)
+                .cloned()
+                .unwrap_or(0)
+        }
+    }
+
+    pub fn tourney_config(&self) -> TourneyConfig {
+        TourneyConfig {
+            points_per_win: self.points_per_win,
+            points_per_loss: self.points_per_loss,
+            points_per_draw: self.points_per_draw,
+            error_on_repeated_opponent: true,
+        }
+    }
+}
+
+/// everything about a player's results that matters for ranking them
+pub struct Record {
+    pub id: i32,
+    pub points: i32,
+    /// (opponent id, result) for every race played (byes aren't games)
+    pub games: Vec<(i32, GameResult)>,
+    pub average_time: f32,
+}
+
+/// player ids, best first: ordered by points, then by each tiebreaker in turn (only ever comparing
+/// players who are still tied), then by id
+pub fn rank(records: &[Record], rules: &ScoringRules) -> Vec<i32> {
+    let points: HashMap<i32, i32> = records.iter().map(|r| (r.id, r.points)).collect();
+    let opponent_points = |r: &Record| -> Vec<i32> {
+        r.games
+            .iter()
+            .map(|(opponent, _)| points.get(opponent).cloned().unwrap_or(0))
+            .collect()
+    };
+
+    let mut groups = split_ties(records.iter().collect(), |r| r.points as f64);
+    for tiebreaker in &rules.tiebreakers {
+        groups = groups
+            .into_iter()
+            .flat_map(|group| {
+                if group.len() < 2 {
+                    return vec![group];
+                }
+                let tied: HashSet<i32> = group.iter().map(|r| r.id).collect();
+                split_ties(group, |r| match tiebreaker {
+                    Tiebreaker::Buchholz => opponent_points(r).into_iter().sum::<i32>() as f64,
+                    Tiebreaker::SonnebornBerger => r
+                        .games
+                        .iter()
+                        .map(|(opponent, result)| {
+                            let opponent_points = points.get(opponent).cloned().unwrap_or(0);
+                            match result {
+                                GameResult::Win => opponent_points as f64,
+                                GameResult::Draw => opponent_points as f64 / 2.0,
+                                GameResult::Loss => 0.0,
+                            }
+                        })
+                        .sum(),
+                    Tiebreaker::HeadToHead => r
+                        .games
+                        .iter()
+                        .filter(|(opponent, _)| tied.contains(opponent))
+                        .map(|(_, result)| rules.points(*result))
+                        .sum::<i32>() as f64,
+                    Tiebreaker::MedianBuchholz => {
+                        let mut scores = opponent_points(r);
+                        scores.sort_unstable();
+                        if scores.len() >= 3 {
+                            scores[1..scores.len() - 1].iter().sum::<i32>() as f64
+                        } else {
+                            scores.into_iter().sum::<i32>() as f64
+                        }
+                    }
+                    // lower times are better
+                    Tiebreaker::AverageTime => -(r.average_time as f64),
+                })
+            })
+            .collect();
+    }
+    groups
+        .into_iter()
+        .flat_map(|mut group| {
+            group.sort_by_key(|r| r.id);
+            group
+        })
+        .map(|r| r.id)
+        .collect()
+}
+
+/// sorts `group` by `key`, highest first, and splits it up into groups with equal keys
+fn split_ties<'a, F>(group: Vec<&'a Record>, key: F) -> Vec<Vec<&'a Record>>
+where
+    F: Fn(&Record) -> f64,
+{
+    let mut keyed: Vec<(f64, &Record)> = group.into_iter().map(|r| (key(r), r)).collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut groups: Vec<Vec<&Record>> = vec![];
+    let mut last_key = None;
+    for (k, r) in keyed {
+        match groups.last_mut() {
+            Some(g) if last_key == Some(k) => g.push(r),
+            _ => groups.push(vec![r]),
+        }
+        last_key = Some(k);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::scoring::{
+        parse_placement_points, parse_tiebreakers, rank, GameResult, Record, ScoringRules,
+        Tiebreaker,
+    };
+
+    fn record(id: i32, points: i32, games: Vec<(i32, GameResult)>, average_time: f32) -> Record {
+        Record {
+            id,
+            points,
+            games,
+            average_time,
+        }
+    }
+
+    #[test]
+    fn test_parse_tiebreakers() {
+        assert_eq!(
+            Ok(vec![Tiebreaker::HeadToHead, Tiebreaker::AverageTime]),
+            parse_tiebreakers("HeadToHead, AverageTime")
+        );
+        assert!(parse_tiebreakers("Coinflip").is_err());
+    }
+
+    #[test]
+    fn test_parse_placement_points() {
+        assert_eq!(Ok(vec![10, 6, 4]), parse_placement_points("10, 6,4"));
+        assert_eq!(Ok(vec![]), parse_placement_points("none"));
+        assert!(parse_placement_points("10, six").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ScoringRules::default().validate().is_ok());
+        let mut rules = ScoringRules::default();
+        rules.points_per_draw = 3;
+        assert!(rules.validate().is_err());
+        let mut rules = ScoringRules::default();
+        rules.tiebreakers = vec![Tiebreaker::Buchholz, Tiebreaker::Buchholz];
+        assert!(rules.validate().is_err());
+        let mut rules = ScoringRules::default();
+        rules.placement_points = vec![6, 4, 5];
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn test_placement_points() {
+        let mut rules = ScoringRules::default();
+        assert_eq!(6, rules.placement_points(1, 4));
+        assert_eq!(0, rules.placement_points(4, 4));
+        rules.placement_points = vec![10, 6, 4];
+        assert_eq!(6, rules.placement_points(2, 4));
+        assert_eq!(0, rules.placement_points(4, 4));
+    }
+
+    #[test]
+    fn test_head_to_head_only_counts_tied_players() {
+        use GameResult::*;
+        // 1, 2 and 6 are tied on points. 2 beat 1 and lost to 6, but 1 has the best opponents
+        let records = vec![
+            record(1, 2, vec![(2, Loss), (5, Win)], 100.0),
+            record(2, 2, vec![(1, Win), (6, Loss)], 200.0),
+            record(5, 4, vec![(1, Loss), (7, Win), (8, Win)], 300.0),
+            record(6, 2, vec![(2, Win), (7, Loss)], 400.0),
+        ];
+        let mut rules = ScoringRules::default();
+        rules.tiebreakers = vec![Tiebreaker::HeadToHead, Tiebreaker::Buchholz];
+        assert_eq!(vec![5, 2, 6, 1], rank(&records, &rules));
+
+        rules.tiebreakers = vec![Tiebreaker::Buchholz];
+        assert_eq!(vec![5, 1, 2, 6], rank(&records, &rules));
+    }
+
+    #[test]
+    fn test_falls_back_to_average_time_then_id() {
+        let records = vec![
+            record(1, 2, vec![], 200.0),
+            record(2, 2, vec![], 100.0),
+            record(3, 2, vec![], 200.0),
+        ];
+        assert_eq!(vec![2, 1, 3], rank(&records, &ScoringRules::default()));
+    }
+}
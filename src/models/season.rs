@@ -2,10 +2,12 @@ use crate::models::brackets::Bracket;
 use chrono::{Duration, Utc};
 use diesel::prelude::*;
 use diesel::{RunQueryDsl, SqliteConnection};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use crate::models::bracket_race_infos::BracketRaceInfo;
 use crate::models::bracket_races::BracketRace;
+use crate::models::qualifier_rules::QualifierRules;
 use crate::schema::seasons;
 use crate::utils::epoch_timestamp;
 use crate::{save_fn, schema, update_fn, BracketRaceState, NMGLeagueBotError};
@@ -20,7 +22,8 @@ pub enum SeasonState {
     Finished,
 }
 
-#[derive(Queryable, Debug, Serialize, Identifiable, AsChangeset)]
+#[derive(Queryable, Debug, Serialize, Identifiable, AsChangeset, JsonSchema)]
+#[diesel(treat_none_as_null = true)]
 pub struct Season {
     pub id: i32,
     /// this is called 'started' but it should be called 'created'
@@ -34,6 +37,9 @@ pub struct Season {
     /// this is something like "Any% NMG". custom goals have their custom name in the same field,
     /// along with a "custom: true" field that I think we can maybe just ignore
     pub rtgg_goal_name: String,
+    /// serialized [crate::models::qualifier_rules::QualifierAggregation]
+    qualifier_aggregation: String,
+    qualifier_max_attempts: Option<i32>,
 }
 
 impl Season {
@@ -93,6 +99,22 @@ impl Season {
         serde_json::from_str(&self.state)
     }
 
+    /// how qualifier submissions are turned into a score, and how many each player gets
+    pub fn qualifier_rules(&self) -> Result<QualifierRules, serde_json::Error> {
+        Ok(QualifierRules {
+            aggregation: serde_json::from_str(&self.qualifier_aggregation)?,
+            max_attempts: self.qualifier_max_attempts.map(|m| m.max(0) as u32),
+        })
+    }
+
+    /// does *not* persist self
+    pub fn set_qualifier_rules(&mut self, rules: &QualifierRules) -> Result<(), NMGLeagueBotError> {
+        rules.validate().map_err(NMGLeagueBotError::Other)?;
+        self.qualifier_aggregation = serde_json::to_string(&rules.aggregation)?;
+        self.qualifier_max_attempts = rules.max_attempts.map(|m| m as i32);
+        Ok(())
+    }
+
     /// this is a heavy duty function, not a normal setter. it will make sure state
     /// transitions are legal, check associated bracket states, etc
     pub fn set_state(
@@ -289,6 +311,8 @@ impl Season {
             state: "".to_string(),
             rtgg_category_name: "".to_string(),
             rtgg_goal_name: goal.to_string(),
+            qualifier_aggregation: "\"Best\"".to_string(),
+            qualifier_max_attempts: None,
         }
     }
 }
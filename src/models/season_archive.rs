@@ -0,0 +1,823 @@
+//! A whole season as a single versioned archive, for moving seasons between databases (e.g. to
+//! backfill an old season, or to seed staging with production data).
+//!
+//! Rows are kept the way they're stored (enums and results are still serialized), so every table
+//! can also be written out as a CSV, and read back in from one. Ids are the ones from the database the archive came from;
+//! importing gives every row a new id, except for players, who are matched up with existing players
+//! by discord id. Discord message ids and async races don't mean anything in another database, so
+//! they're left out.
+
+use crate::models::bracket_races::{Outcome, PlayerResult};
+use crate::models::bracket_rounds::{serialized_policy, MissedDeadlinePolicy};
+use crate::models::brackets::{BracketState, BracketType};
+use crate::models::qualifer_submission::ReviewState;
+use crate::models::qualifier_rules::QualifierAggregation;
+use crate::models::scoring::Tiebreaker;
+use crate::models::season::{Season, SeasonState};
+use crate::schema::{
+    bracket_byes, bracket_race_entrants, bracket_race_games, bracket_race_infos, bracket_races,
+    bracket_rounds, brackets, commentator_signups, player_bracket_entry, players,
+    qualifier_submissions, seasons,
+};
+use crate::BracketRaceState;
+use diesel::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use thiserror::Error;
+
+/// bump this whenever the archive format changes
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Unsupported archive version {0} (this is version {ARCHIVE_VERSION})")]
+    UnsupportedVersion(u32),
+    #[error("Season {0} already exists")]
+    OrdinalTaken(i32),
+    #[error("seasons.csv should have exactly one row, but it has {0}")]
+    SeasonCount(usize),
+    #[error("{table} row {id} refers to {target} row {target_id}, which isn't in the archive")]
+    MissingReference {
+        table: &'static str,
+        id: i32,
+        target: &'static str,
+        target_id: i32,
+    },
+    #[error("Invalid {table} row {id}: {reason}")]
+    InvalidRow {
+        table: &'static str,
+        id: i32,
+        reason: String,
+    },
+    #[error("Database error: {0}")]
+    DBError(#[from] diesel::result::Error),
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeasonRow {
+    pub id: i32,
+    pub started: i64,
+    pub finished: Option<i64>,
+    pub format: String,
+    pub ordinal: i32,
+    pub state: String,
+    pub rtgg_category_name: String,
+    pub rtgg_goal_name: String,
+    pub qualifier_aggregation: String,
+    pub qualifier_max_attempts: Option<i32>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerRow {
+    pub id: i32,
+    pub name: String,
+    pub discord_id: String,
+    pub racetime_username: Option<String>,
+    pub twitch_user_login: Option<String>,
+    pub racetime_user_id: Option<String>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BracketRow {
+    pub id: i32,
+    pub name: String,
+    pub season_id: i32,
+    pub state: String,
+    pub bracket_type: String,
+    pub backfill_note: Option<String>,
+    /// dropped on import if that bracket isn't in the archive
+    pub seed_source_bracket_id: Option<i32>,
+    pub bye_points: i32,
+    pub points_per_win: i32,
+    pub points_per_draw: i32,
+    pub points_per_loss: i32,
+    pub forfeit_time_penalty: i32,
+    pub tiebreakers: String,
+    pub best_of: i32,
+    pub group_size: i32,
+    pub placement_points: String,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BracketEntryRow {
+    pub id: i32,
+    pub bracket_id: i32,
+    pub player_id: i32,
+    pub seed: Option<i32>,
+    pub dropped_at: Option<i64>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoundRow {
+    pub id: i32,
+    pub round_num: i32,
+    pub bracket_id: i32,
+    // archives from before round deadlines existed don't have these
+    #[serde(default)]
+    pub schedule_by: Option<i64>,
+    #[serde(default)]
+    pub play_by: Option<i64>,
+    #[serde(default = "default_missed_deadline_policy")]
+    pub missed_deadline_policy: String,
+    #[serde(default)]
+    pub schedule_reminders_sent: bool,
+    #[serde(default)]
+    pub schedule_by_escalated: bool,
+    #[serde(default)]
+    pub play_by_escalated: bool,
+}
+
+fn default_missed_deadline_policy() -> String {
+    serialized_policy(MissedDeadlinePolicy::Nothing)
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ByeRow {
+    pub id: i32,
+    pub bracket_id: i32,
+    pub round_id: i32,
+    pub player_id: i32,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RaceRow {
+    pub id: i32,
+    pub bracket_id: i32,
+    pub round_id: i32,
+    pub player_1_id: i32,
+    pub player_2_id: i32,
+    pub state: String,
+    pub player_1_result: Option<String>,
+    pub player_2_result: Option<String>,
+    pub outcome: Option<String>,
+    pub best_of: i32,
+    pub placement_race: bool,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameRow {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub game_num: i32,
+    pub player_1_result: String,
+    pub player_2_result: String,
+    pub outcome: String,
+    pub racetime_gg_url: Option<String>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EntrantRow {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub player_id: i32,
+    pub result: Option<String>,
+    pub placement: Option<i32>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RaceInfoRow {
+    pub id: i32,
+    pub bracket_race_id: i32,
+    pub scheduled_for: Option<i64>,
+    pub racetime_gg_url: Option<String>,
+    pub restream_channel: Option<String>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommentatorSignupRow {
+    pub id: i32,
+    pub bracket_race_info_id: i32,
+    pub discord_id: String,
+}
+
+#[derive(Queryable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QualifierRow {
+    pub id: i32,
+    pub player_id: i32,
+    pub season_id: i32,
+    pub reported_time: i32,
+    pub vod_link: String,
+    pub review_state: String,
+    pub reviewer_discord_id: Option<String>,
+    pub review_reason: Option<String>,
+    pub reviewed_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeasonArchive {
+    pub version: u32,
+    pub season: SeasonRow,
+    /// everyone who shows up anywhere else in the archive
+    pub players: Vec<PlayerRow>,
+    pub brackets: Vec<BracketRow>,
+    pub bracket_entries: Vec<BracketEntryRow>,
+    pub rounds: Vec<RoundRow>,
+    pub byes: Vec<ByeRow>,
+    pub races: Vec<RaceRow>,
+    /// games of best-of-N races
+    pub games: Vec<GameRow>,
+    /// entrants of placement races
+    pub entrants: Vec<EntrantRow>,
+    pub race_infos: Vec<RaceInfoRow>,
+    pub commentator_signups: Vec<CommentatorSignupRow>,
+    pub qualifiers: Vec<QualifierRow>,
+}
+
+/// everything in the season with this ordinal
+pub fn export_season(
+    ordinal: i32,
+    conn: &mut SqliteConnection,
+) -> Result<SeasonArchive, ArchiveError> {
+    let season: SeasonRow = seasons::table
+        .filter(seasons::ordinal.eq(ordinal))
+        .first(conn)?;
+    let brackets: Vec<BracketRow> = brackets::table
+        .filter(brackets::season_id.eq(season.id))
+        .order(brackets::id.asc())
+        .load(conn)?;
+    let bracket_ids = brackets.iter().map(|b| b.id).collect::<Vec<_>>();
+    let bracket_entries: Vec<BracketEntryRow> = player_bracket_entry::table
+        .filter(player_bracket_entry::bracket_id.eq_any(&bracket_ids))
+        .order(player_bracket_entry::id.asc())
+        .load(conn)?;
+    let rounds: Vec<RoundRow> = bracket_rounds::table
+        .filter(bracket_rounds::bracket_id.eq_any(&bracket_ids))
+        .order(bracket_rounds::id.asc())
+        .load(conn)?;
+    let byes: Vec<ByeRow> = bracket_byes::table
+        .filter(bracket_byes::bracket_id.eq_any(&bracket_ids))
+        .order(bracket_byes::id.asc())
+        .load(conn)?;
+    let races: Vec<RaceRow> = bracket_races::table
+        .filter(bracket_races::bracket_id.eq_any(&bracket_ids))
+        .order(bracket_races::id.asc())
+        .select((
+            bracket_races::id,
+            bracket_races::bracket_id,
+            bracket_races::round_id,
+            bracket_races::player_1_id,
+            bracket_races::player_2_id,
+            bracket_races::state,
+            bracket_races::player_1_result,
+            bracket_races::player_2_result,
+            bracket_races::outcome,
+            bracket_races::best_of,
+            bracket_races::placement_race,
+        ))
+        .load(conn)?;
+    let race_ids = races.iter().map(|r| r.id).collect::<Vec<_>>();
+    let games: Vec<GameRow> = bracket_race_games::table
+        .filter(bracket_race_games::bracket_race_id.eq_any(&race_ids))
+        .order(bracket_race_games::id.asc())
+        .load(conn)?;
+    let entrants: Vec<EntrantRow> = bracket_race_entrants::table
+        .filter(bracket_race_entrants::bracket_race_id.eq_any(&race_ids))
+        .order(bracket_race_entrants::id.asc())
+        .load(conn)?;
+    let race_infos: Vec<RaceInfoRow> = bracket_race_infos::table
+        .filter(bracket_race_infos::bracket_race_id.eq_any(&race_ids))
+        .order(bracket_race_infos::id.asc())
+        .select((
+            bracket_race_infos::id,
+            bracket_race_infos::bracket_race_id,
+            bracket_race_infos::scheduled_for,
+            bracket_race_infos::racetime_gg_url,
+            bracket_race_infos::restream_channel,
+        ))
+        .load(conn)?;
+    let commentator_signups: Vec<CommentatorSignupRow> = commentator_signups::table
+        .filter(
+            commentator_signups::bracket_race_info_id
+                .eq_any(race_infos.iter().map(|i| i.id).collect::<Vec<_>>()),
+        )
+        .order(commentator_signups::id.asc())
+        .load(conn)?;
+    let qualifiers: Vec<QualifierRow> = qualifier_submissions::table
+        .filter(qualifier_submissions::season_id.eq(season.id))
+        .order(qualifier_submissions::id.asc())
+        .load(conn)?;
+
+    let player_ids = bracket_entries
+        .iter()
+        .map(|e| e.player_id)
+        .chain(byes.iter().map(|b| b.player_id))
+        .chain(races.iter().flat_map(|r| [r.player_1_id, r.player_2_id]))
+        .chain(entrants.iter().map(|e| e.player_id))
+        .chain(qualifiers.iter().map(|q| q.player_id))
+        .collect::<BTreeSet<_>>();
+    let players: Vec<PlayerRow> = players::table
+        .filter(players::id.eq_any(player_ids.into_iter().collect::<Vec<_>>()))
+        .order(players::id.asc())
+        .load(conn)?;
+
+    Ok(SeasonArchive {
+        version: ARCHIVE_VERSION,
+        season,
+        players,
+        brackets,
+        bracket_entries,
+        rounds,
+        byes,
+        races,
+        games,
+        entrants,
+        race_infos,
+        commentator_signups,
+        qualifiers,
+    })
+}
+
+fn write_csv<T: Serialize>(dir: &Path, table: &str, rows: &[T]) -> Result<(), ArchiveError> {
+    let mut writer = csv::Writer::from_path(dir.join(format!("{table}.csv")))?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_csv<T: DeserializeOwned>(dir: &Path, table: &str) -> Result<Vec<T>, ArchiveError> {
+    let mut reader = csv::Reader::from_path(dir.join(format!("{table}.csv")))?;
+    let rows = reader.deserialize().collect::<Result<Vec<T>, _>>()?;
+    Ok(rows)
+}
+
+/// checks that `value` is a serialized `T`
+fn check<T: DeserializeOwned>(
+    table: &'static str,
+    id: i32,
+    field: &str,
+    value: &str,
+) -> Result<(), ArchiveError> {
+    serde_json::from_str::<T>(value)
+        .map(|_| ())
+        .map_err(|e| ArchiveError::InvalidRow {
+            table,
+            id,
+            reason: format!("bad {field} {value:?}: {e}"),
+        })
+}
+
+/// new ids of one table's rows, by their ids in the archive
+struct IdMap {
+    table: &'static str,
+    ids: HashMap<i32, i32>,
+}
+
+impl IdMap {
+    fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            ids: Default::default(),
+        }
+    }
+
+    /// the new id of the row with `id`, which the `from` row with id `from_id` refers to
+    fn get(&self, id: i32, from: &'static str, from_id: i32) -> Result<i32, ArchiveError> {
+        self.ids
+            .get(&id)
+            .copied()
+            .ok_or(ArchiveError::MissingReference {
+                table: from,
+                id: from_id,
+                target: self.table,
+                target_id: id,
+            })
+    }
+}
+
+impl SeasonArchive {
+    /// writes each table to `<table>.csv` in `dir`
+    pub fn write_csvs(&self, dir: &Path) -> Result<(), ArchiveError> {
+        std::fs::create_dir_all(dir)?;
+        write_csv(dir, "seasons", std::slice::from_ref(&self.season))?;
+        write_csv(dir, "players", &self.players)?;
+        write_csv(dir, "brackets", &self.brackets)?;
+        write_csv(dir, "player_bracket_entry", &self.bracket_entries)?;
+        write_csv(dir, "bracket_rounds", &self.rounds)?;
+        write_csv(dir, "bracket_byes", &self.byes)?;
+        write_csv(dir, "bracket_races", &self.races)?;
+        write_csv(dir, "bracket_race_games", &self.games)?;
+        write_csv(dir, "bracket_race_entrants", &self.entrants)?;
+        write_csv(dir, "bracket_race_infos", &self.race_infos)?;
+        write_csv(dir, "commentator_signups", &self.commentator_signups)?;
+        write_csv(dir, "qualifier_submissions", &self.qualifiers)?;
+        Ok(())
+    }
+
+    /// reads an archive back in from the CSVs `write_csvs` wrote to `dir`. CSVs don't say what
+    /// version they are, so they're read as this one. CSV can't tell an empty string from a missing
+    /// one, so empty optional fields come back as `None`.
+    pub fn read_csvs(dir: &Path) -> Result<Self, ArchiveError> {
+        let mut seasons: Vec<SeasonRow> = read_csv(dir, "seasons")?;
+        if seasons.len() != 1 {
+            return Err(ArchiveError::SeasonCount(seasons.len()));
+        }
+        Ok(Self {
+            version: ARCHIVE_VERSION,
+            season: seasons.remove(0),
+            players: read_csv(dir, "players")?,
+            brackets: read_csv(dir, "brackets")?,
+            bracket_entries: read_csv(dir, "player_bracket_entry")?,
+            rounds: read_csv(dir, "bracket_rounds")?,
+            byes: read_csv(dir, "bracket_byes")?,
+            races: read_csv(dir, "bracket_races")?,
+            games: read_csv(dir, "bracket_race_games")?,
+            entrants: read_csv(dir, "bracket_race_entrants")?,
+            race_infos: read_csv(dir, "bracket_race_infos")?,
+            commentator_signups: read_csv(dir, "commentator_signups")?,
+            qualifiers: read_csv(dir, "qualifier_submissions")?,
+        })
+    }
+
+    /// checks that everything stored serialized can be deserialized. References between rows are
+    /// checked while importing.
+    pub fn validate(&self) -> Result<(), ArchiveError> {
+        if self.version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(self.version));
+        }
+        let s = &self.season;
+        check::<SeasonState>("seasons", s.id, "state", &s.state)?;
+        check::<QualifierAggregation>(
+            "seasons",
+            s.id,
+            "qualifier_aggregation",
+            &s.qualifier_aggregation,
+        )?;
+        for b in &self.brackets {
+            check::<BracketState>("brackets", b.id, "state", &b.state)?;
+            check::<BracketType>("brackets", b.id, "bracket_type", &b.bracket_type)?;
+            check::<Vec<Tiebreaker>>("brackets", b.id, "tiebreakers", &b.tiebreakers)?;
+            check::<Vec<i32>>("brackets", b.id, "placement_points", &b.placement_points)?;
+        }
+        for r in &self.rounds {
+            check::<MissedDeadlinePolicy>(
+                "bracket_rounds",
+                r.id,
+                "missed_deadline_policy",
+                &r.missed_deadline_policy,
+            )?;
+        }
+        for r in &self.races {
+            check::<BracketRaceState>("bracket_races", r.id, "state", &r.state)?;
+            for (field, result) in [
+                ("player_1_result", &r.player_1_result),
+                ("player_2_result", &r.player_2_result),
+            ] {
+                if let Some(result) = result {
+                    check::<PlayerResult>("bracket_races", r.id, field, result)?;
+                }
+            }
+            if let Some(outcome) = &r.outcome {
+                check::<Outcome>("bracket_races", r.id, "outcome", outcome)?;
+            }
+        }
+        for g in &self.games {
+            let table = "bracket_race_games";
+            check::<PlayerResult>(table, g.id, "player_1_result", &g.player_1_result)?;
+            check::<PlayerResult>(table, g.id, "player_2_result", &g.player_2_result)?;
+            check::<Outcome>(table, g.id, "outcome", &g.outcome)?;
+        }
+        for e in &self.entrants {
+            if let Some(result) = &e.result {
+                check::<PlayerResult>("bracket_race_entrants", e.id, "result", result)?;
+            }
+        }
+        for q in &self.qualifiers {
+            check::<ReviewState>(
+                "qualifier_submissions",
+                q.id,
+                "review_state",
+                &q.review_state,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// validates this archive and adds it to the database as a new season, all or nothing.
+    /// The season keeps its ordinal unless it's given a new one.
+    pub fn import(
+        &self,
+        ordinal: Option<i32>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Season, ArchiveError> {
+        self.validate()?;
+        conn.transaction(|conn| self.insert(ordinal.unwrap_or(self.season.ordinal), conn))
+    }
+
+    fn insert(&self, ordinal: i32, conn: &mut SqliteConnection) -> Result<Season, ArchiveError> {
+        let taken: i64 = seasons::table
+            .filter(seasons::ordinal.eq(ordinal))
+            .count()
+            .get_result(conn)?;
+        if taken > 0 {
+            return Err(ArchiveError::OrdinalTaken(ordinal));
+        }
+        let s = &self.season;
+        let season_id = diesel::insert_into(seasons::table)
+            .values((
+                seasons::started.eq(s.started),
+                seasons::finished.eq(s.finished),
+                seasons::format.eq(&s.format),
+                seasons::ordinal.eq(ordinal),
+                seasons::state.eq(&s.state),
+                seasons::rtgg_category_name.eq(&s.rtgg_category_name),
+                seasons::rtgg_goal_name.eq(&s.rtgg_goal_name),
+                seasons::qualifier_aggregation.eq(&s.qualifier_aggregation),
+                seasons::qualifier_max_attempts.eq(s.qualifier_max_attempts),
+            ))
+            .returning(seasons::id)
+            .get_result::<i32>(conn)?;
+        let mut season_ids = IdMap::new("seasons");
+        season_ids.ids.insert(s.id, season_id);
+
+        let mut player_ids = IdMap::new("players");
+        for p in &self.players {
+            let existing = players::table
+                .filter(players::discord_id.eq(&p.discord_id))
+                .select(players::id)
+                .first::<i32>(conn)
+                .optional()?;
+            let id = match existing {
+                Some(id) => id,
+                None => diesel::insert_into(players::table)
+                    .values((
+                        players::name.eq(&p.name),
+                        players::discord_id.eq(&p.discord_id),
+                        players::racetime_username.eq(&p.racetime_username),
+                        players::twitch_user_login.eq(&p.twitch_user_login),
+                        players::racetime_user_id.eq(&p.racetime_user_id),
+                    ))
+                    .returning(players::id)
+                    .get_result(conn)?,
+            };
+            player_ids.ids.insert(p.id, id);
+        }
+
+        let mut bracket_ids = IdMap::new("brackets");
+        for b in &self.brackets {
+            let id = diesel::insert_into(brackets::table)
+                .values((
+                    brackets::name.eq(&b.name),
+                    brackets::season_id.eq(season_ids.get(b.season_id, "brackets", b.id)?),
+                    brackets::state.eq(&b.state),
+                    brackets::bracket_type.eq(&b.bracket_type),
+                    brackets::backfill_note.eq(&b.backfill_note),
+                    // brackets are seeded from brackets that came before them
+                    brackets::seed_source_bracket_id.eq(b
+                        .seed_source_bracket_id
+                        .and_then(|s| bracket_ids.ids.get(&s).copied())),
+                    brackets::bye_points.eq(b.bye_points),
+                    brackets::points_per_win.eq(b.points_per_win),
+                    brackets::points_per_draw.eq(b.points_per_draw),
+                    brackets::points_per_loss.eq(b.points_per_loss),
+                    brackets::forfeit_time_penalty.eq(b.forfeit_time_penalty),
+                    brackets::tiebreakers.eq(&b.tiebreakers),
+                    brackets::best_of.eq(b.best_of),
+                    brackets::group_size.eq(b.group_size),
+                    brackets::placement_points.eq(&b.placement_points),
+                ))
+                .returning(brackets::id)
+                .get_result(conn)?;
+            bracket_ids.ids.insert(b.id, id);
+        }
+
+        for e in &self.bracket_entries {
+            let table = "player_bracket_entry";
+            diesel::insert_into(player_bracket_entry::table)
+                .values((
+                    player_bracket_entry::bracket_id.eq(bracket_ids.get(
+                        e.bracket_id,
+                        table,
+                        e.id,
+                    )?),
+                    player_bracket_entry::player_id.eq(player_ids.get(e.player_id, table, e.id)?),
+                    player_bracket_entry::seed.eq(e.seed),
+                    player_bracket_entry::dropped_at.eq(e.dropped_at),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut round_ids = IdMap::new("bracket_rounds");
+        for r in &self.rounds {
+            let id = diesel::insert_into(bracket_rounds::table)
+                .values((
+                    bracket_rounds::round_num.eq(r.round_num),
+                    bracket_rounds::bracket_id.eq(bracket_ids.get(
+                        r.bracket_id,
+                        "bracket_rounds",
+                        r.id,
+                    )?),
+                    bracket_rounds::schedule_by.eq(r.schedule_by),
+                    bracket_rounds::play_by.eq(r.play_by),
+                    bracket_rounds::missed_deadline_policy.eq(&r.missed_deadline_policy),
+                    bracket_rounds::schedule_reminders_sent.eq(r.schedule_reminders_sent),
+                    bracket_rounds::schedule_by_escalated.eq(r.schedule_by_escalated),
+                    bracket_rounds::play_by_escalated.eq(r.play_by_escalated),
+                ))
+                .returning(bracket_rounds::id)
+                .get_result(conn)?;
+            round_ids.ids.insert(r.id, id);
+        }
+
+        for b in &self.byes {
+            let table = "bracket_byes";
+            diesel::insert_into(bracket_byes::table)
+                .values((
+                    bracket_byes::bracket_id.eq(bracket_ids.get(b.bracket_id, table, b.id)?),
+                    bracket_byes::round_id.eq(round_ids.get(b.round_id, table, b.id)?),
+                    bracket_byes::player_id.eq(player_ids.get(b.player_id, table, b.id)?),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut race_ids = IdMap::new("bracket_races");
+        for r in &self.races {
+            let table = "bracket_races";
+            let id = diesel::insert_into(bracket_races::table)
+                .values((
+                    bracket_races::bracket_id.eq(bracket_ids.get(r.bracket_id, table, r.id)?),
+                    bracket_races::round_id.eq(round_ids.get(r.round_id, table, r.id)?),
+                    bracket_races::player_1_id.eq(player_ids.get(r.player_1_id, table, r.id)?),
+                    bracket_races::player_2_id.eq(player_ids.get(r.player_2_id, table, r.id)?),
+                    bracket_races::state.eq(&r.state),
+                    bracket_races::player_1_result.eq(&r.player_1_result),
+                    bracket_races::player_2_result.eq(&r.player_2_result),
+                    bracket_races::outcome.eq(&r.outcome),
+                    bracket_races::best_of.eq(r.best_of),
+                    bracket_races::placement_race.eq(r.placement_race),
+                ))
+                .returning(bracket_races::id)
+                .get_result(conn)?;
+            race_ids.ids.insert(r.id, id);
+        }
+
+        for g in &self.games {
+            diesel::insert_into(bracket_race_games::table)
+                .values((
+                    bracket_race_games::bracket_race_id.eq(race_ids.get(
+                        g.bracket_race_id,
+                        "bracket_race_games",
+                        g.id,
+                    )?),
+                    bracket_race_games::game_num.eq(g.game_num),
+                    bracket_race_games::player_1_result.eq(&g.player_1_result),
+                    bracket_race_games::player_2_result.eq(&g.player_2_result),
+                    bracket_race_games::outcome.eq(&g.outcome),
+                    bracket_race_games::racetime_gg_url.eq(&g.racetime_gg_url),
+                ))
+                .execute(conn)?;
+        }
+
+        for e in &self.entrants {
+            let table = "bracket_race_entrants";
+            diesel::insert_into(bracket_race_entrants::table)
+                .values((
+                    bracket_race_entrants::bracket_race_id.eq(race_ids.get(
+                        e.bracket_race_id,
+                        table,
+                        e.id,
+                    )?),
+                    bracket_race_entrants::player_id.eq(player_ids.get(
+                        e.player_id,
+                        table,
+                        e.id,
+                    )?),
+                    bracket_race_entrants::result.eq(&e.result),
+                    bracket_race_entrants::placement.eq(e.placement),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut info_ids = IdMap::new("bracket_race_infos");
+        for i in &self.race_infos {
+            let id = diesel::insert_into(bracket_race_infos::table)
+                .values((
+                    bracket_race_infos::bracket_race_id.eq(race_ids.get(
+                        i.bracket_race_id,
+                        "bracket_race_infos",
+                        i.id,
+                    )?),
+                    bracket_race_infos::scheduled_for.eq(i.scheduled_for),
+                    bracket_race_infos::racetime_gg_url.eq(&i.racetime_gg_url),
+                    bracket_race_infos::restream_channel.eq(&i.restream_channel),
+                ))
+                .returning(bracket_race_infos::id)
+                .get_result(conn)?;
+            info_ids.ids.insert(i.id, id);
+        }
+
+        for c in &self.commentator_signups {
+            diesel::insert_into(commentator_signups::table)
+                .values((
+                    commentator_signups::bracket_race_info_id.eq(info_ids.get(
+                        c.bracket_race_info_id,
+                        "commentator_signups",
+                        c.id,
+                    )?),
+                    commentator_signups::discord_id.eq(&c.discord_id),
+                ))
+                .execute(conn)?;
+        }
+
+        for q in &self.qualifiers {
+            let table = "qualifier_submissions";
+            diesel::insert_into(qualifier_submissions::table)
+                .values((
+                    qualifier_submissions::player_id.eq(player_ids.get(
+                        q.player_id,
+                        table,
+                        q.id,
+                    )?),
+                    qualifier_submissions::season_id.eq(season_ids.get(
+                        q.season_id,
+                        table,
+                        q.id,
+                    )?),
+                    qualifier_submissions::reported_time.eq(q.reported_time),
+                    qualifier_submissions::vod_link.eq(&q.vod_link),
+                    qualifier_submissions::review_state.eq(&q.review_state),
+                    qualifier_submissions::reviewer_discord_id.eq(&q.reviewer_discord_id),
+                    qualifier_submissions::review_reason.eq(&q.review_reason),
+                    qualifier_submissions::reviewed_at.eq(q.reviewed_at),
+                ))
+                .execute(conn)?;
+        }
+
+        Ok(Season::get_by_id(season_id, conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::{insert_bulk, NewBracketRace, PlayerResult};
+    use crate::models::bracket_rounds::NewBracketRound;
+    use crate::models::brackets::BracketType;
+    use crate::models::season::Season;
+    use crate::models::season_archive::{export_season, ArchiveError, SeasonArchive};
+    use crate::test_utils::{bracket_with_players, setup_db};
+
+    #[test]
+    fn test_export_and_import() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (bracket, players) = bracket_with_players(BracketType::Swiss, 2, &mut db)?;
+        let season = Season::get_by_id(bracket.season_id, &mut db)?;
+        let (p1, p2) = (&players[0], &players[1]);
+        let round = NewBracketRound::new(&bracket, 1).save(&mut db)?;
+        insert_bulk(
+            &vec![NewBracketRace::new(&bracket, &round, p1, p2)],
+            &mut db,
+        )?;
+        let mut race = bracket.bracket_races(&mut db)?.remove(0);
+        race.add_results(
+            Some(&PlayerResult::Finish(5000)),
+            Some(&PlayerResult::Forfeit),
+            false,
+        )?;
+        race.update(&mut db)?;
+
+        let archive = export_season(season.ordinal, &mut db)?;
+        assert_eq!(2, archive.players.len());
+        assert_eq!(1, archive.races.len());
+        // round trips through json
+        let archive: SeasonArchive = serde_json::from_str(&serde_json::to_string(&archive)?)?;
+        // and through csv
+        let dir = std::env::temp_dir().join(format!("season_archive_{}", std::process::id()));
+        archive.write_csvs(&dir)?;
+        let from_csvs = SeasonArchive::read_csvs(&dir);
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(archive, from_csvs?);
+        assert!(matches!(
+            export_season(season.ordinal, &mut db)?.import(None, &mut db),
+            Err(ArchiveError::OrdinalTaken(_))
+        ));
+
+        let imported = archive.import(Some(season.ordinal + 1), &mut db)?;
+        let brackets = imported.brackets(&mut db)?;
+        assert_eq!(1, brackets.len());
+        assert_ne!(bracket.id, brackets[0].id);
+        let races = brackets[0].bracket_races(&mut db)?;
+        assert_eq!(1, races.len());
+        assert_eq!(race.outcome()?, races[0].outcome()?);
+        // players were matched up rather than duplicated
+        assert_eq!((p1.id, p2.id), (races[0].player_1_id, races[0].player_2_id));
+
+        // a broken archive doesn't leave anything behind
+        let mut broken = export_season(season.ordinal, &mut db)?;
+        broken.rounds.clear();
+        assert!(matches!(
+            broken.import(Some(season.ordinal + 2), &mut db),
+            Err(ArchiveError::MissingReference { .. })
+        ));
+        assert!(Season::get_by_ordinal(season.ordinal + 2, &mut db).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+//! Aggregate statistics over every bracket race from every season, for the stats page and API.
+//!
+//! Everything is recomputed from the existing tables by [Statistics::compute], which reads all of
+//! them; callers are expected to cache the result.
+
+use crate::models::bracket_race_entrants::BracketRaceEntrant;
+use crate::models::bracket_race_games::BracketRaceGame;
+use crate::models::bracket_race_infos::{BracketRaceInfo, CommentatorSignup};
+use crate::models::bracket_races::{BracketRace, Outcome, PlayerResult};
+use crate::models::bracket_rounds::BracketRound;
+use crate::models::brackets::Bracket;
+use crate::models::player::Player;
+use crate::models::season::Season;
+use crate::schema::{
+    bracket_race_games, bracket_race_infos, bracket_races, bracket_rounds, brackets,
+    commentator_signups, players, seasons,
+};
+use diesel::prelude::*;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// how many of the fastest times to keep for each season
+const FASTEST_TIMES_PER_SEASON: usize = 10;
+/// how many players to keep in the restream & commentator leaderboards
+const LEADERBOARD_SIZE: usize = 20;
+
+/// one player's result in one race (or one game of a series)
+#[derive(Debug, Clone)]
+pub struct RaceResult {
+    pub season_ordinal: i32,
+    pub bracket_name: String,
+    pub round_num: i32,
+    pub player_id: i32,
+    pub result: PlayerResult,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct FastestTime {
+    pub player_id: i32,
+    pub player_name: String,
+    /// in seconds
+    pub time: u32,
+    pub bracket_name: String,
+    pub round_num: i32,
+}
+
+/// the average finish time of everyone who finished a round (in any bracket) of a season
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct RoundAverage {
+    pub round_num: i32,
+    pub finishes: i32,
+    /// in seconds
+    pub average_time: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct SeasonStatistics {
+    pub season_ordinal: i32,
+    /// fastest first
+    pub fastest_times: Vec<FastestTime>,
+    /// how many results (finishes and forfeits) were recorded this season
+    pub results: i32,
+    pub forfeits: i32,
+    /// None if there are no results
+    pub forfeit_rate: Option<f64>,
+    /// by round number
+    pub round_averages: Vec<RoundAverage>,
+}
+
+/// all-time record in head-to-head races. A best-of-N series counts once, and placement races
+/// don't count at all.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct PlayerRecord {
+    pub player_id: i32,
+    pub player_name: String,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct RestreamCount {
+    pub player_id: i32,
+    pub player_name: String,
+    pub restreams: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct CommentatorCount {
+    pub discord_id: String,
+    /// their player name, if they've ever been a player
+    pub name: Option<String>,
+    pub signups: i32,
+    /// signups for races that ended up being restreamed
+    pub restreamed: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct Statistics {
+    /// oldest first
+    pub seasons: Vec<SeasonStatistics>,
+    pub all_time_results: i32,
+    pub all_time_forfeits: i32,
+    pub all_time_forfeit_rate: Option<f64>,
+    /// most wins first
+    pub records: Vec<PlayerRecord>,
+    /// most restreams first
+    pub most_restreamed: Vec<RestreamCount>,
+    /// most signups first
+    pub commentators: Vec<CommentatorCount>,
+}
+
+fn rate(count: i32, total: i32) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(count as f64 / total as f64)
+    }
+}
+
+fn player_name(names: &HashMap<i32, String>, id: i32) -> String {
+    names
+        .get(&id)
+        .cloned()
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// per-season stats from these results, oldest season first
+pub fn season_statistics(
+    results: &[RaceResult],
+    names: &HashMap<i32, String>,
+) -> Vec<SeasonStatistics> {
+    results
+        .iter()
+        .into_group_map_by(|r| r.season_ordinal)
+        .into_iter()
+        .sorted_by_key(|(ordinal, _)| *ordinal)
+        .map(|(season_ordinal, results)| {
+            let mut finishes = results
+                .iter()
+                .filter_map(|r| match r.result {
+                    PlayerResult::Finish(t) => Some((t, *r)),
+                    PlayerResult::Forfeit => None,
+                })
+                .collect::<Vec<_>>();
+            let forfeits = (results.len() - finishes.len()) as i32;
+            let round_averages = finishes
+                .iter()
+                .into_group_map_by(|(_, r)| r.round_num)
+                .into_iter()
+                .sorted_by_key(|(round_num, _)| *round_num)
+                .map(|(round_num, times)| RoundAverage {
+                    round_num,
+                    finishes: times.len() as i32,
+                    average_time: times.iter().map(|(t, _)| *t as f64).sum::<f64>()
+                        / times.len() as f64,
+                })
+                .collect();
+            finishes.sort_by_key(|(t, _)| *t);
+            let fastest_times = finishes
+                .iter()
+                .take(FASTEST_TIMES_PER_SEASON)
+                .map(|(time, r)| FastestTime {
+                    player_id: r.player_id,
+                    player_name: player_name(names, r.player_id),
+                    time: *time,
+                    bracket_name: r.bracket_name.clone(),
+                    round_num: r.round_num,
+                })
+                .collect();
+            SeasonStatistics {
+                season_ordinal,
+                fastest_times,
+                results: results.len() as i32,
+                forfeits,
+                forfeit_rate: rate(forfeits, results.len() as i32),
+                round_averages,
+            }
+        })
+        .collect()
+}
+
+/// everyone's all-time record from these races, most wins first (then fewest losses)
+pub fn player_records(races: &[BracketRace], names: &HashMap<i32, String>) -> Vec<PlayerRecord> {
+    let mut records: HashMap<i32, PlayerRecord> = Default::default();
+    for race in races.iter().filter(|r| !r.is_placement_race()) {
+        let outcome = match race.outcome() {
+            Ok(Some(o)) => o,
+            _ => {
+                continue;
+            }
+        };
+        for (player_id, won) in [
+            (race.player_1_id, Outcome::P1Win),
+            (race.player_2_id, Outcome::P2Win),
+        ] {
+            let record = records.entry(player_id).or_insert_with(|| PlayerRecord {
+                player_id,
+                player_name: player_name(names, player_id),
+                wins: 0,
+                losses: 0,
+                draws: 0,
+            });
+            if outcome == Outcome::Tie {
+                record.draws += 1;
+            } else if outcome == won {
+                record.wins += 1;
+            } else {
+                record.losses += 1;
+            }
+        }
+    }
+    records
+        .into_values()
+        .sorted_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(a.losses.cmp(&b.losses))
+                .then(a.player_name.cmp(&b.player_name))
+        })
+        .collect()
+}
+
+impl Statistics {
+    pub fn compute(conn: &mut SqliteConnection) -> Result<Self, diesel::result::Error> {
+        let all_players = players::table.load::<Player>(conn)?;
+        let names: HashMap<i32, String> =
+            all_players.iter().map(|p| (p.id, p.name.clone())).collect();
+        let discord_names: HashMap<String, String> = all_players
+            .into_iter()
+            .map(|p| (p.discord_id, p.name))
+            .collect();
+        let races: Vec<(BracketRace, (BracketRound, (Bracket, Season)))> = bracket_races::table
+            .inner_join(
+                bracket_rounds::table.inner_join(brackets::table.inner_join(seasons::table)),
+            )
+            .load(conn)?;
+        let mut games = bracket_race_games::table
+            .load::<BracketRaceGame>(conn)?
+            .into_iter()
+            .into_group_map_by(|g| g.bracket_race_id);
+        let placement_race_ids = races
+            .iter()
+            .filter(|(r, _)| r.is_placement_race())
+            .map(|(r, _)| r.id)
+            .collect::<Vec<_>>();
+        let mut entrants = BracketRaceEntrant::for_races(&placement_race_ids, conn)?;
+        let restreamed_race_ids = bracket_race_infos::table
+            .load::<BracketRaceInfo>(conn)?
+            .into_iter()
+            .filter(|i| i.restream_channel.is_some())
+            .map(|i| i.bracket_race_id)
+            .collect::<HashSet<_>>();
+
+        let mut results = vec![];
+        let mut restreams: HashMap<i32, i32> = Default::default();
+        for (race, (round, (bracket, season))) in &races {
+            let entrants = entrants.remove(&race.id).unwrap_or_default();
+            let games = games.remove(&race.id).unwrap_or_default();
+            let mut add = |player_id, result: Option<PlayerResult>| {
+                if let Some(result) = result {
+                    results.push(RaceResult {
+                        season_ordinal: season.ordinal,
+                        bracket_name: bracket.name.clone(),
+                        round_num: round.round_num,
+                        player_id,
+                        result,
+                    });
+                }
+            };
+            let race_players = if race.is_placement_race() {
+                for e in &entrants {
+                    add(e.player_id, e.result().and_then(Result::ok));
+                }
+                entrants.iter().map(|e| e.player_id).collect()
+            } else {
+                if race.is_series() {
+                    for g in &games {
+                        add(race.player_1_id, g.player_1_result().ok());
+                        add(race.player_2_id, g.player_2_result().ok());
+                    }
+                } else {
+                    add(
+                        race.player_1_id,
+                        race.player_1_result().and_then(Result::ok),
+                    );
+                    add(
+                        race.player_2_id,
+                        race.player_2_result().and_then(Result::ok),
+                    );
+                }
+                vec![race.player_1_id, race.player_2_id]
+            };
+            if restreamed_race_ids.contains(&race.id) {
+                for player_id in race_players {
+                    *restreams.entry(player_id).or_default() += 1;
+                }
+            }
+        }
+
+        let signups: Vec<(CommentatorSignup, BracketRaceInfo)> = commentator_signups::table
+            .inner_join(bracket_race_infos::table)
+            .load(conn)?;
+        let commentators = signups
+            .into_iter()
+            .into_group_map_by(|(s, _)| s.discord_id.clone())
+            .into_iter()
+            .map(|(discord_id, signups)| CommentatorCount {
+                name: discord_names.get(&discord_id).cloned(),
+                signups: signups.len() as i32,
+                restreamed: signups
+                    .iter()
+                    .filter(|(_, info)| info.restream_channel.is_some())
+                    .count() as i32,
+                discord_id,
+            })
+            .sorted_by(|a, b| {
+                b.signups
+                    .cmp(&a.signups)
+                    .then(b.restreamed.cmp(&a.restreamed))
+                    .then(a.discord_id.cmp(&b.discord_id))
+            })
+            .take(LEADERBOARD_SIZE)
+            .collect();
+        let most_restreamed = restreams
+            .into_iter()
+            .map(|(player_id, restreams)| RestreamCount {
+                player_id,
+                player_name: player_name(&names, player_id),
+                restreams,
+            })
+            .sorted_by(|a, b| {
+                b.restreams
+                    .cmp(&a.restreams)
+                    .then(a.player_name.cmp(&b.player_name))
+            })
+            .take(LEADERBOARD_SIZE)
+            .collect();
+
+        let all_time_forfeits = results
+            .iter()
+            .filter(|r| matches!(r.result, PlayerResult::Forfeit))
+            .count() as i32;
+        let races = races.into_iter().map(|(r, _)| r).collect::<Vec<_>>();
+        Ok(Self {
+            seasons: season_statistics(&results, &names),
+            all_time_results: results.len() as i32,
+            all_time_forfeits,
+            all_time_forfeit_rate: rate(all_time_forfeits, results.len() as i32),
+            records: player_records(&races, &names),
+            most_restreamed,
+            commentators,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::bracket_races::PlayerResult::{self, Finish, Forfeit};
+    use crate::models::stats::{season_statistics, FastestTime, RaceResult, RoundAverage};
+    use std::collections::HashMap;
+
+    fn result(season_ordinal: i32, round_num: i32, player_id: i32, r: PlayerResult) -> RaceResult {
+        RaceResult {
+            season_ordinal,
+            bracket_name: "bracket".to_string(),
+            round_num,
+            player_id,
+            result: r,
+        }
+    }
+
+    #[test]
+    fn test_season_statistics() {
+        let names = HashMap::from([(1, "one".to_string()), (2, "two".to_string())]);
+        let stats = season_statistics(
+            &[
+                result(2, 1, 1, Finish(5000)),
+                result(1, 1, 1, Finish(5200)),
+                result(1, 1, 2, Forfeit),
+                result(1, 2, 1, Finish(5100)),
+                result(1, 2, 2, Finish(4900)),
+            ],
+            &names,
+        );
+        assert_eq!(
+            vec![1, 2],
+            stats.iter().map(|s| s.season_ordinal).collect::<Vec<_>>()
+        );
+        let s1 = &stats[0];
+        assert_eq!(4, s1.results);
+        assert_eq!(1, s1.forfeits);
+        assert_eq!(Some(0.25), s1.forfeit_rate);
+        assert_eq!(
+            vec![
+                RoundAverage {
+                    round_num: 1,
+                    finishes: 1,
+                    average_time: 5200.0
+                },
+                RoundAverage {
+                    round_num: 2,
+                    finishes: 2,
+                    average_time: 5000.0
+                }
+            ],
+            s1.round_averages
+        );
+        assert_eq!(
+            FastestTime {
+                player_id: 2,
+                player_name: "two".to_string(),
+                time: 4900,
+                bracket_name: "bracket".to_string(),
+                round_num: 2,
+            },
+            s1.fastest_times[0]
+        );
+        assert_eq!(
+            vec![4900, 5100, 5200],
+            s1.fastest_times.iter().map(|f| f.time).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(0.0), stats[1].forfeit_rate);
+    }
+}
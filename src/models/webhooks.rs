@@ -0,0 +1,416 @@
+//! Outbound webhooks, so other tools don't have to poll the API to notice changes.
+//!
+//! A [WebhookSubscription] is a URL plus the kinds of [LeagueEvent] it wants. Events are queued
+//! with [fire_event] from the same places that post to discord, as one [WebhookDelivery] per
+//! interested subscription. The webhook worker sends them, signed with the subscription's secret,
+//! and retries failures with backoff. Deliveries are kept afterwards as a log.
+
+use crate::schema::{webhook_deliveries, webhook_subscriptions};
+use crate::utils::{epoch_timestamp, hex_string};
+use crate::{save_fn, update_fn, NMGLeagueBotError};
+use diesel::prelude::*;
+use enum_iterator::Sequence;
+use hmac::digest::InvalidLength;
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// the kinds of [LeagueEvent], for subscribing to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash, Sequence, JsonSchema)]
+pub enum LeagueEventKind {
+    RaceScheduled,
+    RaceRescheduled,
+    RaceFinished,
+    CommentatorsAssigned,
+    PairingsGenerated,
+    QualifierSubmitted,
+}
+
+/// Something that happened in the league that webhooks can be told about. These are sent as
+/// `{"event": <kind>, "data": {...}, "created": <timestamp>}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", content = "data")]
+pub enum LeagueEvent {
+    RaceScheduled {
+        race_id: i32,
+        bracket_id: i32,
+        scheduled_for: i64,
+    },
+    RaceRescheduled {
+        race_id: i32,
+        bracket_id: i32,
+        scheduled_for: i64,
+        previously_scheduled_for: i64,
+    },
+    /// sent once a race has all of its results (for series, once the series is decided)
+    RaceFinished {
+        race_id: i32,
+        bracket_id: i32,
+        racetime_gg_url: Option<String>,
+    },
+    CommentatorsAssigned {
+        race_id: i32,
+        commentator_discord_ids: Vec<String>,
+        restream_channel: Option<String>,
+    },
+    PairingsGenerated {
+        bracket_id: i32,
+        round_num: i32,
+    },
+    QualifierSubmitted {
+        qualifier_id: i32,
+        season_ordinal: i32,
+        player_id: i32,
+        time: i32,
+    },
+}
+
+impl LeagueEvent {
+    pub fn kind(&self) -> LeagueEventKind {
+        match self {
+            LeagueEvent::RaceScheduled { .. } => LeagueEventKind::RaceScheduled,
+            LeagueEvent::RaceRescheduled { .. } => LeagueEventKind::RaceRescheduled,
+            LeagueEvent::RaceFinished { .. } => LeagueEventKind::RaceFinished,
+            LeagueEvent::CommentatorsAssigned { .. } => LeagueEventKind::CommentatorsAssigned,
+            LeagueEvent::PairingsGenerated { .. } => LeagueEventKind::PairingsGenerated,
+            LeagueEvent::QualifierSubmitted { .. } => LeagueEventKind::QualifierSubmitted,
+        }
+    }
+
+    fn payload(&self, created: i64) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        value["created"] = created.into();
+        serde_json::to_string(&value)
+    }
+}
+
+fn serialized<T: Serialize>(t: &T) -> String {
+    // we only serialize unit variants and lists of them, which can't fail
+    serde_json::to_string(t).unwrap_or_default()
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone)]
+#[diesel(treat_none_as_null = true)]
+pub struct WebhookSubscription {
+    pub id: i32,
+    pub url: String,
+    #[serde(skip)]
+    secret: String,
+    /// serialized list of [LeagueEventKind]
+    #[serde(skip)]
+    events: String,
+    pub created: i64,
+    pub active: bool,
+}
+
+impl WebhookSubscription {
+    pub fn get_by_id(id: i32, conn: &mut SqliteConnection) -> Result<Self, diesel::result::Error> {
+        webhook_subscriptions::table.find(id).first(conn)
+    }
+
+    pub fn active(conn: &mut SqliteConnection) -> Result<Vec<Self>, diesel::result::Error> {
+        webhook_subscriptions::table
+            .filter(webhook_subscriptions::active.eq(true))
+            .order(webhook_subscriptions::id.asc())
+            .load(conn)
+    }
+
+    pub fn events(&self) -> Result<Vec<LeagueEventKind>, serde_json::Error> {
+        serde_json::from_str(&self.events)
+    }
+
+    /// the signature receivers should expect for `body`: hex HMAC-SHA256, keyed with our secret
+    pub fn sign(&self, body: &str) -> Result<String, InvalidLength> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())?;
+        mac.update(body.as_bytes());
+        Ok(hex_string(&mac.finalize().into_bytes()))
+    }
+
+    /// does *not* persist self. Pending deliveries are dropped by the worker.
+    pub fn deactivate(&mut self) {
+        self.active = false;
+    }
+
+    /// all of this subscription's deliveries, newest first
+    pub fn deliveries(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<WebhookDelivery>, diesel::result::Error> {
+        webhook_deliveries::table
+            .filter(webhook_deliveries::subscription_id.eq(self.id))
+            .order(webhook_deliveries::id.desc())
+            .load(conn)
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=webhook_subscriptions)]
+pub struct NewWebhookSubscription {
+    url: String,
+    secret: String,
+    events: String,
+    created: i64,
+}
+
+impl NewWebhookSubscription {
+    /// returns the new subscription and its secret, which the subscriber needs to check
+    /// signatures
+    pub fn new<S: Into<String>>(url: S, events: &[LeagueEventKind]) -> (Self, String) {
+        let secret = uuid::Uuid::new_v4().simple().to_string();
+        (
+            Self {
+                url: url.into(),
+                secret: secret.clone(),
+                events: serialized(&events),
+                created: epoch_timestamp() as i64,
+            },
+            secret,
+        )
+    }
+
+    save_fn!(webhook_subscriptions::table, WebhookSubscription);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Sequence, JsonSchema)]
+pub enum DeliveryState {
+    /// waiting for its first attempt, or a retry
+    Pending,
+    Delivered,
+    /// gave up after [MAX_DELIVERY_ATTEMPTS], or the subscription was deactivated
+    Failed,
+}
+
+/// failed deliveries are retried until they've been attempted this many times
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+
+/// seconds to wait before retrying a delivery that has failed `attempts` times: 30 seconds,
+/// doubling each time (so the last retry is about half an hour after the one before it)
+fn retry_delay(attempts: i32) -> i64 {
+    30 * 2_i64.pow(attempts.saturating_sub(1).clamp(0, 16) as u32)
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Debug, Serialize, Clone)]
+#[diesel(treat_none_as_null = true)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub subscription_id: i32,
+    /// serialized [LeagueEventKind]
+    #[serde(skip)]
+    event: String,
+    /// the exact body that's sent (and signed)
+    pub payload: String,
+    /// serialized [DeliveryState]
+    #[serde(skip)]
+    state: String,
+    pub attempts: i32,
+    /// when to try next; None once it's delivered or failed
+    pub next_attempt: Option<i64>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created: i64,
+    pub delivered: Option<i64>,
+}
+
+impl WebhookDelivery {
+    pub fn event(&self) -> Result<LeagueEventKind, serde_json::Error> {
+        serde_json::from_str(&self.event)
+    }
+
+    pub fn state(&self) -> Result<DeliveryState, serde_json::Error> {
+        serde_json::from_str(&self.state)
+    }
+
+    /// pending deliveries whose next attempt is at or before `now`, oldest first
+    pub fn due(now: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, diesel::result::Error> {
+        webhook_deliveries::table
+            .filter(webhook_deliveries::state.eq(serialized(&DeliveryState::Pending)))
+            .filter(webhook_deliveries::next_attempt.le(now))
+            .order(webhook_deliveries::id.asc())
+            .load(conn)
+    }
+
+    /// does *not* persist self
+    pub fn record_success(&mut self, status_code: u16) {
+        let now = epoch_timestamp() as i64;
+        self.attempts += 1;
+        self.state = serialized(&DeliveryState::Delivered);
+        self.last_status_code = Some(status_code as i32);
+        self.last_error = None;
+        self.next_attempt = None;
+        self.delivered = Some(now);
+    }
+
+    /// records a failed attempt, and either schedules a retry or gives up.
+    /// `status_code` is None if we didn't get a response at all.
+    ///
+    /// does *not* persist self
+    pub fn record_failure<S: Into<String>>(&mut self, status_code: Option<u16>, error: S) {
+        let now = epoch_timestamp() as i64;
+        self.attempts += 1;
+        self.last_status_code = status_code.map(|c| c as i32);
+        self.last_error = Some(error.into());
+        if self.attempts >= MAX_DELIVERY_ATTEMPTS {
+            self.give_up();
+        } else {
+            self.next_attempt = Some(now + retry_delay(self.attempts));
+        }
+    }
+
+    /// does *not* persist self
+    pub fn give_up(&mut self) {
+        self.state = serialized(&DeliveryState::Failed);
+        self.next_attempt = None;
+    }
+
+    update_fn! {}
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name=webhook_deliveries)]
+struct NewWebhookDelivery {
+    subscription_id: i32,
+    event: String,
+    payload: String,
+    state: String,
+    next_attempt: Option<i64>,
+    created: i64,
+}
+
+impl NewWebhookDelivery {
+    save_fn!(webhook_deliveries::table, WebhookDelivery);
+}
+
+/// queues a delivery of `event` for every active subscription that wants it, to be sent as soon
+/// as the webhook worker gets to it. Returns the number of deliveries queued.
+pub fn queue_event(
+    event: &LeagueEvent,
+    conn: &mut SqliteConnection,
+) -> Result<usize, NMGLeagueBotError> {
+    let now = epoch_timestamp() as i64;
+    let kind = event.kind();
+    let payload = event.payload(now)?;
+    let mut queued = 0;
+    for sub in WebhookSubscription::active(conn)? {
+        match sub.events() {
+            Ok(events) if events.contains(&kind) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                warn!("Webhook subscription {} has invalid events: {e}", sub.id);
+                continue;
+            }
+        }
+        NewWebhookDelivery {
+            subscription_id: sub.id,
+            event: serialized(&kind),
+            payload: payload.clone(),
+            state: serialized(&DeliveryState::Pending),
+            next_attempt: Some(now),
+            created: now,
+        }
+        .save(conn)?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// [queue_event], for callers that shouldn't fail just because webhooks did
+pub fn fire_event(event: LeagueEvent, conn: &mut SqliteConnection) {
+    match queue_event(&event, conn) {
+        Ok(n) => {
+            debug!("Queued {n} webhook deliveries for {:?}", event.kind());
+        }
+        Err(e) => {
+            warn!("Error queueing webhook deliveries for {event:?}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::webhooks::{
+        queue_event, DeliveryState, LeagueEvent, LeagueEventKind, NewWebhookSubscription,
+        WebhookDelivery, MAX_DELIVERY_ATTEMPTS,
+    };
+    use crate::test_utils::setup_db;
+    use crate::utils::epoch_timestamp;
+
+    #[test]
+    fn test_queue_and_retry() -> anyhow::Result<()> {
+        let mut db = setup_db()?;
+        let (finishes, _) = NewWebhookSubscription::new(
+            "https://example.com/finishes",
+            &[LeagueEventKind::RaceFinished],
+        );
+        let finishes = finishes.save(&mut db)?;
+        // inactive subscriptions don't get anything
+        let (inactive, _) = NewWebhookSubscription::new(
+            "https://example.com/inactive",
+            &[
+                LeagueEventKind::PairingsGenerated,
+                LeagueEventKind::RaceFinished,
+            ],
+        );
+        let mut inactive = inactive.save(&mut db)?;
+        inactive.deactivate();
+        inactive.update(&mut db)?;
+
+        let event = LeagueEvent::RaceFinished {
+            race_id: 1,
+            bracket_id: 2,
+            racetime_gg_url: None,
+        };
+        assert_eq!(1, queue_event(&event, &mut db)?);
+        let pairings_event = LeagueEvent::PairingsGenerated {
+            bracket_id: 2,
+            round_num: 1,
+        };
+        assert_eq!(0, queue_event(&pairings_event, &mut db)?);
+
+        let now = epoch_timestamp() as i64;
+        let mut due = WebhookDelivery::due(now, &mut db)?;
+        assert_eq!(1, due.len());
+        let mut delivery = due.remove(0);
+        assert_eq!(finishes.id, delivery.subscription_id);
+        assert_eq!(LeagueEventKind::RaceFinished, delivery.event()?);
+        let payload: serde_json::Value = serde_json::from_str(&delivery.payload)?;
+        assert_eq!("RaceFinished", payload["event"]);
+        assert_eq!(1, payload["data"]["race_id"]);
+
+        delivery.record_failure(Some(500), "oops");
+        delivery.update(&mut db)?;
+        assert!(WebhookDelivery::due(now, &mut db)?.is_empty());
+        assert_eq!(1, WebhookDelivery::due(now + 30, &mut db)?.len());
+
+        for _ in 1..MAX_DELIVERY_ATTEMPTS {
+            delivery.record_failure(None, "still broken");
+        }
+        assert_eq!(DeliveryState::Failed, delivery.state()?);
+        assert_eq!(None, delivery.next_attempt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature() {
+        let (new_sub, secret) =
+            NewWebhookSubscription::new("https://example.com", &[LeagueEventKind::RaceFinished]);
+        assert_eq!(new_sub.secret, secret);
+        let sig = crate::models::webhooks::WebhookSubscription {
+            id: 1,
+            url: new_sub.url,
+            secret: "key".to_string(),
+            events: new_sub.events,
+            created: new_sub.created,
+            active: true,
+        }
+        .sign("The quick brown fox jumps over the lazy dog");
+        // the example from wikipedia's HMAC article
+        assert_eq!(
+            Ok("f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8".to_string()),
+            sig
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::discord::discord_state::DiscordState;
 use crate::shutdown::Shutdown;
 use async_trait::async_trait;
 use diesel::SqliteConnection;
+use itertools::Itertools;
 use log::{debug, error, info, warn};
 use nmg_league_bot::config::CONFIG;
 use nmg_league_bot::models::asyncs::race_run::Filenames;
@@ -214,9 +215,9 @@ async fn create_room_for_race(
         warn!("Can't create racetime room: category mismatch!");
         return Err(RaceTimeBotError::InvalidCategory)?;
     }
-    let (p1, p2) = bri.race(db.deref_mut())?.players(db.deref_mut())?;
+    let race = bri.race(db.deref_mut())?;
     // TODO: something nicer? bracket name? round number?
-    let race_name = format!("NMG League race: {} vs {}", p1.name, p2.name);
+    let race_name = format!("NMG League race: {}", race.title(db.deref_mut())?);
     let sr = StartRace {
         ranked: true,
         goal: szn.rtgg_goal_name,
@@ -365,12 +366,10 @@ impl RaceController {
         }
     }
 
-    fn get_players(
-        &self,
-        db: &mut SqliteConnection,
-    ) -> Result<(Player, Player), NMGLeagueBotError> {
+    /// every player in the race (there are more than two in placement races)
+    fn get_players(&self, db: &mut SqliteConnection) -> Result<Vec<Player>, NMGLeagueBotError> {
         let race = BracketRaceInfo::get_by_id(self.bri_id, db)?.race(db)?;
-        race.players(db).map_err(From::from)
+        race.all_players(db).map_err(From::from)
     }
 
     async fn gethistory(&mut self, ctx: &RaceContext<RacetimeState>) -> Option<Vec<ChatMessage>> {
@@ -404,13 +403,13 @@ impl RaceController {
 
         // if we can't get a db or figure out who the players are, the room really is an error
         let mut db = ctx.global_state.discord_state.diesel_cxn().await?;
-        let (p1, p2) = self.get_players(db.deref_mut())?;
+        let players = self.get_players(db.deref_mut())?;
 
         // if we can't *invite* them, however, it's probably better to just make the room open
         // and let them know about it in discord
         let mut success = true;
 
-        for player in [&p1, &p2] {
+        for player in &players {
             match &player.racetime_user_id {
                 Some(id) => {
                     // TODO: check if the invites actually succeeded
@@ -439,19 +438,18 @@ impl RaceController {
             info!("Set racetime room {} to open", rd.slug);
         }
 
-        let p1_m = p1.mention_or_name();
-        let p2_m = p2.mention_or_name();
+        let mentions = players.iter().map(|p| p.mention_or_name()).join(" ");
         ctx.global_state
             .discord_state
             .discord_client
             .create_message(CONFIG.racetime_room_posting_channel_id)
             .content(&format!(
-                "{p1_m} {p2_m} your race room is ready! {}",
+                "{mentions} your race room is ready! {}",
                 url_from_slug(&rd.slug)
             ))
             .await?;
 
-        for player in [&p1, &p2] {
+        for player in &players {
             let filenames = Filenames::new_random();
             if let Err(e) = ctx
                 .send_message(
@@ -11,6 +11,73 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    api_tokens (id) {
+        id -> Integer,
+        discord_id -> Text,
+        name -> Text,
+        token_hash -> Text,
+        created -> BigInt,
+        last_used -> Nullable<BigInt>,
+        revoked -> Bool,
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> Integer,
+        created -> BigInt,
+        actor -> Text,
+        action -> Text,
+        entity_type -> Text,
+        entity_id -> Text,
+        before_json -> Nullable<Text>,
+        after_json -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    availability_windows (id) {
+        id -> Integer,
+        player_id -> Integer,
+        weekday -> Integer,
+        start_minute -> Integer,
+        end_minute -> Integer,
+        timezone -> Text,
+    }
+}
+
+diesel::table! {
+    bracket_byes (id) {
+        id -> Integer,
+        bracket_id -> Integer,
+        round_id -> Integer,
+        player_id -> Integer,
+    }
+}
+
+diesel::table! {
+    bracket_race_entrants (id) {
+        id -> Integer,
+        bracket_race_id -> Integer,
+        player_id -> Integer,
+        result -> Nullable<Text>,
+        placement -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    bracket_race_games (id) {
+        id -> Integer,
+        bracket_race_id -> Integer,
+        game_num -> Integer,
+        player_1_result -> Text,
+        player_2_result -> Text,
+        outcome -> Text,
+        racetime_gg_url -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     bracket_race_infos (id) {
         id -> Integer,
@@ -22,6 +89,7 @@ diesel::table! {
         tentative_commentary_assignment_message_id -> Nullable<Text>,
         commentary_assignment_message_id -> Nullable<Text>,
         restream_channel -> Nullable<Text>,
+        times_suggested -> Nullable<BigInt>,
     }
 }
 
@@ -37,6 +105,8 @@ diesel::table! {
         player_1_result -> Nullable<Text>,
         player_2_result -> Nullable<Text>,
         outcome -> Nullable<Text>,
+        best_of -> Integer,
+        placement_race -> Bool,
     }
 }
 
@@ -45,6 +115,12 @@ diesel::table! {
         id -> Integer,
         round_num -> Integer,
         bracket_id -> Integer,
+        schedule_by -> Nullable<BigInt>,
+        play_by -> Nullable<BigInt>,
+        missed_deadline_policy -> Text,
+        schedule_reminders_sent -> Bool,
+        schedule_by_escalated -> Bool,
+        play_by_escalated -> Bool,
     }
 }
 
@@ -56,6 +132,28 @@ diesel::table! {
         state -> Text,
         bracket_type -> Text,
         backfill_note -> Nullable<Text>,
+        seed_source_bracket_id -> Nullable<Integer>,
+        bye_points -> Integer,
+        points_per_win -> Integer,
+        points_per_draw -> Integer,
+        points_per_loss -> Integer,
+        forfeit_time_penalty -> Integer,
+        tiebreakers -> Text,
+        best_of -> Integer,
+        group_size -> Integer,
+        placement_points -> Text,
+    }
+}
+
+diesel::table! {
+    cancelled_races (id) {
+        id -> Integer,
+        bracket_race_id -> Integer,
+        bracket_id -> Integer,
+        player_ids -> Text,
+        title -> Text,
+        scheduled_for -> BigInt,
+        cancelled -> BigInt,
     }
 }
 
@@ -81,6 +179,20 @@ diesel::table! {
         id -> Integer,
         bracket_id -> Integer,
         player_id -> Integer,
+        seed -> Nullable<Integer>,
+        dropped_at -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    player_ratings (id) {
+        id -> Integer,
+        player_id -> Integer,
+        round_id -> Integer,
+        rating -> Double,
+        deviation -> Double,
+        volatility -> Double,
+        games -> Integer,
     }
 }
 
@@ -102,6 +214,25 @@ diesel::table! {
         season_id -> Integer,
         reported_time -> Integer,
         vod_link -> Text,
+        review_state -> Text,
+        reviewer_discord_id -> Nullable<Text>,
+        review_reason -> Nullable<Text>,
+        reviewed_at -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    race_disputes (id) {
+        id -> Integer,
+        bracket_race_id -> Integer,
+        opener_discord_id -> Text,
+        reason -> Text,
+        evidence_url -> Nullable<Text>,
+        state -> Text,
+        created -> BigInt,
+        resolver_discord_id -> Nullable<Text>,
+        resolution_note -> Nullable<Text>,
+        resolved -> Nullable<BigInt>,
     }
 }
 
@@ -142,6 +273,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    reschedule_proposals (id) {
+        id -> Integer,
+        bracket_race_id -> Integer,
+        proposer_discord_id -> Text,
+        proposed_time -> BigInt,
+        previous_time -> Nullable<BigInt>,
+        state -> Text,
+        created -> BigInt,
+        responder_discord_id -> Nullable<Text>,
+        resolved -> Nullable<BigInt>,
+        race_deleted -> Nullable<BigInt>,
+    }
+}
+
 diesel::table! {
     seasons (id) {
         id -> Integer,
@@ -152,37 +298,90 @@ diesel::table! {
         state -> Text,
         rtgg_category_name -> Text,
         rtgg_goal_name -> Text,
+        qualifier_aggregation -> Text,
+        qualifier_max_attempts -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Integer,
+        subscription_id -> Integer,
+        event -> Text,
+        payload -> Text,
+        state -> Text,
+        attempts -> Integer,
+        next_attempt -> Nullable<BigInt>,
+        last_status_code -> Nullable<Integer>,
+        last_error -> Nullable<Text>,
+        created -> BigInt,
+        delivered -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    webhook_subscriptions (id) {
+        id -> Integer,
+        url -> Text,
+        secret -> Text,
+        events -> Text,
+        created -> BigInt,
+        active -> Bool,
     }
 }
 
+diesel::joinable!(availability_windows -> players (player_id));
+diesel::joinable!(bracket_byes -> bracket_rounds (round_id));
+diesel::joinable!(bracket_byes -> brackets (bracket_id));
+diesel::joinable!(bracket_byes -> players (player_id));
+diesel::joinable!(bracket_race_entrants -> bracket_races (bracket_race_id));
+diesel::joinable!(bracket_race_entrants -> players (player_id));
+diesel::joinable!(bracket_race_games -> bracket_races (bracket_race_id));
 diesel::joinable!(bracket_race_infos -> bracket_races (bracket_race_id));
 diesel::joinable!(bracket_races -> bracket_rounds (round_id));
 diesel::joinable!(bracket_races -> brackets (bracket_id));
 diesel::joinable!(bracket_races -> races (async_race_id));
 diesel::joinable!(bracket_rounds -> brackets (bracket_id));
 diesel::joinable!(brackets -> seasons (season_id));
+diesel::joinable!(cancelled_races -> brackets (bracket_id));
 diesel::joinable!(commentator_signups -> bracket_race_infos (bracket_race_info_id));
 diesel::joinable!(guild_race_criteria -> players (player_id));
 diesel::joinable!(player_bracket_entry -> brackets (bracket_id));
 diesel::joinable!(player_bracket_entry -> players (player_id));
+diesel::joinable!(player_ratings -> bracket_rounds (round_id));
+diesel::joinable!(player_ratings -> players (player_id));
 diesel::joinable!(qualifier_submissions -> players (player_id));
 diesel::joinable!(qualifier_submissions -> seasons (season_id));
+diesel::joinable!(race_disputes -> bracket_races (bracket_race_id));
 diesel::joinable!(race_events -> bracket_race_infos (bracket_race_info_id));
 diesel::joinable!(race_runs -> races (race_id));
+diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     _sqlx_migrations,
+    api_tokens,
+    audit_events,
+    availability_windows,
+    bracket_byes,
+    bracket_race_entrants,
+    bracket_race_games,
     bracket_race_infos,
     bracket_races,
     bracket_rounds,
     brackets,
+    cancelled_races,
     commentator_signups,
     guild_race_criteria,
     player_bracket_entry,
+    player_ratings,
     players,
     qualifier_submissions,
+    race_disputes,
     race_events,
     race_runs,
     races,
+    reschedule_proposals,
     seasons,
+    webhook_deliveries,
+    webhook_subscriptions,
 );
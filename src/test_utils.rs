@@ -1,6 +1,10 @@
 use diesel::{Connection as _, SqliteConnection};
 use oauth2::http::uri::Scheme;
 
+use crate::models::brackets::{Bracket, BracketType, NewBracket};
+use crate::models::player::Player;
+use crate::models::player_bracket_entries::NewPlayerBracketEntry;
+use crate::models::season::NewSeason;
 use crate::{db::run_migrations, models::player::NewPlayer};
 
 pub fn setup_db() -> Result<SqliteConnection, anyhow::Error> {
@@ -9,6 +13,31 @@ pub fn setup_db() -> Result<SqliteConnection, anyhow::Error> {
     Ok(db)
 }
 
+/// saves `n` players named `p1`, `p2`, ..., with discord ids to match
+pub fn new_players(n: usize, conn: &mut SqliteConnection) -> Result<Vec<Player>, anyhow::Error> {
+    let mut players = vec![];
+    for i in 1..=n {
+        players.push(NewPlayer::new(format!("p{i}"), i.to_string(), None, None, None).save(conn)?);
+    }
+    Ok(players)
+}
+
+/// a new season with one bracket, named "bracket", and `n` new players (see [new_players])
+/// entered in it
+pub fn bracket_with_players(
+    bracket_type: BracketType,
+    n: usize,
+    conn: &mut SqliteConnection,
+) -> Result<(Bracket, Vec<Player>), anyhow::Error> {
+    let season = NewSeason::new("Any% NMG", "alttp", "Any% NMG", conn)?.save(conn)?;
+    let bracket = NewBracket::new(&season, "bracket", bracket_type).save(conn)?;
+    let players = new_players(n, conn)?;
+    for p in &players {
+        NewPlayerBracketEntry::new(&bracket, p).save(conn)?;
+    }
+    Ok((bracket, players))
+}
+
 #[cfg(test)]
 mod tests {
     use super::setup_db;
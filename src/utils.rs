@@ -151,6 +151,22 @@ pub fn uuid_string() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
+/// lowercase hex, e.g. for hashes
+pub fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// the inverse of [hex_string]. None if `s` isn't hex.
+pub fn parse_hex_string(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
 pub fn epoch_timestamp() -> u32 {
     let timestamp = chrono::Utc::now().timestamp();
     let t_u32 = timestamp as u32;
@@ -1,30 +1,68 @@
 //! api lol. the idea is just stuff that returns json i guess
 
+use std::collections::{HashMap, HashSet};
 use std::ops::DerefMut;
-
-use crate::web::auth::Admin;
+use std::sync::Arc;
+
+use crate::discord::discord_state::{DiscordOperations, DiscordState};
+use crate::discord::{schedule_race, ScheduleRaceError};
+use crate::web::auth::{Admin, ApiTokenAdmin};
+use crate::web::openapi::{ApiDescription, Auth, Endpoint};
+use crate::web::pagination::{
+    parse_param, parse_repeated_param, InvalidParam, Page, PageParams, Paginated, Pagination,
+    SortOrder, NEXT_CURSOR_HEADER,
+};
+use crate::web::stats_cache::StatsCache;
 use crate::web::ConnectionWrapper;
-use diesel::SqliteConnection;
+use chrono::{TimeZone, Utc};
+use diesel::{OptionalExtension, SqliteConnection};
+use itertools::Itertools;
 use log::debug;
 use log::warn;
+use nmg_league_bot::models::api_tokens::ApiToken;
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
+use nmg_league_bot::models::bracket_race_entrants::BracketRaceEntrant;
+use nmg_league_bot::models::bracket_race_games::BracketRaceGame;
 use nmg_league_bot::models::bracket_race_infos::BracketRaceInfo;
 use nmg_league_bot::models::bracket_race_infos::CommentatorSignup;
 use nmg_league_bot::models::bracket_races::BracketRace;
 use nmg_league_bot::models::bracket_races::Outcome;
 use nmg_league_bot::models::bracket_races::PlayerResult;
 use nmg_league_bot::models::bracket_rounds::BracketRound;
-use nmg_league_bot::models::brackets::Bracket;
 use nmg_league_bot::models::brackets::BracketState;
 use nmg_league_bot::models::brackets::BracketType;
+use nmg_league_bot::models::brackets::{create_bracket, Bracket, BracketError, BracketOptions};
+use nmg_league_bot::models::head_to_head::{head_to_head, HeadToHead, HeadToHeadRace};
 use nmg_league_bot::models::player::Player;
-use nmg_league_bot::models::qualifer_submission::QualifierSubmission;
+use nmg_league_bot::models::player_bracket_entries::NewPlayerBracketEntry;
+use nmg_league_bot::models::qualifer_submission::{QualifierSubmission, ReviewState};
+use nmg_league_bot::models::ratings::{PlayerRating, RatingHistoryEntry};
+use nmg_league_bot::models::reschedule_proposals;
+use nmg_league_bot::models::scoring::ScoringRules;
+use nmg_league_bot::models::season::{NewSeason, Season};
+use nmg_league_bot::models::stats::Statistics;
+use nmg_league_bot::models::webhooks::{
+    DeliveryState, LeagueEventKind, NewWebhookSubscription, WebhookDelivery, WebhookSubscription,
+};
+use nmg_league_bot::worker_funcs::DroppedPlayerRaces;
+use nmg_league_bot::worker_funcs::{drop_player_from_bracket, rollback_round};
+use nmg_league_bot::worker_funcs::{trigger_race_finish, RaceFinishError, RaceFinishOptions};
 use nmg_league_bot::BracketRaceState;
+use nmg_league_bot::BracketRaceStateError;
+use nmg_league_bot::ChannelConfig;
 use nmg_league_bot::NMGLeagueBotError;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome as RequestOutcome};
 use rocket::response::Responder;
 use rocket::serde::json::Json;
-use rocket::{delete, get, Build, Request, Rocket};
+use rocket::{catch, delete, get, post, put, Build, Request, Rocket, Route, State};
+use schemars::generate::SchemaGenerator;
+use schemars::{JsonSchema, Schema};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -35,8 +73,14 @@ pub enum ApiError {
     #[error("Internal error")]
     NMGLeagueBotError(NMGLeagueBotError),
 
-    #[error("Bad Request")]
-    BadRequest,
+    #[error("Invalid parameters")]
+    InvalidParams(Vec<InvalidParam>),
+
+    #[error("Not found")]
+    NotFound,
+
+    #[error("{0}")]
+    InvalidRequest(String),
 }
 
 // this is kinda cool, its like "passing through" the NMGLeagueBotError From implementations
@@ -49,13 +93,128 @@ where
     }
 }
 
+impl ApiError {
+    fn kind(&self) -> ApiErrorKind {
+        match self {
+            ApiError::CannotDeletePastQualifiers => ApiErrorKind::Conflict,
+            ApiError::NMGLeagueBotError(_) => ApiErrorKind::Internal,
+            ApiError::InvalidParams(_) => ApiErrorKind::InvalidParams,
+            ApiError::NotFound => ApiErrorKind::NotFound,
+            ApiError::InvalidRequest(_) => ApiErrorKind::InvalidRequest,
+        }
+    }
+}
+
+/// what went wrong, in v2 error bodies
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ApiErrorKind {
+    /// some query parameters couldn't be used: see `invalid_params`
+    InvalidParams,
+    /// the request was understood but can't be done, e.g. reporting results for a finished race
+    InvalidRequest,
+    /// there's no API token, or it isn't valid
+    Unauthorized,
+    /// the API token isn't allowed to do this
+    Forbidden,
+    NotFound,
+    /// the request conflicts with what's already happened
+    Conflict,
+    Internal,
+}
+
+impl ApiErrorKind {
+    fn status(&self) -> Status {
+        match self {
+            ApiErrorKind::InvalidParams => Status::BadRequest,
+            ApiErrorKind::InvalidRequest => Status::UnprocessableEntity,
+            ApiErrorKind::Unauthorized => Status::Unauthorized,
+            ApiErrorKind::Forbidden => Status::Forbidden,
+            ApiErrorKind::NotFound => Status::NotFound,
+            ApiErrorKind::Conflict => Status::Conflict,
+            ApiErrorKind::Internal => Status::InternalServerError,
+        }
+    }
+
+    /// for errors that happen before a route gets to run: bad paths, failed guards, bodies
+    /// that don't parse, etc.
+    fn from_status(status: Status) -> Self {
+        match status.code {
+            400 | 422 => ApiErrorKind::InvalidRequest,
+            401 => ApiErrorKind::Unauthorized,
+            403 => ApiErrorKind::Forbidden,
+            404 => ApiErrorKind::NotFound,
+            409 => ApiErrorKind::Conflict,
+            _ => ApiErrorKind::Internal,
+        }
+    }
+}
+
+/// the body of v2 error responses
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+struct ApiErrorBody {
+    error: ApiErrorKind,
+    message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    invalid_params: Vec<InvalidParam>,
+}
+
+const V1_BASE: &str = "/api/v1";
+const V2_BASE: &str = "/api/v2";
+
+/// Both versions of the API are served by the same routes, and only differ in how they answer.
+/// v1 answers everything with a 200 and either `{"Ok": ...}` or `{"Err": ...}`; v2 uses status
+/// codes, returns values as they are, and has [ApiErrorBody]s. v2 also takes enum query parameters
+/// as plain strings rather than JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    fn of(request: &Request<'_>) -> Self {
+        if request.uri().path().as_str().starts_with(V2_BASE) {
+            Self::V2
+        } else {
+            Self::V1
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiVersion {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> RequestOutcome<Self, Self::Error> {
+        RequestOutcome::Success(Self::of(request))
+    }
+}
+
 struct ApiResponse<T>(Result<T, ApiError>);
 
-impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for ApiResponse<T> {
-    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+/// invalid parameters are reported next to the usual error message, so that clients that only
+/// look at the message still work
+#[derive(Serialize)]
+struct InvalidParamsError {
+    #[serde(rename = "Err")]
+    message: String,
+    invalid_params: Vec<InvalidParam>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    fn respond_v1<'r, 'o: 'r>(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
         // is logging in here kosher?
         let returnable = match self.0 {
             Ok(val) => Json(Ok(val)),
+            Err(ApiError::InvalidParams(invalid_params)) => {
+                debug!("Invalid API request parameters: {invalid_params:?}");
+                return Json(InvalidParamsError {
+                    message: ApiError::InvalidParams(vec![]).to_string(),
+                    invalid_params,
+                })
+                .respond_to(request);
+            }
             Err(e) => {
                 // we return a generic error so we want to log the actual error
                 warn!("Error fulfilling API request: {e:?}");
@@ -64,15 +223,198 @@ impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for ApiResponse<T> {
         };
         returnable.respond_to(request)
     }
+
+    fn respond_v2<'r, 'o: 'r>(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        let e = match self.0.map(serde_json::to_value) {
+            // routes that don't return anything return ()
+            Ok(Ok(Value::Null)) => {
+                return Status::NoContent.respond_to(request);
+            }
+            Ok(Ok(val)) => {
+                return Json(val).respond_to(request);
+            }
+            Ok(Err(e)) => ApiError::from(e),
+            Err(e) => e,
+        };
+        let error = e.kind();
+        if error == ApiErrorKind::Internal {
+            // we return a generic error so we want to log the actual error
+            warn!("Error fulfilling API request: {e:?}");
+        } else {
+            debug!("Unsuccessful API request: {e:?}");
+        }
+        let message = e.to_string();
+        let invalid_params = match e {
+            ApiError::InvalidParams(params) => params,
+            _ => vec![],
+        };
+        (
+            error.status(),
+            Json(ApiErrorBody {
+                error,
+                message,
+                invalid_params,
+            }),
+        )
+            .respond_to(request)
+    }
+}
+
+impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for ApiResponse<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match ApiVersion::of(request) {
+            ApiVersion::V1 => self.respond_v1(request),
+            ApiVersion::V2 => self.respond_v2(request),
+        }
+    }
+}
+
+/// v2's errors for requests that never made it to a route
+#[catch(default)]
+fn v2_catcher(status: Status, _request: &Request<'_>) -> (Status, Json<ApiErrorBody>) {
+    (
+        status,
+        Json(ApiErrorBody {
+            error: ApiErrorKind::from_status(status),
+            message: status.reason().unwrap_or("Error").to_string(),
+            invalid_params: vec![],
+        }),
+    )
+}
+
+/// an [ApiResponse] for one [Page] of a list. The body is just the page's items, so paginated
+/// responses look just like unpaginated ones; the cursor for the next page is in a header.
+struct ApiPage<T>(Result<Page<T>, ApiError>);
+
+impl<'r, 'o: 'r, T: Serialize> Responder<'r, 'o> for ApiPage<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        ApiPageIn(self.0.map(|page| (page.items, page.next_cursor))).respond_to(request)
+    }
+}
+
+/// like [ApiPage], for responses with one page of a list somewhere inside them: the body is `B`,
+/// and the cursor for the next page of the list is in the same header
+struct ApiPageIn<B>(Result<(B, Option<String>), ApiError>);
+
+impl<'r, 'o: 'r, B: Serialize> Responder<'r, 'o> for ApiPageIn<B> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self.0 {
+            Ok((body, next_cursor)) => {
+                let mut response = ApiResponse(Ok(body)).respond_to(request)?;
+                if let Some(cursor) = next_cursor {
+                    response.set_raw_header(NEXT_CURSOR_HEADER, cursor);
+                }
+                Ok(response)
+            }
+            Err(e) => ApiResponse::<()>(Err(e)).respond_to(request),
+        }
+    }
+}
+
+/// `pagination` if it and everything else was valid, or an error listing everything that wasn't
+fn checked(
+    pagination: Option<Pagination>,
+    invalid: Vec<InvalidParam>,
+) -> Result<Pagination, ApiError> {
+    match pagination {
+        Some(p) if invalid.is_empty() => Ok(p),
+        _ => Err(ApiError::InvalidParams(invalid)),
+    }
+}
+
+/// parses the enum query parameter `name`, if it was given: as JSON in v1 (e.g. `?state="New"`) and
+/// as a plain string in v2 (`?state=New`)
+fn parse_enum_param<T: DeserializeOwned>(
+    name: &str,
+    value: Option<String>,
+    version: ApiVersion,
+    invalid: &mut Vec<InvalidParam>,
+) -> Option<T> {
+    let value = value?;
+    let parsed = match version {
+        ApiVersion::V1 => serde_json::from_str(&value),
+        ApiVersion::V2 => serde_json::from_value(Value::String(value.clone())),
+    };
+    match parsed {
+        Ok(t) => Some(t),
+        Err(e) => {
+            debug!("Error parsing {name} param {value}: {e}");
+            invalid.push(InvalidParam::new(
+                name,
+                format!("Unable to parse {value:?}"),
+            ));
+            None
+        }
+    }
+}
+
+/// filters for lists of races, or of things about races
+#[derive(Debug, Default)]
+struct RaceFilters {
+    bracket_id: Option<i32>,
+    /// round number
+    round: Option<i32>,
+    player_id: Option<i32>,
+    scheduled_after: Option<i64>,
+    scheduled_before: Option<i64>,
+    restreamed: Option<bool>,
+}
+
+impl RaceFilters {
+    fn parse(
+        bracket_id: Option<String>,
+        round: Option<String>,
+        player_id: Option<String>,
+        scheduled_after: Option<String>,
+        scheduled_before: Option<String>,
+        restreamed: Option<String>,
+        invalid: &mut Vec<InvalidParam>,
+    ) -> Self {
+        Self {
+            bracket_id: parse_param("bracket_id", &bracket_id, invalid),
+            round: parse_param("round", &round, invalid),
+            player_id: parse_param("player_id", &player_id, invalid),
+            scheduled_after: parse_param("scheduled_after", &scheduled_after, invalid),
+            scheduled_before: parse_param("scheduled_before", &scheduled_before, invalid),
+            restreamed: parse_param("restreamed", &restreamed, invalid),
+        }
+    }
+
+    /// `entrants` only matter for placement races
+    fn matches(
+        &self,
+        race: &BracketRace,
+        info: Option<&BracketRaceInfo>,
+        round: &BracketRound,
+        entrants: &[BracketRaceEntrant],
+    ) -> bool {
+        let scheduled_for = info.and_then(|i| i.scheduled_for);
+        let restreamed = info.map(|i| i.restream_channel.is_some()).unwrap_or(false);
+        self.bracket_id.map_or(true, |id| race.bracket_id == id)
+            && self.round.map_or(true, |r| round.round_num == r)
+            && self.player_id.map_or(true, |id| {
+                race.player_1_id == id
+                    || race.player_2_id == id
+                    || entrants.iter().any(|e| e.player_id == id)
+            })
+            && self
+                .scheduled_after
+                .map_or(true, |t| scheduled_for.map_or(false, |s| s >= t))
+            && self
+                .scheduled_before
+                .map_or(true, |t| scheduled_for.map_or(false, |s| s < t))
+            && self.restreamed.map_or(true, |r| restreamed == r)
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 struct ApiBracket {
     id: i32,
     name: String,
     season_id: i32,
     state: BracketState,
     bracket_type: BracketType,
+    scoring_rules: ScoringRules,
 }
 
 impl TryFrom<Bracket> for ApiBracket {
@@ -81,26 +423,76 @@ impl TryFrom<Bracket> for ApiBracket {
     fn try_from(value: Bracket) -> Result<Self, Self::Error> {
         let state = value.state()?;
         let bracket_type = value.bracket_type()?;
+        let scoring_rules = value.scoring_rules()?;
         Ok(Self {
             id: value.id,
             name: value.name,
             season_id: value.season_id,
             state,
             bracket_type,
+            scoring_rules,
         })
     }
 }
 
-#[derive(diesel::Queryable, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 struct ApiQualifier {
     id: i32,
     player_id: i32,
     player_name: String,
     time: i32,
     vod: String,
+    /// the player's score from all of their qualifiers, under the season's qualifier rules
+    score: Option<i32>,
+    /// whether this qualifier is one of the ones that make up the player's score
+    counts: bool,
+    /// only verified qualifiers count towards scores
+    review_state: ReviewState,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiGame {
+    pub game_num: i32,
+    pub player_1_result: PlayerResult,
+    pub player_2_result: PlayerResult,
+    pub outcome: Outcome,
+    pub racetime_gg_url: Option<String>,
+}
+
+impl TryFrom<BracketRaceGame> for ApiGame {
+    type Error = serde_json::Error;
+
+    fn try_from(game: BracketRaceGame) -> Result<Self, Self::Error> {
+        Ok(Self {
+            game_num: game.game_num,
+            player_1_result: game.player_1_result()?,
+            player_2_result: game.player_2_result()?,
+            outcome: game.outcome()?,
+            racetime_gg_url: game.racetime_gg_url,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiEntrant {
+    pub player_id: i32,
+    pub result: Option<PlayerResult>,
+    pub placement: Option<i32>,
+}
+
+impl TryFrom<BracketRaceEntrant> for ApiEntrant {
+    type Error = serde_json::Error;
+
+    fn try_from(entrant: BracketRaceEntrant) -> Result<Self, Self::Error> {
+        Ok(Self {
+            player_id: entrant.player_id,
+            result: entrant.result().transpose()?,
+            placement: entrant.placement,
+        })
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 struct ApiRace {
     // race
     pub id: i32,
@@ -112,19 +504,31 @@ struct ApiRace {
     pub player_1_result: Option<PlayerResult>,
     pub player_2_result: Option<PlayerResult>,
     pub outcome: Option<Outcome>,
+    pub best_of: i32,
+    /// empty unless this race is a series
+    pub games: Vec<ApiGame>,
+    pub placement_race: bool,
+    /// empty unless this is a placement race
+    pub entrants: Vec<ApiEntrant>,
     // race info
     pub scheduled_for: Option<i64>,
     pub racetime_gg_url: Option<String>,
     pub restream_channel: Option<String>,
 }
 
-impl TryFrom<(BracketRace, Option<BracketRaceInfo>, BracketRound)> for ApiRace {
+impl TryFrom<RaceParts> for ApiRace {
     type Error = serde_json::Error;
 
-    fn try_from(
-        value: (BracketRace, Option<BracketRaceInfo>, BracketRound),
-    ) -> Result<Self, Self::Error> {
-        let (race, info, round) = value;
+    fn try_from(value: RaceParts) -> Result<Self, Self::Error> {
+        let (race, info, round, games, entrants) = value;
+        let games = games
+            .into_iter()
+            .map(ApiGame::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let entrants = entrants
+            .into_iter()
+            .map(ApiEntrant::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
         let player_1_result = race.player_1_result().transpose()?;
         let player_2_result = race.player_2_result().transpose()?;
         let state = race.state()?;
@@ -144,6 +548,10 @@ impl TryFrom<(BracketRace, Option<BracketRaceInfo>, BracketRound)> for ApiRace {
             player_1_result,
             player_2_result,
             outcome,
+            best_of: race.best_of,
+            games,
+            placement_race: race.placement_race,
+            entrants,
             scheduled_for: scheduled_for,
             racetime_gg_url: racetime_gg_url,
             restream_channel: restream_channel,
@@ -151,8 +559,9 @@ impl TryFrom<(BracketRace, Option<BracketRaceInfo>, BracketRound)> for ApiRace {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 struct ApiCommentatorSignup {
+    pub id: i32,
     pub bracket_race_id: i32,
     pub discord_id: String,
 }
@@ -165,45 +574,222 @@ impl TryFrom<(CommentatorSignup, BracketRaceInfo, BracketRace)> for ApiCommentat
     ) -> Result<Self, Self::Error> {
         let (signup, _info, race) = value;
         Ok(Self {
+            id: signup.id,
             bracket_race_id: race.id,
             discord_id: signup.discord_id,
         })
     }
 }
 
+impl Paginated for ApiBracket {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name"];
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "name" => json!(self.name),
+            _ => json!(self.id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for ApiQualifier {
+    const SORT_FIELDS: &'static [&'static str] = &["time", "id"];
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "time" => json!(self.time),
+            _ => json!(self.id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for ApiRace {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "scheduled_for", "round"];
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "scheduled_for" => json!(self.scheduled_for),
+            "round" => json!(self.round),
+            _ => json!(self.id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for HeadToHeadRace {
+    const SORT_FIELDS: &'static [&'static str] = &["season_ordinal", "race_id"];
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "season_ordinal" => json!(self.season_ordinal),
+            _ => json!(self.race_id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.race_id as i64
+    }
+}
+
+impl Paginated for ApiCommentatorSignup {
+    const SORT_FIELDS: &'static [&'static str] = &["id"];
+
+    fn sort_key(&self, _field: &str) -> Value {
+        json!(self.id)
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for Player {
+    const SORT_FIELDS: &'static [&'static str] = &["id", "name"];
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "name" => json!(self.name),
+            _ => json!(self.id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for RatingHistoryEntry {
+    const SORT_FIELDS: &'static [&'static str] = &["id"];
+
+    fn sort_key(&self, _field: &str) -> Value {
+        json!(self.id)
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
 fn get_qualifiers(ordinal: i32, db: &mut SqliteConnection) -> Result<Vec<ApiQualifier>, ApiError> {
     use crate::schema::{players, qualifier_submissions as qs, seasons};
     use diesel::prelude::*;
-    Ok(qs::table
+    let season = match Season::get_by_ordinal(ordinal, db) {
+        Ok(s) => s,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok(vec![]);
+        }
+        Err(e) => {
+            return Err(e.into());
+        }
+    };
+    let rules = season.qualifier_rules()?;
+    let submissions: Vec<(i32, i32, String, i32, String, String)> = qs::table
         .inner_join(seasons::table)
         .inner_join(players::table)
-        .filter(seasons::ordinal.eq(ordinal))
+        .filter(seasons::id.eq(season.id))
         .select((
             qs::id,
             qs::player_id,
             players::name,
             qs::reported_time,
             qs::vod_link,
+            qs::review_state,
         ))
-        .order_by(qs::reported_time.asc())
-        .load(db)?)
+        .order_by(qs::id.asc())
+        .load(db)?;
+    let submissions = submissions
+        .into_iter()
+        .map(|(id, player_id, player_name, time, vod, review_state)| {
+            serde_json::from_str(&review_state)
+                .map(|state: ReviewState| (id, player_id, player_name, time, vod, state))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // only verified submissions count towards scores
+    let mut scores: HashMap<i32, i32> = Default::default();
+    let mut counted_ids: HashSet<i32> = Default::default();
+    for (player_id, player_submissions) in submissions
+        .iter()
+        .filter(|s| s.5 == ReviewState::Verified)
+        .into_group_map_by(|s| s.1)
+    {
+        let times = player_submissions.iter().map(|s| s.3).collect::<Vec<_>>();
+        if let Some(score) = rules.score(&times) {
+            scores.insert(player_id, score);
+        }
+        for idx in rules.counted_attempts(&times).unwrap_or_default() {
+            counted_ids.insert(player_submissions[idx].0);
+        }
+    }
+    Ok(submissions
+        .into_iter()
+        .map(
+            |(id, player_id, player_name, time, vod, review_state)| ApiQualifier {
+                id,
+                player_id,
+                player_name,
+                time,
+                vod,
+                score: scores.get(&player_id).cloned(),
+                counts: counted_ids.contains(&id),
+                review_state,
+            },
+        )
+        .sorted_by_key(|q| (q.time, q.id))
+        .collect())
+}
+
+fn get_qualifiers_page(
+    ordinal: i32,
+    player_id: Option<String>,
+    page: PageParams,
+    db: &mut SqliteConnection,
+) -> Result<Page<ApiQualifier>, ApiError> {
+    let mut invalid = vec![];
+    let player_id = parse_param::<i32>("player_id", &player_id, &mut invalid);
+    let pagination = checked(page.validate::<ApiQualifier>(&mut invalid), invalid)?;
+    let qualifiers = get_qualifiers(ordinal, db)?
+        .into_iter()
+        .filter(|q| player_id.map_or(true, |id| q.player_id == id))
+        .collect();
+    Ok(pagination.apply(qualifiers))
 }
 
-#[get("/season/<ordinal>/qualifiers")]
-async fn qualifiers(ordinal: i32, mut db: ConnectionWrapper<'_>) -> ApiResponse<Vec<ApiQualifier>> {
-    ApiResponse(get_qualifiers(ordinal, &mut db))
+#[get("/season/<ordinal>/qualifiers?<player_id>&<page..>")]
+async fn qualifiers(
+    ordinal: i32,
+    player_id: Option<String>,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiQualifier> {
+    ApiPage(get_qualifiers_page(ordinal, player_id, page, &mut db))
 }
 
 #[delete("/qualifiers/<id>")]
-async fn delete_qualifier(
-    id: i32,
-    _admin: Admin,
-    mut db: ConnectionWrapper<'_>,
-) -> ApiResponse<()> {
+async fn delete_qualifier(id: i32, admin: Admin, mut db: ConnectionWrapper<'_>) -> ApiResponse<()> {
     let mut _delete_qualifier = || -> Result<(), ApiError> {
         let q = QualifierSubmission::get_by_id(id, &mut db)?;
         if q.safe_to_delete(&mut db)? {
+            let audit = NewAuditEvent::new(
+                &admin.actor(),
+                "delete_qualifier",
+                AuditEntity::QualifierSubmission,
+                q.id,
+            )
+            .before(&q);
             q.delete(&mut db)?;
+            audit.record(&mut db);
         } else {
             // its weird that you put the `.into`() inside here!
             // Err(...).map_err(Into::into) works too
@@ -214,128 +800,1113 @@ async fn delete_qualifier(
     ApiResponse(_delete_qualifier())
 }
 
-#[get("/players?<player_id>")]
-async fn get_players(
-    player_id: Vec<i32>,
-    mut db: ConnectionWrapper<'_>,
-) -> ApiResponse<Vec<Player>> {
-    use diesel::prelude::*;
-    use nmg_league_bot::schema::players;
-    let res: Result<Vec<Player>, _> = if player_id.is_empty() {
-        players::table.load(db.deref_mut())
-    } else {
-        players::table
-            .filter(players::id.eq_any(player_id))
-            .load(db.deref_mut())
-    };
-
-    ApiResponse(res.map_err(From::from))
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiDropPlayer {
+    player_id: i32,
+    unfinished_races: DroppedPlayerRaces,
 }
 
-fn db_objs_to_api_objs<DB, API>(db_objs: Vec<DB>) -> Result<Vec<API>, ApiError>
-where
-    API: TryFrom<DB>,
-    // I couldn't figure this out on my own, but the compiler explained it.
-    // The compiler actually gave `serde_json::Error: From<<API as TryFrom<DB>>::Error>`,
-    // but we can be a bit more generic and accept any NMGLeagueBotError.
-    NMGLeagueBotError: From<<API as TryFrom<DB>>::Error>,
-{
-    let collection = Vec::with_capacity(db_objs.len());
-    let converted: Result<Vec<API>, ApiError> =
-        db_objs.into_iter().try_fold(collection, |mut coll, b| {
-            let api_bracket = API::try_from(b)?;
-            coll.push(api_bracket);
-            Ok(coll)
-        });
-    converted
+async fn _drop_player(
+    bracket_id: i32,
+    drop: ApiDropPlayer,
+    discord: Option<(&twilight_http::Client, &ChannelConfig)>,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<usize, ApiError> {
+    let mut bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let player = Player::get_by_id(drop.player_id, conn)?.ok_or(ApiError::NotFound)?;
+    let changed =
+        drop_player_from_bracket(&mut bracket, &player, drop.unfinished_races, conn, discord)
+            .await?;
+    NewAuditEvent::new(actor, "drop_player", AuditEntity::Bracket, bracket.id)
+        .after(&json!({
+            "player_id": player.id,
+            "unfinished_races": drop.unfinished_races,
+            "races_changed": changed,
+        }))
+        .record(conn);
+    Ok(changed)
 }
 
-#[get("/season/<ordinal>/brackets")]
-async fn get_season_brackets(
-    ordinal: i32,
+/// drops a player out of a bracket. Returns the number of their unfinished races that were
+/// forfeited or cancelled
+#[post("/bracket/<id>/drop", data = "<drop>")]
+async fn drop_player(
+    id: i32,
+    drop: Json<ApiDropPlayer>,
+    admin: Admin,
+    discord_state: Option<&State<Arc<DiscordState>>>,
     mut db: ConnectionWrapper<'_>,
-) -> ApiResponse<Vec<ApiBracket>> {
-    fn get_brackets(ordinal: i32, conn: &mut SqliteConnection) -> Result<Vec<Bracket>, ApiError> {
-        use crate::schema::{brackets, seasons};
-        use diesel::prelude::*;
-        Ok(brackets::table
-            .inner_join(seasons::table)
-            .filter(seasons::ordinal.eq(ordinal))
-            .select(brackets::all_columns)
-            .load(conn)?)
+) -> ApiResponse<usize> {
+    let discord = discord_state.map(|s| (s.discord_client.as_ref(), &s.channel_config));
+    ApiResponse(_drop_player(id, drop.into_inner(), discord, &admin.actor(), &mut db).await)
+}
+
+fn bracket_error(e: BracketError) -> ApiError {
+    match e {
+        BracketError::DBError(e) => e.into(),
+        BracketError::Other(s) => ApiError::InvalidRequest(s),
+        e => ApiError::InvalidRequest(e.to_string()),
     }
+}
 
-    ApiResponse(get_brackets(ordinal, &mut db).and_then(db_objs_to_api_objs))
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiNewSeason {
+    format: String,
+    rtgg_category_name: String,
+    rtgg_goal_name: String,
 }
 
-#[get("/season/<ordinal>/races?<state>")]
-async fn get_season_races(
-    ordinal: i32,
-    state: Option<String>,
-    mut db: ConnectionWrapper<'_>,
-) -> ApiResponse<Vec<ApiRace>> {
-    let _get_races = |conn: &mut SqliteConnection| -> Result<
-        Vec<(BracketRace, Option<BracketRaceInfo>, BracketRound)>,
-        ApiError,
-    > {
-        use crate::schema::{bracket_race_infos, bracket_races, bracket_rounds, brackets, seasons};
-        use diesel::prelude::*;
+fn _create_season(
+    new: ApiNewSeason,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<Season, ApiError> {
+    let ns = NewSeason::new(new.format, new.rtgg_category_name, new.rtgg_goal_name, conn)?;
+    let season = ns.save(conn)?;
+    NewAuditEvent::new(actor, "create_season", AuditEntity::Season, season.id)
+        .after(&season)
+        .record(conn);
+    Ok(season)
+}
 
-        let mut q = bracket_races::table
-            .inner_join(bracket_rounds::table)
-            .inner_join(brackets::table.inner_join(seasons::table))
-            .left_join(bracket_race_infos::table)
-            .select((
-                bracket_races::all_columns,
-                bracket_race_infos::all_columns.nullable(),
-                bracket_rounds::all_columns,
-            ))
-            .filter(seasons::ordinal.eq(ordinal))
-            .into_boxed();
+#[post("/seasons", data = "<season>")]
+async fn create_season(
+    season: Json<ApiNewSeason>,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<Season> {
+    ApiResponse(_create_season(season.into_inner(), &admin.actor(), &mut db))
+}
 
-        if let Some(state_inner) = state {
-            match serde_json::from_str::<BracketRaceState>(&state_inner) {
-                Ok(_) => {
-                    // we make sure it is parseable, but we pass the unparsed value through
-                    // because that's what's actually in the DB
-                    q = q.filter(bracket_races::state.eq(state_inner));
-                }
-                Err(e) => {
-                    debug!("Error parsing state param {state_inner}: {e}");
-                    return Err(ApiError::BadRequest);
-                }
-            };
-        }
-        Ok(q.load::<(BracketRace, Option<BracketRaceInfo>, BracketRound)>(conn)?)
-    };
-    let data = _get_races(&mut db);
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiNewBracket {
+    name: String,
+    bracket_type: BracketType,
+    #[serde(default)]
+    options: BracketOptions,
+}
 
-    ApiResponse(data.and_then(db_objs_to_api_objs))
+fn _create_bracket(
+    season_ordinal: i32,
+    new: ApiNewBracket,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<ApiBracket, ApiError> {
+    let season = Season::get_by_ordinal(season_ordinal, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let bracket = create_bracket(&season, new.name, new.bracket_type, &new.options, conn)
+        .map_err(bracket_error)?;
+    NewAuditEvent::new(actor, "create_bracket", AuditEntity::Bracket, bracket.id)
+        .after(&bracket)
+        .record(conn);
+    Ok(ApiBracket::try_from(bracket)?)
 }
 
-#[get("/season/<ordinal>/commentator_signups?<bracket_race_id>")]
-async fn get_season_commentator_signups(
+#[post("/season/<ordinal>/brackets", data = "<bracket>")]
+async fn create_season_bracket(
     ordinal: i32,
-    bracket_race_id: Vec<i32>,
+    bracket: Json<ApiNewBracket>,
+    admin: ApiTokenAdmin,
     mut db: ConnectionWrapper<'_>,
-) -> ApiResponse<Vec<ApiCommentatorSignup>> {
-    let _get_comms = |conn: &mut SqliteConnection| -> Result<
-        Vec<(CommentatorSignup, BracketRaceInfo, BracketRace)>,
-        ApiError,
-    > {
-        use crate::schema::{
-            bracket_race_infos, bracket_races, brackets, commentator_signups, seasons,
-        };
-        use diesel::prelude::*;
+) -> ApiResponse<ApiBracket> {
+    ApiResponse(_create_bracket(
+        ordinal,
+        bracket.into_inner(),
+        &admin.actor(),
+        &mut db,
+    ))
+}
 
-        let mut q = commentator_signups::table
-            .inner_join(bracket_race_infos::table.inner_join(
-                bracket_races::table.inner_join(brackets::table.inner_join(seasons::table)),
-            ))
-            .select((
-                commentator_signups::all_columns,
-                bracket_race_infos::all_columns,
-                bracket_races::all_columns,
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiBracketEntry {
+    player_id: i32,
+}
+
+fn _add_bracket_player(
+    bracket_id: i32,
+    entry: ApiBracketEntry,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<(), ApiError> {
+    let bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let player = Player::get_by_id(entry.player_id, conn)?.ok_or(ApiError::NotFound)?;
+    NewPlayerBracketEntry::new(&bracket, &player).save(conn)?;
+    NewAuditEvent::new(
+        actor,
+        "add_player_to_bracket",
+        AuditEntity::Bracket,
+        bracket.id,
+    )
+    .after(&json!({"player_id": player.id}))
+    .record(conn);
+    Ok(())
+}
+
+#[post("/bracket/<id>/players", data = "<entry>")]
+async fn add_bracket_player(
+    id: i32,
+    entry: Json<ApiBracketEntry>,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<()> {
+    ApiResponse(_add_bracket_player(
+        id,
+        entry.into_inner(),
+        &admin.actor(),
+        &mut db,
+    ))
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiSchedule {
+    /// unix timestamp, in seconds
+    scheduled_for: i64,
+}
+
+async fn _schedule_race(
+    race_id: i32,
+    schedule: ApiSchedule,
+    admin: &ApiTokenAdmin,
+    state: &Arc<DiscordState>,
+) -> Result<(), ApiError> {
+    let when = Utc
+        .timestamp_opt(schedule.scheduled_for, 0)
+        .single()
+        .ok_or(ApiError::InvalidRequest("Invalid timestamp".to_string()))?;
+    let (race, previous_time) = {
+        let mut cxn = state.diesel_cxn().await?;
+        let race = BracketRace::get_by_id(race_id, cxn.deref_mut())
+            .optional()?
+            .ok_or(ApiError::NotFound)?;
+        let previous_time = race.info(cxn.deref_mut())?.scheduled_for;
+        (race, previous_time)
+    };
+    schedule_race(race, when, &admin.actor(), state)
+        .await
+        .map_err(|e| match e {
+            ScheduleRaceError::RaceFinished => ApiError::InvalidRequest(e.to_string()),
+            e => NMGLeagueBotError::Other(e.to_string()).into(),
+        })?;
+    // moving an already scheduled race is an admin override, the same as `/reschedule_race`
+    if previous_time.is_some() {
+        let mut cxn = state.diesel_cxn().await?;
+        if let Err(e) = reschedule_proposals::record_admin_override(
+            race_id,
+            admin.token.discord_id.clone(),
+            &when,
+            previous_time,
+            cxn.deref_mut(),
+        ) {
+            // the race has been moved at this point, so don't act like it failed
+            warn!("Error recording admin reschedule of race {race_id}: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// schedules (or reschedules) a race, the same way `/schedule_race` does
+#[put("/race/<id>/schedule", data = "<schedule>")]
+async fn schedule_bracket_race(
+    id: i32,
+    schedule: Json<ApiSchedule>,
+    admin: ApiTokenAdmin,
+    discord_state: &State<Arc<DiscordState>>,
+) -> ApiResponse<()> {
+    ApiResponse(_schedule_race(id, schedule.into_inner(), &admin, discord_state).await)
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiRaceResults {
+    player_1_result: PlayerResult,
+    player_2_result: PlayerResult,
+    racetime_gg_url: Option<String>,
+    /// overwrite the results of an already finished race
+    #[serde(default)]
+    force: bool,
+}
+
+async fn _report_race(
+    race_id: i32,
+    results: ApiRaceResults,
+    client: Option<&twilight_http::Client>,
+    channel_config: &ChannelConfig,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<(), ApiError> {
+    let race = BracketRace::get_by_id(race_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let audit =
+        NewAuditEvent::new(actor, "report_race", AuditEntity::BracketRace, race.id).before(&race);
+    if race.is_placement_race() {
+        return Err(ApiError::InvalidRequest(
+            "That race has more than two players.".to_string(),
+        ));
+    }
+    let mut info = race.info(conn)?;
+    if let Some(rt) = &results.racetime_gg_url {
+        info.racetime_gg_url = Some(rt.clone());
+    }
+    let (player_1, player_2) = race.players(conn)?;
+    let opts = RaceFinishOptions {
+        bracket_race: race,
+        info,
+        player_1,
+        player_1_result: results.player_1_result,
+        player_2,
+        player_2_result: results.player_2_result,
+        racetime_gg_url: results.racetime_gg_url,
+        channel_id: channel_config.match_results,
+        force_update: results.force,
+    };
+    trigger_race_finish(opts, conn, client, channel_config)
+        .await
+        .map_err(|e| match e {
+            RaceFinishError::BracketRaceStateError(BracketRaceStateError::InvalidState(_, _)) => {
+                ApiError::InvalidRequest(
+                    "That race is already finished. Set `force` to change its results.".to_string(),
+                )
+            }
+            RaceFinishError::DatabaseError(e) => e.into(),
+            e => ApiError::InvalidRequest(e.to_string()),
+        })?;
+    let race = BracketRace::get_by_id(race_id, conn)?;
+    audit.after(&race).record(conn);
+    Ok(())
+}
+
+/// reports the results of a head-to-head race, the same way `/report_race` does
+#[post("/race/<id>/results", data = "<results>")]
+async fn report_bracket_race(
+    id: i32,
+    results: Json<ApiRaceResults>,
+    admin: ApiTokenAdmin,
+    discord_state: &State<Arc<DiscordState>>,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<()> {
+    ApiResponse(
+        _report_race(
+            id,
+            results.into_inner(),
+            Some(&discord_state.discord_client),
+            &discord_state.channel_config,
+            &admin.actor(),
+            &mut db,
+        )
+        .await,
+    )
+}
+
+fn _generate_pairings(
+    bracket_id: i32,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<(), ApiError> {
+    let mut bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let audit = NewAuditEvent::new(actor, "generate_pairings", AuditEntity::Bracket, bracket.id)
+        .before(&bracket);
+    bracket.generate_pairings(conn).map_err(bracket_error)?;
+    audit.after(&bracket).record(conn);
+    Ok(())
+}
+
+#[post("/bracket/<id>/pairings")]
+async fn generate_bracket_pairings(
+    id: i32,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<()> {
+    ApiResponse(_generate_pairings(id, &admin.actor(), &mut db))
+}
+
+async fn _rollback_pairings(
+    bracket_id: i32,
+    discord: Option<(&twilight_http::Client, &ChannelConfig)>,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<usize, ApiError> {
+    let mut bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let (round, count) =
+        rollback_round(&mut bracket, conn, discord)
+            .await
+            .map_err(|e| match e {
+                NMGLeagueBotError::BracketError(e) => bracket_error(e),
+                e => e.into(),
+            })?;
+    NewAuditEvent::new(actor, "rollback_round", AuditEntity::BracketRound, round.id)
+        .before(&round)
+        .after(&json!({ "bracket_id": bracket.id, "races_deleted": count }))
+        .record(conn);
+    Ok(count)
+}
+
+/// deletes a bracket's latest round, if none of it has been played. Returns the number of races
+/// that were deleted
+#[delete("/bracket/<id>/pairings")]
+async fn rollback_bracket_pairings(
+    id: i32,
+    admin: ApiTokenAdmin,
+    discord_state: Option<&State<Arc<DiscordState>>>,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<usize> {
+    let discord = discord_state.map(|s| (s.discord_client.as_ref(), &s.channel_config));
+    ApiResponse(_rollback_pairings(id, discord, &admin.actor(), &mut db).await)
+}
+
+/// a player's place in a bracket's standings. Points are halved, as on the website, so with the
+/// default scoring rules a win is worth 1
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+struct ApiStanding {
+    player_id: i32,
+    player_name: String,
+    points: f32,
+    opponent_points: f32,
+    /// in seconds, with forfeits counting as the bracket's forfeit time penalty. None until
+    /// they've raced
+    average_time_adjusted: Option<u32>,
+    /// in seconds, of just the races they finished
+    average_time_finished: Option<u32>,
+    byes: i32,
+    dropped: bool,
+}
+
+/// the bracket's standings, best first. Like the website, everyone is listed with no points until
+/// a round has finished.
+fn _get_standings(
+    bracket_id: i32,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<ApiStanding>, ApiError> {
+    let bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let mut players = bracket.players(conn)?;
+    let standings = match bracket.standings(conn) {
+        Ok(s) => s,
+        // the bracket hasn't started
+        Err(BracketError::InvalidState) => vec![],
+        Err(e) => {
+            return Err(bracket_error(e));
+        }
+    };
+    if standings.is_empty() {
+        players.sort_by_key(|p| p.id);
+        return Ok(players
+            .into_iter()
+            .map(|p| ApiStanding {
+                player_id: p.id,
+                player_name: p.name,
+                points: 0.0,
+                opponent_points: 0.0,
+                average_time_adjusted: None,
+                average_time_finished: None,
+                byes: 0,
+                dropped: false,
+            })
+            .collect());
+    }
+    let mut names: HashMap<i32, String> = players.into_iter().map(|p| (p.id, p.name)).collect();
+    let seconds = |avg: f32| if avg > 0.0 { Some(avg as u32) } else { None };
+    Ok(standings
+        .iter()
+        .map(|s| ApiStanding {
+            player_id: s.id,
+            player_name: names.remove(&s.id).unwrap_or("Unknown".to_string()),
+            points: (s.points as f32) / 2.0,
+            opponent_points: (s.opponent_points as f32) / 2.0,
+            average_time_adjusted: seconds(s.avg_time_adjusted()),
+            average_time_finished: seconds(s.avg_time_finished()),
+            byes: s.byes,
+            dropped: s.dropped,
+        })
+        .collect())
+}
+
+#[get("/bracket/<id>/standings")]
+async fn get_bracket_standings(
+    id: i32,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<Vec<ApiStanding>> {
+    ApiResponse(_get_standings(id, &mut db))
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiRound {
+    round_num: i32,
+    /// the round's pairings
+    races: Vec<ApiRace>,
+    /// ids of the players with a bye this round
+    byes: Vec<i32>,
+}
+
+/// every round of the bracket so far, first round first
+fn _get_rounds(bracket_id: i32, conn: &mut SqliteConnection) -> Result<Vec<ApiRound>, ApiError> {
+    use crate::schema::{bracket_race_infos, bracket_races, bracket_rounds};
+    use diesel::prelude::*;
+
+    let bracket = Bracket::get_by_id(bracket_id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let races = bracket_races::table
+        .inner_join(bracket_rounds::table)
+        .left_join(bracket_race_infos::table)
+        .select((
+            bracket_races::all_columns,
+            bracket_race_infos::all_columns.nullable(),
+            bracket_rounds::all_columns,
+        ))
+        .filter(bracket_races::bracket_id.eq(bracket.id))
+        .order(bracket_races::id.asc())
+        .load::<(BracketRace, Option<BracketRaceInfo>, BracketRound)>(conn)?;
+    let mut races_by_round =
+        db_objs_to_api_objs::<_, ApiRace>(with_games_and_entrants(races, conn)?)?
+            .into_iter()
+            .into_group_map_by(|r| r.round);
+    let mut byes_by_round = bracket
+        .byes(conn)?
+        .into_iter()
+        .into_group_map_by(|b| b.round_id);
+    Ok(bracket
+        .rounds(conn)?
+        .into_iter()
+        .map(|round| ApiRound {
+            round_num: round.round_num,
+            races: races_by_round.remove(&round.round_num).unwrap_or_default(),
+            byes: byes_by_round
+                .remove(&round.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|b| b.player_id)
+                .collect(),
+        })
+        .collect())
+}
+
+#[get("/bracket/<id>/rounds")]
+async fn get_bracket_rounds(id: i32, mut db: ConnectionWrapper<'_>) -> ApiResponse<Vec<ApiRound>> {
+    ApiResponse(_get_rounds(id, &mut db))
+}
+
+/// callers can only revoke their own tokens; anyone else's might as well not exist
+fn _revoke_token(
+    token_id: i32,
+    caller: &ApiToken,
+    conn: &mut SqliteConnection,
+) -> Result<(), ApiError> {
+    let mut token = ApiToken::get_by_id(token_id, conn)
+        .optional()?
+        .filter(|t| t.discord_id == caller.discord_id)
+        .ok_or(ApiError::NotFound)?;
+    let audit = NewAuditEvent::new(
+        &Actor::ApiToken(caller.id),
+        "revoke_api_token",
+        AuditEntity::ApiToken,
+        token.id,
+    )
+    .before(&token);
+    token.revoke();
+    token.update(conn)?;
+    audit.after(&token).record(conn);
+    Ok(())
+}
+
+#[delete("/tokens/<id>")]
+async fn revoke_token(
+    id: i32,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<()> {
+    ApiResponse(_revoke_token(id, &admin.token, &mut db))
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiNewWebhookSubscription {
+    url: String,
+    events: Vec<LeagueEventKind>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiWebhookSubscription {
+    id: i32,
+    url: String,
+    events: Vec<LeagueEventKind>,
+    created: i64,
+    active: bool,
+    /// only ever returned when the subscription is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+}
+
+impl TryFrom<WebhookSubscription> for ApiWebhookSubscription {
+    type Error = serde_json::Error;
+
+    fn try_from(value: WebhookSubscription) -> Result<Self, Self::Error> {
+        Ok(Self {
+            events: value.events()?,
+            id: value.id,
+            url: value.url,
+            created: value.created,
+            active: value.active,
+            secret: None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct ApiWebhookDelivery {
+    id: i32,
+    event: LeagueEventKind,
+    state: DeliveryState,
+    payload: String,
+    attempts: i32,
+    next_attempt: Option<i64>,
+    last_status_code: Option<i32>,
+    last_error: Option<String>,
+    created: i64,
+    delivered: Option<i64>,
+}
+
+impl TryFrom<WebhookDelivery> for ApiWebhookDelivery {
+    type Error = serde_json::Error;
+
+    fn try_from(value: WebhookDelivery) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event: value.event()?,
+            state: value.state()?,
+            id: value.id,
+            payload: value.payload,
+            attempts: value.attempts,
+            next_attempt: value.next_attempt,
+            last_status_code: value.last_status_code,
+            last_error: value.last_error,
+            created: value.created,
+            delivered: value.delivered,
+        })
+    }
+}
+
+impl Paginated for ApiWebhookSubscription {
+    const SORT_FIELDS: &'static [&'static str] = &["id"];
+
+    fn sort_key(&self, _field: &str) -> Value {
+        json!(self.id)
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+impl Paginated for ApiWebhookDelivery {
+    const SORT_FIELDS: &'static [&'static str] = &["id"];
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+
+    fn sort_key(&self, _field: &str) -> Value {
+        json!(self.id)
+    }
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+}
+
+fn _create_webhook(
+    new: ApiNewWebhookSubscription,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<ApiWebhookSubscription, ApiError> {
+    match reqwest::Url::parse(&new.url) {
+        Ok(u) if u.scheme() == "https" || u.scheme() == "http" => {}
+        _ => {
+            return Err(ApiError::InvalidRequest(format!(
+                "Invalid webhook URL {}",
+                new.url
+            )));
+        }
+    }
+    if new.events.is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "Webhooks must subscribe to at least one event".to_string(),
+        ));
+    }
+    let (sub, secret) = NewWebhookSubscription::new(new.url, &new.events);
+    let mut created = ApiWebhookSubscription::try_from(sub.save(conn)?)?;
+    NewAuditEvent::new(
+        actor,
+        "create_webhook",
+        AuditEntity::WebhookSubscription,
+        created.id,
+    )
+    .after(&created)
+    .record(conn);
+    created.secret = Some(secret);
+    Ok(created)
+}
+
+#[post("/webhooks", data = "<subscription>")]
+async fn create_webhook(
+    subscription: Json<ApiNewWebhookSubscription>,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<ApiWebhookSubscription> {
+    ApiResponse(_create_webhook(
+        subscription.into_inner(),
+        &admin.actor(),
+        &mut db,
+    ))
+}
+
+fn _get_webhooks(
+    page: PageParams,
+    conn: &mut SqliteConnection,
+) -> Result<Page<ApiWebhookSubscription>, ApiError> {
+    let mut invalid = vec![];
+    let pagination = checked(
+        page.validate::<ApiWebhookSubscription>(&mut invalid),
+        invalid,
+    )?;
+    Ok(pagination.apply(db_objs_to_api_objs(WebhookSubscription::active(conn)?)?))
+}
+
+#[get("/webhooks?<page..>")]
+async fn get_webhooks(
+    page: PageParams,
+    _admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiWebhookSubscription> {
+    ApiPage(_get_webhooks(page, &mut db))
+}
+
+fn _deactivate_webhook(
+    id: i32,
+    actor: &Actor,
+    conn: &mut SqliteConnection,
+) -> Result<(), ApiError> {
+    let mut sub = WebhookSubscription::get_by_id(id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    let audit = NewAuditEvent::new(
+        actor,
+        "deactivate_webhook",
+        AuditEntity::WebhookSubscription,
+        sub.id,
+    )
+    .before(&sub);
+    sub.deactivate();
+    sub.update(conn)?;
+    audit.after(&sub).record(conn);
+    Ok(())
+}
+
+#[delete("/webhooks/<id>")]
+async fn deactivate_webhook(
+    id: i32,
+    admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<()> {
+    ApiResponse(_deactivate_webhook(id, &admin.actor(), &mut db))
+}
+
+fn _get_webhook_deliveries(
+    id: i32,
+    page: PageParams,
+    conn: &mut SqliteConnection,
+) -> Result<Page<ApiWebhookDelivery>, ApiError> {
+    let mut invalid = vec![];
+    let pagination = checked(page.validate::<ApiWebhookDelivery>(&mut invalid), invalid)?;
+    let sub = WebhookSubscription::get_by_id(id, conn)
+        .optional()?
+        .ok_or(ApiError::NotFound)?;
+    Ok(pagination.apply(db_objs_to_api_objs(sub.deliveries(conn)?)?))
+}
+
+#[get("/webhooks/<id>/deliveries?<page..>")]
+async fn get_webhook_deliveries(
+    id: i32,
+    page: PageParams,
+    _admin: ApiTokenAdmin,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiWebhookDelivery> {
+    ApiPage(_get_webhook_deliveries(id, page, &mut db))
+}
+
+#[get("/players?<player_id>&<page..>")]
+async fn get_players(
+    player_id: Vec<String>,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<Player> {
+    use diesel::prelude::*;
+    use nmg_league_bot::schema::players;
+    let mut invalid = vec![];
+    let player_id = parse_repeated_param::<i32>("player_id", &player_id, &mut invalid);
+    let pagination = match checked(page.validate::<Player>(&mut invalid), invalid) {
+        Ok(p) => p,
+        Err(e) => {
+            return ApiPage(Err(e));
+        }
+    };
+    let res: Result<Vec<Player>, _> = if player_id.is_empty() {
+        players::table.load(db.deref_mut())
+    } else {
+        players::table
+            .filter(players::id.eq_any(player_id))
+            .load(db.deref_mut())
+    };
+
+    ApiPage(res.map(|p| pagination.apply(p)).map_err(From::from))
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
+struct ApiRating {
+    player_id: i32,
+    rating: f64,
+    deviation: f64,
+    volatility: f64,
+}
+
+impl Paginated for ApiRating {
+    const SORT_FIELDS: &'static [&'static str] = &["rating", "player_id"];
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+
+    fn sort_key(&self, field: &str) -> Value {
+        match field {
+            "rating" => json!(self.rating),
+            _ => json!(self.player_id),
+        }
+    }
+
+    fn id(&self) -> i64 {
+        self.player_id as i64
+    }
+}
+
+impl From<PlayerRating> for ApiRating {
+    fn from(r: PlayerRating) -> Self {
+        Self {
+            player_id: r.player_id,
+            rating: r.rating,
+            deviation: r.deviation,
+            volatility: r.volatility,
+        }
+    }
+}
+
+/// everyone's current rating (filtered to `player_ids`, if there are any), best first unless
+/// `page` says otherwise
+fn get_ratings(
+    player_ids: Vec<String>,
+    page: PageParams,
+    db: &mut SqliteConnection,
+) -> Result<Page<ApiRating>, ApiError> {
+    let mut invalid = vec![];
+    let player_ids = parse_repeated_param::<i32>("player_id", &player_ids, &mut invalid);
+    let pagination = checked(page.validate::<ApiRating>(&mut invalid), invalid)?;
+    let ratings = PlayerRating::current(db)?
+        .into_values()
+        .filter(|r| player_ids.is_empty() || player_ids.contains(&r.player_id))
+        .map(ApiRating::from)
+        .collect();
+    Ok(pagination.apply(ratings))
+}
+
+#[get("/ratings?<player_id>&<page..>")]
+async fn ratings(
+    player_id: Vec<String>,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiRating> {
+    ApiPage(get_ratings(player_id, page, &mut db))
+}
+
+fn get_rating_history(
+    player_id: i32,
+    page: PageParams,
+    db: &mut SqliteConnection,
+) -> Result<Page<RatingHistoryEntry>, ApiError> {
+    let mut invalid = vec![];
+    let pagination = checked(page.validate::<RatingHistoryEntry>(&mut invalid), invalid)?;
+    Ok(pagination.apply(PlayerRating::history_for_player(player_id, db)?))
+}
+
+#[get("/player/<id>/rating_history?<page..>")]
+async fn rating_history(
+    id: i32,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<RatingHistoryEntry> {
+    ApiPage(get_rating_history(id, page, &mut db))
+}
+
+/// the two players' head to head with one page of their races. The record is always over all of
+/// them.
+fn get_head_to_head(
+    player_1_id: i32,
+    player_2_id: i32,
+    page: PageParams,
+    db: &mut SqliteConnection,
+) -> Result<(HeadToHead, Option<String>), ApiError> {
+    let mut invalid = vec![];
+    if player_1_id == player_2_id {
+        invalid.push(InvalidParam::new(
+            "player_2_id",
+            "Must be a different player from player_1_id",
+        ));
+    }
+    let pagination = checked(page.validate::<HeadToHeadRace>(&mut invalid), invalid)?;
+    for id in [player_1_id, player_2_id] {
+        if Player::get_by_id(id, db)?.is_none() {
+            return Err(ApiError::NotFound);
+        }
+    }
+    let mut h2h = head_to_head(player_1_id, player_2_id, db)?;
+    let page = pagination.apply(std::mem::take(&mut h2h.races));
+    h2h.races = page.items;
+    Ok((h2h, page.next_cursor))
+}
+
+#[get("/head_to_head?<player_1_id>&<player_2_id>&<page..>")]
+async fn get_head_to_head_races(
+    player_1_id: i32,
+    player_2_id: i32,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPageIn<HeadToHead> {
+    ApiPageIn(get_head_to_head(player_1_id, player_2_id, page, &mut db))
+}
+
+#[get("/stats")]
+async fn get_stats(
+    cache: &State<StatsCache>,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiResponse<Statistics> {
+    ApiResponse(
+        cache
+            .get(&mut db)
+            .await
+            .map(|s| s.as_ref().clone())
+            .map_err(From::from),
+    )
+}
+
+fn db_objs_to_api_objs<DB, API>(db_objs: Vec<DB>) -> Result<Vec<API>, ApiError>
+where
+    API: TryFrom<DB>,
+    // I couldn't figure this out on my own, but the compiler explained it.
+    // The compiler actually gave `serde_json::Error: From<<API as TryFrom<DB>>::Error>`,
+    // but we can be a bit more generic and accept any NMGLeagueBotError.
+    NMGLeagueBotError: From<<API as TryFrom<DB>>::Error>,
+{
+    let collection = Vec::with_capacity(db_objs.len());
+    let converted: Result<Vec<API>, ApiError> =
+        db_objs.into_iter().try_fold(collection, |mut coll, b| {
+            let api_bracket = API::try_from(b)?;
+            coll.push(api_bracket);
+            Ok(coll)
+        });
+    converted
+}
+
+#[get("/season/<ordinal>/brackets?<page..>")]
+async fn get_season_brackets(
+    ordinal: i32,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiBracket> {
+    fn get_brackets(ordinal: i32, conn: &mut SqliteConnection) -> Result<Vec<Bracket>, ApiError> {
+        use crate::schema::{brackets, seasons};
+        use diesel::prelude::*;
+        Ok(brackets::table
+            .inner_join(seasons::table)
+            .filter(seasons::ordinal.eq(ordinal))
+            .select(brackets::all_columns)
+            .load(conn)?)
+    }
+
+    let mut invalid = vec![];
+    ApiPage(
+        checked(page.validate::<ApiBracket>(&mut invalid), invalid).and_then(|pagination| {
+            get_brackets(ordinal, &mut db)
+                .and_then(db_objs_to_api_objs)
+                .map(|brackets| pagination.apply(brackets))
+        }),
+    )
+}
+
+/// everything an [ApiRace] is made of
+type RaceParts = (
+    BracketRace,
+    Option<BracketRaceInfo>,
+    BracketRound,
+    Vec<BracketRaceGame>,
+    Vec<BracketRaceEntrant>,
+);
+
+/// looks up the games and entrants of `races`
+fn with_games_and_entrants(
+    races: Vec<(BracketRace, Option<BracketRaceInfo>, BracketRound)>,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<RaceParts>, ApiError> {
+    use crate::schema::bracket_race_games;
+    use diesel::prelude::*;
+
+    let race_ids = races.iter().map(|r| r.0.id).collect::<Vec<_>>();
+    let mut entrants = BracketRaceEntrant::for_races(&race_ids, conn)?;
+    let mut games = bracket_race_games::table
+        .filter(bracket_race_games::bracket_race_id.eq_any(race_ids))
+        .order(bracket_race_games::game_num.asc())
+        .load::<BracketRaceGame>(conn)?
+        .into_iter()
+        .into_group_map_by(|g| g.bracket_race_id);
+    Ok(races
+        .into_iter()
+        .map(|(race, info, round)| {
+            let race_games = games.remove(&race.id).unwrap_or_default();
+            let race_entrants = entrants.remove(&race.id).unwrap_or_default();
+            (race, info, round, race_games, race_entrants)
+        })
+        .collect())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get(
+    "/season/<ordinal>/races?<state>&<bracket_id>&<round>&<player_id>&<scheduled_after>&<scheduled_before>&<restreamed>&<page..>"
+)]
+async fn get_season_races(
+    ordinal: i32,
+    state: Option<String>,
+    bracket_id: Option<String>,
+    round: Option<String>,
+    player_id: Option<String>,
+    scheduled_after: Option<String>,
+    scheduled_before: Option<String>,
+    restreamed: Option<String>,
+    page: PageParams,
+    version: ApiVersion,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiRace> {
+    let mut invalid = vec![];
+    let state = parse_enum_param::<BracketRaceState>("state", state, version, &mut invalid);
+    let filters = RaceFilters::parse(
+        bracket_id,
+        round,
+        player_id,
+        scheduled_after,
+        scheduled_before,
+        restreamed,
+        &mut invalid,
+    );
+    let pagination = match checked(page.validate::<ApiRace>(&mut invalid), invalid) {
+        Ok(p) => p,
+        Err(e) => {
+            return ApiPage(Err(e));
+        }
+    };
+
+    let _get_races = |conn: &mut SqliteConnection| -> Result<Vec<RaceParts>, ApiError> {
+        use crate::schema::{bracket_race_infos, bracket_races, bracket_rounds, brackets, seasons};
+        use diesel::prelude::*;
+
+        let mut q = bracket_races::table
+            .inner_join(bracket_rounds::table)
+            .inner_join(brackets::table.inner_join(seasons::table))
+            .left_join(bracket_race_infos::table)
+            .select((
+                bracket_races::all_columns,
+                bracket_race_infos::all_columns.nullable(),
+                bracket_rounds::all_columns,
+            ))
+            .filter(seasons::ordinal.eq(ordinal))
+            .into_boxed();
+
+        if let Some(state) = &state {
+            // states are stored serialized
+            q = q.filter(bracket_races::state.eq(serde_json::to_string(state)?));
+        }
+        if let Some(bracket_id) = filters.bracket_id {
+            q = q.filter(bracket_races::bracket_id.eq(bracket_id));
+        }
+        let races = q.load::<(BracketRace, Option<BracketRaceInfo>, BracketRound)>(conn)?;
+        Ok(with_games_and_entrants(races, conn)?
+            .into_iter()
+            .filter(|(race, info, round, _, entrants)| {
+                filters.matches(race, info.as_ref(), round, entrants)
+            })
+            .collect())
+    };
+    let data = _get_races(&mut db);
+
+    ApiPage(
+        data.and_then(db_objs_to_api_objs)
+            .map(|races| pagination.apply(races)),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+#[get(
+    "/season/<ordinal>/commentator_signups?<bracket_race_id>&<bracket_id>&<round>&<player_id>&<scheduled_after>&<scheduled_before>&<restreamed>&<page..>"
+)]
+async fn get_season_commentator_signups(
+    ordinal: i32,
+    bracket_race_id: Vec<i32>,
+    bracket_id: Option<String>,
+    round: Option<String>,
+    player_id: Option<String>,
+    scheduled_after: Option<String>,
+    scheduled_before: Option<String>,
+    restreamed: Option<String>,
+    page: PageParams,
+    mut db: ConnectionWrapper<'_>,
+) -> ApiPage<ApiCommentatorSignup> {
+    let mut invalid = vec![];
+    let filters = RaceFilters::parse(
+        bracket_id,
+        round,
+        player_id,
+        scheduled_after,
+        scheduled_before,
+        restreamed,
+        &mut invalid,
+    );
+    let pagination = match checked(page.validate::<ApiCommentatorSignup>(&mut invalid), invalid) {
+        Ok(p) => p,
+        Err(e) => {
+            return ApiPage(Err(e));
+        }
+    };
+
+    let _get_comms = |conn: &mut SqliteConnection| -> Result<
+        Vec<(CommentatorSignup, BracketRaceInfo, BracketRace)>,
+        ApiError,
+    > {
+        use crate::schema::{
+            bracket_race_infos, bracket_races, bracket_rounds, brackets, commentator_signups,
+            seasons,
+        };
+        use diesel::prelude::*;
+
+        let mut q = commentator_signups::table
+            .inner_join(
+                bracket_race_infos::table.inner_join(
+                    bracket_races::table
+                        .inner_join(brackets::table.inner_join(seasons::table))
+                        .inner_join(bracket_rounds::table),
+                ),
+            )
+            .select((
+                commentator_signups::all_columns,
+                bracket_race_infos::all_columns,
+                bracket_races::all_columns,
+                bracket_rounds::all_columns,
             ))
             .filter(seasons::ordinal.eq(ordinal))
             .into_boxed();
@@ -343,27 +1914,205 @@ async fn get_season_commentator_signups(
         if !bracket_race_id.is_empty() {
             q = q.filter(bracket_races::id.eq_any(bracket_race_id));
         }
-        Ok(q.load::<(CommentatorSignup, BracketRaceInfo, BracketRace)>(conn)?)
+        if let Some(bracket_id) = filters.bracket_id {
+            q = q.filter(bracket_races::bracket_id.eq(bracket_id));
+        }
+        let signups = q.load::<(
+            CommentatorSignup,
+            BracketRaceInfo,
+            BracketRace,
+            BracketRound,
+        )>(conn)?;
+        let entrants = BracketRaceEntrant::for_races(
+            &signups.iter().map(|s| s.2.id).collect::<Vec<_>>(),
+            conn,
+        )?;
+        Ok(signups
+            .into_iter()
+            .filter(|(_, info, race, round)| {
+                let race_entrants = entrants
+                    .get(&race.id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                filters.matches(race, Some(info), round, race_entrants)
+            })
+            .map(|(signup, info, race, _)| (signup, info, race))
+            .collect())
     };
     let data = _get_comms(&mut db);
 
-    ApiResponse(data.and_then(db_objs_to_api_objs))
+    ApiPage(
+        data.and_then(db_objs_to_api_objs)
+            .map(|signups| pagination.apply(signups)),
+    )
+}
+
+/// the routes of both versions of the API
+fn routes() -> Vec<Route> {
+    rocket::routes![
+        qualifiers,
+        delete_qualifier,
+        drop_player,
+        create_season,
+        create_season_bracket,
+        add_bracket_player,
+        schedule_bracket_race,
+        report_bracket_race,
+        generate_bracket_pairings,
+        rollback_bracket_pairings,
+        get_bracket_standings,
+        get_bracket_rounds,
+        revoke_token,
+        create_webhook,
+        get_webhooks,
+        deactivate_webhook,
+        get_webhook_deliveries,
+        get_players,
+        ratings,
+        rating_history,
+        get_head_to_head_races,
+        get_stats,
+        get_season_brackets,
+        get_season_races,
+        get_season_commentator_signups
+    ]
 }
 
-pub fn build_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
-    rocket.mount(
-        "/api/v1",
-        rocket::routes![
-            qualifiers,
-            delete_qualifier,
-            get_players,
-            get_season_brackets,
-            get_season_races,
-            get_season_commentator_signups
-        ],
+/// the type of every path and query parameter the routes take
+fn param_schema(name: &str, generator: &mut SchemaGenerator) -> Option<Schema> {
+    Some(match name {
+        "id" | "ordinal" | "player_id" | "player_1_id" | "player_2_id" | "bracket_id"
+        | "bracket_race_id" | "round" => generator.subschema_for::<i32>(),
+        "scheduled_after" | "scheduled_before" => generator.subschema_for::<i64>(),
+        "restreamed" => generator.subschema_for::<bool>(),
+        "state" => generator.subschema_for::<BracketRaceState>(),
+        _ => return None,
+    })
+}
+
+/// what the OpenAPI document says about each route
+fn endpoints() -> Vec<Endpoint> {
+    vec![
+        Endpoint::new("qualifiers", "A season's qualifiers").paginated::<ApiQualifier>(),
+        Endpoint::new("delete_qualifier", "Deletes a qualifier that's still open")
+            .auth(Auth::AdminSession),
+        Endpoint::new(
+            "drop_player",
+            "Drops a player out of a bracket. Returns how many of their races were forfeited or cancelled",
+        )
+        .auth(Auth::AdminSession)
+        .body::<ApiDropPlayer>()
+        .returns::<usize>(),
+        Endpoint::new("create_season", "Creates a season")
+            .auth(Auth::ApiToken)
+            .body::<ApiNewSeason>()
+            .returns::<Season>(),
+        Endpoint::new("create_season_bracket", "Creates a bracket in a season")
+            .auth(Auth::ApiToken)
+            .body::<ApiNewBracket>()
+            .returns::<ApiBracket>(),
+        Endpoint::new("add_bracket_player", "Adds a player to a bracket")
+            .auth(Auth::ApiToken)
+            .body::<ApiBracketEntry>(),
+        Endpoint::new("schedule_bracket_race", "Schedules (or reschedules) a race")
+            .auth(Auth::ApiToken)
+            .body::<ApiSchedule>(),
+        Endpoint::new("report_bracket_race", "Reports the results of a head-to-head race")
+            .auth(Auth::ApiToken)
+            .body::<ApiRaceResults>(),
+        Endpoint::new("generate_bracket_pairings", "Pairs a bracket's next round")
+            .auth(Auth::ApiToken),
+        Endpoint::new(
+            "rollback_bracket_pairings",
+            "Deletes a bracket's latest round, if none of it has been played, so it can be paired again. \
+            Returns how many races were deleted",
+        )
+        .auth(Auth::ApiToken)
+        .returns::<usize>(),
+        Endpoint::new(
+            "get_bracket_standings",
+            "A bracket's standings, best first. Everyone has 0 points until a round finishes",
+        )
+        .returns::<Vec<ApiStanding>>(),
+        Endpoint::new(
+            "get_bracket_rounds",
+            "A bracket's rounds, with their pairings and byes",
+        )
+        .returns::<Vec<ApiRound>>(),
+        Endpoint::new("revoke_token", "Revokes one of your API tokens").auth(Auth::ApiToken),
+        Endpoint::new(
+            "create_webhook",
+            "Subscribes a URL to league events. This is the only time the secret is returned",
+        )
+        .auth(Auth::ApiToken)
+        .body::<ApiNewWebhookSubscription>()
+        .returns::<ApiWebhookSubscription>(),
+        Endpoint::new("get_webhooks", "Active webhook subscriptions")
+            .auth(Auth::ApiToken)
+            .paginated::<ApiWebhookSubscription>(),
+        Endpoint::new("deactivate_webhook", "Unsubscribes a webhook").auth(Auth::ApiToken),
+        Endpoint::new("get_webhook_deliveries", "A webhook's deliveries, newest first")
+            .auth(Auth::ApiToken)
+            .paginated::<ApiWebhookDelivery>(),
+        Endpoint::new("get_players", "Players")
+            .paginated::<Player>()
+            .repeated(&["player_id"]),
+        Endpoint::new("ratings", "Everyone's current rating")
+            .paginated::<ApiRating>()
+            .repeated(&["player_id"]),
+        Endpoint::new(
+            "rating_history",
+            "A player's rating after every bracket round they raced in",
+        )
+        .paginated::<RatingHistoryEntry>(),
+        Endpoint::new(
+            "get_head_to_head_races",
+            "Every bracket race two players have been in together. The races are paginated; the \
+             record covers all of them",
+        )
+        .paginated_in::<HeadToHead, HeadToHeadRace>()
+        .required(&["player_1_id", "player_2_id"]),
+        Endpoint::new("get_stats", "League statistics").returns::<Statistics>(),
+        Endpoint::new("get_season_brackets", "A season's brackets").paginated::<ApiBracket>(),
+        Endpoint::new("get_season_races", "A season's races").paginated::<ApiRace>(),
+        Endpoint::new(
+            "get_season_commentator_signups",
+            "Commentator signups for a season's races",
+        )
+        .paginated::<ApiCommentatorSignup>()
+        .repeated(&["bracket_race_id"]),
+    ]
+}
+
+fn v2_description() -> ApiDescription {
+    ApiDescription {
+        title: "NMG League API",
+        version: "2",
+        base: V2_BASE,
+        routes: routes(),
+        endpoints: endpoints(),
+        param: param_schema,
+        error: |generator| generator.subschema_for::<ApiErrorBody>(),
+    }
+}
+
+#[get("/openapi.json")]
+async fn openapi_spec() -> ApiResponse<Value> {
+    ApiResponse(
+        v2_description()
+            .spec()
+            .map_err(|e| NMGLeagueBotError::Other(e).into()),
     )
 }
 
+pub fn build_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
+    rocket
+        .mount(V1_BASE, routes())
+        .mount(V2_BASE, routes())
+        .mount(V2_BASE, rocket::routes![openapi_spec])
+        .register(V2_BASE, rocket::catchers![v2_catcher])
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -374,14 +2123,30 @@ mod tests {
     use diesel::prelude::*;
     use diesel::SqliteConnection;
     use itertools::Itertools;
+    use nmg_league_bot::models::api_tokens::{ApiToken, NewApiToken};
+    use nmg_league_bot::models::audit_events::{Actor, AuditEntity, AuditEvent, AuditFilter};
     use nmg_league_bot::models::bracket_races;
     use nmg_league_bot::models::bracket_races::BracketRace;
     use nmg_league_bot::models::bracket_races::NewBracketRace;
+    use nmg_league_bot::models::bracket_races::Outcome;
+    use nmg_league_bot::models::bracket_races::PlayerResult;
     use nmg_league_bot::models::bracket_rounds::NewBracketRound;
     use nmg_league_bot::models::brackets::BracketType;
     use nmg_league_bot::models::brackets::NewBracket;
+    use nmg_league_bot::models::brackets::{Bracket, BracketOptions};
+    use nmg_league_bot::models::head_to_head::HeadToHead;
     use nmg_league_bot::models::player_bracket_entries::NewPlayerBracketEntry;
+    use nmg_league_bot::models::qualifer_submission::{NewQualifierSubmission, ReviewState};
+    use nmg_league_bot::models::qualifier_rules::{QualifierAggregation, QualifierRules};
+    use nmg_league_bot::models::ratings::{recompute_ratings, RatingHistoryEntry};
+    use nmg_league_bot::models::scoring::ScoringRules;
     use nmg_league_bot::models::season::NewSeason;
+    use nmg_league_bot::models::stats::Statistics;
+    use nmg_league_bot::models::webhooks::{
+        queue_event, DeliveryState, LeagueEvent, LeagueEventKind, WebhookSubscription,
+    };
+    use nmg_league_bot::test_utils::bracket_with_players;
+    use nmg_league_bot::ChannelConfig;
     use nmg_league_bot::{
         db::{run_migrations, DieselConnectionManager},
         models::{
@@ -390,14 +2155,28 @@ mod tests {
         },
         schema::players,
     };
+    use rocket::http::Header;
     use rocket::local::asynchronous::Client;
     use twilight_model::id::Id;
 
     use crate::web::api::ApiBracket;
     use crate::web::api::ApiCommentatorSignup;
+    use crate::web::api::ApiQualifier;
     use crate::web::api::ApiRace;
+    use crate::web::api::ApiRating;
+    use crate::web::api::{
+        _add_bracket_player, _create_bracket, _create_season, _generate_pairings, _report_race,
+        _revoke_token, ApiBracketEntry, ApiError, ApiNewBracket, ApiNewSeason, ApiRaceResults,
+    };
+    use crate::web::api::{
+        _create_webhook, _deactivate_webhook, _get_webhook_deliveries, ApiNewWebhookSubscription,
+    };
+    use crate::web::api::{ApiRound, ApiStanding};
 
-    use super::build_rocket;
+    use super::{build_rocket, v2_description, ApiErrorBody, ApiErrorKind};
+    use crate::web::pagination::{InvalidParam, NEXT_CURSOR_HEADER};
+    use crate::web::stats_cache::StatsCache;
+    use serde_json::{json, Value};
 
     /// this builds a rocket instance. it won't be a "full" rocket instance, the idea is to have a minimal one
     /// for testing just the API. this is not fully realistic, though so maybe that's a mistake...?
@@ -410,7 +2189,9 @@ mod tests {
             let mut db = p.get().await?;
             run_migrations(&mut db)?;
         }
-        let rocket = build_rocket(rocket::build()).manage(p);
+        let rocket = build_rocket(rocket::build())
+            .manage(p)
+            .manage(StatsCache::new());
         let client = Client::tracked(rocket).await?;
         Ok(client)
     }
@@ -496,105 +2277,571 @@ mod tests {
         })
         .await?;
 
-        let mut players_to_find: HashSet<Player> = HashSet::from_iter(players.clone());
-        assert!(players_to_find.remove(&players[1]));
-        let qs = players_to_find
-            .iter()
-            .map(|p| format!("player_id={}", p.id))
-            .join("&");
-        let url = format!("/api/v1/players?{qs}");
-        // this is lazy, i should get IDs properly
-        let resp = c.get(url).dispatch().await;
-
+        let mut players_to_find: HashSet<Player> = HashSet::from_iter(players.clone());
+        assert!(players_to_find.remove(&players[1]));
+        let qs = players_to_find
+            .iter()
+            .map(|p| format!("player_id={}", p.id))
+            .join("&");
+        let url = format!("/api/v1/players?{qs}");
+        // this is lazy, i should get IDs properly
+        let resp = c.get(url).dispatch().await;
+
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<Vec<Player>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(players_to_find, HashSet::from_iter(parsed.into_iter()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_brackets() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let s = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
+            NewBracket::new(&ns, "bracket 2", BracketType::RoundRobin).save(db)?;
+            Ok(ns)
+        })
+        .await?;
+        let resp = c
+            .get(format!("/api/v1/season/{}/brackets", s.ordinal))
+            .dispatch()
+            .await;
+
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<Vec<ApiBracket>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(2, parsed.len());
+        assert_eq!(ScoringRules::default(), parsed[0].scoring_rules);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_races() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let s = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
+            let round = NewBracketRound::new(&b, 1).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            NewPlayerBracketEntry::new(&b, &p1).save(db)?;
+            NewPlayerBracketEntry::new(&b, &p2).save(db)?;
+            bracket_races::insert_bulk(&vec![NewBracketRace::new(&b, &round, &p1, &p2)], db)?;
+            Ok(ns)
+        })
+        .await?;
+
+        let unfiltered_resp = c
+            .get(format!("/api/v1/season/{}/races", s.ordinal))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, unfiltered_resp.status(),);
+        let parsed = parse_result::<Vec<ApiRace>>(&unfiltered_resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.len());
+
+        let bad_state = c
+            .get(format!("/api/v1/season/{}/races?state=foo", s.ordinal))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, bad_state.status(),);
+        let parsed = serde_json::from_str::<Value>(&bad_state.into_string().await.unwrap())?;
+        assert_eq!("Invalid parameters", parsed["Err"]);
+        assert_eq!("state", parsed["invalid_params"][0]["param"]);
+
+        // `urlencoding::encode()` urlencodes the `=` sign!
+        let new_resp = c
+            .get(format!(
+                "/api/v1/season/{}/races?state={}",
+                s.ordinal,
+                urlencoding::encode(r#""New""#)
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, new_resp.status(),);
+        let parsed = parse_result::<Vec<ApiRace>>(&new_resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.len());
+
+        let scheduled_resp = c
+            .get(format!(
+                "/api/v1/season/{}/races?state={}",
+                s.ordinal,
+                urlencoding::encode(r#""Scheduled""#)
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, scheduled_resp.status(),);
+        let parsed = parse_result::<Vec<ApiRace>>(&scheduled_resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(0, parsed.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginate_and_filter_races() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let (s, p1) = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
+            let round_1 = NewBracketRound::new(&b, 1).save(db)?;
+            let round_2 = NewBracketRound::new(&b, 2).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            let p3 = NewPlayer::new("p3", "3", None, None, None).save(db)?;
+            let p4 = NewPlayer::new("p4", "4", None, None, None).save(db)?;
+            bracket_races::insert_bulk(
+                &vec![
+                    NewBracketRace::new(&b, &round_1, &p1, &p2),
+                    NewBracketRace::new(&b, &round_1, &p3, &p4),
+                    NewBracketRace::new(&b, &round_2, &p1, &p3),
+                ],
+                db,
+            )?;
+            Ok((ns, p1))
+        })
+        .await?;
+
+        let mut seen = vec![];
+        let mut url = format!("/api/v1/season/{}/races?limit=2", s.ordinal);
+        loop {
+            let resp = c.get(url.clone()).dispatch().await;
+            assert_eq!(rocket::http::Status::Ok, resp.status(),);
+            let cursor = resp.headers().get_one(NEXT_CURSOR_HEADER).map(String::from);
+            let parsed = parse_result::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?
+                .map_err(|e| anyhow!("{e}"))?;
+            assert!(parsed.len() <= 2);
+            seen.extend(parsed.into_iter().map(|r| r.id));
+            match cursor {
+                Some(cursor) => {
+                    url = format!("/api/v1/season/{}/races?limit=2&cursor={cursor}", s.ordinal);
+                }
+                None => break,
+            }
+        }
+        assert_eq!(3, seen.len());
+        assert!(seen.windows(2).all(|w| w[0] < w[1]));
+
+        let resp = c
+            .get(format!(
+                "/api/v1/season/{}/races?round=1&player_id={}",
+                s.ordinal, p1.id
+            ))
+            .dispatch()
+            .await;
+        let parsed = parse_result::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.len());
+        assert_eq!(1, parsed[0].round);
+        assert_eq!(p1.id, parsed[0].player_1_id);
+
+        let resp = c
+            .get(format!(
+                "/api/v1/season/{}/races?round=1&restreamed=false&sort=round&order=desc",
+                s.ordinal
+            ))
+            .dispatch()
+            .await;
+        let parsed = parse_result::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(2, parsed.len());
+        assert!(parsed[0].id > parsed[1].id);
+
+        let resp = c
+            .get(format!(
+                "/api/v1/season/{}/races?round=first&limit=0&sort=player",
+                s.ordinal
+            ))
+            .dispatch()
+            .await;
+        let parsed = serde_json::from_str::<Value>(&resp.into_string().await.unwrap())?;
+        assert_eq!("Invalid parameters", parsed["Err"]);
+        let invalid =
+            serde_json::from_value::<Vec<InvalidParam>>(parsed["invalid_params"].clone())?;
+        assert_eq!(
+            vec!["round", "limit", "sort"],
+            invalid.iter().map(|i| i.param.as_str()).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_races_with_games() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let s = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::SingleElimination).save(db)?;
+            let round = NewBracketRound::new(&b, 1).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            let mut nbr = NewBracketRace::new(&b, &round, &p1, &p2);
+            nbr.best_of = 3;
+            let mut race = nbr.save(db)?;
+            race.add_game(
+                &PlayerResult::Finish(5000),
+                &PlayerResult::Finish(5100),
+                Some("https://racetime.gg/alttp/game-1".to_string()),
+                false,
+                db,
+            )?;
+            // the same room again doesn't count twice
+            let dupe = race.add_game(
+                &PlayerResult::Finish(5000),
+                &PlayerResult::Finish(5100),
+                Some("https://racetime.gg/alttp/game-1".to_string()),
+                false,
+                db,
+            )?;
+            assert!(dupe.is_none());
+            Ok(ns)
+        })
+        .await?;
+
+        let resp = c
+            .get(format!("/api/v1/season/{}/races", s.ordinal))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.len());
+        assert_eq!(3, parsed[0].best_of);
+        assert_eq!(1, parsed[0].games.len());
+        assert_eq!(Outcome::P1Win, parsed[0].games[0].outcome);
+        // one game isn't enough to win a best of 3
+        assert!(parsed[0].outcome.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_races_with_entrants() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let s = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::Group).save(db)?;
+            let round = NewBracketRound::new(&b, 1).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            let p3 = NewPlayer::new("p3", "3", None, None, None).save(db)?;
+            let mut race = bracket_races::create_placement_race(&b, &round, &[&p1, &p2, &p3], db)?;
+            race.add_placement_results(
+                &[
+                    (p1.id, PlayerResult::Finish(5100)),
+                    (p2.id, PlayerResult::Forfeit),
+                    (p3.id, PlayerResult::Finish(5000)),
+                ],
+                false,
+                db,
+            )?;
+            Ok(ns)
+        })
+        .await?;
+
+        let resp = c
+            .get(format!("/api/v1/season/{}/races", s.ordinal))
+            .dispatch()
+            .await;
         assert_eq!(rocket::http::Status::Ok, resp.status(),);
-        let parsed = parse_result::<Vec<Player>>(&resp.into_string().await.unwrap())?
+        let parsed = parse_result::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?
             .map_err(|e| anyhow!("{e}"))?;
-        assert_eq!(players_to_find, HashSet::from_iter(parsed.into_iter()));
+        assert_eq!(1, parsed.len());
+        assert!(parsed[0].placement_race);
+        let placements = parsed[0]
+            .entrants
+            .iter()
+            .map(|e| e.placement)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(2), Some(3), Some(1)], placements);
+        assert!(parsed[0].outcome.is_none());
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_get_brackets() -> anyhow::Result<()> {
+    async fn test_get_qualifiers_with_scores() -> anyhow::Result<()> {
         let c = setup().await?;
         let s = run_with_db(&c, |db| {
-            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
-            NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
-            NewBracket::new(&ns, "bracket 2", BracketType::RoundRobin).save(db)?;
+            let mut ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            ns.set_qualifier_rules(&QualifierRules {
+                aggregation: QualifierAggregation::AverageOfBest(2),
+                max_attempts: None,
+            })?;
+            ns.update(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            for time in [5300, 5000, 5100] {
+                let mut q = NewQualifierSubmission::new(&p1, &ns, time, "".to_string()).save(db)?;
+                q.review(ReviewState::Verified, None, None)?;
+                q.update(db)?;
+            }
+            // unreviewed submissions don't count towards scores
+            NewQualifierSubmission::new(&p1, &ns, 4000, "".to_string()).save(db)?;
+            NewQualifierSubmission::new(&p2, &ns, 4900, "".to_string()).save(db)?;
             Ok(ns)
         })
         .await?;
+
         let resp = c
-            .get(format!("/api/v1/season/{}/brackets", s.ordinal))
+            .get(format!("/api/v1/season/{}/qualifiers", s.ordinal))
             .dispatch()
             .await;
-
         assert_eq!(rocket::http::Status::Ok, resp.status(),);
-        let parsed = parse_result::<Vec<ApiBracket>>(&resp.into_string().await.unwrap())?
+        let parsed = parse_result::<Vec<ApiQualifier>>(&resp.into_string().await.unwrap())?
             .map_err(|e| anyhow!("{e}"))?;
-        assert_eq!(2, parsed.len());
+        let summary = parsed
+            .iter()
+            .map(|q| {
+                (
+                    q.player_name.as_str(),
+                    q.time,
+                    q.score,
+                    q.counts,
+                    q.review_state,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("p1", 4000, Some(5050), false, ReviewState::Pending),
+                ("p2", 4900, None, false, ReviewState::Pending),
+                ("p1", 5000, Some(5050), true, ReviewState::Verified),
+                ("p1", 5100, Some(5050), true, ReviewState::Verified),
+                ("p1", 5300, Some(5050), false, ReviewState::Verified),
+            ],
+            summary
+        );
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_get_races() -> anyhow::Result<()> {
+    async fn test_get_ratings() -> anyhow::Result<()> {
         let c = setup().await?;
-        let s = run_with_db(&c, |db| {
+        let (p1, p2) = run_with_db(&c, |db| {
             let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
             let b = NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
             let round = NewBracketRound::new(&b, 1).save(db)?;
             let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
             let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
-            NewPlayerBracketEntry::new(&b, &p1).save(db)?;
-            NewPlayerBracketEntry::new(&b, &p2).save(db)?;
-            bracket_races::insert_bulk(&vec![NewBracketRace::new(&b, &round, &p1, &p2)], db)?;
-            Ok(ns)
+            let p3 = NewPlayer::new("p3", "3", None, None, None).save(db)?;
+            let mut race = NewBracketRace::new(&b, &round, &p1, &p2).save(db)?;
+            race.add_results(
+                Some(&PlayerResult::Finish(5000)),
+                Some(&PlayerResult::Finish(5100)),
+                false,
+            )?;
+            race.update(db)?;
+            // unfinished races don't count
+            NewBracketRace::new(&b, &round, &p1, &p3).save(db)?;
+            recompute_ratings(db)?;
+            Ok((p1, p2))
         })
         .await?;
 
-        let unfiltered_resp = c
-            .get(format!("/api/v1/season/{}/races", s.ordinal))
+        let resp = c.get("/api/v1/ratings").dispatch().await;
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<Vec<ApiRating>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(
+            vec![p1.id, p2.id],
+            parsed.iter().map(|r| r.player_id).collect::<Vec<_>>()
+        );
+        assert!(parsed[0].rating > 1500.0);
+
+        let resp = c
+            .get(format!("/api/v1/ratings?player_id={}", p2.id))
             .dispatch()
             .await;
-        assert_eq!(rocket::http::Status::Ok, unfiltered_resp.status(),);
-        let parsed = parse_result::<Vec<ApiRace>>(&unfiltered_resp.into_string().await.unwrap())?
+        let parsed = parse_result::<Vec<ApiRating>>(&resp.into_string().await.unwrap())?
             .map_err(|e| anyhow!("{e}"))?;
         assert_eq!(1, parsed.len());
+        assert!(parsed[0].rating < 1500.0);
 
-        let bad_state = c
-            .get(format!("/api/v1/season/{}/races?state=foo", s.ordinal))
+        let resp = c.get("/api/v2/ratings?player_id=nope").dispatch().await;
+        assert_eq!(rocket::http::Status::BadRequest, resp.status());
+        let error = serde_json::from_str::<ApiErrorBody>(&resp.into_string().await.unwrap())?;
+        assert_eq!(
+            vec![InvalidParam::new("player_id", r#"Unable to parse "nope""#)],
+            error.invalid_params
+        );
+
+        let resp = c
+            .get(format!("/api/v1/player/{}/rating_history", p1.id))
             .dispatch()
             .await;
-        assert_eq!(rocket::http::Status::Ok, bad_state.status(),);
-        let parsed = parse_result::<Vec<ApiRace>>(&bad_state.into_string().await.unwrap())?;
-        assert!(parsed.is_err());
-        assert_eq!("Bad Request", parsed.err().unwrap());
+        let parsed = parse_result::<Vec<RatingHistoryEntry>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.len());
+        assert_eq!("bracket 1", parsed[0].bracket_name);
+        Ok(())
+    }
 
-        // `urlencoding::encode()` urlencodes the `=` sign!
-        let new_resp = c
+    #[tokio::test]
+    async fn test_get_head_to_head() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let (p1, p2) = run_with_db(&c, |db| {
+            let (b, players) = bracket_with_players(BracketType::Swiss, 3, db)?;
+            let (p1, p2, p3) = (&players[0], &players[1], &players[2]);
+            let round_1 = NewBracketRound::new(&b, 1).save(db)?;
+            let round_2 = NewBracketRound::new(&b, 2).save(db)?;
+            let round_3 = NewBracketRound::new(&b, 3).save(db)?;
+            let mut race = NewBracketRace::new(&b, &round_1, p1, p2).save(db)?;
+            race.add_results(
+                Some(&PlayerResult::Finish(5000)),
+                Some(&PlayerResult::Finish(5100)),
+                false,
+            )?;
+            race.update(db)?;
+            // p2 is player 1 in this one
+            let mut race = NewBracketRace::new(&b, &round_2, p2, p1).save(db)?;
+            race.add_results(
+                Some(&PlayerResult::Finish(5000)),
+                Some(&PlayerResult::Finish(5300)),
+                false,
+            )?;
+            race.update(db)?;
+            NewBracketRace::new(&b, &round_2, p1, p3).save(db)?;
+            let mut placement =
+                bracket_races::create_placement_race(&b, &round_3, &[p3, p2, p1], db)?;
+            placement.add_placement_results(
+                &[
+                    (p3.id, PlayerResult::Finish(4000)),
+                    (p2.id, PlayerResult::Finish(4500)),
+                    (p1.id, PlayerResult::Forfeit),
+                ],
+                false,
+                db,
+            )?;
+            Ok((p1.clone(), p2.clone()))
+        })
+        .await?;
+
+        let resp = c
             .get(format!(
-                "/api/v1/season/{}/races?state={}",
-                s.ordinal,
-                urlencoding::encode(r#""New""#)
+                "/api/v1/head_to_head?player_1_id={}&player_2_id={}",
+                p1.id, p2.id
             ))
             .dispatch()
             .await;
-        assert_eq!(rocket::http::Status::Ok, new_resp.status(),);
-        let parsed = parse_result::<Vec<ApiRace>>(&new_resp.into_string().await.unwrap())?
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<HeadToHead>(&resp.into_string().await.unwrap())?
             .map_err(|e| anyhow!("{e}"))?;
-        assert_eq!(1, parsed.len());
+        assert_eq!(
+            vec![
+                (1, Some(Outcome::P1Win)),
+                (2, Some(Outcome::P2Win)),
+                (3, Some(Outcome::P2Win))
+            ],
+            parsed
+                .races
+                .iter()
+                .map(|r| (r.round_num, r.outcome))
+                .collect::<Vec<_>>()
+        );
+        assert!(parsed.races[2].placement_race);
+        assert_eq!(1, parsed.record.player_1_wins);
+        assert_eq!(2, parsed.record.player_2_wins);
+        // -100 and +300; the placement race doesn't count because p1 forfeited
+        assert_eq!(Some(100.0), parsed.record.average_time_delta);
 
-        let scheduled_resp = c
+        // the races are paginated, but the record is still over all of them
+        let resp = c
             .get(format!(
-                "/api/v1/season/{}/races?state={}",
-                s.ordinal,
-                urlencoding::encode(r#""Scheduled""#)
+                "/api/v1/head_to_head?player_1_id={}&player_2_id={}&limit=2",
+                p1.id, p2.id
             ))
             .dispatch()
             .await;
-        assert_eq!(rocket::http::Status::Ok, scheduled_resp.status(),);
-        let parsed = parse_result::<Vec<ApiRace>>(&scheduled_resp.into_string().await.unwrap())?
+        assert!(resp.headers().get_one(NEXT_CURSOR_HEADER).is_some());
+        let parsed = parse_result::<HeadToHead>(&resp.into_string().await.unwrap())?
             .map_err(|e| anyhow!("{e}"))?;
-        assert_eq!(0, parsed.len());
+        assert_eq!(2, parsed.races.len());
+        assert_eq!(2, parsed.record.player_2_wins);
+
+        let resp = c
+            .get(format!(
+                "/api/v1/head_to_head?player_1_id={}&player_2_id=12345",
+                p1.id
+            ))
+            .dispatch()
+            .await;
+        let parsed = parse_result::<HeadToHead>(&resp.into_string().await.unwrap())?;
+        assert!(parsed.is_err());
+
+        let resp = c
+            .get(format!(
+                "/api/v1/head_to_head?player_1_id={}&player_2_id={}",
+                p1.id, p1.id
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::BadRequest, resp.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_stats() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let (p1, p2) = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
+            let round = NewBracketRound::new(&b, 1).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            let p3 = NewPlayer::new("p3", "3", None, None, None).save(db)?;
+            let p4 = NewPlayer::new("p4", "4", None, None, None).save(db)?;
+            let mut race = NewBracketRace::new(&b, &round, &p1, &p2).save(db)?;
+            race.add_results(
+                Some(&PlayerResult::Finish(5000)),
+                Some(&PlayerResult::Finish(5100)),
+                false,
+            )?;
+            race.update(db)?;
+            let mut info = race.info(db)?;
+            info.restream_channel = Some("https://twitch.tv/somewhere".to_string());
+            info.update(db)?;
+            info.new_commentator_signup(Id::new(11111), db)?;
+            let mut race = NewBracketRace::new(&b, &round, &p3, &p4).save(db)?;
+            race.add_results(
+                Some(&PlayerResult::Forfeit),
+                Some(&PlayerResult::Finish(5300)),
+                false,
+            )?;
+            race.update(db)?;
+            race.info(db)?.new_commentator_signup(Id::new(11111), db)?;
+            Ok((p1, p2))
+        })
+        .await?;
+
+        let resp = c.get("/api/v1/stats").dispatch().await;
+        assert_eq!(rocket::http::Status::Ok, resp.status(),);
+        let parsed = parse_result::<Statistics>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, parsed.seasons.len());
+        let season = &parsed.seasons[0];
+        assert_eq!(
+            vec![5000, 5100, 5300],
+            season
+                .fastest_times
+                .iter()
+                .map(|f| f.time)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(0.25), parsed.all_time_forfeit_rate);
+        assert!((season.round_averages[0].average_time - 15400.0 / 3.0).abs() < 0.001);
+        assert_eq!(p1.id, parsed.records[0].player_id);
+        assert_eq!(1, parsed.records[0].wins);
+        assert_eq!(
+            vec![(p1.id, 1), (p2.id, 1)],
+            parsed
+                .most_restreamed
+                .iter()
+                .map(|r| (r.player_id, r.restreams))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, parsed.commentators.len());
+        assert_eq!(2, parsed.commentators[0].signups);
+        assert_eq!(1, parsed.commentators[0].restreamed);
         Ok(())
     }
 
@@ -720,4 +2967,409 @@ mod tests {
         assert_eq!(0, grouped.len(), "{grouped:?}");
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_write_api_requires_token() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let body = r#"{"format": "Any% NMG", "rtgg_category_name": "alttp", "rtgg_goal_name": "Any% NMG"}"#;
+        let resp = c.post("/api/v1/seasons").body(body).dispatch().await;
+        assert_eq!(rocket::http::Status::Unauthorized, resp.status());
+
+        let resp = c
+            .post("/api/v1/seasons")
+            .header(Header::new("Authorization", "Bearer nope"))
+            .body(body)
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Unauthorized, resp.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_api_bracket_setup() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let admin = Actor::ApiToken(1);
+        let race = run_with_db(&c, |db| {
+            let season = _create_season(
+                ApiNewSeason {
+                    format: "Any% NMG".to_string(),
+                    rtgg_category_name: "alttp".to_string(),
+                    rtgg_goal_name: "Any% NMG".to_string(),
+                },
+                &admin,
+                db,
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+            let bad_options = _create_bracket(
+                season.ordinal,
+                ApiNewBracket {
+                    name: "swiss".to_string(),
+                    bracket_type: BracketType::Swiss,
+                    options: BracketOptions {
+                        group_size: Some(4),
+                        ..Default::default()
+                    },
+                },
+                &admin,
+                db,
+            );
+            assert!(matches!(bad_options, Err(ApiError::InvalidRequest(_))));
+            let bracket = _create_bracket(
+                season.ordinal,
+                ApiNewBracket {
+                    name: "swiss".to_string(),
+                    bracket_type: BracketType::Swiss,
+                    options: Default::default(),
+                },
+                &admin,
+                db,
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+            for (name, id) in [("p1", "1"), ("p2", "2")] {
+                let p = NewPlayer::new(name, id, None, None, None).save(db)?;
+                _add_bracket_player(bracket.id, ApiBracketEntry { player_id: p.id }, &admin, db)
+                    .map_err(|e| anyhow!("{e}"))?;
+            }
+            assert!(matches!(
+                _add_bracket_player(bracket.id, ApiBracketEntry { player_id: 1000 }, &admin, db),
+                Err(ApiError::NotFound)
+            ));
+            _generate_pairings(bracket.id, &admin, db).map_err(|e| anyhow!("{e}"))?;
+            let mut races = Bracket::get_by_id(bracket.id, db)?.bracket_races(db)?;
+            assert_eq!(1, races.len());
+            Ok(races.remove(0))
+        })
+        .await?;
+
+        let channel_config = ChannelConfig {
+            commportunities: Id::new(1),
+            sirius_inbox: Id::new(1),
+            zsr: Id::new(1),
+            commentary_discussion: Id::new(1),
+            match_results: Id::new(1),
+            admin: Id::new(1),
+        };
+        let results = || ApiRaceResults {
+            player_1_result: PlayerResult::Finish(5000),
+            player_2_result: PlayerResult::Forfeit,
+            racetime_gg_url: None,
+            force: false,
+        };
+        let pool = c.rocket().state::<Pool<DieselConnectionManager>>().unwrap();
+        let mut db = pool.get().await?;
+        _report_race(race.id, results(), None, &channel_config, &admin, &mut db)
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(
+            Some(Outcome::P1Win),
+            BracketRace::get_by_id(race.id, &mut db)?.outcome()?
+        );
+        // reporting again needs `force`
+        let again = _report_race(race.id, results(), None, &channel_config, &admin, &mut db).await;
+        assert!(matches!(again, Err(ApiError::InvalidRequest(_))));
+
+        let race_filter = AuditFilter {
+            entity_type: Some(AuditEntity::BracketRace),
+            entity_id: Some(race.id.to_string()),
+            ..Default::default()
+        };
+        // the failed report isn't audited
+        let audited = AuditEvent::search(&race_filter, 10, &mut db)?;
+        assert_eq!(1, audited.len());
+        assert_eq!("report_race", audited[0].action);
+        assert_eq!(Ok(admin), audited[0].actor());
+        assert!(audited[0].before_json.is_some() && audited[0].after_json.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bracket_standings_and_rounds() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let admin = Actor::ApiToken(1);
+        let bracket_id = run_with_db(&c, |db| {
+            let season = _create_season(
+                ApiNewSeason {
+                    format: "Any% NMG".to_string(),
+                    rtgg_category_name: "alttp".to_string(),
+                    rtgg_goal_name: "Any% NMG".to_string(),
+                },
+                &admin,
+                db,
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+            let bracket = _create_bracket(
+                season.ordinal,
+                ApiNewBracket {
+                    name: "swiss".to_string(),
+                    bracket_type: BracketType::Swiss,
+                    options: Default::default(),
+                },
+                &admin,
+                db,
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+            for (name, id) in [("p1", "1"), ("p2", "2")] {
+                let p = NewPlayer::new(name, id, None, None, None).save(db)?;
+                _add_bracket_player(bracket.id, ApiBracketEntry { player_id: p.id }, &admin, db)
+                    .map_err(|e| anyhow!("{e}"))?;
+            }
+            Ok(bracket.id)
+        })
+        .await?;
+        let standings = || async {
+            let resp = c
+                .get(format!("/api/v1/bracket/{bracket_id}/standings"))
+                .dispatch()
+                .await;
+            parse_result::<Vec<ApiStanding>>(&resp.into_string().await.unwrap())?
+                .map_err(|e| anyhow!("{e}"))
+        };
+
+        // everyone's listed before the bracket starts
+        let unstarted = standings().await?;
+        assert_eq!(2, unstarted.len());
+        assert!(unstarted.iter().all(|s| s.points == 0.0));
+
+        let race = run_with_db(&c, |db| {
+            _generate_pairings(bracket_id, &admin, db).map_err(|e| anyhow!("{e}"))?;
+            let mut race = Bracket::get_by_id(bracket_id, db)?
+                .bracket_races(db)?
+                .remove(0);
+            race.add_results(
+                Some(&PlayerResult::Finish(5000)),
+                Some(&PlayerResult::Forfeit),
+                false,
+            )?;
+            race.update(db)?;
+            Ok(race)
+        })
+        .await?;
+
+        let finished = standings().await?;
+        assert_eq!(
+            vec![race.player_1_id, race.player_2_id],
+            finished.iter().map(|s| s.player_id).collect::<Vec<_>>()
+        );
+        assert_eq!(1.0, finished[0].points);
+        assert_eq!(Some(5000), finished[0].average_time_finished);
+        assert_eq!(0.0, finished[1].points);
+        assert_eq!(None, finished[1].average_time_finished);
+        assert!(finished[1].average_time_adjusted.is_some());
+
+        let resp = c
+            .get(format!("/api/v1/bracket/{bracket_id}/rounds"))
+            .dispatch()
+            .await;
+        let rounds = parse_result::<Vec<ApiRound>>(&resp.into_string().await.unwrap())?
+            .map_err(|e| anyhow!("{e}"))?;
+        assert_eq!(1, rounds.len());
+        assert_eq!(1, rounds[0].round_num);
+        assert_eq!(
+            vec![race.id],
+            rounds[0].races.iter().map(|r| r.id).collect::<Vec<_>>()
+        );
+        assert!(rounds[0].byes.is_empty());
+
+        let missing = c.get("/api/v2/bracket/1000/rounds").dispatch().await;
+        assert_eq!(rocket::http::Status::NotFound, missing.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_revoke_token() -> anyhow::Result<()> {
+        let c = setup().await?;
+        run_with_db(&c, |db| {
+            let mine = NewApiToken::new(Id::new(1), "mine").0.save(db)?;
+            let other = NewApiToken::new(Id::new(1), "other").0.save(db)?;
+            let theirs = NewApiToken::new(Id::new(2), "theirs").0.save(db)?;
+            assert!(matches!(
+                _revoke_token(theirs.id, &mine, db),
+                Err(ApiError::NotFound)
+            ));
+            _revoke_token(other.id, &mine, db).map_err(|e| anyhow!("{e}"))?;
+            assert_eq!(
+                vec![mine.id],
+                ApiToken::active_for_user(Id::new(1), db)?
+                    .iter()
+                    .map(|t| t.id)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(1, ApiToken::active_for_user(Id::new(2), db)?.len());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_webhook_subscriptions() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let admin = Actor::ApiToken(1);
+        run_with_db(&c, |db| {
+            assert!(matches!(
+                _create_webhook(
+                    ApiNewWebhookSubscription {
+                        url: "not a url".to_string(),
+                        events: vec![LeagueEventKind::RaceFinished],
+                    },
+                    &admin,
+                    db
+                ),
+                Err(ApiError::InvalidRequest(_))
+            ));
+            assert!(matches!(
+                _create_webhook(
+                    ApiNewWebhookSubscription {
+                        url: "https://example.com/hook".to_string(),
+                        events: vec![],
+                    },
+                    &admin,
+                    db
+                ),
+                Err(ApiError::InvalidRequest(_))
+            ));
+
+            let created = _create_webhook(
+                ApiNewWebhookSubscription {
+                    url: "https://example.com/hook".to_string(),
+                    events: vec![LeagueEventKind::RaceFinished],
+                },
+                &admin,
+                db,
+            )
+            .map_err(|e| anyhow!("{e}"))?;
+            assert!(created.secret.is_some());
+            assert_eq!(vec![LeagueEventKind::RaceFinished], created.events);
+
+            queue_event(
+                &LeagueEvent::RaceFinished {
+                    race_id: 1,
+                    bracket_id: 1,
+                    racetime_gg_url: None,
+                },
+                db,
+            )?;
+            let deliveries = _get_webhook_deliveries(created.id, PageParams::default(), db)
+                .map_err(|e| anyhow!("{e}"))?;
+            assert_eq!(1, deliveries.items.len());
+            assert_eq!(DeliveryState::Pending, deliveries.items[0].state);
+            assert!(matches!(
+                _get_webhook_deliveries(created.id + 1, PageParams::default(), db),
+                Err(ApiError::NotFound)
+            ));
+            assert_eq!(
+                1,
+                _get_webhooks(PageParams::default(), db)
+                    .map_err(|e| anyhow!("{e}"))?
+                    .items
+                    .len()
+            );
+
+            _deactivate_webhook(created.id, &admin, db).map_err(|e| anyhow!("{e}"))?;
+            assert!(WebhookSubscription::active(db)?.is_empty());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_v2_responses() -> anyhow::Result<()> {
+        let c = setup().await?;
+        let (s, p1) = run_with_db(&c, |db| {
+            let ns = NewSeason::new("Any% NMG", "alttp", "Any% NMG", db)?.save(db)?;
+            let b = NewBracket::new(&ns, "bracket 1", BracketType::Swiss).save(db)?;
+            let round = NewBracketRound::new(&b, 1).save(db)?;
+            let p1 = NewPlayer::new("p1", "1", None, None, None).save(db)?;
+            let p2 = NewPlayer::new("p2", "2", None, None, None).save(db)?;
+            bracket_races::insert_bulk(&vec![NewBracketRace::new(&b, &round, &p1, &p2)], db)?;
+            Ok((ns, p1))
+        })
+        .await?;
+
+        // values aren't wrapped in {"Ok": ...}
+        let resp = c.get("/api/v2/players").dispatch().await;
+        assert_eq!(rocket::http::Status::Ok, resp.status());
+        let players = serde_json::from_str::<Vec<Player>>(&resp.into_string().await.unwrap())?;
+        assert_eq!(2, players.len());
+
+        // enum parameters are plain strings
+        let resp = c
+            .get(format!("/api/v2/season/{}/races?state=New", s.ordinal))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, resp.status());
+        let races = serde_json::from_str::<Vec<ApiRace>>(&resp.into_string().await.unwrap())?;
+        assert_eq!(1, races.len());
+
+        let resp = c
+            .get(format!(
+                "/api/v2/season/{}/races?state={}",
+                s.ordinal,
+                urlencoding::encode(r#""New""#)
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::BadRequest, resp.status());
+        let error = serde_json::from_str::<ApiErrorBody>(&resp.into_string().await.unwrap())?;
+        assert_eq!(ApiErrorKind::InvalidParams, error.error);
+        assert_eq!("state", error.invalid_params[0].param);
+
+        let resp = c
+            .get(format!(
+                "/api/v2/head_to_head?player_1_id={}&player_2_id=1000",
+                p1.id
+            ))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::NotFound, resp.status());
+        let error = serde_json::from_str::<ApiErrorBody>(&resp.into_string().await.unwrap())?;
+        assert_eq!(ApiErrorKind::NotFound, error.error);
+
+        // requests that never make it to a route get the same kind of errors
+        let body = r#"{"format": "Any% NMG", "rtgg_category_name": "alttp", "rtgg_goal_name": "Any% NMG"}"#;
+        let resp = c.post("/api/v2/seasons").body(body).dispatch().await;
+        assert_eq!(rocket::http::Status::Unauthorized, resp.status());
+        let error = serde_json::from_str::<ApiErrorBody>(&resp.into_string().await.unwrap())?;
+        assert_eq!(ApiErrorKind::Unauthorized, error.error);
+
+        // and v1 is the same as ever
+        let resp = c
+            .get(format!("/api/v1/season/{}/races?state=New", s.ordinal))
+            .dispatch()
+            .await;
+        assert_eq!(rocket::http::Status::Ok, resp.status());
+        let parsed = serde_json::from_str::<Value>(&resp.into_string().await.unwrap())?;
+        assert_eq!("Invalid parameters", parsed["Err"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_openapi_spec() -> anyhow::Result<()> {
+        // this fails if any route isn't documented
+        let spec = v2_description().spec().map_err(|e| anyhow!(e))?;
+        let races = &spec["paths"]["/season/{ordinal}/races"]["get"];
+        let params = races["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|p| p["name"].as_str())
+            .collect::<Vec<_>>();
+        for param in ["ordinal", "state", "round", "limit", "cursor", "sort"] {
+            assert!(params.contains(&param), "{param} missing from {params:?}");
+        }
+        assert!(races["responses"]["200"]["headers"][NEXT_CURSOR_HEADER].is_object());
+        assert!(spec["components"]["schemas"]["ApiRace"].is_object());
+        let add_player = &spec["paths"]["/bracket/{id}/players"]["post"];
+        assert!(add_player["responses"]["204"].is_object());
+        assert_eq!(json!([{"apiToken": []}]), add_player["security"]);
+
+        let c = setup().await?;
+        let resp = c.get("/api/v2/openapi.json").dispatch().await;
+        assert_eq!(rocket::http::Status::Ok, resp.status());
+        let served = serde_json::from_str::<Value>(&resp.into_string().await.unwrap())?;
+        assert_eq!(spec, served);
+        // it's only part of v2
+        let resp = c.get("/api/v1/openapi.json").dispatch().await;
+        assert_eq!(rocket::http::Status::NotFound, resp.status());
+        Ok(())
+    }
 }
@@ -1,9 +1,13 @@
+use crate::discord::discord_state::DiscordOperations;
 use crate::discord::discord_state::DiscordState;
 use crate::web::session_manager::SessionToken;
 use crate::web::{BaseContext, ConnectionWrapper, SessionManager, SESSION_COOKIE_NAME};
 use log::{debug, info, warn};
 use nmg_league_bot::config::CONFIG;
 use nmg_league_bot::constants::{DISCORD_AUTHORIZE_URL, DISCORD_TOKEN_URL};
+use nmg_league_bot::models::api_tokens::ApiToken;
+use nmg_league_bot::models::audit_events::Actor;
+use nmg_league_bot::models::player::Player;
 use oauth2::basic::{BasicClient, BasicErrorResponseType, BasicTokenType};
 use oauth2::reqwest::async_http_client;
 use oauth2::url::Url;
@@ -13,6 +17,8 @@ use oauth2::{
     StandardRevocableToken, StandardTokenIntrospectionResponse, StandardTokenResponse,
     TokenResponse as OauthTokenResponse, TokenUrl,
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 use rocket::http::{Cookie, CookieJar, Status};
 use rocket::request::{FromRequest, Outcome};
 use rocket::response::Redirect;
@@ -23,7 +29,8 @@ use rocket_dyn_templates::{context, Template};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::time::Instant;
-use crate::discord::discord_state::DiscordOperations;
+use twilight_model::id::marker::UserMarker;
+use twilight_model::id::Id;
 type TokenResponse = StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType>;
 
 type _OauthClient = Client<
@@ -123,7 +130,46 @@ impl OauthClient {
     }
 }
 
-pub(super) struct Admin {}
+/// the discord user logged in to the website, if any
+async fn session_user(request: &Request<'_>) -> Option<Id<UserMarker>> {
+    let cookie = match request.cookies().get(SESSION_COOKIE_NAME) {
+        Some(c) => c.value(),
+        None => {
+            debug!("No session cookie");
+            return None;
+        }
+    };
+    let st = SessionToken::new(cookie.to_string());
+    let sm_lock = match request
+        .guard::<&State<Arc<tokio::sync::Mutex<SessionManager>>>>()
+        .await
+    {
+        Outcome::Success(s) => s,
+        _ => {
+            return None;
+        }
+    };
+    let mut sm = sm_lock.lock().await;
+    match sm.get_user(&st) {
+        Ok(u) => Some(u),
+        Err(e) => {
+            info!("User not found for session token {}: {:?}", st, e);
+            request.cookies().remove(Cookie::named(SESSION_COOKIE_NAME));
+            None
+        }
+    }
+}
+
+pub(super) struct Admin {
+    /// the logged in admin's discord id. `None` when the website is running without auth
+    pub(super) user_id: Option<Id<UserMarker>>,
+}
+
+impl Admin {
+    pub(super) fn actor(&self) -> Actor {
+        self.user_id.map(Actor::discord).unwrap_or(Actor::Anonymous)
+    }
+}
 
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for Admin {
@@ -131,59 +177,155 @@ impl<'r> FromRequest<'r> for Admin {
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         if cfg!(feature = "no_auth_website") {
-            return Outcome::Success(Admin {});
+            return Outcome::Success(Admin { user_id: None });
         }
 
-        let cookie = match request.cookies().get(SESSION_COOKIE_NAME) {
-            Some(c) => c.value(),
+        let uid = match session_user(request).await {
+            Some(u) => u,
             None => {
-                debug!("No session cookie");
                 return Outcome::Forward(());
             }
         };
-        let st = SessionToken::new(cookie.to_string());
-        let sm_lock = match request
-            .guard::<&State<Arc<tokio::sync::Mutex<SessionManager>>>>()
-            .await
-        {
+
+        let state = match request.guard::<&State<Arc<DiscordState>>>().await {
             Outcome::Success(s) => s,
             _ => {
                 return Outcome::Forward(());
             }
         };
-        let uid = {
-            let mut sm = sm_lock.lock().await;
-            match sm.get_user(&st) {
-                Ok(u) => u,
+        match state.has_nmg_league_admin_role(uid).await {
+            Ok(true) => Outcome::Success(Admin { user_id: Some(uid) }),
+            // players can log in too, so people without the role (anymore) stay logged in as
+            // themselves
+            Ok(false) => Outcome::Failure((Status::Forbidden, ())),
+            _ => Outcome::Forward(()),
+        }
+    }
+}
+
+/// anyone logged in to the website: admins, and players managing their own things
+pub(super) struct SignedIn {
+    pub(super) user_id: Id<UserMarker>,
+}
+
+impl SignedIn {
+    pub(super) fn actor(&self) -> Actor {
+        Actor::discord(self.user_id)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SignedIn {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match session_user(request).await {
+            Some(user_id) => Outcome::Success(SignedIn { user_id }),
+            None => Outcome::Forward(()),
+        }
+    }
+}
+
+/// An admin calling the API with one of their tokens, as `Authorization: Bearer <token>`.
+///
+/// The token's owner has to still have the admin role: tokens don't outlive it.
+pub(super) struct ApiTokenAdmin {
+    pub(super) token: ApiToken,
+}
+
+impl ApiTokenAdmin {
+    pub(super) fn actor(&self) -> Actor {
+        Actor::ApiToken(self.token.id)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiTokenAdmin {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        static AUTHZ_REGEX: Lazy<Result<Regex, regex::Error>> =
+            Lazy::new(|| Regex::new(r"^Bearer\s+(?<token>\w+)$"));
+        let re = match &*AUTHZ_REGEX {
+            Ok(re) => re,
+            Err(e) => {
+                warn!("Error with AUTHZ_REGEX, unable to authorize for API: {e}");
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+        let secret = match request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|az| re.captures(az))
+            .and_then(|c| c.name("token"))
+        {
+            Some(m) => m.as_str().to_string(),
+            None => {
+                return Outcome::Failure((Status::Unauthorized, ()));
+            }
+        };
+        // the connection is dropped before checking roles so that the route can have it
+        let found = match request.guard::<ConnectionWrapper<'_>>().await {
+            Outcome::Success(mut db) => ApiToken::get_by_secret(&secret, &mut db),
+            _ => {
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+        let mut token = match found {
+            Ok(Some(t)) => t,
+            Ok(None) => {
+                return Outcome::Failure((Status::Unauthorized, ()));
+            }
+            Err(e) => {
+                warn!("Error looking up API token: {e}");
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+
+        if !cfg!(feature = "no_auth_website") {
+            let uid = match token.discord_id() {
+                Ok(uid) => uid,
                 Err(e) => {
-                    info!("User not found for session token {}: {:?}", st, e);
-                    request.cookies().remove(Cookie::named(SESSION_COOKIE_NAME));
-                    return Outcome::Forward(());
+                    warn!("API token {} has an invalid discord id: {e}", token.id);
+                    return Outcome::Failure((Status::InternalServerError, ()));
+                }
+            };
+            let state = match request.guard::<&State<Arc<DiscordState>>>().await {
+                Outcome::Success(s) => s,
+                _ => {
+                    return Outcome::Failure((Status::Forbidden, ()));
+                }
+            };
+            match state.has_nmg_league_admin_role(uid).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!("API token {} belongs to a non-admin", token.id);
+                    return Outcome::Failure((Status::Forbidden, ()));
+                }
+                Err(e) => {
+                    warn!(
+                        "Error checking admin status for API token {}: {e}",
+                        token.id
+                    );
+                    return Outcome::Failure((Status::InternalServerError, ()));
                 }
             }
-        };
+        }
 
-        let state = match request.guard::<&State<Arc<DiscordState>>>().await {
-            Outcome::Success(s) => s,
+        // the token might have been revoked while we were checking roles
+        let mut db = match request.guard::<ConnectionWrapper<'_>>().await {
+            Outcome::Success(db) => db,
             _ => {
-                return Outcome::Forward(());
+                return Outcome::Failure((Status::InternalServerError, ()));
             }
         };
-        match state.has_nmg_league_admin_role(uid).await {
-            Ok(true) => Outcome::Success(Admin {}),
-            Ok(false) => {
-                // N.B. would it be better to just get this lock once?
-                let mut sm = sm_lock.lock().await;
-                let un = state
-                    .get_user(uid)
-                    .map(|u| u.global_name.unwrap_or(u.name))
-                    .unwrap_or("Unknown username".to_string());
-                debug!("Logging out {un} because they no longer have admin status");
-                sm.log_out_user(&st);
-                request.cookies().remove(Cookie::named(SESSION_COOKIE_NAME));
-                Outcome::Failure((Status::Forbidden, ()))
+        match token.mark_used(&mut db) {
+            Ok(true) => Outcome::Success(ApiTokenAdmin { token }),
+            Ok(false) => Outcome::Failure((Status::Unauthorized, ())),
+            Err(e) => {
+                warn!("Error updating last use of API token {}: {e}", token.id);
+                Outcome::Failure((Status::InternalServerError, ()))
             }
-            _ => Outcome::Forward(()),
         }
     }
 }
@@ -210,6 +352,7 @@ async fn discord_login(
     session_manager: &State<Arc<tokio::sync::Mutex<SessionManager>>>,
     role_checker: &State<Arc<DiscordState>>,
     cookies: &CookieJar<'_>,
+    mut db: ConnectionWrapper<'_>,
 ) -> Result<Template, Redirect> {
     let redirect = Redirect::to(rocket::uri!("/", login_page));
     let got_code = code.ok_or(Redirect::to(rocket::uri!("/", login_page)))?;
@@ -253,7 +396,16 @@ async fn discord_login(
             Redirect::to(rocket::uri!("/", login_page))
         })?;
 
-    if is_admin {
+    // players can log in to manage their own things (like their availability)
+    let is_player = match Player::get_by_discord_id(&user_info.id.to_string(), &mut db) {
+        Ok(p) => p.is_some(),
+        Err(e) => {
+            warn!("Error looking up player: {}", e);
+            false
+        }
+    };
+
+    if is_admin || is_player {
         let st = {
             let mut sm = session_manager.lock().await;
             sm.log_in_user(
@@ -268,17 +420,34 @@ async fn discord_login(
             .max_age(Duration::days(30))
             .finish();
         cookies.add(cookie);
-        info!("User {} has logged in as an admin", user_info.name);
+        if is_admin {
+            info!("User {} has logged in as an admin", user_info.name);
+        } else {
+            info!("User {} has logged in as a player", user_info.name);
+        }
         Ok(Template::render(
             "login_redirect",
             HashMap::<String, String>::new(),
         ))
     } else {
-        info!("Non-admin user");
+        info!("User is neither an admin nor a player");
         Err(redirect)
     }
 }
 
+#[get("/logout")]
+async fn logout(
+    session_manager: &State<Arc<tokio::sync::Mutex<SessionManager>>>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if let Some(c) = cookies.get(SESSION_COOKIE_NAME) {
+        let st = SessionToken::new(c.value().to_string());
+        session_manager.lock().await.log_out_user(&st);
+    }
+    cookies.remove(Cookie::named(SESSION_COOKIE_NAME));
+    Redirect::to("/")
+}
+
 pub fn build_rocket(rocket: Rocket<Build>) -> Rocket<Build> {
-    rocket.mount("/", rocket::routes![login_page, discord_login])
+    rocket.mount("/", rocket::routes![login_page, discord_login, logout])
 }
@@ -1,11 +1,13 @@
+use chrono::{TimeZone, Utc};
 use itertools::Itertools;
 use nmg_league_bot::{BracketRaceState, NMGLeagueBotError};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use rocket::http::Status;
-use rocket::{get, Request, State};
+use rocket::form::Form;
+use rocket::http::{ContentType, Status};
+use rocket::{get, post, FromForm, Request, State};
 
 use rocket_dyn_templates::{context, Template};
 use tokio::sync::Mutex as AsyncMutex;
@@ -13,36 +15,58 @@ use tokio::time::Duration;
 
 use crate::discord::discord_state::DiscordOperations;
 use crate::discord::discord_state::DiscordState;
+use crate::discord::review_qualifier_submission;
 use crate::schema;
 use crate::shutdown::Shutdown;
-use crate::web::auth::{Admin, OauthClient};
+use crate::web::auth::{Admin, OauthClient, SignedIn};
 use crate::web::session_manager::SessionManager as _SessionManager;
+use crate::web::stats_cache::StatsCache;
 use bb8::{Pool, PooledConnection};
 use diesel::prelude::*;
 use log::{debug, info, warn};
 use nmg_league_bot::db::{get_diesel_pool, DieselConnectionManager};
 use nmg_league_bot::models::asyncs::race::{AsyncRace, RaceState};
 use nmg_league_bot::models::asyncs::race_run::{AsyncRaceRun, RaceRunState};
+use nmg_league_bot::models::audit_events::{
+    Actor, AuditEntity, AuditEvent, AuditFilter, NewAuditEvent,
+};
+use nmg_league_bot::models::availability::{
+    format_time_of_day, AvailabilityWindow, NewAvailabilityWindow, WEEKDAYS,
+};
+use nmg_league_bot::models::bracket_race_entrants::BracketRaceEntrant;
+use nmg_league_bot::models::bracket_race_games::{series_score, BracketRaceGame};
 use nmg_league_bot::models::bracket_race_infos::{BracketRaceInfo, BracketRaceInfoId};
 use nmg_league_bot::models::bracket_races::{BracketRace, PlayerResult};
 use nmg_league_bot::models::bracket_rounds::BracketRound;
 use nmg_league_bot::models::brackets::{Bracket, BracketError, BracketType};
+use nmg_league_bot::models::calendar::{calendar_races, to_ics, CalendarScope};
+use nmg_league_bot::models::elimination::{pair_key, EliminationMatch, EliminationSide, Slot};
+use nmg_league_bot::models::head_to_head::{head_to_head, HeadToHeadRace};
 use nmg_league_bot::models::player::Player;
+use nmg_league_bot::models::qualifer_submission::{QualifierSubmission, ReviewState};
+use nmg_league_bot::models::qualifier_tiers::{SeedOrder, TierError, TierPlan, TierSplit};
+use nmg_league_bot::models::race_disputes::RaceDispute;
+use nmg_league_bot::models::ratings::{PlayerRating, RatingHistoryEntry};
+use nmg_league_bot::models::reschedule_proposals::RescheduleProposal;
 use nmg_league_bot::models::season::{Season, SeasonState};
-use nmg_league_bot::utils::format_hms;
+use nmg_league_bot::utils::{epoch_timestamp, format_hms};
 use rocket::request::{FromRequest, Outcome};
 use rocket::response::Redirect;
 use rocket_dyn_templates::tera::{to_value, try_get_value, Value};
 use serde::Serialize;
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
 use tokio::sync::mpsc::Sender;
 use twilight_model::id::marker::UserMarker;
 use twilight_model::id::Id;
 mod api;
 mod auth;
 mod internal_api;
+mod openapi;
+mod pagination;
 mod session_manager;
 mod statics;
+mod stats_cache;
 
 type SessionManager = _SessionManager<Id<UserMarker>>;
 
@@ -314,6 +338,9 @@ struct DisplayRace {
     race_id: i32,
     player_1: DisplayPlayer,
     player_2: DisplayPlayer,
+    /// every entrant of a placement race, best placement first (empty for other races)
+    entrants: Vec<DisplayPlayer>,
+    complete: bool,
     scheduled: Option<String>,
     channel: Option<String>,
 }
@@ -339,22 +366,54 @@ impl DisplayPlayer {
     }
 }
 
+/// e.g. "2: 1:30:00, Forfeit, 1:29:00" for someone who won 2 games of a series
+fn series_status<I>(wins: i32, results: I) -> String
+where
+    I: Iterator<Item = Result<PlayerResult, serde_json::Error>>,
+{
+    let times = results
+        .map(|r| match r {
+            Ok(pr) => pr.to_string(),
+            Err(_) => "?".to_string(),
+        })
+        .join(", ");
+    format!("{wins}: {times}")
+}
+
 impl DisplayRace {
-    fn new(p1: &Player, p2: &Player, race: &BracketRace, race_info: &BracketRaceInfo) -> Self {
+    /// `games` are the race's games, if it's a series
+    fn new(
+        p1: &Player,
+        p2: &Player,
+        race: &BracketRace,
+        race_info: &BracketRaceInfo,
+        games: &[BracketRaceGame],
+    ) -> Self {
         use nmg_league_bot::models::bracket_races::Outcome::{P1Win, P2Win};
         let outcome = race.outcome().unwrap_or(None);
-        let player_1 = DisplayPlayer::new(
+        let mut player_1 = DisplayPlayer::new(
             p1,
             race.player_1_result(),
             outcome == Some(P1Win),
             outcome == Some(P2Win),
         );
-        let player_2 = DisplayPlayer::new(
+        let mut player_2 = DisplayPlayer::new(
             p2,
             race.player_2_result(),
             outcome == Some(P2Win),
             outcome == Some(P1Win),
         );
+        if !games.is_empty() {
+            let outcomes = games
+                .iter()
+                .filter_map(|g| g.outcome().ok())
+                .collect::<Vec<_>>();
+            let (p1_wins, p2_wins) = series_score(&outcomes);
+            player_1.status =
+                series_status(p1_wins, games.iter().map(BracketRaceGame::player_1_result));
+            player_2.status =
+                series_status(p2_wins, games.iter().map(BracketRaceGame::player_2_result));
+        }
 
         let (scheduled, channel) = match outcome {
             Some(_) => (None, None),
@@ -376,26 +435,156 @@ impl DisplayRace {
             race_id: race.id,
             player_1,
             player_2,
+            entrants: vec![],
+            complete: race.is_complete(),
             scheduled,
             channel,
         }
     }
+
+    /// fills in the entrants of a placement race. The winner is whoever placed first.
+    fn with_entrants(
+        mut self,
+        entrants: Vec<BracketRaceEntrant>,
+        players_by_id: &HashMap<i32, Player>,
+    ) -> Self {
+        if self.complete {
+            // placement races don't have a scheduled time once they're finished either
+            self.scheduled = None;
+            self.channel = None;
+        }
+        self.entrants = entrants
+            .into_iter()
+            .sorted_by_key(|e| (e.placement.unwrap_or(i32::MAX), e.id))
+            .filter_map(|e| {
+                let player = players_by_id.get(&e.player_id)?;
+                Some(DisplayPlayer::new(
+                    player,
+                    e.result(),
+                    e.placement == Some(1),
+                    false,
+                ))
+            })
+            .collect();
+        self
+    }
 }
 
 #[derive(Serialize)]
 struct DisplayRound {
     round_num: i32,
     races: Vec<DisplayRace>,
+    /// names of players with a bye this round
+    byes: Vec<String>,
+    /// e.g. "Schedule by Monday, ... Play by Sunday, ...", if the round has deadlines
+    deadlines: Option<String>,
+}
+
+impl DisplayRound {
+    fn new(round: &BracketRound) -> Self {
+        let deadlines = [
+            round
+                .schedule_by
+                .map(|t| format!("Schedule by {}.", format_eastern(t))),
+            round
+                .play_by
+                .map(|t| format!("Play by {}.", format_eastern(t))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        Self {
+            round_num: round.round_num,
+            races: vec![],
+            byes: vec![],
+            deadlines: (!deadlines.is_empty()).then(|| deadlines.join(" ")),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DisplayEliminationMatch {
+    /// set once this match's race exists
+    race: Option<DisplayRace>,
+    /// used for matches that don't have a race (yet): player names, "TBD" or "Bye"
+    slot_1: String,
+    slot_2: String,
+}
+
+#[derive(Serialize)]
+struct DisplayEliminationColumn {
+    round_num: i32,
+    matches: Vec<DisplayEliminationMatch>,
+}
+
+#[derive(Serialize, Default)]
+struct DisplayEliminationTree {
+    winners: Vec<DisplayEliminationColumn>,
+    losers: Vec<DisplayEliminationColumn>,
+    grand_final: Option<DisplayEliminationMatch>,
 }
 
 #[derive(Serialize)]
 struct DisplayBracket {
     bracket: Bracket,
     /// all the rounds of the bracket, in ascending order (i.e. round 1 first, round 2 second)
+    /// (empty for elimination brackets, which use `elimination_tree` instead)
     rounds: Vec<DisplayRound>,
     is_round_robin: bool,
+    elimination_tree: Option<DisplayEliminationTree>,
 }
 
+/// lays out an elimination bracket's tree, pulling races out of `races` as they're placed
+fn get_elimination_tree(
+    bracket: &Bracket,
+    mut races: HashMap<(i32, (i32, i32)), DisplayRace>,
+    players_by_id: &HashMap<i32, Player>,
+    conn: &mut SqliteConnection,
+) -> Result<DisplayEliminationTree, NMGLeagueBotError> {
+    let slot_name = |slot: Slot| match slot {
+        Slot::Player(id) => players_by_id
+            .get(&id)
+            .map(|p| p.name.clone())
+            .unwrap_or("Unknown".to_string()),
+        Slot::Bye => "Bye".to_string(),
+        Slot::Tbd => "TBD".to_string(),
+    };
+    let mut display_match = |round_num: i32, m: &EliminationMatch| DisplayEliminationMatch {
+        race: m
+            .players()
+            .and_then(|(p1, p2)| races.remove(&(round_num, pair_key(p1, p2)))),
+        slot_1: slot_name(m.slot_1),
+        slot_2: slot_name(m.slot_2),
+    };
+
+    let mut tree = DisplayEliminationTree::default();
+    for round in bracket.elimination_rounds(conn)? {
+        let mut winners = vec![];
+        let mut losers = vec![];
+        for m in &round.matches {
+            match m.side {
+                EliminationSide::Winners => winners.push(display_match(round.round_num, m)),
+                EliminationSide::Losers => losers.push(display_match(round.round_num, m)),
+                EliminationSide::GrandFinal => {
+                    tree.grand_final = Some(display_match(round.round_num, m));
+                }
+            }
+        }
+        if !winners.is_empty() {
+            tree.winners.push(DisplayEliminationColumn {
+                round_num: round.round_num,
+                matches: winners,
+            });
+        }
+        if !losers.is_empty() {
+            tree.losers.push(DisplayEliminationColumn {
+                round_num: round.round_num,
+                matches: losers,
+            });
+        }
+    }
+    Ok(tree)
+}
 
 fn get_display_bracket(
     bracket: Bracket,
@@ -407,7 +596,10 @@ fn get_display_bracket(
     let players_by_id: HashMap<i32, Player> =
         HashMap::from_iter(bracket.players(conn)?.into_iter().map(|r| (r.id, r)));
 
+    let is_elimination = bracket.bracket_type()?.is_elimination();
+
     let mut display_rounds_by_num: HashMap<i32, DisplayRound> = Default::default();
+    let mut elimination_races: HashMap<(i32, (i32, i32)), DisplayRace> = Default::default();
     for race in races {
         let round = match rounds_by_id.get(&race.round_id) {
             Some(r) => r,
@@ -432,27 +624,60 @@ fn get_display_bracket(
             }
         };
         let r = race.info(conn)?;
-        let dr = DisplayRace::new(p1, p2, &race, &r);
+        let games = if race.is_series() {
+            race.games(conn)?
+        } else {
+            vec![]
+        };
+        let mut dr = DisplayRace::new(p1, p2, &race, &r, &games);
+        if race.is_placement_race() {
+            dr = dr.with_entrants(race.race_entrants(conn)?, &players_by_id);
+        }
+        if is_elimination {
+            elimination_races.insert((round.round_num, pair_key(p1.id, p2.id)), dr);
+            continue;
+        }
 
         display_rounds_by_num
             .entry(round.round_num)
-            .or_insert(DisplayRound {
-                round_num: round.round_num,
-                races: vec![],
-            })
+            .or_insert_with(|| DisplayRound::new(round))
             .races
             .push(dr);
     }
+    for bye in bracket.byes(conn)? {
+        let round = rounds_by_id.get(&bye.round_id);
+        let player = players_by_id.get(&bye.player_id);
+        if let (Some(round), Some(player)) = (round, player) {
+            display_rounds_by_num
+                .entry(round.round_num)
+                .or_insert_with(|| DisplayRound::new(round))
+                .byes
+                .push(player.name.clone());
+        } else {
+            info!("Missing round or player for bye {}", bye.id);
+        }
+    }
     let rounds = display_rounds_by_num
         .into_iter()
         .sorted_by_key(|(n, _rs)| n.clone())
         .map(|(_n, rs)| rs)
         .collect();
     let is_round_robin = bracket.bracket_type()? == BracketType::RoundRobin;
+    let elimination_tree = if is_elimination {
+        Some(get_elimination_tree(
+            &bracket,
+            elimination_races,
+            &players_by_id,
+            conn,
+        )?)
+    } else {
+        None
+    };
     Ok(DisplayBracket {
         bracket,
         rounds,
         is_round_robin,
+        elimination_tree,
     })
 }
 
@@ -538,12 +763,16 @@ struct StandingsPlayer {
     opponent_points: f32,
     average_time_adjusted: String,
     average_time_finished: String,
+    byes: i32,
+    dropped: bool,
 }
 
 #[derive(Serialize)]
 struct StandingsBracket {
     name: String,
     backfill_note: Option<String>,
+    /// descriptions of the bracket's tiebreakers, in order
+    tiebreakers: Vec<String>,
     players: Vec<StandingsPlayer>,
 }
 
@@ -591,6 +820,8 @@ fn get_standings_context(
                     opponent_points: 0.0,
                     average_time_adjusted: "".to_string(),
                     average_time_finished: "".to_string(),
+                    byes: 0,
+                    dropped: false,
                 })
                 .collect()
         } else {
@@ -617,13 +848,22 @@ fn get_standings_context(
                         opponent_points: (s.opponent_points as f32) / 2.0,
                         average_time_adjusted: format_hms(s.avg_time_adjusted() as u64),
                         average_time_finished: avg,
+                        byes: s.byes,
+                        dropped: s.dropped,
                     }
                 })
                 .collect()
         };
+        let tiebreakers = bracket
+            .scoring_rules()?
+            .tiebreakers
+            .iter()
+            .map(ToString::to_string)
+            .collect();
         ctx_brackets.push(StandingsBracket {
             name: bracket.name,
             backfill_note: bracket.backfill_note,
+            tiebreakers,
             players: sps,
         });
     }
@@ -669,6 +909,593 @@ async fn season_qualifiers(
     ))
 }
 
+/// builds a [TierPlan] out of the seeding page's parameters:
+/// `names` is a comma separated list of bracket names (best bracket first), `cutoffs` is as in
+/// [TierSplit::parse], and `bracket_type` and `order` are [BracketType] and [SeedOrder] variant
+/// names
+fn tier_plan_from_params(
+    season: &Season,
+    names: &str,
+    cutoffs: &str,
+    bracket_type: &str,
+    order: &str,
+    conn: &mut SqliteConnection,
+) -> Result<TierPlan, String> {
+    let names = names
+        .split(',')
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect::<Vec<_>>();
+    let split = TierSplit::parse(cutoffs).map_err(|e| e.to_string())?;
+    let bt: BracketType =
+        serde_json::from_value(serde_json::Value::String(bracket_type.to_string()))
+            .map_err(|_| format!("Unknown bracket type {bracket_type}"))?;
+    let order: SeedOrder = serde_json::from_value(serde_json::Value::String(order.to_string()))
+        .map_err(|_| format!("Unknown seed order {order}"))?;
+    TierPlan::new(season, &names, &split, bt, order, conn).map_err(|e| e.to_string())
+}
+
+/// previews splitting the season's qualifier leaderboard into brackets. Nothing is created until
+/// the form on this page is posted to [create_seeded_brackets].
+#[get("/season/<season_ordinal>/seeding?<names>&<cutoffs>&<bracket_type>&<order>")]
+async fn season_seeding(
+    season_ordinal: i32,
+    names: Option<String>,
+    cutoffs: Option<String>,
+    bracket_type: Option<String>,
+    order: Option<String>,
+    admin: Admin,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    let cutoffs = cutoffs.unwrap_or_default();
+    let bracket_type = bracket_type.unwrap_or("Swiss".to_string());
+    let order = order.unwrap_or("QualifierScore".to_string());
+    let (plan, error) = match &names {
+        Some(n) => match tier_plan_from_params(&szn, n, &cutoffs, &bracket_type, &order, &mut db) {
+            Ok(p) => (Some(p), None),
+            Err(e) => (None, Some(e)),
+        },
+        None => (None, None),
+    };
+    let bracket_types = enum_iterator::all::<BracketType>()
+        .filter_map(|bt| serde_json::to_value(bt).ok())
+        .collect::<Vec<_>>();
+    let orders = enum_iterator::all::<SeedOrder>()
+        .filter_map(|o| serde_json::to_value(o).ok())
+        .collect::<Vec<_>>();
+    let base_context = BaseContext::new(&mut db, &Some(admin));
+    Ok(Template::render(
+        "season_seeding",
+        context!(
+            season: szn,
+            names,
+            cutoffs,
+            bracket_type,
+            bracket_types,
+            order,
+            orders,
+            plan,
+            error,
+            base_context
+        ),
+    ))
+}
+
+#[derive(FromForm)]
+struct SeedingForm {
+    names: String,
+    cutoffs: String,
+    bracket_type: String,
+    order: String,
+}
+
+/// creates the brackets previewed on [season_seeding]. The plan is rebuilt from the current
+/// qualifiers, so it can differ from the preview if qualifiers changed in the meantime.
+#[post("/season/<season_ordinal>/seeding", data = "<form>")]
+async fn create_seeded_brackets(
+    season_ordinal: i32,
+    form: Form<SeedingForm>,
+    admin: Admin,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Redirect, (Status, String)> {
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err((Status::NotFound, String::new())),
+        Err(e) => Err((Status::InternalServerError, e.to_string())),
+    }?;
+    let plan = tier_plan_from_params(
+        &szn,
+        &form.names,
+        &form.cutoffs,
+        &form.bracket_type,
+        &form.order,
+        &mut db,
+    )
+    .map_err(|e| (Status::BadRequest, e))?;
+    match plan.commit(&mut db) {
+        Ok(brackets) => {
+            for bracket in brackets {
+                NewAuditEvent::new(
+                    &admin.actor(),
+                    "create_seeded_brackets",
+                    AuditEntity::Bracket,
+                    bracket.id,
+                )
+                .after(&bracket)
+                .record(&mut db);
+            }
+            Ok(Redirect::to(uri!(season_brackets(
+                season_ordinal = szn.ordinal
+            ))))
+        }
+        Err(TierError::DBError(e)) => {
+            warn!("Error creating seeded brackets: {e}");
+            Err((Status::InternalServerError, e.to_string()))
+        }
+        Err(e) => Err((Status::BadRequest, e.to_string())),
+    }
+}
+
+/// queue of the season's qualifier submissions that haven't been checked against their VoDs yet
+#[get("/season/<season_ordinal>/qualifiers/review")]
+async fn qualifier_review_queue(
+    season_ordinal: i32,
+    admin: Admin,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    let pending =
+        QualifierSubmission::pending(szn.id, &mut db).map_err(|_| Status::InternalServerError)?;
+    let mut players = Player::by_id(Some(pending.iter().map(|q| q.player_id).collect()), &mut db)
+        .map_err(|_| Status::InternalServerError)?;
+
+    #[derive(Serialize)]
+    struct PendingSubmission {
+        id: i32,
+        player_name: String,
+        time: String,
+        vod_link: String,
+    }
+    let submissions = pending
+        .into_iter()
+        .map(|q| PendingSubmission {
+            id: q.id,
+            player_name: players
+                .remove(&q.player_id)
+                .map(|p| p.name)
+                .unwrap_or("Unknown player".to_string()),
+            time: format_hms(q.reported_time as u64),
+            vod_link: q.vod_link,
+        })
+        .collect::<Vec<_>>();
+    let base_context = BaseContext::new(&mut db, &Some(admin));
+    Ok(Template::render(
+        "qualifier_review",
+        context!(season: szn, submissions, base_context),
+    ))
+}
+
+#[derive(FromForm)]
+struct QualifierReviewForm {
+    decision: String,
+    reason: Option<String>,
+}
+
+/// verifies or rejects a qualifier submission from [qualifier_review_queue]. Rejected players
+/// get a DM about it.
+#[post("/season/<season_ordinal>/qualifiers/<id>/review", data = "<form>")]
+async fn review_qualifier(
+    season_ordinal: i32,
+    id: i32,
+    form: Form<QualifierReviewForm>,
+    admin: Admin,
+    discord_state: &State<Arc<DiscordState>>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Redirect, (Status, String)> {
+    let not_found = || {
+        (
+            Status::NotFound,
+            format!("No qualifier {id} in season {season_ordinal}"),
+        )
+    };
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(not_found()),
+        Err(e) => Err((Status::InternalServerError, e.to_string())),
+    }?;
+    match QualifierSubmission::get_by_id(id, &mut db) {
+        Ok(q) if q.season_id == szn.id => Ok(()),
+        Ok(_) | Err(diesel::result::Error::NotFound) => Err(not_found()),
+        Err(e) => Err((Status::InternalServerError, e.to_string())),
+    }?;
+    let decision = match form.decision.as_str() {
+        "verify" => ReviewState::Verified,
+        "reject" => ReviewState::Rejected,
+        other => {
+            return Err((Status::BadRequest, format!("Unknown decision {other}")));
+        }
+    };
+    let reason = form
+        .reason
+        .as_ref()
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    review_qualifier_submission(id, decision, admin.user_id, reason, discord_state.inner())
+        .await
+        .map_err(|e| (Status::InternalServerError, e))?;
+    Ok(Redirect::to(uri!(qualifier_review_queue(
+        season_ordinal = season_ordinal
+    ))))
+}
+
+fn format_eastern(timestamp: i64) -> String {
+    match Utc.timestamp_opt(timestamp, 0).single() {
+        Some(t) => t
+            .with_timezone(&chrono_tz::US::Eastern)
+            .format("%A, %B %d at %_I:%M %p (%Z)")
+            .to_string(),
+        None => format!("Invalid time ({timestamp})"),
+    }
+}
+
+/// admin view of a single bracket race, including every attempt to reschedule it and every dispute
+/// of its result
+#[get("/race/<id>")]
+async fn race_admin(
+    id: i32,
+    admin: Admin,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    let race = match BracketRace::get_by_id(id, &mut db) {
+        Ok(r) => Ok(r),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    let info = race
+        .info(&mut db)
+        .map_err(|_| Status::InternalServerError)?;
+    let players = race
+        .all_players(&mut db)
+        .map_err(|_| Status::InternalServerError)?;
+    let proposals =
+        RescheduleProposal::for_race(race.id, &mut db).map_err(|_| Status::InternalServerError)?;
+    let disputes =
+        RaceDispute::for_race(race.id, &mut db).map_err(|_| Status::InternalServerError)?;
+
+    let name_for = |discord_id: &str| {
+        players
+            .iter()
+            .find(|p| p.discord_id == discord_id)
+            .map(|p| p.name.clone())
+            .unwrap_or(format!("Admin ({discord_id})"))
+    };
+    #[derive(Serialize)]
+    struct ViewProposal {
+        proposed_by: String,
+        created: String,
+        from: Option<String>,
+        to: String,
+        state: String,
+        answered_by: Option<String>,
+    }
+    let history = proposals
+        .iter()
+        .map(|p| ViewProposal {
+            proposed_by: name_for(&p.proposer_discord_id),
+            created: format_eastern(p.created),
+            from: p.previous_time.map(format_eastern),
+            to: format_eastern(p.proposed_time),
+            state: p
+                .state()
+                .map(|s| format!("{s:?}"))
+                .unwrap_or("Unknown".to_string()),
+            answered_by: p.responder_discord_id.as_deref().map(&name_for),
+        })
+        .collect::<Vec<_>>();
+    #[derive(Serialize)]
+    struct ViewDispute {
+        id: i32,
+        opened_by: String,
+        created: String,
+        reason: String,
+        evidence_url: Option<String>,
+        state: String,
+        resolved_by: Option<String>,
+        resolution_note: Option<String>,
+    }
+    let disputes = disputes
+        .into_iter()
+        .map(|d| ViewDispute {
+            id: d.id,
+            opened_by: name_for(&d.opener_discord_id),
+            created: format_eastern(d.created),
+            state: d
+                .state()
+                .map(|s| format!("{s:?}"))
+                .unwrap_or("Unknown".to_string()),
+            resolved_by: d.resolver_discord_id.as_deref().map(&name_for),
+            reason: d.reason,
+            evidence_url: d.evidence_url,
+            resolution_note: d.resolution_note,
+        })
+        .collect::<Vec<_>>();
+    let ctx = context! {
+        race_id: race.id,
+        title: players.iter().map(|p| p.name.as_str()).join(" vs "),
+        state: race.state().map(|s| format!("{s:?}")).unwrap_or("Unknown".to_string()),
+        scheduled: info.scheduled_for.map(format_eastern),
+        history,
+        disputes,
+        base_context: BaseContext::new(&mut db, &Some(admin)),
+    };
+    Ok(Template::render("race_admin", ctx))
+}
+
+/// how many events [audit_log] shows at once
+const AUDIT_LOG_LIMIT: i64 = 200;
+
+/// `YYYY-MM-DD` (in UTC) to the timestamp at the start of that day, or at the end of it if
+/// `end_of_day` is set
+fn parse_audit_date(date: &str, end_of_day: bool) -> Result<i64, String> {
+    let day = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {date}: {e}"))?;
+    let time = if end_of_day {
+        day.and_hms_opt(23, 59, 59)
+    } else {
+        day.and_hms_opt(0, 0, 0)
+    };
+    time.map(|t| t.and_utc().timestamp())
+        .ok_or(format!("Invalid date {date}"))
+}
+
+fn audit_filter_from_params(
+    entity_type: &Option<String>,
+    entity_id: &Option<String>,
+    actor: &Option<String>,
+    since: &Option<String>,
+    until: &Option<String>,
+) -> Result<AuditFilter, String> {
+    let nonempty = |s: &Option<String>| {
+        s.as_ref()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    Ok(AuditFilter {
+        entity_type: nonempty(entity_type)
+            .map(|e| {
+                enum_iterator::all::<AuditEntity>()
+                    .find(|a| format!("{a:?}") == e)
+                    .ok_or(format!("Unknown entity type {e}"))
+            })
+            .transpose()?,
+        entity_id: nonempty(entity_id),
+        actor: nonempty(actor).map(|a| Actor::from_str(&a)).transpose()?,
+        since: nonempty(since)
+            .map(|d| parse_audit_date(&d, false))
+            .transpose()?,
+        until: nonempty(until)
+            .map(|d| parse_audit_date(&d, true))
+            .transpose()?,
+    })
+}
+
+/// admin search of the audit log, newest first
+#[get("/audit?<entity_type>&<entity_id>&<actor>&<since>&<until>")]
+async fn audit_log(
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    actor: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    admin: Admin,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    #[derive(Serialize)]
+    struct ViewAuditEvent {
+        created: String,
+        actor: String,
+        action: String,
+        entity_type: String,
+        entity_id: String,
+        before: Option<String>,
+        after: Option<String>,
+    }
+    let (events, error) =
+        match audit_filter_from_params(&entity_type, &entity_id, &actor, &since, &until) {
+            Ok(filter) => {
+                let events = AuditEvent::search(&filter, AUDIT_LOG_LIMIT, &mut db)
+                    .map_err(|_| Status::InternalServerError)?;
+                (events, None)
+            }
+            Err(e) => (vec![], Some(e)),
+        };
+    let players = Player::by_id(None, &mut db).map_err(|_| Status::InternalServerError)?;
+    let describe_actor = |e: &AuditEvent| match e.actor() {
+        Ok(Actor::Discord(id)) => players
+            .values()
+            .find(|p| p.discord_id == id)
+            .map(|p| format!("{} ({id})", p.name))
+            .unwrap_or(format!("Discord user {id}")),
+        Ok(Actor::ApiToken(id)) => format!("API token #{id}"),
+        Ok(Actor::System) => "The bot".to_string(),
+        Ok(Actor::Anonymous) => "Anonymous".to_string(),
+        Err(e) => e,
+    };
+    let view = events
+        .iter()
+        .map(|e| ViewAuditEvent {
+            created: format_eastern(e.created),
+            actor: describe_actor(e),
+            action: e.action.clone(),
+            entity_type: e
+                .entity_type()
+                .map(|t| format!("{t:?}"))
+                .unwrap_or("Unknown".to_string()),
+            entity_id: e.entity_id.clone(),
+            before: e.before_json.clone(),
+            after: e.after_json.clone(),
+        })
+        .collect::<Vec<_>>();
+    let entity_types = enum_iterator::all::<AuditEntity>()
+        .map(|e| format!("{e:?}"))
+        .collect::<Vec<_>>();
+    let ctx = context! {
+        events: view,
+        limit: AUDIT_LOG_LIMIT,
+        error,
+        entity_types,
+        entity_type,
+        entity_id,
+        actor,
+        since,
+        until,
+        base_context: BaseContext::new(&mut db, &Some(admin)),
+    };
+    Ok(Template::render("audit_log", ctx))
+}
+
+/// the logged in player's weekly availability, which is used to suggest times for their races
+#[get("/availability")]
+async fn availability(
+    user: SignedIn,
+    admin: Option<Admin>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    #[derive(Serialize)]
+    struct ViewWindow {
+        id: i32,
+        day: String,
+        start: String,
+        end: String,
+        timezone: String,
+    }
+    let player = Player::get_by_discord_id(&user.user_id.to_string(), &mut db)
+        .map_err(|_| Status::InternalServerError)?;
+    let windows = match &player {
+        Some(p) => AvailabilityWindow::for_player(p.id, &mut db)
+            .map_err(|_| Status::InternalServerError)?
+            .into_iter()
+            .map(|w| ViewWindow {
+                id: w.id,
+                day: w
+                    .weekday()
+                    .map(|d| d.to_string())
+                    .unwrap_or("???".to_string()),
+                start: format_time_of_day(w.start_minute),
+                end: format_time_of_day(w.end_minute),
+                timezone: w.timezone,
+            })
+            .collect(),
+        None => vec![],
+    };
+    let weekdays = WEEKDAYS.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+    let timezones = chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .collect::<Vec<_>>();
+    let base_context = BaseContext::new(&mut db, &admin);
+    Ok(Template::render(
+        "availability",
+        context!(player, windows, weekdays, timezones, base_context),
+    ))
+}
+
+#[derive(FromForm)]
+struct AvailabilityForm {
+    weekday: i32,
+    start: String,
+    end: String,
+    timezone: String,
+}
+
+fn signed_in_player(
+    user: &SignedIn,
+    db: &mut SqliteConnection,
+) -> Result<Player, (Status, String)> {
+    match Player::get_by_discord_id(&user.user_id.to_string(), db) {
+        Ok(Some(p)) => Ok(p),
+        Ok(None) => Err((
+            Status::Forbidden,
+            "You are not a registered player".to_string(),
+        )),
+        Err(e) => Err((Status::InternalServerError, e.to_string())),
+    }
+}
+
+#[post("/availability", data = "<form>")]
+async fn add_availability(
+    form: Form<AvailabilityForm>,
+    user: SignedIn,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Redirect, (Status, String)> {
+    let player = signed_in_player(&user, &mut db)?;
+    let new = NewAvailabilityWindow::parse(
+        player.id,
+        form.weekday,
+        &form.start,
+        &form.end,
+        &form.timezone,
+    )
+    .map_err(|e| (Status::BadRequest, e.to_string()))?;
+    let window = new
+        .save(&mut db)
+        .map_err(|e| (Status::InternalServerError, e.to_string()))?;
+    NewAuditEvent::new(
+        &user.actor(),
+        "add_availability",
+        AuditEntity::AvailabilityWindow,
+        window.id,
+    )
+    .after(&window)
+    .record(&mut db);
+    Ok(Redirect::to(uri!(availability())))
+}
+
+#[post("/availability/<id>/delete")]
+async fn delete_availability(
+    id: i32,
+    user: SignedIn,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Redirect, (Status, String)> {
+    let player = signed_in_player(&user, &mut db)?;
+    let window = match AvailabilityWindow::get_by_id(id, &mut db) {
+        Ok(w) => w,
+        Err(diesel::result::Error::NotFound) => {
+            return Err((Status::NotFound, "No such availability".to_string()));
+        }
+        Err(e) => {
+            return Err((Status::InternalServerError, e.to_string()));
+        }
+    };
+    if window.player_id != player.id {
+        return Err((
+            Status::Forbidden,
+            "That isn't your availability".to_string(),
+        ));
+    }
+    let audit = NewAuditEvent::new(
+        &user.actor(),
+        "delete_availability",
+        AuditEntity::AvailabilityWindow,
+        window.id,
+    )
+    .before(&window);
+    window
+        .delete(&mut db)
+        .map_err(|e| (Status::InternalServerError, e.to_string()))?;
+    audit.record(&mut db);
+    Ok(Redirect::to(uri!(availability())))
+}
+
 fn redirect_for_season(season: &Season) -> Redirect {
     match season.get_state() {
         Ok(s) => match s {
@@ -720,6 +1547,69 @@ async fn season_history(
     Ok(Template::render("season_history", ctx))
 }
 
+#[derive(Debug, Serialize)]
+struct RatingChartPoint {
+    x: f64,
+    y: f64,
+    label: String,
+}
+
+/// an SVG line chart of a player's rating over time
+#[derive(Debug, Serialize)]
+struct RatingChart {
+    width: f64,
+    height: f64,
+    /// the `points` attribute of the chart's polyline
+    polyline: String,
+    points: Vec<RatingChartPoint>,
+    min_rating: i64,
+    max_rating: i64,
+}
+
+impl RatingChart {
+    const WIDTH: f64 = 600.0;
+    const HEIGHT: f64 = 200.0;
+    /// room around the edges so the points aren't cut off
+    const PADDING: f64 = 6.0;
+
+    /// `None` if there isn't enough history to draw a line
+    fn new(history: &[RatingHistoryEntry]) -> Option<Self> {
+        if history.len() < 2 {
+            return None;
+        }
+        let min = history.iter().map(|h| h.rating).fold(f64::MAX, f64::min);
+        let max = history.iter().map(|h| h.rating).fold(f64::MIN, f64::max);
+        // a flat line goes in the middle
+        let range = if max - min < 1.0 { 1.0 } else { max - min };
+        let inner_width = Self::WIDTH - 2.0 * Self::PADDING;
+        let inner_height = Self::HEIGHT - 2.0 * Self::PADDING;
+        let points = history
+            .iter()
+            .enumerate()
+            .map(|(idx, h)| RatingChartPoint {
+                x: Self::PADDING + inner_width * idx as f64 / (history.len() - 1) as f64,
+                y: Self::PADDING + inner_height * (max - h.rating) / range,
+                label: format!(
+                    "Season {} {} round {}: {:.0}",
+                    h.season_ordinal, h.bracket_name, h.round_num, h.rating
+                ),
+            })
+            .collect::<Vec<_>>();
+        let polyline = points
+            .iter()
+            .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+            .join(" ");
+        Some(Self {
+            width: Self::WIDTH,
+            height: Self::HEIGHT,
+            polyline,
+            points,
+            min_rating: min.round() as i64,
+            max_rating: max.round() as i64,
+        })
+    }
+}
+
 async fn render_player_detail<F>(
     func: F,
     admin: Option<Admin>,
@@ -752,6 +1642,7 @@ where
         outcome: &'static str,
         time: String,
         opponent_time: String,
+        head_to_head_url: String,
     }
 
     #[derive(Debug, Serialize)]
@@ -789,6 +1680,7 @@ where
         fn add_race(
             &mut self,
             race: BracketRace,
+            games: Vec<BracketRaceGame>,
             round: BracketRound,
             our_player_id: i32,
             players: &HashMap<i32, Player>,
@@ -859,13 +1751,44 @@ where
                 .get(&their_id)
                 .map(|p| p.name.clone())
                 .unwrap_or("Unknown".to_string());
+            let head_to_head_url = uri!(head_to_head_detail(
+                player_1 = players
+                    .get(&our_player_id)
+                    .map(|p| p.name.as_str())
+                    .unwrap_or("Unknown"),
+                player_2 = &p
+            ))
+            .to_string();
+
+            let (time, opponent_time) = if games.is_empty() {
+                (
+                    res_opt_to_display(our_result),
+                    res_opt_to_display(their_result),
+                )
+            } else {
+                let outcomes = games
+                    .iter()
+                    .filter_map(|g| g.outcome().ok())
+                    .collect::<Vec<_>>();
+                let (p1_wins, p2_wins) = series_score(&outcomes);
+                let p1_status =
+                    series_status(p1_wins, games.iter().map(BracketRaceGame::player_1_result));
+                let p2_status =
+                    series_status(p2_wins, games.iter().map(BracketRaceGame::player_2_result));
+                if we_are_p1 {
+                    (p1_status, p2_status)
+                } else {
+                    (p2_status, p1_status)
+                }
+            };
 
             let rh = RaceHistory {
                 opponent: p,
                 round: round.round_num,
                 outcome: outcome,
-                time: res_opt_to_display(our_result),
-                opponent_time: res_opt_to_display(their_result),
+                time,
+                opponent_time,
+                head_to_head_url,
             };
             self.races.push(rh);
             self.races.sort_by_key(|rh| rh.round);
@@ -908,7 +1831,12 @@ where
                 .entry(season.id)
                 .or_insert(SeasonHistory::new(season, bracket));
 
-            history.add_race(race, round, id, &player_map);
+            let games = if race.is_series() {
+                race.games(db)?
+            } else {
+                vec![]
+            };
+            history.add_race(race, games, round, id, &player_map);
         }
         let mut histories: Vec<SeasonHistory> = season_histories.into_values().collect();
         histories.sort_by_key(|s| s.season.season_ordinal);
@@ -922,6 +1850,25 @@ where
             None
         }
     };
+    #[derive(Debug, Serialize)]
+    struct CurrentRating {
+        rating: String,
+        /// twice the rating deviation, i.e. roughly a 95% confidence interval
+        uncertainty: String,
+    }
+    let (rating, rating_chart) = match PlayerRating::history_for_player(player.id, db) {
+        Ok(history) => (
+            history.last().map(|h| CurrentRating {
+                rating: format!("{:.0}", h.rating),
+                uncertainty: format!("{:.0}", 2.0 * h.deviation),
+            }),
+            RatingChart::new(&history),
+        ),
+        Err(e) => {
+            warn!("Error getting player rating history: {e}");
+            (None, None)
+        }
+    };
     let pfp = match state.get_player_pfp(&player).await {
         Ok(s) => s,
         Err(e) => {
@@ -934,6 +1881,8 @@ where
         base_context: bc,
         player,
         player_history,
+        rating,
+        rating_chart,
         pfp
     };
     Ok(Template::render("player_detail", ctx))
@@ -967,6 +1916,229 @@ async fn player_detail(
     .await
 }
 
+/// renders an iCalendar feed of the races in `scope`. Commentator names can come from the player
+/// table or from discord, and looking them up needs its own db connection, so this gives `db` up
+/// first.
+async fn calendar_feed(
+    name: String,
+    scope: CalendarScope,
+    state: &Arc<DiscordState>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<(ContentType, String), Status> {
+    let races = calendar_races(scope, &mut db).map_err(|e| {
+        warn!("Error getting races for calendar {scope:?}: {e}");
+        Status::InternalServerError
+    })?;
+    drop(db);
+    let mut commentator_names = HashMap::new();
+    for discord_id in races
+        .iter()
+        .flat_map(|r| r.commentator_discord_ids.iter())
+        .unique()
+    {
+        if let Ok(id) = discord_id.parse::<Id<UserMarker>>() {
+            commentator_names.insert(discord_id.clone(), state.best_name_for(id).await);
+        }
+    }
+    Ok((
+        ContentType::Calendar,
+        to_ics(&name, &races, &commentator_names, epoch_timestamp() as i64),
+    ))
+}
+
+#[get("/season/<season_ordinal>/calendar.ics")]
+async fn season_calendar(
+    season_ordinal: i32,
+    state: &State<Arc<DiscordState>>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<(ContentType, String), Status> {
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    calendar_feed(
+        format!("NMG League Season {}", szn.ordinal),
+        CalendarScope::Season(szn.id),
+        state,
+        db,
+    )
+    .await
+}
+
+#[get("/season/<season_ordinal>/bracket/<bracket_id>/calendar.ics")]
+async fn bracket_calendar(
+    season_ordinal: i32,
+    bracket_id: i32,
+    state: &State<Arc<DiscordState>>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<(ContentType, String), Status> {
+    let szn = match Season::get_by_ordinal(season_ordinal, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    let bracket = match Bracket::get_by_id(bracket_id, &mut db) {
+        Ok(s) => Ok(s),
+        Err(diesel::result::Error::NotFound) => Err(Status::NotFound),
+        Err(_) => Err(Status::InternalServerError),
+    }?;
+    if bracket.season_id != szn.id {
+        return Err(Status::NotFound);
+    }
+    calendar_feed(
+        format!("NMG League Season {}: {}", szn.ordinal, bracket.name),
+        CalendarScope::Bracket(bracket.id),
+        state,
+        db,
+    )
+    .await
+}
+
+#[get("/player/<name>/calendar.ics")]
+async fn player_calendar(
+    name: String,
+    state: &State<Arc<DiscordState>>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<(ContentType, String), Status> {
+    let player = match Player::get_by_name(&name, &mut db) {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
+        Err(e) => {
+            warn!("Error getting player for calendar: {e}");
+            return Err(Status::InternalServerError);
+        }
+    };
+    calendar_feed(
+        format!("NMG League: {}", player.name),
+        CalendarScope::Player(player.id),
+        state,
+        db,
+    )
+    .await
+}
+
+#[derive(Debug, Serialize)]
+struct HeadToHeadRow {
+    title: String,
+    /// "win", "loss", or "draw" for player 1; empty if unfinished
+    outcome: &'static str,
+    player_1_result: String,
+    player_2_result: String,
+    racetime_gg_url: Option<String>,
+}
+
+impl HeadToHeadRow {
+    fn new(race: &HeadToHeadRace) -> Self {
+        use nmg_league_bot::models::bracket_races::Outcome as RaceOutcome;
+        use nmg_league_bot::models::bracket_races::Outcome::{P1Win, P2Win, Tie};
+        let display = |r: &Option<PlayerResult>| match r {
+            Some(pr) => pr.to_string(),
+            None => "-".to_string(),
+        };
+        let (player_1_result, player_2_result) = if race.games.is_empty() {
+            (
+                display(&race.player_1_result),
+                display(&race.player_2_result),
+            )
+        } else {
+            let outcomes = race
+                .games
+                .iter()
+                .filter_map(|g| match (&g.player_1_result, &g.player_2_result) {
+                    (Some(p1), Some(p2)) => Some(RaceOutcome::from((p1, p2))),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let (p1_wins, p2_wins) = series_score(&outcomes);
+            let p1_times = race
+                .games
+                .iter()
+                .map(|g| display(&g.player_1_result))
+                .join(", ");
+            let p2_times = race
+                .games
+                .iter()
+                .map(|g| display(&g.player_2_result))
+                .join(", ");
+            (
+                format!("{p1_wins}: {p1_times}"),
+                format!("{p2_wins}: {p2_times}"),
+            )
+        };
+        let round = if race.placement_race {
+            format!("Round {} (placement race)", race.round_num)
+        } else {
+            format!("Round {}", race.round_num)
+        };
+        Self {
+            title: format!(
+                "Season {} ({}), {round}",
+                race.season_ordinal, race.bracket_name
+            ),
+            outcome: match race.outcome {
+                Some(P1Win) => "win",
+                Some(P2Win) => "loss",
+                Some(Tie) => "draw",
+                None => "",
+            },
+            player_1_result,
+            player_2_result,
+            racetime_gg_url: race.racetime_gg_url.clone(),
+        }
+    }
+}
+
+#[get("/head_to_head/<player_1>/<player_2>")]
+async fn head_to_head_detail(
+    player_1: String,
+    player_2: String,
+    admin: Option<Admin>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    let bc = BaseContext::new(&mut db, &admin);
+    let (p1, p2) = match (
+        Player::get_by_name(&player_1, &mut db),
+        Player::get_by_name(&player_2, &mut db),
+    ) {
+        // nobody has a head to head with themselves
+        (Ok(Some(p1)), Ok(Some(p2))) if p1.id != p2.id => (p1, p2),
+        (Ok(_), Ok(_)) => {
+            return Err(Status::NotFound);
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            warn!("Error getting players for head to head: {e}");
+            return Err(Status::InternalServerError);
+        }
+    };
+    let h2h = head_to_head(p1.id, p2.id, &mut db).map_err(|e| {
+        warn!("Error getting head to head races: {e}");
+        Status::InternalServerError
+    })?;
+    let races = h2h.races.iter().map(HeadToHeadRow::new).collect::<Vec<_>>();
+    let average_time_delta = h2h.record.average_time_delta.map(|d| {
+        let secs = d.abs().round() as u64;
+        if secs == 0 {
+            return "even".to_string();
+        }
+        let faster = if d < 0.0 { &p1.name } else { &p2.name };
+        format!("{faster} by {}", format_hms(secs))
+    });
+    Ok(Template::render(
+        "head_to_head",
+        context! {
+            base_context: bc,
+            player_1: p1,
+            player_2: p2,
+            record: h2h.record,
+            average_time_delta,
+            races,
+        },
+    ))
+}
+
 #[get("/helper_bot")]
 async fn helper_bot(
     mut db: ConnectionWrapper<'_>,
@@ -977,6 +2149,25 @@ async fn helper_bot(
         context! { base_context: BaseContext::new(&mut db, &admin)},
     ))
 }
+#[get("/stats")]
+async fn stats(
+    admin: Option<Admin>,
+    cache: &State<StatsCache>,
+    mut db: ConnectionWrapper<'_>,
+) -> Result<Template, Status> {
+    let stats = cache.get(&mut db).await.map_err(|e| {
+        warn!("Error computing statistics: {e}");
+        Status::InternalServerError
+    })?;
+    Ok(Template::render(
+        "stats",
+        context! {
+            base_context: BaseContext::new(&mut db, &admin),
+            stats: stats.as_ref(),
+        },
+    ))
+}
+
 #[get("/")]
 async fn home(mut db: ConnectionWrapper<'_>, admin: Option<Admin>) -> Result<Template, Status> {
     Ok(Template::render(
@@ -1007,6 +2198,12 @@ fn option_default(
     }
 }
 
+/// this function is for use in templates: formats a number of seconds as h:mm:ss
+fn hms(v: &Value, _h: &HashMap<String, Value>) -> rocket_dyn_templates::tera::Result<Value> {
+    let secs = try_get_value!("hms", "value", f64, v);
+    Ok(to_value(format_hms(secs.round() as u64))?)
+}
+
 pub(crate) async fn launch_website(
     state: Arc<DiscordState>,
     bri_sender: Sender<BracketRaceInfoId>,
@@ -1027,22 +2224,38 @@ pub(crate) async fn launch_website(
                 season_standings,
                 season_brackets,
                 season_qualifiers,
+                season_seeding,
+                create_seeded_brackets,
+                qualifier_review_queue,
+                review_qualifier,
+                race_admin,
+                audit_log,
                 season_redirect,
                 season_history,
                 current_season_redirect,
                 home,
                 player_detail,
                 player_detail_by_id,
+                head_to_head_detail,
+                season_calendar,
+                bracket_calendar,
+                player_calendar,
+                stats,
                 bracket_detail,
-                helper_bot
+                helper_bot,
+                availability,
+                add_availability,
+                delete_availability
             ],
         )
         .attach(Template::custom(|e| {
             e.tera.register_filter("option_default", option_default);
+            e.tera.register_filter("hms", hms);
         }))
         .manage(state)
         .manage(session_manager)
         .manage(oauth_client)
+        .manage(StatsCache::new())
         .manage(db);
     let rocket = api::build_rocket(rocket);
     let rocket = auth::build_rocket(rocket);
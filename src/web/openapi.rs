@@ -0,0 +1,319 @@
+//! Builds an [OpenAPI](https://spec.openapis.org/oas/v3.0.3) document for the API out of its
+//! routes and the types they take and return.
+//!
+//! Paths, methods and parameter names come from the [Route]s themselves. Everything a route
+//! doesn't know about itself (what it's for, what it returns, who can call it) is described by an
+//! [Endpoint] with the same name as the route's handler, and every route needs one.
+
+use crate::web::pagination::{Paginated, MAX_PAGE_SIZE, NEXT_CURSOR_HEADER};
+use rocket::Route;
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use schemars::transform::Transform;
+use schemars::{JsonSchema, Schema};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+pub(crate) type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Auth {
+    Public,
+    /// an API token, as `Authorization: Bearer <token>`
+    ApiToken,
+    /// a website admin's session cookie
+    AdminSession,
+}
+
+/// what the spec says about a route that the route can't say for itself
+pub(crate) struct Endpoint {
+    /// the name of the route's handler
+    handler: &'static str,
+    summary: &'static str,
+    auth: Auth,
+    body: Option<SchemaFn>,
+    /// None if there's nothing in successful responses
+    response: Option<SchemaFn>,
+    /// [Paginated::SORT_FIELDS] of whatever paginated endpoints list
+    sort_fields: &'static [&'static str],
+    /// query parameters that can be given more than once
+    repeated: &'static [&'static str],
+    /// query parameters that have to be given
+    required: &'static [&'static str],
+}
+
+impl Endpoint {
+    pub(crate) fn new(handler: &'static str, summary: &'static str) -> Self {
+        Self {
+            handler,
+            summary,
+            auth: Auth::Public,
+            body: None,
+            response: None,
+            sort_fields: &[],
+            repeated: &[],
+            required: &[],
+        }
+    }
+
+    pub(crate) fn auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub(crate) fn body<T: JsonSchema>(mut self) -> Self {
+        self.body = Some(schema::<T>);
+        self
+    }
+
+    pub(crate) fn returns<T: JsonSchema>(mut self) -> Self {
+        self.response = Some(schema::<T>);
+        self
+    }
+
+    /// returns pages of `T`s
+    pub(crate) fn paginated<T: JsonSchema + Paginated>(mut self) -> Self {
+        self.response = Some(schema::<Vec<T>>);
+        self.sort_fields = T::SORT_FIELDS;
+        self
+    }
+
+    /// returns a `B` with one page of `T`s in it
+    pub(crate) fn paginated_in<B: JsonSchema, T: Paginated>(mut self) -> Self {
+        self.response = Some(schema::<B>);
+        self.sort_fields = T::SORT_FIELDS;
+        self
+    }
+
+    pub(crate) fn repeated(mut self, params: &'static [&'static str]) -> Self {
+        self.repeated = params;
+        self
+    }
+
+    pub(crate) fn required(mut self, params: &'static [&'static str]) -> Self {
+        self.required = params;
+        self
+    }
+}
+
+/// everything needed to describe one version of the API
+pub(crate) struct ApiDescription {
+    pub(crate) title: &'static str,
+    pub(crate) version: &'static str,
+    /// where the routes are mounted
+    pub(crate) base: &'static str,
+    pub(crate) routes: Vec<Route>,
+    pub(crate) endpoints: Vec<Endpoint>,
+    /// the type of path and query parameters, by name. Parameters mean the same thing everywhere
+    /// they're used.
+    pub(crate) param: fn(&str, &mut SchemaGenerator) -> Option<Schema>,
+    /// what error responses look like
+    pub(crate) error: SchemaFn,
+}
+
+/// `schema`, with the transforms the generator applies to the schemas it keeps
+fn finish(mut schema: Schema, generator: &mut SchemaGenerator) -> Value {
+    for transform in generator.transforms_mut() {
+        transform.transform(&mut schema);
+    }
+    schema.to_value()
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({"application/json": {"schema": schema}})
+}
+
+/// turns rocket's `<ordinal>` path segments into OpenAPI's `{ordinal}`, returning the new path
+/// and the parameter names
+fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut params = vec![];
+    let segments = path
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                Some(name) => {
+                    params.push(name.to_string());
+                    format!("{{{name}}}")
+                }
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<_>>();
+    (segments.join("/"), params)
+}
+
+fn pagination_params(sort_fields: &[&str]) -> Vec<Value> {
+    vec![
+        json!({
+            "name": "limit",
+            "in": "query",
+            "description": "Return at most this many items. Without it, everything is returned.",
+            "schema": {"type": "integer", "minimum": 1, "maximum": MAX_PAGE_SIZE},
+        }),
+        json!({
+            "name": "cursor",
+            "in": "query",
+            "description": format!("Return the items after the page this came from (see the {NEXT_CURSOR_HEADER} header)"),
+            "schema": {"type": "string"},
+        }),
+        json!({
+            "name": "sort",
+            "in": "query",
+            "description": "Field to sort by. Ties are broken by id.",
+            "schema": {"type": "string", "enum": sort_fields, "default": sort_fields.first()},
+        }),
+        json!({
+            "name": "order",
+            "in": "query",
+            "schema": {"type": "string", "enum": ["asc", "desc"]},
+        }),
+    ]
+}
+
+impl ApiDescription {
+    fn operation(
+        &self,
+        route: &Route,
+        endpoint: &Endpoint,
+        generator: &mut SchemaGenerator,
+    ) -> Result<(String, Value), String> {
+        let (path, path_params) = openapi_path(route.uri.path());
+        let param_schema = |name: &str, generator: &mut SchemaGenerator| {
+            (self.param)(name, generator)
+                .map(|s| finish(s, generator))
+                .ok_or_else(|| format!("{}: unknown parameter {name}", endpoint.handler))
+        };
+
+        let mut parameters = vec![];
+        for name in path_params {
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": param_schema(&name, generator)?,
+            }));
+        }
+        let mut paginated = false;
+        for segment in route.uri.query().into_iter().flat_map(|q| q.split('&')) {
+            let name = segment.trim_start_matches('<').trim_end_matches('>');
+            if name.ends_with("..") {
+                // the only trailing query parameters are pages
+                paginated = true;
+                parameters.extend(pagination_params(endpoint.sort_fields));
+                continue;
+            }
+            let mut schema = param_schema(name, generator)?;
+            if endpoint.repeated.contains(&name) {
+                schema = json!({"type": "array", "items": schema});
+            }
+            parameters.push(json!({
+                "name": name,
+                "in": "query",
+                "required": endpoint.required.contains(&name),
+                "schema": schema,
+            }));
+        }
+        if paginated == endpoint.sort_fields.is_empty() {
+            return Err(format!(
+                "{}: routes with pages need to be documented as paginated (and vice versa)",
+                endpoint.handler
+            ));
+        }
+
+        let mut success = match endpoint.response {
+            Some(response) => json!({
+                "description": "OK",
+                "content": json_content(finish(response(generator), generator)),
+            }),
+            None => json!({"description": "No Content"}),
+        };
+        if paginated {
+            success["headers"] = json!({
+                NEXT_CURSOR_HEADER: {
+                    "description": "Pass this as `cursor` to get the next page. Missing on the last page.",
+                    "schema": {"type": "string"},
+                }
+            });
+        }
+        let success_status = if endpoint.response.is_some() {
+            "200"
+        } else {
+            "204"
+        };
+        let mut operation = json!({
+            "operationId": endpoint.handler,
+            "summary": endpoint.summary,
+            "parameters": parameters,
+            "responses": {
+                success_status: success,
+                "default": {
+                    "description": "Error",
+                    "content": json_content(finish((self.error)(generator), generator)),
+                },
+            },
+        });
+        if let Some(body) = endpoint.body {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": json_content(finish(body(generator), generator)),
+            });
+        }
+        match endpoint.auth {
+            Auth::Public => {}
+            Auth::ApiToken => {
+                operation["security"] = json!([{"apiToken": []}]);
+            }
+            Auth::AdminSession => {
+                operation["security"] = json!([{"adminSession": []}]);
+            }
+        }
+        Ok((path, operation))
+    }
+
+    /// the OpenAPI document. Fails if a route doesn't have an [Endpoint], or has a parameter that
+    /// [ApiDescription::param] doesn't know about.
+    pub(crate) fn spec(&self) -> Result<Value, String> {
+        let mut generator = SchemaSettings::openapi3().for_serialize().into_generator();
+        let endpoints = self
+            .endpoints
+            .iter()
+            .map(|e| (e.handler, e))
+            .collect::<HashMap<_, _>>();
+        let mut paths = Map::new();
+        for route in &self.routes {
+            let name = route.name.as_deref().unwrap_or_default();
+            let endpoint = endpoints
+                .get(name)
+                .ok_or_else(|| format!("Route {name} ({}) isn't documented", route.uri))?;
+            let (path, operation) = self.operation(route, endpoint, &mut generator)?;
+            let methods = paths
+                .entry(path)
+                .or_insert_with(|| Value::Object(Map::new()));
+            methods[route.method.as_str().to_lowercase()] = operation;
+        }
+
+        Ok(json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": self.title,
+                "version": self.version,
+            },
+            "servers": [{"url": self.base}],
+            "paths": paths,
+            "components": {
+                "schemas": generator.take_definitions(true),
+                "securitySchemes": {
+                    "apiToken": {"type": "http", "scheme": "bearer"},
+                    "adminSession": {
+                        "type": "apiKey",
+                        "in": "cookie",
+                        "name": crate::web::SESSION_COOKIE_NAME,
+                    },
+                },
+            },
+        }))
+    }
+}
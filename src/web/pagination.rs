@@ -0,0 +1,376 @@
+//! Cursor pagination and sorting for the API's list endpoints.
+//!
+//! Lists are sorted by one of a few fields (with the item's id breaking ties), and a cursor is the
+//! sort key of the last item on a page. Asking for the page after a cursor returns everything that
+//! sorts after it, so pages don't shift around when new things are added.
+
+use nmg_league_bot::utils::{hex_string, parse_hex_string};
+use rocket::FromForm;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// the biggest `limit` a page can have
+pub(crate) const MAX_PAGE_SIZE: usize = 500;
+/// set on paginated responses that have more results after them
+pub(crate) const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+/// a query parameter that couldn't be used, and why
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub(crate) struct InvalidParam {
+    pub(crate) param: String,
+    pub(crate) reason: String,
+}
+
+impl InvalidParam {
+    pub(crate) fn new<S: Into<String>>(param: &str, reason: S) -> Self {
+        Self {
+            param: param.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// parses the query parameter `name`, if it was given, recording it in `invalid` if it can't be
+/// parsed
+pub(crate) fn parse_param<T: FromStr>(
+    name: &str,
+    value: &Option<String>,
+    invalid: &mut Vec<InvalidParam>,
+) -> Option<T> {
+    let value = value.as_ref()?;
+    match value.parse() {
+        Ok(t) => Some(t),
+        Err(_) => {
+            invalid.push(InvalidParam::new(
+                name,
+                format!("Unable to parse {value:?}"),
+            ));
+            None
+        }
+    }
+}
+
+/// [parse_param] for query parameters that can be given more than once
+pub(crate) fn parse_repeated_param<T: FromStr>(
+    name: &str,
+    values: &[String],
+    invalid: &mut Vec<InvalidParam>,
+) -> Vec<T> {
+    values
+        .iter()
+        .filter_map(|v| parse_param(name, &Some(v.clone()), invalid))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl FromStr for SortOrder {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            _ => Err(()),
+        }
+    }
+}
+
+/// things the API returns lists of
+pub(crate) trait Paginated {
+    /// the fields lists of these can be sorted by. The first one is the default.
+    const SORT_FIELDS: &'static [&'static str];
+    /// the order used if the request doesn't say
+    const DEFAULT_ORDER: SortOrder = SortOrder::Asc;
+
+    /// the value of `field` (which is always one of [Self::SORT_FIELDS]) for this item
+    fn sort_key(&self, field: &str) -> Value;
+    /// unique within the list
+    fn id(&self) -> i64;
+}
+
+/// the query parameters every list endpoint takes
+#[derive(FromForm, Debug, Default)]
+pub(crate) struct PageParams {
+    limit: Option<String>,
+    cursor: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Cursor {
+    sort: String,
+    order: SortOrder,
+    key: Value,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        // serializing a struct of strings, json values and ints can't fail
+        hex_string(serde_json::to_string(self).unwrap_or_default().as_bytes())
+    }
+
+    fn decode(s: &str) -> Option<Self> {
+        serde_json::from_slice(&parse_hex_string(s)?).ok()
+    }
+}
+
+/// [PageParams] that have been checked against what's being paginated
+#[derive(Debug)]
+pub(crate) struct Pagination {
+    /// None for everything
+    limit: Option<usize>,
+    after: Option<Cursor>,
+    sort: String,
+    order: SortOrder,
+}
+
+/// one page of a list
+#[derive(Debug)]
+pub(crate) struct Page<T> {
+    pub(crate) items: Vec<T>,
+    /// pass this as `cursor` to get the next page. None if this is the last one.
+    pub(crate) next_cursor: Option<String>,
+}
+
+impl PageParams {
+    /// checks these parameters make sense for a list of `T`s, recording any that don't in
+    /// `invalid`
+    pub(crate) fn validate<T: Paginated>(
+        &self,
+        invalid: &mut Vec<InvalidParam>,
+    ) -> Option<Pagination> {
+        let errors_before = invalid.len();
+        let limit = parse_param::<usize>("limit", &self.limit, invalid);
+        if let Some(l) = limit {
+            if l == 0 || l > MAX_PAGE_SIZE {
+                invalid.push(InvalidParam::new(
+                    "limit",
+                    format!("Must be between 1 and {MAX_PAGE_SIZE}"),
+                ));
+            }
+        }
+        let sort = match &self.sort {
+            Some(s) if T::SORT_FIELDS.contains(&s.as_str()) => s.clone(),
+            Some(_) => {
+                invalid.push(InvalidParam::new(
+                    "sort",
+                    format!("Must be one of {}", T::SORT_FIELDS.join(", ")),
+                ));
+                String::new()
+            }
+            None => T::SORT_FIELDS[0].to_string(),
+        };
+        let order = match &self.order {
+            Some(o) => o.parse().unwrap_or_else(|_| {
+                invalid.push(InvalidParam::new("order", "Must be asc or desc"));
+                T::DEFAULT_ORDER
+            }),
+            None => T::DEFAULT_ORDER,
+        };
+        let after = match &self.cursor {
+            Some(c) => match Cursor::decode(c) {
+                Some(cursor) if cursor.sort == sort && cursor.order == order => Some(cursor),
+                Some(_) => {
+                    invalid.push(InvalidParam::new(
+                        "cursor",
+                        "This cursor is from a differently sorted list",
+                    ));
+                    None
+                }
+                None => {
+                    invalid.push(InvalidParam::new("cursor", "Invalid cursor"));
+                    None
+                }
+            },
+            None => None,
+        };
+        if invalid.len() > errors_before {
+            return None;
+        }
+        Some(Pagination {
+            limit,
+            after,
+            sort,
+            order,
+        })
+    }
+}
+
+/// nulls, then booleans, then numbers, then strings
+fn compare_keys(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) | Value::Object(_) => 4,
+        }
+    }
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => a
+                .as_f64()
+                .unwrap_or_default()
+                .total_cmp(&b.as_f64().unwrap_or_default()),
+        },
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+impl Pagination {
+    fn compare(&self, a: &(Value, i64), b: &(Value, i64)) -> Ordering {
+        let ord = compare_keys(&a.0, &b.0).then(a.1.cmp(&b.1));
+        match self.order {
+            SortOrder::Asc => ord,
+            SortOrder::Desc => ord.reverse(),
+        }
+    }
+
+    /// sorts `items` and returns the requested page of them
+    pub(crate) fn apply<T: Paginated>(&self, items: Vec<T>) -> Page<T> {
+        let mut keyed = items
+            .into_iter()
+            .map(|t| ((t.sort_key(&self.sort), t.id()), t))
+            .collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| self.compare(a, b));
+        if let Some(after) = &self.after {
+            let after = (after.key.clone(), after.id);
+            keyed.retain(|(k, _)| self.compare(k, &after) == Ordering::Greater);
+        }
+        let next_cursor = match self.limit {
+            Some(limit) if keyed.len() > limit => {
+                keyed.truncate(limit);
+                keyed.last().map(|((key, id), _)| {
+                    Cursor {
+                        sort: self.sort.clone(),
+                        order: self.order,
+                        key: key.clone(),
+                        id: *id,
+                    }
+                    .encode()
+                })
+            }
+            _ => None,
+        };
+        Page {
+            items: keyed.into_iter().map(|(_, t)| t).collect(),
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::web::pagination::{InvalidParam, PageParams, Paginated};
+    use serde_json::{json, Value};
+
+    struct Thing {
+        id: i32,
+        time: Option<i32>,
+    }
+
+    impl Paginated for Thing {
+        const SORT_FIELDS: &'static [&'static str] = &["id", "time"];
+
+        fn sort_key(&self, field: &str) -> Value {
+            match field {
+                "time" => json!(self.time),
+                _ => json!(self.id),
+            }
+        }
+
+        fn id(&self) -> i64 {
+            self.id as i64
+        }
+    }
+
+    fn things() -> Vec<Thing> {
+        [(3, Some(50)), (1, Some(100)), (2, None), (4, Some(50))]
+            .into_iter()
+            .map(|(id, time)| Thing { id, time })
+            .collect()
+    }
+
+    fn params(limit: Option<&str>, cursor: Option<String>, sort: Option<&str>) -> PageParams {
+        PageParams {
+            limit: limit.map(String::from),
+            cursor,
+            sort: sort.map(String::from),
+            order: None,
+        }
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut invalid = vec![];
+        let mut cursor = None;
+        let mut seen = vec![];
+        loop {
+            let page = params(Some("3"), cursor, Some("time"))
+                .validate::<Thing>(&mut invalid)
+                .unwrap()
+                .apply(things());
+            seen.extend(page.items.iter().map(|t| t.id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert!(invalid.is_empty());
+        // no time first, then ties broken by id
+        assert_eq!(vec![2, 3, 4, 1], seen);
+
+        let all = params(None, None, None)
+            .validate::<Thing>(&mut invalid)
+            .unwrap()
+            .apply(things());
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            all.items.iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+        assert!(all.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let mut invalid = vec![];
+        let first = params(Some("1"), None, Some("time"))
+            .validate::<Thing>(&mut invalid)
+            .unwrap()
+            .apply(things());
+        assert!(params(Some("0"), first.next_cursor.clone(), Some("id"))
+            .validate::<Thing>(&mut invalid)
+            .is_none());
+        assert!(params(Some("x"), Some("zz".to_string()), Some("name"))
+            .validate::<Thing>(&mut invalid)
+            .is_none());
+        let reversed = PageParams {
+            order: Some("desc".to_string()),
+            ..params(Some("1"), first.next_cursor, Some("time"))
+        };
+        assert!(reversed.validate::<Thing>(&mut invalid).is_none());
+        assert_eq!(
+            vec!["limit", "cursor", "limit", "sort", "cursor", "cursor"],
+            invalid.iter().map(|i| i.param.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            InvalidParam::new("limit", "Must be between 1 and 500"),
+            invalid[0]
+        );
+    }
+}
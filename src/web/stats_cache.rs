@@ -0,0 +1,39 @@
+use diesel::SqliteConnection;
+use nmg_league_bot::models::stats::Statistics;
+use std::sync::Arc;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::{Duration, Instant};
+
+/// how long computed statistics are served before being recomputed
+const STATS_CACHE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Computing [Statistics] reads every bracket race there has ever been, so the stats page and API
+/// share one copy and only recompute it once it's stale.
+pub(crate) struct StatsCache {
+    cached: AsyncMutex<Option<(Instant, Arc<Statistics>)>>,
+}
+
+impl StatsCache {
+    pub(crate) fn new() -> Self {
+        Self {
+            cached: AsyncMutex::new(None),
+        }
+    }
+
+    pub(crate) async fn get(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<Arc<Statistics>, diesel::result::Error> {
+        // holding the lock while computing means concurrent requests wait for one computation
+        // instead of all doing their own
+        let mut cached = self.cached.lock().await;
+        if let Some((computed_at, stats)) = cached.as_ref() {
+            if computed_at.elapsed() < STATS_CACHE_DURATION {
+                return Ok(stats.clone());
+            }
+        }
+        let stats = Arc::new(Statistics::compute(conn)?);
+        *cached = Some((Instant::now(), stats.clone()));
+        Ok(stats)
+    }
+}
@@ -2,77 +2,98 @@
 this shitty module is stuff for workers::* to call so that I can also call it from test code
  */
 
+use crate::models::bracket_race_games::{series_score, BracketRaceGame};
 use crate::models::bracket_race_infos::BracketRaceInfo;
 use crate::models::bracket_races::{BracketRace, Outcome, PlayerResult};
+use crate::models::bracket_rounds::BracketRound;
+use crate::models::brackets::{Bracket, BracketError};
+use crate::models::cancelled_races::CancelledRace;
 use crate::models::player::Player;
+use crate::models::ratings::mark_ratings_stale;
 use crate::models::season::Season;
+use crate::models::webhooks::{fire_event, LeagueEvent};
 use crate::racetime_types::{Entrant, RacetimeRace};
 use crate::{BracketRaceStateError, ChannelConfig, NMGLeagueBotError};
-use diesel::SqliteConnection;
+use diesel::{Connection, SqliteConnection};
+use enum_iterator::Sequence;
+use itertools::Itertools;
 use log::{debug, info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 use twilight_http::response::DeserializeBodyError;
 use twilight_http::Client;
-use twilight_model::channel::message::embed::{Embed, EmbedField};
+use twilight_model::channel::message::embed::{Embed, EmbedField, EmbedFooter};
 use twilight_model::channel::Message;
 use twilight_model::id::marker::ChannelMarker;
 use twilight_model::id::Id;
 use twilight_validate::message::MessageValidationError;
 
+/// a bracket race we're looking for, along with all of its players (for head-to-head races,
+/// that's (Player 1, Player 2))
+pub type InterestingRace<'a> = (&'a BracketRaceInfo, &'a BracketRace, Vec<&'a Player>);
+
 /// takes a list of all existing players & bracket races, and returns a map of
 /// <one of the player's racetime usernames : a bunch of info about the race>
 /// the username in question is lowercased
 ///
+/// `race_entrants` has the player ids of the entrants of each placement race, by race id
+///
 /// this is sort of insane, right?
 pub fn races_by_player_rtgg<'a>(
     all_players: &'a [Player],
     bracket_races: &'a [(BracketRaceInfo, BracketRace)],
-) -> HashMap<String, (&'a BracketRaceInfo, &'a BracketRace, &'a Player, &'a Player)> {
+    race_entrants: &HashMap<i32, Vec<i32>>,
+) -> HashMap<String, InterestingRace<'a>> {
     let players_lookup: HashMap<i32, &Player> = all_players.iter().map(|p| (p.id, p)).collect();
 
-    let mut interesting_rtgg_ids: HashMap<
-        String,
-        (&BracketRaceInfo, &BracketRace, &Player, &Player),
-    > = Default::default();
+    let mut interesting_rtgg_ids: HashMap<String, InterestingRace> = Default::default();
 
     for (bri, br) in bracket_races {
-        let p1 = match players_lookup.get(&br.player_1_id) {
-            Some(p) => p,
-            None => {
-                continue;
+        let player_ids = if br.is_placement_race() {
+            match race_entrants.get(&br.id) {
+                Some(ids) => ids.clone(),
+                None => {
+                    continue;
+                }
             }
+        } else {
+            vec![br.player_1_id, br.player_2_id]
         };
-        let p2 = match players_lookup.get(&br.player_2_id) {
-            Some(p) => p,
+        let players = match player_ids
+            .iter()
+            .map(|id| players_lookup.get(id).cloned())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(ps) => ps,
             None => {
                 continue;
             }
         };
-        match (&p1.racetime_username, &p2.racetime_username) {
-            (Some(rtu), Some(_)) => {
-                interesting_rtgg_ids.insert(rtu.to_lowercase(), (bri, br, p1, p2));
-            }
-            _ => {
-                // we need to know both players' rtgg usernames to find out that a race contains
-                // both of them
-            }
-        };
+        if players.iter().any(|p| p.racetime_username.is_none()) {
+            // we need to know every player's rtgg username to find out that a race contains all
+            // of them
+            continue;
+        }
+        if let Some(rtu) = players.first().and_then(|p| p.racetime_username.as_ref()) {
+            interesting_rtgg_ids.insert(rtu.to_lowercase(), (bri, br, players));
+        }
     }
     interesting_rtgg_ids
 }
 
-/// if this `race` is one we're looking for, return all the relevant info
+/// if this `race` is one we're looking for, return all the relevant info, with each of the bracket
+/// race's players (in the same order as in `bracket_races`) matched up with their racetime entrant
 /// consumes race.entrants
 pub fn interesting_race<'a>(
     race: &mut RacetimeRace,
-    bracket_races: &HashMap<String, (&'a BracketRaceInfo, &'a BracketRace, &'a Player, &'a Player)>,
+    bracket_races: &HashMap<String, InterestingRace<'a>>,
     season: &Season,
 ) -> Option<(
     &'a BracketRaceInfo,
     &'a BracketRace,
-    (&'a Player, Entrant),
-    (&'a Player, Entrant),
+    Vec<(&'a Player, Entrant)>,
 )> {
     if &race.goal.name != &season.rtgg_goal_name {
         debug!("Skipping because invalid goal name {}", race.goal.name);
@@ -104,7 +125,7 @@ pub fn interesting_race<'a>(
     let ids = { entrant_ids.keys().cloned().collect::<Vec<_>>() };
     for id in ids {
         // if *any* entrant is in one of the races we're looking for, let's check if they all are
-        if let Some((bri, br, p1, p2)) = bracket_races.get(&id) {
+        if let Some((bri, br, players)) = bracket_races.get(&id) {
             debug!("Found interesting rtgg id {id}, looking closer");
             // but, okay, let's not pick up a weekly from 2 months ago, lmao
             let scheduled = match bri.scheduled() {
@@ -121,26 +142,32 @@ pub fn interesting_race<'a>(
                 );
                 continue;
             }
-            let p1rt = match &p1.racetime_username {
-                Some(s) => s,
+            let rt_usernames = match players
+                .iter()
+                .map(|p| p.racetime_username.as_ref().map(|s| s.to_lowercase()))
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(names) => names,
                 None => {
                     continue;
                 }
             };
-            let p2rt = match &p2.racetime_username {
-                Some(s) => s,
-                None => {
-                    continue;
-                }
-            };
-            debug!("Found rt usernames for both players: {p1rt} vs {p2rt}");
+            debug!(
+                "Found rt usernames for all players: {}",
+                rt_usernames.join(" vs ")
+            );
 
-            let e1o = entrant_ids.remove(&p1rt.to_lowercase());
-            let e2o = entrant_ids.remove(&p2rt.to_lowercase());
-            debug!("Found these entrants: {e1o:?}, {e2o:?}");
-            if let (Some(e1), Some(e2)) = (e1o, e2o) {
-                return Some((bri, br, (p1, e1), (p2, e2)));
+            if !rt_usernames.iter().all(|n| entrant_ids.contains_key(n)) {
+                debug!("Not every player entered this race");
+                continue;
             }
+            let entrants = players
+                .iter()
+                .zip(rt_usernames.iter())
+                .filter_map(|(p, n)| entrant_ids.remove(n).map(|e| (*p, e)))
+                .collect::<Vec<_>>();
+            debug!("Found these entrants: {entrants:?}");
+            return Some((bri, br, entrants));
         }
     }
     None
@@ -166,6 +193,9 @@ pub enum RaceFinishError {
     #[error("Race wasn't finished?")]
     NotFinished,
 
+    #[error("{0}")]
+    BracketError(#[from] BracketError),
+
     #[error("Error deserializing discord response: {0}")]
     DeserializeBodyError(#[from] DeserializeBodyError),
 }
@@ -180,6 +210,9 @@ pub struct RaceFinishOptions {
     pub player_1_result: PlayerResult,
     pub player_2: Player,
     pub player_2_result: PlayerResult,
+    /// the racetime room these results are from, if known. For series, this is how we tell games
+    /// apart, so reporting results from the same room twice doesn't record two games.
+    pub racetime_gg_url: Option<String>,
     pub channel_id: Id<ChannelMarker>,
     pub force_update: bool,
 }
@@ -187,9 +220,14 @@ pub struct RaceFinishOptions {
 /**
 This function does these things:
 
-1. set the result fields on the race, and update its state to finished if relevant
+1. set the result fields on the race (or, for best-of-N series, records a game), and update its
+   state to finished if relevant
 2. saves that race
-3. if a [Client] is supplied, posts a message in #match-results
+3. if the race is over, queues a webhook event
+4. if a [Client] is supplied, posts a message in #match-results
+5. if this finished the current round of an elimination bracket, generates the next round.
+   Results can't change who won once that round exists.
+6. if the race is over, marks everyone's ratings as needing to be recomputed
 */
 // N.B. this doesn't take a DiscordState because that's part of the `discord` module, which isn't
 // compiled into the `lib`
@@ -199,15 +237,67 @@ pub async fn trigger_race_finish(
     client: Option<&Client>,
     channel_config: &ChannelConfig,
 ) -> Result<(), RaceFinishError> {
-    options.bracket_race.add_results(
-        Some(&options.player_1_result),
-        Some(&options.player_2_result),
-        options.force_update,
-    )?;
-    options.bracket_race.update(conn)?;
+    let previous_outcome = options.bracket_race.outcome()?;
+    // None if this game was already recorded
+    let game = conn.transaction(|c| -> Result<_, RaceFinishError> {
+        let game = if options.bracket_race.is_series() {
+            match options.bracket_race.add_game(
+                &options.player_1_result,
+                &options.player_2_result,
+                options.racetime_gg_url.clone(),
+                options.force_update,
+                c,
+            )? {
+                Some(g) => Some(g),
+                None => {
+                    debug!(
+                        "Game in {:?} was already recorded for race {}",
+                        options.racetime_gg_url, options.bracket_race.id
+                    );
+                    return Ok(None);
+                }
+            }
+        } else {
+            options.bracket_race.add_results(
+                Some(&options.player_1_result),
+                Some(&options.player_2_result),
+                options.force_update,
+            )?;
+            options.bracket_race.update(c)?;
+            None
+        };
+        if previous_outcome.is_some() && options.bracket_race.outcome()? != previous_outcome {
+            options
+                .bracket_race
+                .bracket(c)?
+                .check_winner_can_change(&options.bracket_race, c)?;
+        }
+        Ok(Some(game))
+    })?;
+    let game = match game {
+        Some(g) => g,
+        None => {
+            return Ok(());
+        }
+    };
+    after_results_recorded(options, game.as_ref(), conn, client, channel_config).await;
+    Ok(())
+}
+
+/// steps 3-6 of [trigger_race_finish]
+async fn after_results_recorded(
+    mut options: RaceFinishOptions,
+    game: Option<&BracketRaceGame>,
+    conn: &mut SqliteConnection,
+    client: Option<&Client>,
+    channel_config: &ChannelConfig,
+) {
+    if options.bracket_race.is_complete() {
+        fire_race_finished(&options.bracket_race, &options.info, conn);
+    }
 
     if let Some(c) = client {
-        if let Err(e) = post_match_results(c, &options, conn).await {
+        if let Err(e) = post_match_results(c, &options, game, conn).await {
             warn!(
                 "Error posting match results for race {}: {e}",
                 options.bracket_race.id
@@ -223,20 +313,216 @@ pub async fn trigger_race_finish(
         // TODO: maybe clear other messages? under some circumstances?
     }
 
-    Ok(())
+    match options.bracket_race.bracket(conn) {
+        Ok(mut bracket) => match bracket.advance_elimination(conn) {
+            Ok(true) => {
+                info!("Generated the next round of bracket {}", bracket.id);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                warn!("Error advancing bracket {}: {e}", bracket.id);
+            }
+        },
+        Err(e) => {
+            warn!(
+                "Error fetching bracket for race {}: {e}",
+                options.bracket_race.id
+            );
+        }
+    }
+
+    if options.bracket_race.is_complete() {
+        mark_ratings_stale();
+    }
 }
 
-async fn post_match_results(
-    c: &Client,
-    options: &RaceFinishOptions,
+fn fire_race_finished(race: &BracketRace, info: &BracketRaceInfo, conn: &mut SqliteConnection) {
+    fire_event(
+        LeagueEvent::RaceFinished {
+            race_id: race.id,
+            bracket_id: race.bracket_id,
+            racetime_gg_url: info.racetime_gg_url.clone(),
+        },
+        conn,
+    );
+}
+
+pub struct PlacementRaceFinishOptions {
+    pub bracket_race: BracketRace,
+    pub info: BracketRaceInfo,
+    /// results for some or all of the race's entrants
+    pub results: Vec<(Player, PlayerResult)>,
+    pub channel_id: Id<ChannelMarker>,
+    pub force_update: bool,
+}
+
+/**
+Like [trigger_race_finish], but for placement races:
+
+1. records the entrants' results, and if everyone has a result, their placements
+2. if the race is now finished, marks everyone's ratings as needing to be recomputed and queues a
+   webhook event
+3. if a [Client] is supplied and the race is now finished, posts a message in #match-results
+*/
+pub async fn trigger_placement_race_finish(
+    mut options: PlacementRaceFinishOptions,
     conn: &mut SqliteConnection,
-) -> Result<Message, RaceFinishError> {
-    let bracket = options.bracket_race.bracket(conn)?;
-    let outcome = options
+    client: Option<&Client>,
+    channel_config: &ChannelConfig,
+) -> Result<(), RaceFinishError> {
+    let results = options
+        .results
+        .iter()
+        .map(|(p, r)| (p.id, r.clone()))
+        .collect::<Vec<_>>();
+    options
         .bracket_race
-        .outcome()?
-        .ok_or(RaceFinishError::NotFinished)?;
-    let winner = match outcome {
+        .add_placement_results(&results, options.force_update, conn)?;
+    after_placement_results_recorded(options, conn, client, channel_config).await
+}
+
+/// steps 2 and 3 of [trigger_placement_race_finish]
+async fn after_placement_results_recorded(
+    mut options: PlacementRaceFinishOptions,
+    conn: &mut SqliteConnection,
+    client: Option<&Client>,
+    channel_config: &ChannelConfig,
+) -> Result<(), RaceFinishError> {
+    if options.bracket_race.is_complete() {
+        mark_ratings_stale();
+        fire_race_finished(&options.bracket_race, &options.info, conn);
+    }
+
+    if let Some(c) = client {
+        if !options.bracket_race.is_complete() {
+            debug!(
+                "Placement race {} is still waiting on some results",
+                options.bracket_race.id
+            );
+            return Ok(());
+        }
+        let bracket = options.bracket_race.bracket(conn)?;
+        let description = describe_placements(&options.bracket_race, conn)?;
+        if let Err(e) = post_results_embed(
+            c,
+            options.channel_id,
+            options.bracket_race.id,
+            bracket.name,
+            description,
+            options.info.racetime_gg_url.as_ref(),
+        )
+        .await
+        {
+            warn!(
+                "Error posting match results for race {}: {e}",
+                options.bracket_race.id
+            );
+        }
+        if let Err(e) = clear_commportunities_message(&mut options.info, c, channel_config).await {
+            warn!(
+                "Error clearing commportunities message for race {}: {e}",
+                options.bracket_race.id
+            );
+        }
+    }
+    Ok(())
+}
+
+/// applies `round`'s [MissedDeadlinePolicy](crate::models::bracket_rounds::MissedDeadlinePolicy)
+/// to its unfinished races (see [BracketRound::apply_missed_deadline_policy]), then follows up on
+/// each race it finished the way [trigger_race_finish] and [trigger_placement_race_finish] do.
+/// Returns the races it finished.
+pub async fn apply_missed_deadline_policy(
+    round: &BracketRound,
+    conn: &mut SqliteConnection,
+    client: Option<&Client>,
+    channel_config: &ChannelConfig,
+) -> Result<Vec<BracketRace>, NMGLeagueBotError> {
+    let forfeited = round.apply_missed_deadline_policy(conn)?;
+    for race in &forfeited {
+        // the results are saved at this point, so don't act like it failed
+        if let Err(e) = after_double_forfeit(race.clone(), conn, client, channel_config).await {
+            warn!(
+                "Error following up on double forfeit of race {}: {e}",
+                race.id
+            );
+        }
+    }
+    Ok(forfeited)
+}
+
+async fn after_double_forfeit(
+    race: BracketRace,
+    conn: &mut SqliteConnection,
+    client: Option<&Client>,
+    channel_config: &ChannelConfig,
+) -> Result<(), RaceFinishError> {
+    let info = race.info(conn)?;
+    if race.is_placement_race() {
+        let results = race
+            .all_players(conn)?
+            .into_iter()
+            .map(|p| (p, PlayerResult::Forfeit))
+            .collect();
+        let options = PlacementRaceFinishOptions {
+            bracket_race: race,
+            info,
+            results,
+            channel_id: channel_config.match_results,
+            force_update: false,
+        };
+        after_placement_results_recorded(options, conn, client, channel_config).await
+    } else {
+        let (player_1, player_2) = race.players(conn)?;
+        let options = RaceFinishOptions {
+            bracket_race: race,
+            info,
+            player_1,
+            player_1_result: PlayerResult::Forfeit,
+            player_2,
+            player_2_result: PlayerResult::Forfeit,
+            racetime_gg_url: None,
+            channel_id: channel_config.match_results,
+            force_update: false,
+        };
+        after_results_recorded(options, None, conn, client, channel_config).await;
+        Ok(())
+    }
+}
+
+/// one line per entrant, best first, e.g. "1. **player 1** (1:30:00)"
+fn describe_placements(
+    race: &BracketRace,
+    conn: &mut SqliteConnection,
+) -> Result<String, RaceFinishError> {
+    let names = race
+        .all_players(conn)?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect::<HashMap<_, _>>();
+    let mut lines = vec![];
+    for entrant in race
+        .race_entrants(conn)?
+        .into_iter()
+        .sorted_by_key(|e| (e.placement, e.id))
+    {
+        let result = entrant.result().ok_or(RaceFinishError::NotFinished)??;
+        lines.push(format!(
+            "{}. **{}** ({})",
+            entrant.placement.ok_or(RaceFinishError::NotFinished)?,
+            names
+                .get(&entrant.player_id)
+                .map(String::as_str)
+                .unwrap_or("Unknown"),
+            result
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// e.g. "**player 1** (1:30:00) defeats **player 2** (1:35:00)"
+fn describe_result(outcome: Outcome, options: &RaceFinishOptions) -> String {
+    match outcome {
         Outcome::Tie => {
             format!(
                 "It's a tie?! **{}** ({}) vs **{}** ({})",
@@ -264,13 +550,89 @@ async fn post_match_results(
                 options.player_1_result
             )
         }
+    }
+}
+
+/// e.g. "**player 1** leads the series 1-0"
+fn describe_series(
+    options: &RaceFinishOptions,
+    conn: &mut SqliteConnection,
+) -> Result<String, RaceFinishError> {
+    let outcomes = options
+        .bracket_race
+        .games(conn)?
+        .iter()
+        .map(BracketRaceGame::outcome)
+        .collect::<Result<Vec<_>, _>>()?;
+    let (p1_wins, p2_wins) = series_score(&outcomes);
+    let (leader, leader_wins, trailer_wins) = if p1_wins >= p2_wins {
+        (&options.player_1.name, p1_wins, p2_wins)
+    } else {
+        (&options.player_2.name, p2_wins, p1_wins)
     };
+    Ok(match options.bracket_race.outcome()? {
+        Some(Outcome::Tie) => format!("The series ends in a tie, {p1_wins}-{p2_wins}"),
+        Some(_) => format!("**{leader}** wins the series {leader_wins}-{trailer_wins}"),
+        None if p1_wins == p2_wins => format!("The series is tied {p1_wins}-{p2_wins}"),
+        None => format!("**{leader}** leads the series {leader_wins}-{trailer_wins}"),
+    })
+}
+
+async fn post_match_results(
+    c: &Client,
+    options: &RaceFinishOptions,
+    game: Option<&BracketRaceGame>,
+    conn: &mut SqliteConnection,
+) -> Result<Message, RaceFinishError> {
+    let bracket = options.bracket_race.bracket(conn)?;
+    let (description, racetime_gg_url) = match game {
+        Some(g) => (
+            format!(
+                "Game {}: {}\n{}",
+                g.game_num,
+                describe_result(g.outcome()?, options),
+                describe_series(options, conn)?
+            ),
+            g.racetime_gg_url.as_ref(),
+        ),
+        None => {
+            let outcome = options
+                .bracket_race
+                .outcome()?
+                .ok_or(RaceFinishError::NotFinished)?;
+            (
+                describe_result(outcome, options),
+                options.info.racetime_gg_url.as_ref(),
+            )
+        }
+    };
+    post_results_embed(
+        c,
+        options.channel_id,
+        options.bracket_race.id,
+        bracket.name,
+        description,
+        racetime_gg_url,
+    )
+    .await
+}
+
+/// posts the results of a race in `channel_id`. The race id is in the footer, so players can
+/// `/dispute_race` it.
+async fn post_results_embed(
+    c: &Client,
+    channel_id: Id<ChannelMarker>,
+    race_id: i32,
+    bracket_name: String,
+    description: String,
+    racetime_gg_url: Option<&String>,
+) -> Result<Message, RaceFinishError> {
     let mut fields = vec![EmbedField {
         inline: false,
         name: "Division".to_string(),
-        value: bracket.name,
+        value: bracket_name,
     }];
-    if let Some(url) = &options.info.racetime_gg_url {
+    if let Some(url) = racetime_gg_url {
         fields.push(EmbedField {
             inline: false,
             name: "RaceTime room".to_string(),
@@ -281,9 +643,13 @@ async fn post_match_results(
     let embed = Embed {
         author: None,
         color: None,
-        description: Some(winner),
+        description: Some(description),
         fields,
-        footer: None,
+        footer: Some(EmbedFooter {
+            icon_url: None,
+            proxy_icon_url: None,
+            text: format!("Race #{race_id}"),
+        }),
         image: None,
         kind: "rich".to_string(),
         provider: None,
@@ -294,10 +660,7 @@ async fn post_match_results(
         video: None,
     };
     // the docs for .embeds() indicate that it returns an error, but in fact it saves the error for later
-    let m = c
-        .create_message(options.channel_id)
-        .embeds(&[embed])
-        .await?;
+    let m = c.create_message(channel_id).embeds(&[embed]).await?;
     m.model().await.map_err(From::from)
 }
 
@@ -361,6 +724,132 @@ pub async fn clear_commportunities_message(
     )
 }
 
+/// what to do with the unfinished races of a player who drops out of a bracket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Sequence, JsonSchema)]
+pub enum DroppedPlayerRaces {
+    /// the dropped player forfeits, and their opponent gets the win
+    Forfeit,
+    /// the races are deleted, as though they were never paired
+    Cancel,
+}
+
+/**
+Drops `player` out of `bracket` (see [Bracket::drop_player]) and then either forfeits or cancels
+their unfinished races in it.
+
+Cancelled races' commportunities and tentative commentary assignment messages are deleted if a
+[Client] is supplied; their Discord events are cleaned up by the helper bot. Scheduled ones are
+recorded as [CancelledRace]s, so calendar feeds show them as cancelled.
+
+Races in elimination brackets can't be cancelled, since somebody has to advance.
+
+Returns the number of races that were forfeited or cancelled
+*/
+pub async fn drop_player_from_bracket(
+    bracket: &mut Bracket,
+    player: &Player,
+    races: DroppedPlayerRaces,
+    conn: &mut SqliteConnection,
+    discord: Option<(&Client, &ChannelConfig)>,
+) -> Result<usize, NMGLeagueBotError> {
+    if races == DroppedPlayerRaces::Cancel && bracket.bracket_type()?.is_elimination() {
+        return Err(BracketError::Other(
+            "Races in elimination brackets can't be cancelled; forfeit them instead".to_string(),
+        )
+        .into());
+    }
+    let mut cancelled_infos = vec![];
+    let count = conn.transaction(|c| -> Result<usize, NMGLeagueBotError> {
+        let unfinished = bracket.drop_player(player, c)?;
+        let count = unfinished.len();
+        for mut race in unfinished {
+            match races {
+                DroppedPlayerRaces::Forfeit if race.is_placement_race() => {
+                    race.add_placement_results(&[(player.id, PlayerResult::Forfeit)], false, c)?;
+                }
+                DroppedPlayerRaces::Forfeit => {
+                    race.forfeit_player(player)?;
+                    race.update(c)?;
+                }
+                DroppedPlayerRaces::Cancel => {
+                    let info = race.info(c)?;
+                    CancelledRace::record(&race, &info, c)?;
+                    cancelled_infos.push(info);
+                    race.delete(c)?;
+                }
+            }
+        }
+        Ok(count)
+    })?;
+
+    if let Some((client, channel_config)) = discord {
+        for mut info in cancelled_infos {
+            if let Err(e) = clear_commportunities_message(&mut info, client, channel_config).await {
+                warn!(
+                    "Error clearing commportunities message for cancelled race {}: {e}",
+                    info.bracket_race_id
+                );
+            }
+            if let Err(e) =
+                clear_tentative_commentary_assignment_message(&mut info, client, channel_config)
+                    .await
+            {
+                warn!(
+                    "Error clearing tentative commentary assignment message for cancelled race {}: {e}",
+                    info.bracket_race_id
+                );
+            }
+        }
+    }
+
+    if races == DroppedPlayerRaces::Forfeit && bracket.advance_elimination(conn)? {
+        info!("Generated the next round of bracket {}", bracket.id);
+    }
+    Ok(count)
+}
+
+/**
+Deletes the latest round of `bracket` if none of it has been played yet (see
+[Bracket::rollback_round]), so that it can be generated again.
+
+The deleted races' commportunities and tentative commentary assignment messages are deleted if a
+[Client] is supplied; their Discord events are cleaned up by the helper bot.
+
+Returns the deleted round and the number of races that were in it
+*/
+pub async fn rollback_round(
+    bracket: &mut Bracket,
+    conn: &mut SqliteConnection,
+    discord: Option<(&Client, &ChannelConfig)>,
+) -> Result<(BracketRound, usize), NMGLeagueBotError> {
+    let (round, infos) = bracket.rollback_round(conn)?;
+    let count = infos.len();
+    if let Some((client, channel_config)) = discord {
+        for mut info in infos {
+            if let Err(e) = clear_commportunities_message(&mut info, client, channel_config).await {
+                warn!(
+                    "Error clearing commportunities message for rolled back race {}: {e}",
+                    info.bracket_race_id
+                );
+            }
+            if let Err(e) =
+                clear_tentative_commentary_assignment_message(&mut info, client, channel_config)
+                    .await
+            {
+                warn!(
+                    "Error clearing tentative commentary assignment message for rolled back race {}: {e}",
+                    info.bracket_race_id
+                );
+            }
+        }
+    }
+    info!(
+        "Rolled back round {} of bracket {} ({count} races)",
+        round.round_num, bracket.id
+    );
+    Ok((round, count))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::bracket_race_infos::BracketRaceInfo;
@@ -370,7 +859,7 @@ mod tests {
     use crate::racetime_types::{
         Entrant, EntrantStatus, Goal, RaceStatus, Races, RacetimeRace, User,
     };
-    use crate::worker_funcs::interesting_race;
+    use crate::worker_funcs::{interesting_race, races_by_player_rtgg};
     use chrono::{DateTime, TimeZone, Utc};
     use std::collections::HashMap;
     use std::fs::read_to_string;
@@ -409,6 +898,7 @@ mod tests {
             tentative_commentary_assignment_message_id: None,
             commentary_assignment_message_id: None,
             restream_channel: None,
+            times_suggested: None,
         }
     }
 
@@ -460,6 +950,8 @@ mod tests {
             player_1_result: None,
             player_2_result: None,
             outcome: None,
+            best_of: 1,
+            placement_race: false,
         };
         let bri = bracket_race_info(1, 1, Some(None));
         let p1 = Player {
@@ -479,13 +971,106 @@ mod tests {
             racetime_user_id: None,
         };
         let mut races = HashMap::new();
-        races.insert(p1.racetime_username.clone().unwrap(), (&bri, &br, &p1, &p2));
-        races.insert(p2.racetime_username.clone().unwrap(), (&bri, &br, &p1, &p2));
+        races.insert(
+            p1.racetime_username.clone().unwrap(),
+            (&bri, &br, vec![&p1, &p2]),
+        );
+        races.insert(
+            p2.racetime_username.clone().unwrap(),
+            (&bri, &br, vec![&p1, &p2]),
+        );
         let whatever = interesting_race(&mut race, &races, &season);
         assert!(whatever.is_some(), "{:?}", whatever);
-        let (_, _, (p1, e1), (p2, e2)) = whatever.unwrap();
-        assert_eq!(p1.racetime_username, Some(e1.user.full_name));
-        assert_eq!(p2.racetime_username, Some(e2.user.full_name));
+        let (_, _, entrants) = whatever.unwrap();
+        assert_eq!(2, entrants.len());
+        for (p, e) in entrants {
+            assert_eq!(p.racetime_username, Some(e.user.full_name));
+        }
+    }
+
+    fn player(id: i32) -> Player {
+        Player {
+            id,
+            name: format!("player {id}"),
+            discord_id: id.to_string(),
+            racetime_username: Some(format!("p{id}#1234")),
+            twitch_user_login: None,
+            racetime_user_id: None,
+        }
+    }
+
+    fn entrant(full_name: &str, finish_time: &str) -> Entrant {
+        Entrant {
+            user: User {
+                full_name: full_name.to_string(),
+            },
+            status: EntrantStatus {
+                value: "done".to_string(),
+            },
+            finish_time: Some(finish_time.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_interesting_placement_race() {
+        let mut race = RacetimeRace {
+            name: "asdf".to_string(),
+            status: RaceStatus {
+                value: "finished".to_string(),
+            },
+            url: "asdf".to_string(),
+            entrants: vec![
+                entrant("p3#1234", "PT1H23M45S"),
+                entrant("p1#1234", "PT1H34M56S"),
+                entrant("p4#1234", "PT1H45M00S"),
+            ],
+            opened_at: "".to_string(),
+            started_at: Utc::now().to_rfc3339(),
+            ended_at: "".to_string(),
+            goal: Goal {
+                name: "Any% NMG".to_string(),
+            },
+        };
+        let season = Season::new(1, "Any% NMG");
+        let br = BracketRace {
+            id: 1,
+            bracket_id: 1,
+            round_id: 1,
+            player_1_id: 1,
+            player_2_id: 2,
+            async_race_id: None,
+            state: "Scheduled".to_string(),
+            player_1_result: None,
+            player_2_result: None,
+            outcome: None,
+            best_of: 1,
+            placement_race: true,
+        };
+        let bri = bracket_race_info(1, 1, Some(None));
+        let players = vec![player(1), player(2), player(3), player(4)];
+        let race_entrants = HashMap::from([(1, vec![1, 3, 4])]);
+        let pairs = vec![(bri, br)];
+        let races = races_by_player_rtgg(&players, &pairs, &race_entrants);
+        assert_eq!(1, races.len());
+
+        let (_, _, entrants) = interesting_race(&mut race, &races, &season).unwrap();
+        let ids = entrants.iter().map(|(p, _)| p.id).collect::<Vec<_>>();
+        assert_eq!(vec![1, 3, 4], ids);
+        for (p, e) in entrants {
+            assert_eq!(p.racetime_username, Some(e.user.full_name));
+        }
+
+        // as a head-to-head race, this is player 1 vs player 2, and player 2 didn't enter
+        let mut race_without_p2 = race;
+        race_without_p2.entrants = vec![
+            entrant("p1#1234", "PT1H23M45S"),
+            entrant("p3#1234", "PT1H34M56S"),
+        ];
+        let (bri, mut br) = pairs.into_iter().next().unwrap();
+        br.placement_race = false;
+        let pairs = vec![(bri, br)];
+        let races = races_by_player_rtgg(&players, &pairs, &race_entrants);
+        assert!(interesting_race(&mut race_without_p2, &races, &season).is_none());
     }
 
     #[test]
@@ -536,6 +1121,8 @@ mod tests {
             player_1_result: None,
             player_2_result: None,
             outcome: None,
+            best_of: 1,
+            placement_race: false,
         };
         let bri = bracket_race_info(1, 1, Some(None));
         let p1 = Player {
@@ -557,11 +1144,11 @@ mod tests {
         let mut races = HashMap::new();
         races.insert(
             p1.racetime_username.clone().unwrap().to_lowercase(),
-            (&bri, &br, &p1, &p3),
+            (&bri, &br, vec![&p1, &p3]),
         );
         races.insert(
             p3.racetime_username.clone().unwrap().to_lowercase(),
-            (&bri, &br, &p1, &p3),
+            (&bri, &br, vec![&p1, &p3]),
         );
         let season = Season::new(1, "Any% NMG");
         let whatever = interesting_race(&mut race, &races, &season);
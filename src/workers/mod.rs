@@ -1,5 +1,8 @@
 pub mod async_race_worker;
 pub mod forfeit_own_races;
 pub mod racetime_scanner_worker;
+pub mod ratings_worker;
+pub mod round_deadlines_worker;
 #[cfg(feature = "racetime_bot")]
 pub mod upcoming_races_worker;
+pub mod webhook_worker;
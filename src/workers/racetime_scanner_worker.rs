@@ -6,14 +6,14 @@ use diesel::prelude::*;
 use itertools::Itertools;
 use log::{debug, info, warn};
 use nmg_league_bot::config::CONFIG;
-use nmg_league_bot::models::bracket_race_infos::BracketRaceInfo;
-use nmg_league_bot::models::bracket_races::BracketRace;
+use nmg_league_bot::models::bracket_race_entrants::BracketRaceEntrant;
 use nmg_league_bot::models::player::Player;
 use nmg_league_bot::models::season::Season;
 use nmg_league_bot::racetime_types::{PlayerResultError, Races, RacetimeRace};
 use nmg_league_bot::utils::racetime_base_url;
 use nmg_league_bot::worker_funcs::{
-    interesting_race, races_by_player_rtgg, trigger_race_finish, RaceFinishOptions,
+    interesting_race, races_by_player_rtgg, trigger_placement_race_finish, trigger_race_finish,
+    InterestingRace, PlacementRaceFinishOptions, RaceFinishOptions,
 };
 use nmg_league_bot::BracketRaceStateError;
 use racetime_api::client::RacetimeClient;
@@ -74,7 +74,17 @@ async fn scan(
     // *shrug*
     // it's like 40 rows
     let all_players: Vec<Player> = players::table.load(cxn.deref_mut())?;
-    let interesting_rtgg_ids = races_by_player_rtgg(&all_players, &bracket_races);
+    let placement_race_ids = bracket_races
+        .iter()
+        .filter(|(_, br)| br.is_placement_race())
+        .map(|(_, br)| br.id)
+        .collect::<Vec<_>>();
+    let race_entrants: HashMap<i32, Vec<i32>> =
+        BracketRaceEntrant::for_races(&placement_race_ids, cxn.deref_mut())?
+            .into_iter()
+            .map(|(race_id, entrants)| (race_id, entrants.iter().map(|e| e.player_id).collect()))
+            .collect();
+    let interesting_rtgg_ids = races_by_player_rtgg(&all_players, &bracket_races, &race_entrants);
     let rtgg_ids_str = interesting_rtgg_ids.keys().join(", ");
     debug!("Interesting rtgg ids that we're looking for: {rtgg_ids_str}");
     if interesting_rtgg_ids.is_empty() {
@@ -100,29 +110,60 @@ async fn scan(
 
 async fn maybe_do_race_stuff(
     mut race: RacetimeRace,
-    bracket_races: &HashMap<String, (&BracketRaceInfo, &BracketRace, &Player, &Player)>,
+    bracket_races: &HashMap<String, InterestingRace<'_>>,
     season: &Season,
     state: &Arc<DiscordState>,
 ) -> Result<(), ScanError> {
-    if let Some((bri, br, (p1, e1), (p2, e2))) = interesting_race(&mut race, bracket_races, season)
-    {
+    if let Some((bri, br, entrants)) = interesting_race(&mut race, bracket_races, season) {
         // this is awful, i hate doing it this way, i'm just tired of thinking about this
         let mutable_br = br.clone();
         let mut mutable_bri = bri.clone();
-        mutable_bri.racetime_gg_url = Some(format!("{}{}", racetime_base_url(), race.url));
-        let p1r = e1.result()?;
-        let p2r = e2.result()?;
+        let racetime_gg_url = format!("{}{}", racetime_base_url(), race.url);
+        mutable_bri.racetime_gg_url = Some(racetime_gg_url.clone());
+        let mut results = vec![];
+        for (p, e) in entrants {
+            results.push((p.clone(), e.result()?));
+        }
         let mut conn = state.diesel_cxn().await?;
         if let Err(e) = mutable_bri.update(conn.deref_mut()) {
             warn!("Error updating BRI with racetimeurl: {e} - BRI {mutable_bri:?}");
         }
+        if mutable_br.is_placement_race() {
+            let opts = PlacementRaceFinishOptions {
+                bracket_race: mutable_br,
+                info: mutable_bri,
+                results,
+                channel_id: state.channel_config.match_results,
+                force_update: false,
+            };
+            if let Err(e) = trigger_placement_race_finish(
+                opts,
+                conn.deref_mut(),
+                Some(&state.discord_client),
+                &state.channel_config,
+            )
+            .await
+            {
+                warn!("Error triggering placement race finish: {}", e);
+            }
+            return Ok(());
+        }
+        let mut results = results.into_iter();
+        let (p1, p1r, p2, p2r) = match (results.next(), results.next()) {
+            (Some((p1, p1r)), Some((p2, p2r))) => (p1, p1r, p2, p2r),
+            _ => {
+                warn!("Head-to-head race {} doesn't have two players?", mutable_br.id);
+                return Ok(());
+            }
+        };
         let opts = RaceFinishOptions {
             bracket_race: mutable_br,
             info: mutable_bri,
-            player_1: p1.clone(),
+            player_1: p1,
             player_1_result: p1r,
-            player_2: p2.clone(),
+            player_2: p2,
             player_2_result: p2r,
+            racetime_gg_url: Some(racetime_gg_url),
             channel_id: state.channel_config.match_results,
             force_update: false,
         };
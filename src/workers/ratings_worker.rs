@@ -0,0 +1,51 @@
+//! Recomputes player ratings when races have finished since the last time (see
+//! [nmg_league_bot::models::ratings::mark_ratings_stale]), so a burst of results only costs one
+//! recompute.
+
+use crate::discord::discord_state::{DiscordOperations, DiscordState};
+use crate::shutdown::Shutdown;
+use log::{debug, info, warn};
+use nmg_league_bot::config::CONFIG;
+use nmg_league_bot::models::ratings::recompute_ratings_if_stale;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use tokio::sync::broadcast::Receiver;
+
+async fn sweep(state: &Arc<DiscordState>) {
+    let mut cxn = match state.diesel_cxn().await {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Ratings worker: error getting db connection: {e}");
+            return;
+        }
+    };
+    match recompute_ratings_if_stale(cxn.deref_mut()) {
+        Ok(Some(n)) => {
+            debug!("Recomputed {n} ratings");
+        }
+        Ok(None) => {}
+        Err(e) => {
+            warn!("Error recomputing ratings: {e}");
+        }
+    }
+}
+
+pub(crate) async fn cron(mut sd: Receiver<Shutdown>, state: Arc<DiscordState>) {
+    let tick_duration = core::time::Duration::from_secs(CONFIG.cron_tick_seconds);
+    info!(
+        "Starting ratings worker: running every {} seconds",
+        tick_duration.as_secs()
+    );
+    let mut intv = tokio::time::interval(tick_duration);
+    loop {
+        tokio::select! {
+            _ = intv.tick() => {
+                sweep(&state).await;
+            }
+            _sd = sd.recv() => {
+                info!("ratings worker shutting down");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Enforces rounds' scheduling and play-by deadlines (see [BracketRound::pending_deadline_steps]):
+//! reminds players whose races aren't scheduled yet, tells admins about races that missed a
+//! deadline, and applies the round's [MissedDeadlinePolicy] to races that weren't played.
+//!
+//! It also DMs the players in each new race some times they're all available for, so they don't
+//! have to ask with `/suggest_times`.
+
+use crate::discord::discord_state::{DiscordOperations, DiscordState};
+use crate::discord::{long_timestamp, send_dm, send_suggested_times, MAX_SUGGESTED_TIMES};
+use crate::shutdown::Shutdown;
+use chrono::Utc;
+use diesel::SqliteConnection;
+use log::{debug, info, warn};
+use nmg_league_bot::config::CONFIG;
+use nmg_league_bot::models::audit_events::{Actor, AuditEntity, NewAuditEvent};
+use nmg_league_bot::models::availability::suggested_times;
+use nmg_league_bot::models::bracket_races::BracketRace;
+use nmg_league_bot::models::bracket_rounds::{BracketRound, DeadlineStep, MissedDeadlinePolicy};
+use nmg_league_bot::worker_funcs::apply_missed_deadline_policy;
+use nmg_league_bot::NMGLeagueBotError;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use tokio::sync::broadcast::Receiver;
+
+/// admin messages list at most this many races, to stay under Discord's message length limit
+const MAX_LISTED_RACES: usize = 25;
+
+fn race_list(
+    races: &[BracketRace],
+    conn: &mut SqliteConnection,
+) -> Result<String, NMGLeagueBotError> {
+    let mut lines = races
+        .iter()
+        .take(MAX_LISTED_RACES)
+        .map(|r| Ok(format!("* #{}: {}", r.id, r.title(conn)?)))
+        .collect::<Result<Vec<_>, NMGLeagueBotError>>()?;
+    if races.len() > MAX_LISTED_RACES {
+        lines.push(format!("...and {} more", races.len() - MAX_LISTED_RACES));
+    }
+    Ok(lines.join("\n"))
+}
+
+async fn post_to_admins(content: &str, state: &Arc<DiscordState>) -> Result<(), NMGLeagueBotError> {
+    state
+        .discord_client
+        .create_message(state.channel_config.admin)
+        .content(content)
+        .await?;
+    Ok(())
+}
+
+/// DMs everyone in the round's unscheduled races. Undeliverable DMs are only logged: there's
+/// nothing else to do about them, and retrying would spam everyone else.
+async fn remind_unscheduled(
+    round: &BracketRound,
+    round_name: &str,
+    schedule_by: i64,
+    conn: &mut SqliteConnection,
+    state: &Arc<DiscordState>,
+) -> Result<(), NMGLeagueBotError> {
+    for race in round.unscheduled_races(conn)? {
+        let title = race.title(conn)?;
+        let content = format!(
+            "Reminder: your {round_name} race ({title}) needs to be scheduled by {}. Use \
+            `/schedule_race` once you've agreed on a time.",
+            long_timestamp(schedule_by)
+        );
+        for player in race.all_players(conn)? {
+            let sent = match player.discord_id() {
+                Ok(uid) => send_dm(uid, &content, state).await,
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = sent {
+                warn!(
+                    "Error reminding {} to schedule race {}: {e}",
+                    player.name, race.id
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn escalate_unscheduled(
+    round: &BracketRound,
+    round_name: &str,
+    schedule_by: i64,
+    conn: &mut SqliteConnection,
+    state: &Arc<DiscordState>,
+) -> Result<(), NMGLeagueBotError> {
+    let races = round.unscheduled_races(conn)?;
+    if races.is_empty() {
+        return Ok(());
+    }
+    let content = format!(
+        "These {round_name} races missed the scheduling deadline ({}):\n{}",
+        long_timestamp(schedule_by),
+        race_list(&races, conn)?
+    );
+    post_to_admins(&content, state).await
+}
+
+async fn escalate_unplayed(
+    round: &BracketRound,
+    round_name: &str,
+    play_by: i64,
+    conn: &mut SqliteConnection,
+    state: &Arc<DiscordState>,
+) -> Result<(), NMGLeagueBotError> {
+    let races = round.unfinished_races(conn)?;
+    if races.is_empty() {
+        return Ok(());
+    }
+    let list = race_list(&races, conn)?;
+    let deadline = long_timestamp(play_by);
+    let policy = round.missed_deadline_policy()?;
+    let content = match policy {
+        MissedDeadlinePolicy::Nothing => {
+            format!("These {round_name} races weren't played by the deadline ({deadline}):\n{list}")
+        }
+        MissedDeadlinePolicy::DoubleForfeit => format!(
+            "These {round_name} races weren't played by the deadline ({deadline}), so they're \
+            being recorded as double forfeits:\n{list}"
+        ),
+        MissedDeadlinePolicy::AdminDecision => format!(
+            "These {round_name} races weren't played by the deadline ({deadline}) and need an \
+            admin decision. Report results with `/report_race`; `/overdue_races` lists everything \
+            that's waiting.\n{list}"
+        ),
+    };
+    // tell admins first: once the forfeits are saved, these races aren't unfinished anymore, so a
+    // retry wouldn't find anything to tell them about
+    post_to_admins(&content, state).await?;
+    if policy == MissedDeadlinePolicy::DoubleForfeit {
+        let forfeited = apply_missed_deadline_policy(
+            round,
+            conn,
+            Some(&state.discord_client),
+            &state.channel_config,
+        )
+        .await?;
+        for race in &forfeited {
+            NewAuditEvent::new(
+                &Actor::System,
+                "missed_deadline_double_forfeit",
+                AuditEntity::BracketRace,
+                race.id,
+            )
+            .after(race)
+            .record(conn);
+        }
+    }
+    Ok(())
+}
+
+/// suggests times for each unscheduled race, once. Races whose players don't have any time in
+/// common are tried again next time, in case someone has added some availability since.
+async fn suggest_times(
+    conn: &mut SqliteConnection,
+    state: &Arc<DiscordState>,
+) -> Result<(), NMGLeagueBotError> {
+    let now = Utc::now();
+    for race in BracketRace::unscheduled(conn)? {
+        let mut info = race.info(conn)?;
+        if info.times_suggested.is_some() {
+            continue;
+        }
+        let times = suggested_times(&race, now, MAX_SUGGESTED_TIMES, conn)?;
+        if times.is_empty() {
+            continue;
+        }
+        let title = race.title(conn)?;
+        let players = race.all_players(conn)?;
+        // like reminders, this isn't retried: that would spam whoever did get it
+        send_suggested_times(&race, &title, &times, players, state).await;
+        info.times_suggested = Some(now.timestamp());
+        info.update(conn)?;
+    }
+    Ok(())
+}
+
+async fn run_once(state: &Arc<DiscordState>) -> Result<(), NMGLeagueBotError> {
+    let now = Utc::now().timestamp();
+    let mut conn = state.diesel_cxn().await?;
+    if let Err(e) = suggest_times(conn.deref_mut(), state).await {
+        warn!("Error suggesting times for unscheduled races: {e}");
+    }
+    for mut round in BracketRound::with_open_deadlines(conn.deref_mut())? {
+        let steps = round.pending_deadline_steps(now);
+        if steps.is_empty() {
+            continue;
+        }
+        let bracket = round.bracket(conn.deref_mut())?;
+        let round_name = format!("{} round {}", bracket.name, round.round_num);
+        for step in steps {
+            debug!("Doing {step:?} for {round_name}");
+            let done = match (step, round.schedule_by, round.play_by) {
+                (DeadlineStep::RemindUnscheduled, Some(t), _) => {
+                    remind_unscheduled(&round, &round_name, t, conn.deref_mut(), state).await
+                }
+                (DeadlineStep::EscalateUnscheduled, Some(t), _) => {
+                    escalate_unscheduled(&round, &round_name, t, conn.deref_mut(), state).await
+                }
+                (DeadlineStep::EscalateUnplayed, _, Some(t)) => {
+                    escalate_unplayed(&round, &round_name, t, conn.deref_mut(), state).await
+                }
+                // steps are only pending for deadlines that exist
+                _ => Ok(()),
+            };
+            if let Err(e) = done {
+                // try again next time
+                warn!("Error doing {step:?} for {round_name}: {e}");
+                break;
+            }
+            round.mark_done(step);
+            round.update(conn.deref_mut())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn cron(mut sd: Receiver<Shutdown>, state: Arc<DiscordState>) {
+    let tick_duration = core::time::Duration::from_secs(CONFIG.cron_tick_seconds);
+    info!(
+        "Starting round deadlines worker: running every {} seconds",
+        tick_duration.as_secs()
+    );
+    let mut intv = tokio::time::interval(tick_duration);
+    loop {
+        tokio::select! {
+            _ = intv.tick() => {
+                if let Err(e) = run_once(&state).await {
+                    warn!("Error running round deadlines worker: {e}");
+                }
+            }
+            _sd = sd.recv() => {
+                info!("round deadlines worker shutting down");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Sends the webhook deliveries queued by [nmg_league_bot::models::webhooks::fire_event], and
+//! records how each attempt went.
+
+use crate::discord::discord_state::{DiscordOperations, DiscordState};
+use crate::shutdown::Shutdown;
+use log::{debug, info, warn};
+use nmg_league_bot::config::CONFIG;
+use nmg_league_bot::models::webhooks::{WebhookDelivery, WebhookSubscription};
+use nmg_league_bot::utils::epoch_timestamp;
+use reqwest::header::CONTENT_TYPE;
+use std::collections::HashMap;
+use std::ops::DerefMut;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::Receiver;
+use tokio::task::JoinSet;
+
+/// hex HMAC-SHA256 of the body, keyed with the subscription's secret, as `sha256=<signature>`
+const SIGNATURE_HEADER: &str = "X-NMG-League-Signature";
+/// the delivery's id. Retries of the same delivery have the same id.
+const DELIVERY_HEADER: &str = "X-NMG-League-Delivery";
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// how many deliveries are sent at once, so one slow receiver doesn't hold up everyone else's
+const MAX_CONCURRENT_DELIVERIES: usize = 8;
+
+async fn deliver(
+    client: &reqwest::Client,
+    subscription: &WebhookSubscription,
+    delivery: &mut WebhookDelivery,
+) {
+    let signature = match subscription.sign(&delivery.payload) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!(
+                "Error signing webhook delivery {} for subscription {}: {e}",
+                delivery.id, subscription.id
+            );
+            delivery.record_failure(None, format!("Error signing payload: {e}"));
+            return;
+        }
+    };
+    let resp = client
+        .post(&subscription.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    match resp {
+        Ok(r) if r.status().is_success() => {
+            debug!("Delivered webhook delivery {}", delivery.id);
+            delivery.record_success(r.status().as_u16());
+        }
+        Ok(r) => {
+            delivery.record_failure(Some(r.status().as_u16()), format!("HTTP {}", r.status()));
+        }
+        Err(e) => {
+            delivery.record_failure(None, e.to_string());
+        }
+    }
+}
+
+/// waits for one of the deliveries in `sending` to finish, and adds it to `attempted`
+async fn collect_one(sending: &mut JoinSet<WebhookDelivery>, attempted: &mut Vec<WebhookDelivery>) {
+    match sending.join_next().await {
+        Some(Ok(delivery)) => {
+            attempted.push(delivery);
+        }
+        Some(Err(e)) => {
+            // that delivery stays due, so it'll be sent again next time
+            warn!("Error sending webhook delivery: {e}");
+        }
+        None => {}
+    }
+}
+
+async fn sweep(client: &reqwest::Client, state: &Arc<DiscordState>) {
+    // we don't hold onto a connection while waiting on other people's servers
+    let (due, subscriptions) = {
+        let mut cxn = match state.diesel_cxn().await {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("Webhook worker: error getting db connection: {e}");
+                return;
+            }
+        };
+        let due = match WebhookDelivery::due(epoch_timestamp() as i64, cxn.deref_mut()) {
+            Ok(d) => d,
+            Err(e) => {
+                warn!("Error fetching due webhook deliveries: {e}");
+                return;
+            }
+        };
+        if due.is_empty() {
+            return;
+        }
+        let subscriptions = match WebhookSubscription::active(cxn.deref_mut()) {
+            Ok(subs) => subs
+                .into_iter()
+                .map(|s| (s.id, s))
+                .collect::<HashMap<_, _>>(),
+            Err(e) => {
+                warn!("Error fetching webhook subscriptions: {e}");
+                return;
+            }
+        };
+        (due, subscriptions)
+    };
+
+    info!("Sending {} webhook deliveries", due.len());
+    let mut attempted = Vec::with_capacity(due.len());
+    let mut sending = JoinSet::new();
+    for mut delivery in due {
+        let Some(sub) = subscriptions.get(&delivery.subscription_id).cloned() else {
+            debug!(
+                "Dropping webhook delivery {}: its subscription is inactive",
+                delivery.id
+            );
+            delivery.give_up();
+            attempted.push(delivery);
+            continue;
+        };
+        if sending.len() >= MAX_CONCURRENT_DELIVERIES {
+            collect_one(&mut sending, &mut attempted).await;
+        }
+        let client = client.clone();
+        sending.spawn(async move {
+            deliver(&client, &sub, &mut delivery).await;
+            delivery
+        });
+    }
+    while !sending.is_empty() {
+        collect_one(&mut sending, &mut attempted).await;
+    }
+
+    let mut cxn = match state.diesel_cxn().await {
+        Ok(c) => c,
+        Err(e) => {
+            // they'll get sent again next time, which is better than not at all
+            warn!("Webhook worker: error getting db connection to record deliveries: {e}");
+            return;
+        }
+    };
+    for delivery in attempted {
+        if let Err(e) = delivery.update(cxn.deref_mut()) {
+            warn!("Error updating webhook delivery {}: {e}", delivery.id);
+        }
+    }
+}
+
+pub(crate) async fn cron(mut sd: Receiver<Shutdown>, state: Arc<DiscordState>) {
+    // a redirect could send the signed payload somewhere the subscriber never asked for, so
+    // receivers have to answer at the URL they gave us
+    let client = match reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Unable to build webhook client, not sending webhooks: {e}");
+            return;
+        }
+    };
+    let tick_duration = core::time::Duration::from_secs(CONFIG.cron_tick_seconds);
+    info!(
+        "Starting webhook worker: running every {} seconds",
+        tick_duration.as_secs()
+    );
+    let mut intv = tokio::time::interval(tick_duration);
+    loop {
+        tokio::select! {
+            _ = intv.tick() => {
+                sweep(&client, &state).await;
+            }
+            _sd = sd.recv() => {
+                info!("webhook worker shutting down");
+                break;
+            }
+        }
+    }
+}